@@ -1,5 +1,7 @@
 mod allocation;
 mod app_state;
+mod balance;
 
 pub use allocation::Allocation;
 pub use app_state::AppState;
+pub use balance::{Balance, EscrowBalance};
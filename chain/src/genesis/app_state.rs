@@ -1,7 +1,8 @@
+use penumbra_crypto::{FlowCommittee, FlowEncryptionKey};
 use penumbra_proto::{chain as pb, stake as pb_stake, Protobuf};
 use serde::{Deserialize, Serialize};
 
-use super::Allocation;
+use super::{Allocation, Balance, EscrowBalance};
 use crate::params::ChainParameters;
 
 /// The application state at genesis.
@@ -14,6 +15,31 @@ pub struct AppState {
     pub validators: Vec<pb_stake::Validator>,
     /// The initial token allocations.
     pub allocations: Vec<Allocation>,
+    /// The key that swap inputs are encrypted to, if swaps are enabled.
+    pub flow_encryption_key: Option<FlowEncryptionKey>,
+    /// The public key shares of the validators holding the shares of the flow decryption key,
+    /// which must be present if the flow encryption key is.
+    ///
+    /// The key shares themselves are kept by each validator, and never recorded on chain. The
+    /// committee is fixed at genesis, since there's no way yet to reshare the key as the
+    /// validator set changes.
+    pub flow_committee: Option<FlowCommittee>,
+    /// The rates of the initial validators, if they are carried over from an exported chain.
+    ///
+    /// Validators without rates start with an exchange rate of 1.
+    pub validator_rates: Vec<pb_stake::RateData>,
+    /// The base rate, if it is carried over from an exported chain.
+    pub base_rate: Option<pb_stake::BaseRateData>,
+    /// The initial balances of the DAO treasury.
+    pub dao_balances: Vec<Balance>,
+    /// The initial balances escrowed for outbound ICS20 transfers.
+    pub ibc_escrow_balances: Vec<EscrowBalance>,
+    /// The states of the initial validators, if they are carried over from an exported chain.
+    ///
+    /// Validators without a status start active and bonded.
+    pub validator_statuses: Vec<pb_stake::ValidatorStatus>,
+    /// The epochs in which the initial jailed validators were jailed.
+    pub jailed_epochs: Vec<pb::genesis_app_state::JailedEpoch>,
 }
 
 impl From<AppState> for pb::GenesisAppState {
@@ -22,6 +48,14 @@ impl From<AppState> for pb::GenesisAppState {
             validators: a.validators.into_iter().map(Into::into).collect(),
             allocations: a.allocations.into_iter().map(Into::into).collect(),
             chain_params: Some(a.chain_params.into()),
+            flow_encryption_key: a.flow_encryption_key.map(Into::into),
+            flow_committee: a.flow_committee.map(Into::into),
+            validator_rates: a.validator_rates,
+            base_rate: a.base_rate,
+            dao_balances: a.dao_balances.into_iter().map(Into::into).collect(),
+            ibc_escrow_balances: a.ibc_escrow_balances.into_iter().map(Into::into).collect(),
+            validator_statuses: a.validator_statuses,
+            jailed_epochs: a.jailed_epochs,
         }
     }
 }
@@ -43,6 +77,23 @@ impl TryFrom<pb::GenesisAppState> for AppState {
                 .into_iter()
                 .map(TryInto::try_into)
                 .collect::<Result<_, _>>()?,
+
+            flow_encryption_key: msg.flow_encryption_key.map(TryInto::try_into).transpose()?,
+            flow_committee: msg.flow_committee.map(TryInto::try_into).transpose()?,
+            validator_rates: msg.validator_rates,
+            base_rate: msg.base_rate,
+            dao_balances: msg
+                .dao_balances
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            ibc_escrow_balances: msg
+                .ibc_escrow_balances
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            validator_statuses: msg.validator_statuses,
+            jailed_epochs: msg.jailed_epochs,
         })
     }
 }
@@ -0,0 +1,68 @@
+use penumbra_proto::{chain as pb, Protobuf};
+use serde::{Deserialize, Serialize};
+
+/// A balance held in the chain state, rather than in a note, carried over by an exported genesis.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    try_from = "pb::genesis_app_state::Balance",
+    into = "pb::genesis_app_state::Balance"
+)]
+pub struct Balance {
+    pub amount: u64,
+    pub denom: String,
+}
+
+impl From<Balance> for pb::genesis_app_state::Balance {
+    fn from(b: Balance) -> Self {
+        pb::genesis_app_state::Balance {
+            amount: b.amount,
+            denom: b.denom,
+        }
+    }
+}
+
+impl TryFrom<pb::genesis_app_state::Balance> for Balance {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::genesis_app_state::Balance) -> Result<Self, Self::Error> {
+        Ok(Balance {
+            amount: msg.amount,
+            denom: msg.denom,
+        })
+    }
+}
+
+impl Protobuf<pb::genesis_app_state::Balance> for Balance {}
+
+/// The tokens escrowed on an ICS20 channel, carried over by an exported genesis.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    try_from = "pb::genesis_app_state::EscrowBalance",
+    into = "pb::genesis_app_state::EscrowBalance"
+)]
+pub struct EscrowBalance {
+    pub channel_id: String,
+    pub amount: u64,
+}
+
+impl From<EscrowBalance> for pb::genesis_app_state::EscrowBalance {
+    fn from(b: EscrowBalance) -> Self {
+        pb::genesis_app_state::EscrowBalance {
+            channel_id: b.channel_id,
+            amount: b.amount,
+        }
+    }
+}
+
+impl TryFrom<pb::genesis_app_state::EscrowBalance> for EscrowBalance {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::genesis_app_state::EscrowBalance) -> Result<Self, Self::Error> {
+        Ok(EscrowBalance {
+            channel_id: msg.channel_id,
+            amount: msg.amount,
+        })
+    }
+}
+
+impl Protobuf<pb::genesis_app_state::EscrowBalance> for EscrowBalance {}
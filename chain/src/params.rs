@@ -56,6 +56,8 @@ pub struct ChainParameters {
     pub signed_blocks_window_len: u64,
     /// The maximum number of blocks in the window each validator can miss signing without slashing.
     pub missed_blocks_maximum: u64,
+    /// The number of epochs a validator jailed for downtime must wait before it can be unjailed.
+    pub jail_duration_epochs: u64,
 
     /// Whether IBC (forming connections, processing IBC packets) is enabled.
     pub ibc_enabled: bool,
@@ -80,6 +82,111 @@ pub struct ChainParameters {
 
 impl Protobuf<pb::ChainParameters> for ChainParameters {}
 
+impl ChainParameters {
+    /// Set the parameter with the given `name` to `value`, parsed according to the type of the
+    /// parameter.
+    ///
+    /// Only parameters which can safely change while the chain is running can be set: changing the
+    /// chain ID or the epoch duration would invalidate existing chain state, so these are rejected.
+    pub fn set(&mut self, name: &str, value: &str) -> anyhow::Result<()> {
+        fn parse<T: std::str::FromStr>(name: &str, value: &str) -> anyhow::Result<T>
+        where
+            T::Err: std::fmt::Display,
+        {
+            value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("invalid value {:?} for `{}`: {}", value, name, e))
+        }
+
+        fn bps(name: &str, value: &str) -> anyhow::Result<u64> {
+            let bps = parse(name, value)?;
+            if bps > 10_000 {
+                anyhow::bail!("`{}` must be at most 10000 basis points", name);
+            }
+            Ok(bps)
+        }
+
+        fn nonzero(name: &str, value: &str) -> anyhow::Result<u64> {
+            let n = parse(name, value)?;
+            if n == 0 {
+                anyhow::bail!("`{}` must be nonzero", name);
+            }
+            Ok(n)
+        }
+
+        fn fraction(name: &str, value: &str) -> anyhow::Result<Ratio<u64>> {
+            let ratio: Ratio<u64> = parse(name, value)?;
+            if ratio > Ratio::from_integer(1) {
+                anyhow::bail!("`{}` must be at most 1", name);
+            }
+            Ok(ratio)
+        }
+
+        match name {
+            "chain_id" | "epoch_duration" => {
+                anyhow::bail!("`{}` cannot be changed while the chain is running", name)
+            }
+            "unbonding_epochs" => self.unbonding_epochs = nonzero(name, value)?,
+            "active_validator_limit" => self.active_validator_limit = nonzero(name, value)?,
+            "base_reward_rate" => self.base_reward_rate = parse(name, value)?,
+            "slashing_penalty_misbehavior_bps" => {
+                self.slashing_penalty_misbehavior_bps = bps(name, value)?
+            }
+            "slashing_penalty_downtime_bps" => {
+                self.slashing_penalty_downtime_bps = bps(name, value)?
+            }
+            "signed_blocks_window_len" => self.signed_blocks_window_len = nonzero(name, value)?,
+            "missed_blocks_maximum" => self.missed_blocks_maximum = parse(name, value)?,
+            "jail_duration_epochs" => self.jail_duration_epochs = parse(name, value)?,
+            "ibc_enabled" => self.ibc_enabled = parse(name, value)?,
+            "inbound_ics20_transfers_enabled" => {
+                self.inbound_ics20_transfers_enabled = parse(name, value)?
+            }
+            "outbound_ics20_transfers_enabled" => {
+                self.outbound_ics20_transfers_enabled = parse(name, value)?
+            }
+            "proposal_voting_blocks" => self.proposal_voting_blocks = nonzero(name, value)?,
+            "proposal_deposit_amount" => self.proposal_deposit_amount = parse(name, value)?,
+            "proposal_valid_quorum" => self.proposal_valid_quorum = fraction(name, value)?,
+            "proposal_pass_threshold" => self.proposal_pass_threshold = fraction(name, value)?,
+            "proposal_veto_threshold" => self.proposal_veto_threshold = fraction(name, value)?,
+            _ => anyhow::bail!("unknown chain parameter `{}`", name),
+        }
+
+        Ok(())
+    }
+
+    /// Check that the parameters are consistent with each other.
+    pub fn check_valid(&self) -> anyhow::Result<()> {
+        if self.missed_blocks_maximum > self.signed_blocks_window_len {
+            anyhow::bail!(
+                "`missed_blocks_maximum` of {} exceeds `signed_blocks_window_len` of {}",
+                self.missed_blocks_maximum,
+                self.signed_blocks_window_len
+            );
+        }
+        if self.jail_duration_epochs == 0 {
+            anyhow::bail!("`jail_duration_epochs` must be nonzero");
+        }
+        Ok(())
+    }
+
+    /// Compute the parameters resulting from applying all of the given changes, each a pair of a
+    /// parameter name and its new value, failing if any change is invalid or the result is
+    /// inconsistent.
+    pub fn with_changes<'a>(
+        &self,
+        changes: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> anyhow::Result<ChainParameters> {
+        let mut params = self.clone();
+        for (name, value) in changes {
+            params.set(name, value)?;
+        }
+        params.check_valid()?;
+        Ok(params)
+    }
+}
+
 impl TryFrom<pb::ChainParameters> for ChainParameters {
     type Error = anyhow::Error;
 
@@ -94,6 +201,7 @@ impl TryFrom<pb::ChainParameters> for ChainParameters {
             base_reward_rate: msg.base_reward_rate,
             missed_blocks_maximum: msg.missed_blocks_maximum,
             signed_blocks_window_len: msg.signed_blocks_window_len,
+            jail_duration_epochs: msg.jail_duration_epochs,
             ibc_enabled: msg.ibc_enabled,
             inbound_ics20_transfers_enabled: msg.inbound_ics20_transfers_enabled,
             outbound_ics20_transfers_enabled: msg.outbound_ics20_transfers_enabled,
@@ -124,6 +232,7 @@ impl From<ChainParameters> for pb::ChainParameters {
             active_validator_limit: params.active_validator_limit,
             signed_blocks_window_len: params.signed_blocks_window_len,
             missed_blocks_maximum: params.missed_blocks_maximum,
+            jail_duration_epochs: params.jail_duration_epochs,
             slashing_penalty_downtime_bps: params.slashing_penalty_downtime_bps,
             slashing_penalty_misbehavior_bps: params.slashing_penalty_misbehavior_bps,
             base_reward_rate: params.base_reward_rate,
@@ -151,6 +260,7 @@ impl Default for ChainParameters {
             // copied from cosmos hub
             signed_blocks_window_len: 10000,
             missed_blocks_maximum: 9500,
+            jail_duration_epochs: 1,
             // 1000 basis points = 10%
             slashing_penalty_misbehavior_bps: 1000,
             // 1 basis point = 0.01%
@@ -210,3 +320,40 @@ impl Default for FmdParameters {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameter_changes_are_atomic_and_typed() {
+        let params = ChainParameters::default();
+
+        let changed = params
+            .with_changes([
+                ("proposal_voting_blocks", "50"),
+                ("slashing_penalty_downtime_bps", "10"),
+                ("proposal_pass_threshold", "2/3"),
+            ])
+            .unwrap();
+        assert_eq!(changed.proposal_voting_blocks, 50);
+        assert_eq!(changed.slashing_penalty_downtime_bps, 10);
+        assert_eq!(changed.proposal_pass_threshold, Ratio::new(2, 3));
+
+        assert!(params
+            .with_changes([("proposal_voting_blocks", "soon")])
+            .is_err());
+        assert!(params.with_changes([("epoch_duration", "10")]).is_err());
+        assert!(params.with_changes([("no_such_parameter", "1")]).is_err());
+        assert!(params
+            .with_changes([("slashing_penalty_misbehavior_bps", "10001")])
+            .is_err());
+        assert!(params
+            .with_changes([("missed_blocks_maximum", "20000")])
+            .is_err());
+        assert!(params.with_changes([("unbonding_epochs", "0")]).is_err());
+        assert!(params
+            .with_changes([("jail_duration_epochs", "0")])
+            .is_err());
+    }
+}
@@ -6,6 +6,10 @@ pub fn block_height() -> &'static str {
     "block_height"
 }
 
+pub fn halted() -> &'static str {
+    "halted"
+}
+
 pub fn block_timestamp() -> &'static str {
     "block_timestamp"
 }
@@ -30,6 +30,20 @@ pub trait View: StateExt {
             .await
     }
 
+    /// Returns whether the chain has been halted by an emergency proposal.
+    async fn is_chain_halted(&self) -> Result<bool> {
+        Ok(self
+            .get_proto(state_key::halted().into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Sets whether the chain is halted: once halted, no further blocks will be executed until the
+    /// operator resumes the chain.
+    async fn put_chain_halted(&self, halted: bool) {
+        self.put_proto(state_key::halted().into(), halted).await
+    }
+
     /// Gets the current epoch for the chain.
     async fn get_current_epoch(&self) -> Result<Epoch> {
         let block_height = self.get_block_height().await?;
@@ -0,0 +1,79 @@
+//! State migrations, run when the chain is upgraded.
+//!
+//! An upgrade proposal names the migration to run by its identifier. Each node halts after the
+//! upgrade height, and its operator runs `pd migrate` with the new binary, which checks that the
+//! migration is registered here; the migration itself then runs at the start of the first block
+//! after the upgrade, so that its effects are part of consensus.
+
+use anyhow::Result;
+use penumbra_storage::{State, StateExt};
+
+use crate::{
+    governance::{self, proposal::ProposalList},
+    stake::{self, validator, View as _},
+};
+
+/// The identifiers of all the state migrations known to this version of `pd`.
+pub const MIGRATIONS: &[&str] = &[NOOP, INDEX_LISTS];
+
+/// A migration which leaves the state unchanged, for upgrades which only change behavior.
+const NOOP: &str = "noop";
+
+/// A migration which moves the lists of validators and of unfinished proposals, which used to be
+/// stored as single values, into the key index.
+const INDEX_LISTS: &str = "index_lists";
+
+/// Returns whether a migration with the given identifier is known to this version of `pd`.
+pub fn is_registered(migration_id: &str) -> bool {
+    MIGRATIONS.contains(&migration_id)
+}
+
+/// Runs the migration with the given identifier against the state.
+pub async fn run(migration_id: &str, state: &State) -> Result<()> {
+    match migration_id {
+        NOOP => Ok(()),
+        INDEX_LISTS => index_lists(state).await,
+        _ => Err(anyhow::anyhow!(
+            "unknown migration {:?}: this version of pd knows migrations {:?}",
+            migration_id,
+            MIGRATIONS
+        )),
+    }
+}
+
+async fn index_lists(state: &State) -> Result<()> {
+    const VALIDATOR_LIST: &str = "staking/validators";
+    const UNFINISHED_PROPOSALS: &str = "governance/unfinished_proposals";
+
+    if let Some(list) = state
+        .get_domain::<validator::List, _>(VALIDATOR_LIST.into())
+        .await?
+    {
+        for identity_key in list.0 {
+            let validator = state.validator(&identity_key).await?.ok_or_else(|| {
+                anyhow::anyhow!("missing definition for validator {}", identity_key)
+            })?;
+            state
+                .put_domain_indexed(stake::state_key::validator_by_id(&identity_key), validator)
+                .await;
+        }
+        state.delete(VALIDATOR_LIST.into()).await;
+    }
+
+    if let Some(list) = state
+        .get_domain::<ProposalList, _>(UNFINISHED_PROPOSALS.into())
+        .await?
+    {
+        for proposal_id in list.proposals {
+            state
+                .put_proto_indexed(
+                    governance::state_key::unfinished_proposal(proposal_id),
+                    proposal_id,
+                )
+                .await;
+        }
+        state.delete(UNFINISHED_PROPOSALS.into()).await;
+    }
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::dex::Dex;
-use crate::governance::Governance;
+use crate::governance::{Governance, View as _};
 use crate::ibc::IBCComponent;
 use crate::shielded_pool::ShieldedPool;
 use crate::stake::component::Staking;
@@ -15,7 +15,10 @@ use tendermint::abci::{self, types::ValidatorUpdate};
 
 use tracing::instrument;
 
+pub mod export;
+pub mod migration;
 pub mod state_key;
+
 /// The Penumbra application, written as a bundle of [`Component`]s.
 ///
 /// The [`App`] is also a [`Component`], but as the top-level component,
@@ -90,6 +93,27 @@ impl App {
         Ok((app_hash, version))
     }
 
+    /// Returns whether the chain has been halted, by an emergency proposal or a scheduled upgrade.
+    pub async fn is_halted(&self) -> Result<bool> {
+        self.state.is_chain_halted().await
+    }
+
+    /// Resumes the chain from a halt, as part of the block about to be executed.
+    ///
+    /// If the chain halted for an upgrade, this first runs the upgrade's state migration.
+    ///
+    /// This must only be called at the start of a block, once the node operator has chosen to
+    /// resume the chain: every node must do the same for them to stay in consensus.
+    pub async fn resume_from_halt(&mut self) -> Result<()> {
+        let height = self.state.get_block_height().await?;
+        if let Some(upgrade) = self.state.upgrade_plan(height).await? {
+            tracing::info!(%height, migration_id = %upgrade.migration_id, "running state migration");
+            migration::run(&upgrade.migration_id, &self.state).await?;
+        }
+        self.state.put_chain_halted(false).await;
+        Ok(())
+    }
+
     // TODO: should this just be returned by `commit`? both are called during every `EndBlock`
     pub fn tendermint_validator_updates(&self) -> Vec<ValidatorUpdate> {
         self.staking.tendermint_validator_updates()
@@ -139,58 +163,90 @@ impl Component for App {
             .put_block_timestamp(begin_block.header.time)
             .await;
 
-        self.staking.begin_block(ctx.clone(), begin_block).await;
-        self.ibc.begin_block(ctx.clone(), begin_block).await;
-        self.dex.begin_block(ctx.clone(), begin_block).await;
-        self.governance.begin_block(ctx.clone(), begin_block).await;
+        self.staking
+            .begin_block(ctx.for_component("stake"), begin_block)
+            .await;
+        self.ibc
+            .begin_block(ctx.for_component("ibc"), begin_block)
+            .await;
+        self.dex
+            .begin_block(ctx.for_component("dex"), begin_block)
+            .await;
+        self.governance
+            .begin_block(ctx.for_component("governance"), begin_block)
+            .await;
         // Shielded pool always executes last.
         self.shielded_pool
-            .begin_block(ctx.clone(), begin_block)
+            .begin_block(ctx.for_component("shielded_pool"), begin_block)
             .await;
     }
 
     #[instrument(skip(ctx, tx))]
     fn check_tx_stateless(ctx: Context, tx: &Transaction) -> Result<()> {
-        Staking::check_tx_stateless(ctx.clone(), tx)?;
-        IBCComponent::check_tx_stateless(ctx.clone(), tx)?;
-        Dex::check_tx_stateless(ctx.clone(), tx)?;
-        Governance::check_tx_stateless(ctx.clone(), tx)?;
-        ShieldedPool::check_tx_stateless(ctx, tx)?;
+        Staking::check_tx_stateless(ctx.for_component("stake"), tx)?;
+        IBCComponent::check_tx_stateless(ctx.for_component("ibc"), tx)?;
+        Dex::check_tx_stateless(ctx.for_component("dex"), tx)?;
+        Governance::check_tx_stateless(ctx.for_component("governance"), tx)?;
+        ShieldedPool::check_tx_stateless(ctx.for_component("shielded_pool"), tx)?;
         Ok(())
     }
 
     #[instrument(skip(self, ctx, tx))]
     async fn check_tx_stateful(&self, ctx: Context, tx: &Transaction) -> Result<()> {
-        self.staking.check_tx_stateful(ctx.clone(), tx).await?;
-        self.ibc.check_tx_stateful(ctx.clone(), tx).await?;
-        self.dex.check_tx_stateful(ctx.clone(), tx).await?;
-        self.governance.check_tx_stateful(ctx.clone(), tx).await?;
+        self.staking
+            .check_tx_stateful(ctx.for_component("stake"), tx)
+            .await?;
+        self.ibc
+            .check_tx_stateful(ctx.for_component("ibc"), tx)
+            .await?;
+        self.dex
+            .check_tx_stateful(ctx.for_component("dex"), tx)
+            .await?;
+        self.governance
+            .check_tx_stateful(ctx.for_component("governance"), tx)
+            .await?;
 
         // Shielded pool always executes last.
         self.shielded_pool
-            .check_tx_stateful(ctx.clone(), tx)
+            .check_tx_stateful(ctx.for_component("shielded_pool"), tx)
             .await?;
         Ok(())
     }
 
     #[instrument(skip(self, ctx, tx))]
     async fn execute_tx(&mut self, ctx: Context, tx: &Transaction) {
-        self.staking.execute_tx(ctx.clone(), tx).await;
-        self.ibc.execute_tx(ctx.clone(), tx).await;
-        self.dex.execute_tx(ctx.clone(), tx).await;
-        self.governance.execute_tx(ctx.clone(), tx).await;
+        self.staking
+            .execute_tx(ctx.for_component("stake"), tx)
+            .await;
+        self.ibc.execute_tx(ctx.for_component("ibc"), tx).await;
+        self.dex.execute_tx(ctx.for_component("dex"), tx).await;
+        self.governance
+            .execute_tx(ctx.for_component("governance"), tx)
+            .await;
         // Shielded pool always executes last.
-        self.shielded_pool.execute_tx(ctx.clone(), tx).await;
+        self.shielded_pool
+            .execute_tx(ctx.for_component("shielded_pool"), tx)
+            .await;
     }
 
     #[instrument(skip(self, ctx, end_block))]
     async fn end_block(&mut self, ctx: Context, end_block: &abci::request::EndBlock) {
-        self.staking.end_block(ctx.clone(), end_block).await;
-        self.ibc.end_block(ctx.clone(), end_block).await;
-        self.dex.end_block(ctx.clone(), end_block).await;
-        self.governance.end_block(ctx.clone(), end_block).await;
+        self.staking
+            .end_block(ctx.for_component("stake"), end_block)
+            .await;
+        self.ibc
+            .end_block(ctx.for_component("ibc"), end_block)
+            .await;
+        self.dex
+            .end_block(ctx.for_component("dex"), end_block)
+            .await;
+        self.governance
+            .end_block(ctx.for_component("governance"), end_block)
+            .await;
 
         // Shielded pool always executes last.
-        self.shielded_pool.end_block(ctx.clone(), end_block).await;
+        self.shielded_pool
+            .end_block(ctx.for_component("shielded_pool"), end_block)
+            .await;
     }
 }
@@ -0,0 +1,490 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Result};
+use penumbra_crypto::{
+    asset,
+    dex::{lp::position, TradingPair},
+    fixpoint::U64x64,
+};
+use penumbra_storage::State;
+
+use super::View as _;
+
+/// The maximum number of positions a route can trade through.
+const MAX_HOPS: usize = 4;
+
+/// The open positions relevant to a batch swap, held in memory so that a batch can be traded
+/// against them and its effects discarded if the batch can't be filled.
+#[derive(Debug, Default)]
+pub struct Book {
+    positions: BTreeMap<position::Id, position::Metadata>,
+    /// The IDs of the positions in the book, indexed by their trading pair.
+    by_pair: BTreeMap<TradingPair, BTreeSet<position::Id>>,
+}
+
+/// A trade against a single position, in a single direction.
+#[derive(Debug, Clone, Copy)]
+struct Hop {
+    id: position::Id,
+    /// Whether this hop trades asset 1 of the position's pair for asset 2, or the reverse.
+    one_to_two: bool,
+    /// The amount output for each unit of input.
+    rate: U64x64,
+    /// The largest input whose output the position's reserves can cover.
+    capacity: u64,
+}
+
+/// A sequence of hops from one asset to another, through distinct intermediate assets.
+#[derive(Debug, Clone)]
+struct Route {
+    /// The assets the route passes through, including its endpoints.
+    assets: Vec<asset::Id>,
+    hops: Vec<Hop>,
+    /// The amount output for each unit of input, across all the hops.
+    rate: U64x64,
+}
+
+impl Book {
+    /// Load the open positions that trades on the given trading pair can use.
+    ///
+    /// Routes can pass through any trading pair with open positions, so the positions on all of
+    /// them are loaded.
+    pub async fn load(state: &State, trading_pair: TradingPair) -> Result<Self> {
+        let mut trading_pairs = state
+            .liquid_trading_pairs()
+            .await?
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        trading_pairs.insert(trading_pair);
+
+        let mut book = Book::default();
+        for trading_pair in trading_pairs {
+            for id in state.open_positions(trading_pair).await? {
+                let metadata = state
+                    .position_by_id(&id)
+                    .await?
+                    .ok_or_else(|| anyhow!("open position {} does not exist", id))?;
+                book.insert(metadata);
+            }
+        }
+
+        Ok(book)
+    }
+
+    fn insert(&mut self, metadata: position::Metadata) {
+        let id = metadata.position.id();
+        self.by_pair
+            .entry(metadata.position.pair)
+            .or_default()
+            .insert(id);
+        self.positions.insert(id, metadata);
+    }
+
+    /// Consume the book, returning the positions it contains with their updated reserves.
+    pub fn into_positions(self) -> impl Iterator<Item = position::Metadata> {
+        self.positions.into_values()
+    }
+
+    /// Clear a batch of `delta_1` of asset 1 and `delta_2` of asset 2 swapped on the given
+    /// trading pair, returning the outputs `(lambda_1, lambda_2)` of asset 1 and asset 2.
+    ///
+    /// The two sides of the batch are netted against each other first, at the midpoint of the
+    /// best prices the book offers in each direction, so neither side gets a worse price for
+    /// the matched amounts because of the order in which they're filled. Only the residual of
+    /// the larger side is routed through the book, so that side alone bears the price impact of
+    /// the liquidity it takes. Returns an error if the residual can't be filled, or if both sides
+    /// are nonzero but the book has no price to net them at.
+    pub fn clear(
+        &mut self,
+        trading_pair: TradingPair,
+        delta_1: u64,
+        delta_2: u64,
+    ) -> Result<(u64, u64)> {
+        let (asset_1, asset_2) = (trading_pair.asset_1(), trading_pair.asset_2());
+
+        if delta_1 == 0 || delta_2 == 0 {
+            let lambda_2 = self.route(asset_1, asset_2, delta_1)?;
+            let lambda_1 = self.route(asset_2, asset_1, delta_2)?;
+            return Ok((lambda_1, lambda_2));
+        }
+
+        // The price of asset 1 in terms of asset 2.
+        let price = self
+            .mid_price(asset_1, asset_2)
+            .ok_or_else(|| anyhow!("no price to net the batch on {:?} at", trading_pair))?;
+
+        // Whether the asset 1 side is at least as large as the asset 2 side, at that price.
+        let one_is_larger = price
+            .checked_mul_u64(delta_1)
+            .map_or(true, |value_1| value_1 >= delta_2);
+
+        if one_is_larger {
+            // All of the asset 2 side is matched against part of the asset 1 side, and the rest
+            // of the asset 1 side is routed.
+            let matched_1 = U64x64::from(delta_2)
+                .checked_div(&price)
+                .map_or(delta_1, |matched| matched.round_down())
+                .min(delta_1);
+            let routed_2 = self.route(asset_1, asset_2, delta_1 - matched_1)?;
+            let lambda_2 = delta_2
+                .checked_add(routed_2)
+                .ok_or_else(|| anyhow!("batch output overflows"))?;
+            Ok((matched_1, lambda_2))
+        } else {
+            // All of the asset 1 side is matched against part of the asset 2 side, and the rest
+            // of the asset 2 side is routed.
+            let matched_2 = price
+                .checked_mul_u64(delta_1)
+                .expect("asset 1 side was checked not to overflow")
+                .min(delta_2);
+            let routed_1 = self.route(asset_2, asset_1, delta_2 - matched_2)?;
+            let lambda_1 = delta_1
+                .checked_add(routed_1)
+                .ok_or_else(|| anyhow!("batch output overflows"))?;
+            Ok((lambda_1, matched_2))
+        }
+    }
+
+    /// The midpoint of the best prices of `from` in terms of `to` that the book offers for
+    /// selling and for buying it, or whichever one it offers if it only offers one.
+    fn mid_price(&self, from: asset::Id, to: asset::Id) -> Option<U64x64> {
+        let sell = self.best_route(from, to, &[]).map(|route| route.rate);
+        let buy = self
+            .best_route(to, from, &[])
+            .and_then(|route| U64x64::ONE.checked_div(&route.rate));
+        match (sell, buy) {
+            (Some(sell), Some(buy)) => sell
+                .checked_add(&buy)
+                .and_then(|sum| sum.checked_div(&U64x64::from(2))),
+            (sell, buy) => sell.or(buy),
+        }
+    }
+
+    /// Trade `amount` of the `from` asset into the `to` asset, returning the total output.
+    ///
+    /// The input is filled in price order: at each step, it's routed along the best-priced route
+    /// of up to [`MAX_HOPS`] positions, through any intermediate assets, until that route runs
+    /// out of liquidity. Returns an error if the whole input can't be filled.
+    pub fn route(&mut self, from: asset::Id, to: asset::Id, amount: u64) -> Result<u64> {
+        // Routes which can't absorb even a single unit of input, which are no longer considered.
+        let mut exhausted = Vec::<Vec<asset::Id>>::new();
+        let mut remaining = amount;
+        let mut output = 0u64;
+
+        while remaining > 0 {
+            let route = self.best_route(from, to, &exhausted).ok_or_else(|| {
+                anyhow!(
+                    "insufficient liquidity to fill remaining {} of {}",
+                    remaining,
+                    from
+                )
+            })?;
+
+            // Work backwards along the route to find the largest input it can fill.
+            let mut limit = route.hops.last().expect("routes are nonempty").capacity;
+            for hop in route.hops.iter().rev().skip(1) {
+                let max_input = U64x64::from(limit)
+                    .checked_div(&hop.rate)
+                    .map_or(u64::MAX, |max_input| max_input.round_down());
+                limit = hop.capacity.min(max_input);
+            }
+            let input = remaining.min(limit);
+
+            if input == 0 {
+                // A later hop can't absorb even a single unit of an earlier hop's output, so stop
+                // considering this route.
+                exhausted.push(route.assets);
+                continue;
+            }
+
+            let mut amount = input;
+            for hop in &route.hops {
+                amount = self.fill(hop, amount)?;
+            }
+
+            remaining -= input;
+            output = output
+                .checked_add(amount)
+                .ok_or_else(|| anyhow!("batch output overflows"))?;
+        }
+
+        Ok(output)
+    }
+
+    /// Find the best-priced route from `from` to `to` of up to [`MAX_HOPS`] hops, each against
+    /// the best-priced position on its trading pair, other than the `exhausted` routes.
+    ///
+    /// Routes never pass through the same asset twice, so they never trade against the same
+    /// position twice. Ties are broken in favor of the route found first, searching assets in
+    /// order, so that routing is deterministic.
+    fn best_route(
+        &self,
+        from: asset::Id,
+        to: asset::Id,
+        exhausted: &[Vec<asset::Id>],
+    ) -> Option<Route> {
+        let mut best = None;
+        let mut route = Route {
+            assets: vec![from],
+            hops: Vec::new(),
+            rate: U64x64::ONE,
+        };
+        self.search(to, exhausted, &mut route, &mut best);
+        best
+    }
+
+    fn search(
+        &self,
+        to: asset::Id,
+        exhausted: &[Vec<asset::Id>],
+        route: &mut Route,
+        best: &mut Option<Route>,
+    ) {
+        let current = *route.assets.last().expect("routes start with an asset");
+        if current == to {
+            if !exhausted.contains(&route.assets)
+                && best
+                    .as_ref()
+                    .map(|best| route.rate > best.rate)
+                    .unwrap_or(true)
+            {
+                *best = Some(route.clone());
+            }
+            return;
+        }
+        if route.hops.len() == MAX_HOPS {
+            return;
+        }
+
+        for next in self.neighbors(current) {
+            if route.assets.contains(&next) {
+                continue;
+            }
+            let hop = match self.best_hop(current, next) {
+                Some(hop) => hop,
+                None => continue,
+            };
+            let rate = match route.rate.checked_mul(&hop.rate) {
+                Some(rate) => rate,
+                None => continue,
+            };
+
+            let prev_rate = route.rate;
+            route.assets.push(next);
+            route.hops.push(hop);
+            route.rate = rate;
+            self.search(to, exhausted, route, best);
+            route.rate = prev_rate;
+            route.hops.pop();
+            route.assets.pop();
+        }
+    }
+
+    /// The assets which have open positions against `asset`, in order.
+    fn neighbors(&self, asset: asset::Id) -> BTreeSet<asset::Id> {
+        self.by_pair
+            .keys()
+            .filter_map(|pair| {
+                if pair.asset_1() == asset {
+                    Some(pair.asset_2())
+                } else if pair.asset_2() == asset {
+                    Some(pair.asset_1())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Find the best-priced open position that can trade `from` into `to`, if any.
+    ///
+    /// Ties are broken in favor of the position with the lowest ID, so that routing is
+    /// deterministic.
+    fn best_hop(&self, from: asset::Id, to: asset::Id) -> Option<Hop> {
+        let pair = TradingPair::canonical_order_for((from, to)).ok()?;
+        let mut best: Option<Hop> = None;
+        for id in self.by_pair.get(&pair)? {
+            let metadata = &self.positions[id];
+            if let Some(hop) = Self::hop(id, metadata, from, to) {
+                if best.map(|best| hop.rate > best.rate).unwrap_or(true) {
+                    best = Some(hop);
+                }
+            }
+        }
+        best
+    }
+
+    fn hop(
+        id: &position::Id,
+        metadata: &position::Metadata,
+        from: asset::Id,
+        to: asset::Id,
+    ) -> Option<Hop> {
+        if metadata.state != position::State::Opened {
+            return None;
+        }
+
+        let pair = metadata.position.pair;
+        let phi = &metadata.position.phi;
+        let (one_to_two, rate, reserves_out) = if (pair.asset_1(), pair.asset_2()) == (from, to) {
+            (true, phi.rate_1_to_2().ok()?, metadata.reserves.r2)
+        } else if (pair.asset_2(), pair.asset_1()) == (from, to) {
+            (false, phi.rate_2_to_1().ok()?, metadata.reserves.r1)
+        } else {
+            return None;
+        };
+
+        // A position that outputs nothing would swallow the input, so never trade against one.
+        if rate == U64x64::ZERO {
+            return None;
+        }
+
+        let capacity = U64x64::from(reserves_out)
+            .checked_div(&rate)
+            .map_or(u64::MAX, |capacity| capacity.round_down());
+        if capacity == 0 {
+            return None;
+        }
+
+        Some(Hop {
+            id: *id,
+            one_to_two,
+            rate,
+            capacity,
+        })
+    }
+
+    /// Fill `input` against the position for the given hop, updating its reserves and returning
+    /// the output.
+    fn fill(&mut self, hop: &Hop, input: u64) -> Result<u64> {
+        let metadata = self
+            .positions
+            .get_mut(&hop.id)
+            .expect("hops are only constructed for positions in the book");
+
+        let delta = if hop.one_to_two {
+            (input, 0)
+        } else {
+            (0, input)
+        };
+        let ((lambda_1, lambda_2), reserves) =
+            metadata.position.phi.fill(delta, &metadata.reserves)?;
+        metadata.reserves = reserves;
+
+        Ok(if hop.one_to_two { lambda_2 } else { lambda_1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::dex::lp::{position::Position, Reserves, TradingFunction};
+
+    use super::*;
+
+    fn asset(denom: &str) -> asset::Id {
+        asset::REGISTRY.parse_denom(denom).unwrap().id()
+    }
+
+    /// A fee-free position selling `reserves` of `to` for `from`, at a price of `price` units
+    /// of `to` per unit of `from`.
+    fn position(from: asset::Id, to: asset::Id, price: u64, reserves: u64) -> position::Metadata {
+        let pair = TradingPair::canonical_order_for((from, to)).unwrap();
+        let (p, q, reserves) = if pair.asset_1() == from {
+            (
+                price,
+                1,
+                Reserves {
+                    r1: 0,
+                    r2: reserves,
+                },
+            )
+        } else {
+            (
+                1,
+                price,
+                Reserves {
+                    r1: reserves,
+                    r2: 0,
+                },
+            )
+        };
+
+        position::Metadata {
+            position: Position {
+                pair,
+                phi: TradingFunction {
+                    fee: U64x64::ZERO,
+                    k: U64x64::ZERO,
+                    p: U64x64::from(p),
+                    q: U64x64::from(q),
+                },
+                nonce: [price as u8; 32],
+            },
+            state: position::State::Opened,
+            reserves,
+        }
+    }
+
+    #[test]
+    fn fills_in_price_order() {
+        let (gm, gn) = (asset("gm"), asset("gn"));
+
+        let mut book = Book::default();
+        book.insert(position(gm, gn, 2, 100));
+        book.insert(position(gm, gn, 3, 30));
+
+        // The first 10 are filled at a price of 3, and the rest at a price of 2.
+        assert_eq!(book.route(gm, gn, 30).unwrap(), 30 + 20 * 2);
+        // The book is now out of liquidity.
+        assert!(book.route(gm, gn, 100).is_err());
+    }
+
+    #[test]
+    fn routes_through_intermediate_assets() {
+        let (gm, gn, cube) = (asset("gm"), asset("gn"), asset("cube"));
+
+        let mut book = Book::default();
+        book.insert(position(gm, gn, 1, 10));
+        book.insert(position(gm, cube, 2, 1_000));
+        book.insert(position(cube, gn, 2, 1_000));
+
+        // The two-hop route has a better price, so it's used for the whole input.
+        assert_eq!(book.route(gm, gn, 10).unwrap(), 40);
+    }
+
+    #[test]
+    fn routes_through_several_intermediate_assets() {
+        let (gm, gn, cube, pizza) = (asset("gm"), asset("gn"), asset("cube"), asset("pizza"));
+
+        let mut book = Book::default();
+        book.insert(position(gm, cube, 2, 1_000));
+        book.insert(position(cube, pizza, 2, 1_000));
+        book.insert(position(pizza, gn, 2, 1_000));
+
+        // The only route to gn passes through both cube and pizza.
+        assert_eq!(book.route(gm, gn, 10).unwrap(), 80);
+    }
+
+    #[test]
+    fn nets_opposing_sides_of_a_batch() {
+        let (gm, gn) = (asset("gm"), asset("gn"));
+        let pair = TradingPair::canonical_order_for((gm, gn)).unwrap();
+        let (asset_1, asset_2) = (pair.asset_1(), pair.asset_2());
+        let book = || {
+            let mut book = Book::default();
+            // Asset 1 sells for 3 of asset 2, and 1 of asset 2 buys 1 of asset 1, so the midpoint
+            // price of asset 1 is 2.
+            book.insert(position(asset_1, asset_2, 3, 1_000));
+            book.insert(position(asset_2, asset_1, 1, 1_000));
+            book
+        };
+
+        // The 30 of asset 2 is matched against 15 of asset 1 at a price of 2, and the remaining 5
+        // of asset 1 are routed at a price of 3.
+        assert_eq!(book().clear(pair, 20, 30).unwrap(), (15, 30 + 15));
+
+        // The 10 of asset 1 is matched against 20 of asset 2 at the same price, and the remaining
+        // 30 of asset 2 are routed at a price of 1.
+        assert_eq!(book().clear(pair, 10, 50).unwrap(), (10 + 30, 20));
+    }
+}
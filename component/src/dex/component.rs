@@ -1,48 +1,162 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::shielded_pool::View as _;
 use crate::{Component, Context};
-use anyhow::Result;
+use anyhow::{Context as _, Result};
+use ark_ff::Zero;
 use async_trait::async_trait;
-use penumbra_chain::genesis;
+use decaf377::Fr;
+use penumbra_chain::{genesis, View as _};
+use penumbra_crypto::{
+    dex::{
+        lp::{
+            position::{self, Position},
+            LpNft, Reserves,
+        },
+        BatchSwapOutputData, TradingPair,
+    },
+    value, FlowCiphertext, Value,
+};
 use penumbra_storage::State;
 use penumbra_transaction::{Action, Transaction};
 use tendermint::abci;
 use tracing::instrument;
 
+use super::{
+    book::Book,
+    flow::{PendingBatch, MAX_SWAPS_PER_BATCH},
+    View as _,
+};
+
 pub struct Dex {
-    _state: State,
+    state: State,
+    /// The encrypted swap inputs for each trading pair submitted during the current block, which
+    /// are cleared as a single batch at the end of the block.
+    swap_flows: BTreeMap<TradingPair, SwapFlow>,
+}
+
+/// The aggregate of the encrypted inputs of the swaps in a batch.
+#[derive(Debug, Default)]
+struct SwapFlow {
+    enc_delta_1: FlowCiphertext,
+    enc_delta_2: FlowCiphertext,
+    swaps: usize,
 }
 
 impl Dex {
-    #[instrument(name = "dex", skip(_state))]
-    pub async fn new(_state: State) -> Self {
-        Self { _state }
+    #[instrument(name = "dex", skip(state))]
+    pub async fn new(state: State) -> Self {
+        Self {
+            state,
+            swap_flows: BTreeMap::new(),
+        }
     }
 }
 
 #[async_trait]
 impl Component for Dex {
-    #[instrument(name = "dex", skip(self, _app_state))]
-    async fn init_chain(&mut self, _app_state: &genesis::AppState) {}
+    #[instrument(name = "dex", skip(self, app_state))]
+    async fn init_chain(&mut self, app_state: &genesis::AppState) {
+        // Swaps are only enabled if the genesis includes a flow encryption key, and the committee
+        // of validators who hold the shares of its decryption key.
+        if let Some(flow_encryption_key) = app_state.flow_encryption_key {
+            let flow_committee = app_state
+                .flow_committee
+                .clone()
+                .expect("genesis must include the flow committee");
+            self.state
+                .set_flow_encryption_key(flow_encryption_key)
+                .await;
+            self.state.set_flow_committee(flow_committee).await;
+        }
+    }
 
     #[instrument(name = "dex", skip(self, _ctx, _begin_block))]
     async fn begin_block(&mut self, _ctx: Context, _begin_block: &abci::request::BeginBlock) {}
 
     #[instrument(name = "dex", skip(_ctx, tx))]
     fn check_tx_stateless(_ctx: Context, tx: &Transaction) -> Result<()> {
-        // It's important to reject all LP actions for now, to prevent
-        // inflation / minting bugs until we implement all required checks
-        // (e.g., minting tokens by withdrawing reserves we don't check)
+        // Each position can only change state once per transaction, so that the stateful checks
+        // for each LP action can be made against the position's state before the transaction.
+        let mut position_ids = BTreeSet::new();
+        // Likewise, each pending batch can only be contributed to once per transaction.
+        let mut batches = BTreeSet::new();
+
         for action in tx.transaction_body.actions.iter() {
+            let position_id = match action {
+                Action::PositionOpen(position_open) => Some(position_open.position.id()),
+                Action::PositionClose(position_close) => Some(position_close.position_id),
+                Action::PositionWithdraw(position_withdraw) => Some(position_withdraw.position_id),
+                Action::PositionRewardClaim(position_reward_claim) => {
+                    Some(position_reward_claim.position_id)
+                }
+                _ => None,
+            };
+            if let Some(position_id) = position_id {
+                if !position_ids.insert(position_id) {
+                    return Err(anyhow::anyhow!(
+                        "position {} is acted on more than once in a single transaction",
+                        position_id
+                    ));
+                }
+            }
+
             match action {
-                Action::PositionOpen { .. }
-                | Action::PositionClose { .. }
-                | Action::PositionWithdraw { .. }
-                | Action::PositionRewardClaim { .. } => {
-                    return Err(anyhow::anyhow!("lp actions not supported yet"));
+                Action::PositionOpen(position_open) => {
+                    let pair = position_open.position.pair;
+                    if pair.asset_1() >= pair.asset_2() {
+                        return Err(anyhow::anyhow!(
+                            "position trading pair must be canonically ordered"
+                        ));
+                    }
+
+                    let reserves = position_open.initial_reserves;
+                    if reserves.r1 == 0 && reserves.r2 == 0 {
+                        return Err(anyhow::anyhow!(
+                            "position must have nonzero initial reserves"
+                        ));
+                    }
+                }
+                Action::Swap(swap) => {
+                    let trading_pair = swap.body.trading_pair;
+                    if trading_pair.asset_1() >= trading_pair.asset_2() {
+                        return Err(anyhow::anyhow!(
+                            "swap trading pair must be canonically ordered"
+                        ));
+                    }
+
+                    // The swap proof and the encryption proofs for its inputs depend on the flow
+                    // encryption key, so they're verified in check_tx_stateful.
                 }
-                // TODO: Swap/SwapClaim actions are currently always rejected
-                // until implementation is complete
-                Action::Swap(..) | Action::SwapClaim(..) => {
-                    return Err(anyhow::anyhow!("swap actions not supported yet"));
+                Action::SwapClaim(swap_claim) => {
+                    let trading_pair = swap_claim.body.trading_pair;
+                    if trading_pair.asset_1() >= trading_pair.asset_2() {
+                        return Err(anyhow::anyhow!(
+                            "swap claim trading pair must be canonically ordered"
+                        ));
+                    }
+                    // The swap claim proof depends on chain state (the batch swap output data
+                    // and the epoch duration), so it's verified in check_tx_stateful.
+                }
+                Action::FlowDecryption(flow_decryption) => {
+                    let trading_pair = flow_decryption.trading_pair;
+                    if !batches.insert((flow_decryption.height, trading_pair)) {
+                        return Err(anyhow::anyhow!(
+                            "the batch for trading pair {:?} at height {} is contributed to more than once in a single transaction",
+                            trading_pair,
+                            flow_decryption.height
+                        ));
+                    }
+
+                    if flow_decryption.delta_1_share.participant_index()
+                        != flow_decryption.delta_2_share.participant_index()
+                    {
+                        return Err(anyhow::anyhow!(
+                            "flow decryption shares must be from the same participant"
+                        ));
+                    }
+                    // The decryption shares are verified against the flow committee and the
+                    // pending batch, so they're verified in check_tx_stateful.
                 }
                 _ => {}
             }
@@ -51,19 +165,576 @@ impl Component for Dex {
         Ok(())
     }
 
-    #[instrument(name = "dex", skip(self, _ctx, _tx))]
-    async fn check_tx_stateful(&self, _ctx: Context, _tx: &Transaction) -> Result<()> {
-        // TODO: Implement for Swap/SwapClaim
+    #[instrument(name = "dex", skip(self, _ctx, tx))]
+    async fn check_tx_stateful(&self, _ctx: Context, tx: &Transaction) -> Result<()> {
+        // Each LP action must apply to a position in the appropriate state. The LP NFTs consumed
+        // and produced by the action are accounted for in the transaction's value balance.
+        for action in tx.transaction_body.actions.iter() {
+            match action {
+                Action::PositionOpen(position_open) => {
+                    let id = position_open.position.id();
+                    if self.state.position_by_id(&id).await?.is_some() {
+                        return Err(anyhow::anyhow!("position {} already exists", id));
+                    }
+                }
+                Action::PositionClose(position_close) => {
+                    self.check_position_state(&position_close.position_id, position::State::Opened)
+                        .await?;
+                }
+                Action::PositionWithdraw(position_withdraw) => {
+                    let metadata = self
+                        .check_position_state(
+                            &position_withdraw.position_id,
+                            position::State::Closed,
+                        )
+                        .await?;
+
+                    // The withdrawal must pay out exactly the reserves recorded for the position.
+                    let r1 = Value {
+                        amount: metadata.reserves.r1,
+                        asset_id: metadata.position.pair.asset_1(),
+                    }
+                    .commit(Fr::zero());
+                    let r2 = Value {
+                        amount: metadata.reserves.r2,
+                        asset_id: metadata.position.pair.asset_2(),
+                    }
+                    .commit(Fr::zero());
+
+                    if position_withdraw.reserves_commitment != r1 + r2 {
+                        return Err(anyhow::anyhow!(
+                            "withdrawn reserves do not match the reserves of position {}",
+                            position_withdraw.position_id
+                        ));
+                    }
+                }
+                Action::PositionRewardClaim(position_reward_claim) => {
+                    self.check_position_state(
+                        &position_reward_claim.position_id,
+                        position::State::Withdrawn,
+                    )
+                    .await?;
+
+                    // There are no retroactive rewards yet, so the claimed rewards must be zero.
+                    if position_reward_claim.rewards_commitment != value::Commitment::default() {
+                        return Err(anyhow::anyhow!(
+                            "claimed rewards for position {} must be zero",
+                            position_reward_claim.position_id
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Swaps must encrypt their inputs to the flow encryption key. The swap NFT is added to the
+        // note commitment tree by the shielded pool, and the inputs are drawn from the
+        // transaction's value balance.
+        let mut swaps_per_pair = BTreeMap::<TradingPair, usize>::new();
+        for swap in tx.swaps() {
+            let flow_encryption_key = self
+                .state
+                .flow_encryption_key()
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("swaps are not enabled on this chain"))?;
+
+            swap.proof
+                .verify(
+                    swap.body.trading_pair,
+                    -swap.body.delta_1_commitment,
+                    -swap.body.delta_2_commitment,
+                    -swap.body.fee_commitment,
+                    swap.body.swap_nft.note_commitment,
+                    swap.body.swap_nft.ephemeral_key,
+                    swap.enc_amount_1,
+                    swap.enc_amount_2,
+                    &flow_encryption_key,
+                )
+                .context("a swap proof did not verify")?;
+
+            // The swap proof links the ciphertexts to the committed amounts, but the range proofs
+            // are what ensure that each limb encrypts a 16-bit value, so that a batch total can't
+            // be corrupted by a malformed ciphertext.
+            swap.enc_amount_1_proof
+                .verify(&swap.enc_amount_1, &flow_encryption_key)
+                .context("the encryption proof for a swap's asset 1 input did not verify")?;
+            swap.enc_amount_2_proof
+                .verify(&swap.enc_amount_2, &flow_encryption_key)
+                .context("the encryption proof for a swap's asset 2 input did not verify")?;
+
+            // Each batch can only aggregate so many swaps before it can no longer be decrypted.
+            let pair = swap.body.trading_pair;
+            let swaps = swaps_per_pair.entry(pair).or_insert_with(|| {
+                self.swap_flows
+                    .get(&pair)
+                    .map(|flow| flow.swaps)
+                    .unwrap_or_default()
+            });
+            *swaps += 1;
+            if *swaps > MAX_SWAPS_PER_BATCH {
+                return Err(anyhow::anyhow!(
+                    "the batch for trading pair {:?} is full",
+                    pair
+                ));
+            }
+        }
+
+        // Flow decryptions must contribute valid decryption shares of a pending batch's inputs,
+        // from a member of the flow committee who hasn't already contributed to that batch.
+        if tx.flow_decryptions().next().is_some() {
+            let flow_committee = self
+                .state
+                .flow_committee()
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("swaps are not enabled on this chain"))?;
+            let pending_batches = self.state.pending_batches().await?;
+
+            for flow_decryption in tx.flow_decryptions() {
+                let batch = pending_batches
+                    .iter()
+                    .find(|batch| {
+                        batch.height == flow_decryption.height
+                            && batch.trading_pair == flow_decryption.trading_pair
+                    })
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "no pending batch for height {} and trading pair {:?}",
+                            flow_decryption.height,
+                            flow_decryption.trading_pair
+                        )
+                    })?;
+
+                let participant_index = flow_decryption.delta_1_share.participant_index();
+                if batch.has_contributed(participant_index) {
+                    return Err(anyhow::anyhow!(
+                        "participant {} has already contributed to the batch",
+                        participant_index
+                    ));
+                }
+
+                flow_decryption
+                    .delta_1_share
+                    .verify(&batch.enc_delta_1, &flow_committee)
+                    .context("the decryption share of a batch's asset 1 input did not verify")?;
+                flow_decryption
+                    .delta_2_share
+                    .verify(&batch.enc_delta_2, &flow_committee)
+                    .context("the decryption share of a batch's asset 2 input did not verify")?;
+            }
+        }
+
+        // Swap claims must claim the output of a batch swap that actually happened. The anchor
+        // and the swap NFT nullifier are checked by the shielded pool, which also adds the output
+        // notes to the note commitment tree.
+        for swap_claim in tx.swap_claims() {
+            let claimed_output_data = swap_claim.body.output_data;
+            let output_data = self
+                .state
+                .output_data(claimed_output_data.height, swap_claim.body.trading_pair)
+                .await?
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "no batch swap output data for height {} and trading pair {:?}",
+                        claimed_output_data.height,
+                        swap_claim.body.trading_pair
+                    )
+                })?;
+
+            if output_data != claimed_output_data {
+                return Err(anyhow::anyhow!(
+                    "claimed batch swap output data does not match recorded output data"
+                ));
+            }
+
+            let epoch_duration = self.state.get_epoch_duration().await?;
+
+            swap_claim
+                .zkproof
+                .verify(
+                    tx.anchor,
+                    swap_claim.value_commitment(),
+                    swap_claim.body.nullifier,
+                    swap_claim.body.trading_pair,
+                    output_data,
+                    epoch_duration,
+                    swap_claim.body.fee.clone(),
+                    swap_claim.body.output_1.note_commitment,
+                    swap_claim.body.output_1.ephemeral_key,
+                    swap_claim.body.output_2.note_commitment,
+                    swap_claim.body.output_2.ephemeral_key,
+                )
+                .context("a swap claim proof did not verify")?;
+        }
+
         Ok(())
     }
 
-    #[instrument(name = "dex", skip(self, _ctx, _tx))]
-    async fn execute_tx(&mut self, _ctx: Context, _tx: &Transaction) {
-        // TODO: implement
+    #[instrument(name = "dex", skip(self, _ctx, tx))]
+    async fn execute_tx(&mut self, _ctx: Context, tx: &Transaction) {
+        for action in tx.transaction_body.actions.iter() {
+            match action {
+                Action::PositionOpen(position_open) => {
+                    let metadata = position::Metadata {
+                        position: position_open.position.clone(),
+                        state: position::State::Opened,
+                        reserves: position_open.initial_reserves,
+                    };
+                    tracing::debug!(id = %metadata.position.id(), "opened position");
+                    self.mint_lpnft(metadata.position.id(), position::State::Opened)
+                        .await;
+                    self.index_open_position(&metadata.position).await;
+                    self.state.put_position(metadata).await;
+                }
+                Action::PositionClose(position_close) => {
+                    self.transition_position(&position_close.position_id, position::State::Closed)
+                        .await;
+                }
+                Action::PositionWithdraw(position_withdraw) => {
+                    self.transition_position(
+                        &position_withdraw.position_id,
+                        position::State::Withdrawn,
+                    )
+                    .await;
+                }
+                Action::PositionRewardClaim(position_reward_claim) => {
+                    self.transition_position(
+                        &position_reward_claim.position_id,
+                        position::State::Claimed,
+                    )
+                    .await;
+                }
+                _ => {}
+            }
+        }
+
+        // Aggregate the encrypted inputs of each swap into its batch, so that only the batch
+        // totals are ever decrypted.
+        for swap in tx.swaps() {
+            let flow = self.swap_flows.entry(swap.body.trading_pair).or_default();
+            flow.enc_delta_1 = flow.enc_delta_1 + swap.enc_amount_1;
+            flow.enc_delta_2 = flow.enc_delta_2 + swap.enc_amount_2;
+            flow.swaps += 1;
+        }
+
+        // Record the decryption shares contributed to each pending batch, which is decrypted at
+        // the end of the block once it has enough of them.
+        if tx.flow_decryptions().next().is_some() {
+            let mut pending_batches = self
+                .state
+                .pending_batches()
+                .await
+                .expect("can read pending batches");
+            for flow_decryption in tx.flow_decryptions() {
+                let participant_index = flow_decryption.delta_1_share.participant_index();
+                if let Some(batch) = pending_batches.iter_mut().find(|batch| {
+                    batch.height == flow_decryption.height
+                        && batch.trading_pair == flow_decryption.trading_pair
+                        && !batch.has_contributed(participant_index)
+                }) {
+                    tracing::debug!(
+                        height = batch.height,
+                        trading_pair = ?batch.trading_pair,
+                        participant_index,
+                        "recorded flow decryption shares"
+                    );
+                    batch
+                        .delta_1_shares
+                        .push(flow_decryption.delta_1_share.clone());
+                    batch
+                        .delta_2_shares
+                        .push(flow_decryption.delta_2_share.clone());
+                }
+            }
+            self.state.set_pending_batches(pending_batches).await;
+        }
     }
 
     #[instrument(name = "dex", skip(self, _ctx, _end_block))]
     async fn end_block(&mut self, _ctx: Context, _end_block: &abci::request::EndBlock) {
-        // TODO: implement
+        let height = self
+            .state
+            .get_block_height()
+            .await
+            .expect("block height must be set");
+
+        let flow_committee = match self
+            .state
+            .flow_committee()
+            .await
+            .expect("can read flow committee")
+        {
+            Some(flow_committee) => flow_committee,
+            // Swaps aren't enabled, so there are no batches to clear.
+            None => return,
+        };
+
+        // Decrypt the total inputs of each pending batch that has enough decryption shares, then
+        // clear the batch, and record the results so that swappers can claim their outputs. The
+        // results are recorded at the height the swaps were submitted, since that's where the
+        // swap NFTs are found.
+        let (ready, pending_batches): (Vec<_>, Vec<_>) = self
+            .state
+            .pending_batches()
+            .await
+            .expect("can read pending batches")
+            .into_iter()
+            .partition(|batch| batch.is_decryptable(&flow_committee));
+        // Batches that the committee hasn't decrypted by their deadline fail, so that their
+        // swappers aren't left waiting forever.
+        let (expired, mut pending_batches): (Vec<_>, Vec<_>) = pending_batches
+            .into_iter()
+            .partition(|batch| batch.is_expired(height));
+        if ready.is_empty() && expired.is_empty() && self.swap_flows.is_empty() {
+            return;
+        }
+
+        for batch in ready {
+            // The number of swaps per batch is limited so that each limb of the totals can be
+            // found in the decryption table, but the totals can still overflow a u64, and a
+            // failure to decrypt mustn't halt the chain. Instead, the batch fails, so that each
+            // swapper can claim a refund of their inputs.
+            let output_data = match batch.decrypt(&flow_committee).await {
+                Ok((delta_1, delta_2)) => {
+                    tracing::debug!(
+                        height = batch.height,
+                        trading_pair = ?batch.trading_pair,
+                        swaps = batch.swaps,
+                        "decrypted batch swap inputs"
+                    );
+                    self.clear_batch(batch.height, batch.trading_pair, delta_1, delta_2)
+                        .await
+                }
+                Err(error) => {
+                    tracing::error!(
+                        height = batch.height,
+                        trading_pair = ?batch.trading_pair,
+                        swaps = batch.swaps,
+                        %error,
+                        "could not decrypt batch swap inputs, refunding the batch"
+                    );
+                    failed_batch(batch.height)
+                }
+            };
+
+            tracing::debug!(trading_pair = ?batch.trading_pair, ?output_data, "cleared batch swap");
+
+            self.state
+                .set_output_data(batch.height, batch.trading_pair, output_data)
+                .await;
+        }
+
+        for batch in expired {
+            tracing::warn!(
+                height = batch.height,
+                trading_pair = ?batch.trading_pair,
+                swaps = batch.swaps,
+                shares = batch.delta_1_shares.len(),
+                threshold = flow_committee.threshold(),
+                "batch swap missed its decryption deadline, refunding the batch"
+            );
+            self.state
+                .set_output_data(batch.height, batch.trading_pair, failed_batch(batch.height))
+                .await;
+        }
+
+        // The batches of swaps submitted during this block await the validators' decryption
+        // shares, which are contributed in later blocks.
+        for (trading_pair, flow) in std::mem::take(&mut self.swap_flows) {
+            pending_batches.push(PendingBatch {
+                height,
+                trading_pair,
+                enc_delta_1: flow.enc_delta_1,
+                enc_delta_2: flow.enc_delta_2,
+                swaps: flow.swaps as u32,
+                delta_1_shares: Vec::new(),
+                delta_2_shares: Vec::new(),
+            });
+        }
+
+        self.state.set_pending_batches(pending_batches).await;
+    }
+}
+
+/// The output data of a batch that couldn't be cleared, which refunds each swapper's inputs.
+fn failed_batch(height: u64) -> BatchSwapOutputData {
+    BatchSwapOutputData {
+        delta_1: 0,
+        delta_2: 0,
+        lambda_1: 0,
+        lambda_2: 0,
+        success: false,
+        height,
+    }
+}
+
+impl Dex {
+    /// Check that the position with the given ID exists and is in the `expected` state, returning
+    /// its metadata.
+    async fn check_position_state(
+        &self,
+        id: &position::Id,
+        expected: position::State,
+    ) -> Result<position::Metadata> {
+        let metadata = self
+            .state
+            .position_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("position {} does not exist", id))?;
+
+        if metadata.state != expected {
+            return Err(anyhow::anyhow!(
+                "position {} is {}, but must be {}",
+                id,
+                metadata.state,
+                expected
+            ));
+        }
+
+        Ok(metadata)
+    }
+
+    /// Move the position with the given ID into the `next` state, burning the LP NFT for its
+    /// previous state and minting the LP NFT for the new one.
+    ///
+    /// Withdrawing a position pays out all of its reserves, leaving it empty.
+    async fn transition_position(&mut self, id: &position::Id, next: position::State) {
+        let mut metadata = self
+            .state
+            .position_by_id(id)
+            .await
+            .expect("can read position")
+            .expect("position was checked to exist");
+
+        tracing::debug!(%id, prev = %metadata.state, %next, "position state transition");
+
+        self.burn_lpnft(*id, metadata.state).await;
+        self.mint_lpnft(*id, next).await;
+
+        if next == position::State::Closed {
+            self.unindex_open_position(&metadata.position).await;
+        }
+
+        metadata.state = next;
+        if next == position::State::Withdrawn {
+            metadata.reserves = Reserves { r1: 0, r2: 0 };
+        }
+        self.state.put_position(metadata).await;
+    }
+
+    async fn mint_lpnft(&mut self, id: position::Id, state: position::State) {
+        let lpnft = LpNft::new(id, state);
+        self.state
+            .register_denom(&lpnft.denom())
+            .await
+            .expect("can register lp nft denom");
+        self.state
+            .update_token_supply(&lpnft.asset_id(), 1)
+            .await
+            .expect("can mint lp nft");
+    }
+
+    async fn burn_lpnft(&mut self, id: position::Id, state: position::State) {
+        self.state
+            .update_token_supply(&LpNft::new(id, state).asset_id(), -1)
+            .await
+            .expect("can burn lp nft");
+    }
+
+    /// Add a newly opened position to the index of open positions on its trading pair, so that
+    /// batches on that pair (or routed through it) can trade against it.
+    async fn index_open_position(&mut self, position: &Position) {
+        let mut open_positions = self
+            .state
+            .open_positions(position.pair)
+            .await
+            .expect("can read open positions");
+        open_positions.push(position.id());
+        self.state
+            .set_open_positions(position.pair, open_positions)
+            .await;
+
+        let mut trading_pairs = self
+            .state
+            .liquid_trading_pairs()
+            .await
+            .expect("can read liquid trading pairs");
+        if !trading_pairs.contains(&position.pair) {
+            trading_pairs.push(position.pair);
+            self.state.set_liquid_trading_pairs(trading_pairs).await;
+        }
+    }
+
+    /// Remove a closed position from the index of open positions on its trading pair.
+    async fn unindex_open_position(&mut self, position: &Position) {
+        let id = position.id();
+        let mut open_positions = self
+            .state
+            .open_positions(position.pair)
+            .await
+            .expect("can read open positions");
+        open_positions.retain(|open_id| *open_id != id);
+
+        if open_positions.is_empty() {
+            let mut trading_pairs = self
+                .state
+                .liquid_trading_pairs()
+                .await
+                .expect("can read liquid trading pairs");
+            trading_pairs.retain(|pair| *pair != position.pair);
+            self.state.set_liquid_trading_pairs(trading_pairs).await;
+        }
+
+        self.state
+            .set_open_positions(position.pair, open_positions)
+            .await;
+    }
+
+    /// Execute the batch of swaps on the given trading pair, returning the batch's output data.
+    ///
+    /// The two sides of the batch are netted against each other, and the residual of the larger
+    /// side is filled against the open positions in price order, possibly routed through
+    /// intermediate assets (see [`Book::clear`]).
+    ///
+    /// A batch which can't be filled fails as a whole, leaving the positions untouched, in which
+    /// case each swapper can claim a refund of their inputs.
+    async fn clear_batch(
+        &mut self,
+        height: u64,
+        trading_pair: TradingPair,
+        delta_1: u64,
+        delta_2: u64,
+    ) -> BatchSwapOutputData {
+        let mut book = Book::load(&self.state, trading_pair)
+            .await
+            .expect("can load open positions");
+
+        match book.clear(trading_pair, delta_1, delta_2) {
+            Ok((lambda_1, lambda_2)) => {
+                for metadata in book.into_positions() {
+                    self.state.put_position(metadata).await;
+                }
+
+                BatchSwapOutputData {
+                    delta_1,
+                    delta_2,
+                    lambda_1,
+                    lambda_2,
+                    success: true,
+                    height,
+                }
+            }
+            Err(e) => {
+                tracing::debug!(?trading_pair, error = ?e, "batch swap could not be filled");
+
+                BatchSwapOutputData {
+                    delta_1,
+                    delta_2,
+                    lambda_1: 0,
+                    lambda_2: 0,
+                    success: false,
+                    height,
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use penumbra_crypto::{dex::TradingPair, FlowCiphertext, FlowCommittee, FlowDecryptionShare};
+use penumbra_eddy::{DecryptionTable, MockDecryptionTable};
+use penumbra_proto::{dex as pb, Protobuf};
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+
+/// The maximum number of swaps on a single trading pair in a single block.
+///
+/// Each limb of a batch's encrypted inputs is the sum of up to this many 16-bit limbs, and
+/// decrypting it requires a discrete log lookup, so this bounds the size of the lookup table.
+pub const MAX_SWAPS_PER_BATCH: usize = 16;
+
+/// The number of blocks after a batch's swaps are submitted within which the flow committee must
+/// contribute enough decryption shares to decrypt it.
+///
+/// A batch that misses this deadline fails, so that its swappers can claim refunds of their
+/// inputs rather than having them locked forever. Since each block adds at most one batch per
+/// trading pair, this also bounds the number of pending batches.
+pub const FLOW_DECRYPTION_DEADLINE: u64 = 32;
+
+/// The size of the decryption table, as a power of two: the sum of [`MAX_SWAPS_PER_BATCH`]
+/// 16-bit limbs fits in `16 + lg(MAX_SWAPS_PER_BATCH)` bits.
+const DECRYPTION_TABLE_BITS: usize = 20;
+
+/// The decryption table is expensive to build, so it's built once, when the first batch is
+/// decrypted, and shared for the life of the process.
+static DECRYPTION_TABLE: Lazy<OnceCell<MockDecryptionTable>> = Lazy::new(OnceCell::new);
+
+async fn decryption_table() -> Result<&'static MockDecryptionTable> {
+    DECRYPTION_TABLE
+        .get_or_try_init(|| async {
+            tracing::info!(
+                bits = DECRYPTION_TABLE_BITS,
+                "building flow decryption table"
+            );
+            let table = MockDecryptionTable::default();
+            table.initialize(DECRYPTION_TABLE_BITS).await?;
+            Ok::<_, anyhow::Error>(table)
+        })
+        .await
+}
+
+/// A batch swap whose total inputs are awaiting decryption by the flow committee.
+///
+/// Each batch collects the validators' decryption shares of its inputs, contributed by
+/// [`FlowDecryption`](penumbra_transaction::action::FlowDecryption) actions, until it has enough
+/// to be decrypted and cleared.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "pb::PendingBatch", into = "pb::PendingBatch")]
+pub struct PendingBatch {
+    /// The height of the block in which the batch's swaps were submitted.
+    pub height: u64,
+    pub trading_pair: TradingPair,
+    pub enc_delta_1: FlowCiphertext,
+    pub enc_delta_2: FlowCiphertext,
+    /// The number of swaps in the batch.
+    pub swaps: u32,
+    pub delta_1_shares: Vec<FlowDecryptionShare>,
+    pub delta_2_shares: Vec<FlowDecryptionShare>,
+}
+
+impl PendingBatch {
+    /// Whether the validator with the given index has contributed its decryption shares.
+    pub fn has_contributed(&self, participant_index: u32) -> bool {
+        self.delta_1_shares
+            .iter()
+            .any(|share| share.participant_index() == participant_index)
+    }
+
+    /// Whether enough decryption shares have been contributed to decrypt the batch.
+    pub fn is_decryptable(&self, committee: &FlowCommittee) -> bool {
+        self.delta_1_shares.len() >= committee.threshold() as usize
+    }
+
+    /// Whether the batch has missed its decryption deadline, as of the given height.
+    pub fn is_expired(&self, height: u64) -> bool {
+        height >= self.height + FLOW_DECRYPTION_DEADLINE
+    }
+
+    /// Decrypt the total inputs of the batch, using the contributed decryption shares.
+    pub async fn decrypt(&self, committee: &FlowCommittee) -> Result<(u64, u64)> {
+        let delta_1 = decrypt(&self.enc_delta_1, &self.delta_1_shares, committee).await?;
+        let delta_2 = decrypt(&self.enc_delta_2, &self.delta_2_shares, committee).await?;
+        Ok((delta_1, delta_2))
+    }
+}
+
+impl Protobuf<pb::PendingBatch> for PendingBatch {}
+
+impl From<PendingBatch> for pb::PendingBatch {
+    fn from(batch: PendingBatch) -> Self {
+        pb::PendingBatch {
+            height: batch.height,
+            trading_pair: Some(batch.trading_pair.into()),
+            enc_delta_1: Some(batch.enc_delta_1.into()),
+            enc_delta_2: Some(batch.enc_delta_2.into()),
+            swaps: batch.swaps,
+            delta_1_shares: batch.delta_1_shares.into_iter().map(Into::into).collect(),
+            delta_2_shares: batch.delta_2_shares.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::PendingBatch> for PendingBatch {
+    type Error = anyhow::Error;
+
+    fn try_from(batch: pb::PendingBatch) -> Result<Self, Self::Error> {
+        Ok(PendingBatch {
+            height: batch.height,
+            trading_pair: batch
+                .trading_pair
+                .ok_or_else(|| anyhow!("missing trading pair"))?
+                .try_into()?,
+            enc_delta_1: batch
+                .enc_delta_1
+                .ok_or_else(|| anyhow!("missing encrypted input of asset 1"))?
+                .try_into()?,
+            enc_delta_2: batch
+                .enc_delta_2
+                .ok_or_else(|| anyhow!("missing encrypted input of asset 2"))?
+                .try_into()?,
+            swaps: batch.swaps,
+            delta_1_shares: batch
+                .delta_1_shares
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_>>()?,
+            delta_2_shares: batch
+                .delta_2_shares
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Threshold-decrypt the aggregate of a batch of flow ciphertexts, by combining the decryption
+/// shares contributed by the members of the committee.
+///
+/// The shares are checked when they're contributed, but they're stored unverified, so they're
+/// verified again here.
+async fn decrypt(
+    ciphertext: &FlowCiphertext,
+    shares: &[FlowDecryptionShare],
+    committee: &FlowCommittee,
+) -> Result<u64> {
+    let decryption_shares = shares
+        .iter()
+        .map(|share| share.verify(ciphertext, committee))
+        .collect::<Result<Vec<_>>>()?;
+
+    let value = ciphertext
+        .0
+        .decrypt(decryption_shares, decryption_table().await?)
+        .await?;
+
+    u64::try_from(value.0).map_err(|_| anyhow!("decrypted flow {} overflows a u64", value.0))
+}
@@ -0,0 +1,95 @@
+use penumbra_crypto::dex::{lp::position, TradingPair};
+use penumbra_proto::{dex as pb, Protobuf};
+use serde::{Deserialize, Serialize};
+
+use super::flow::PendingBatch;
+
+/// A list of positions.
+///
+/// This is a newtype wrapper for a Vec that allows us to define a proto type.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(try_from = "pb::PositionIdList", into = "pb::PositionIdList")]
+pub struct PositionList(pub Vec<position::Id>);
+
+impl Protobuf<pb::PositionIdList> for PositionList {}
+
+impl TryFrom<pb::PositionIdList> for PositionList {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::PositionIdList) -> Result<Self, Self::Error> {
+        Ok(PositionList(
+            msg.position_ids
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        ))
+    }
+}
+
+impl From<PositionList> for pb::PositionIdList {
+    fn from(list: PositionList) -> Self {
+        pb::PositionIdList {
+            position_ids: list.0.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A list of trading pairs.
+///
+/// This is a newtype wrapper for a Vec that allows us to define a proto type.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(try_from = "pb::TradingPairList", into = "pb::TradingPairList")]
+pub struct TradingPairList(pub Vec<TradingPair>);
+
+impl Protobuf<pb::TradingPairList> for TradingPairList {}
+
+impl TryFrom<pb::TradingPairList> for TradingPairList {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::TradingPairList) -> Result<Self, Self::Error> {
+        Ok(TradingPairList(
+            msg.trading_pairs
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        ))
+    }
+}
+
+impl From<TradingPairList> for pb::TradingPairList {
+    fn from(list: TradingPairList) -> Self {
+        pb::TradingPairList {
+            trading_pairs: list.0.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A list of pending batch swaps.
+///
+/// This is a newtype wrapper for a Vec that allows us to define a proto type.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(try_from = "pb::PendingBatchList", into = "pb::PendingBatchList")]
+pub struct PendingBatchList(pub Vec<PendingBatch>);
+
+impl Protobuf<pb::PendingBatchList> for PendingBatchList {}
+
+impl TryFrom<pb::PendingBatchList> for PendingBatchList {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::PendingBatchList) -> Result<Self, Self::Error> {
+        Ok(PendingBatchList(
+            msg.batches
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        ))
+    }
+}
+
+impl From<PendingBatchList> for pb::PendingBatchList {
+    fn from(list: PendingBatchList) -> Self {
+        pb::PendingBatchList {
+            batches: list.0.into_iter().map(Into::into).collect(),
+        }
+    }
+}
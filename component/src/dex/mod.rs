@@ -1,5 +1,14 @@
+mod book;
 mod component;
+mod flow;
+mod list;
 mod metrics;
+mod view;
+
+pub mod state_key;
 
 pub use self::metrics::register_metrics;
+pub use book::Book;
 pub use component::Dex;
+pub use flow::PendingBatch;
+pub use view::View;
@@ -0,0 +1,38 @@
+use penumbra_crypto::dex::{lp::position, TradingPair};
+
+pub fn output_data(height: u64, trading_pair: TradingPair) -> String {
+    format!(
+        "dex/output/{}/{}/{}",
+        height,
+        trading_pair.asset_1(),
+        trading_pair.asset_2()
+    )
+}
+
+pub fn position_by_id(id: &position::Id) -> String {
+    format!("dex/position/{}", id)
+}
+
+pub fn open_positions(trading_pair: TradingPair) -> String {
+    format!(
+        "dex/open_positions/{}/{}",
+        trading_pair.asset_1(),
+        trading_pair.asset_2()
+    )
+}
+
+pub fn liquid_trading_pairs() -> &'static str {
+    "dex/liquid_trading_pairs"
+}
+
+pub fn flow_encryption_key() -> &'static str {
+    "dex/flow_encryption_key"
+}
+
+pub fn flow_committee() -> &'static str {
+    "dex/flow_committee"
+}
+
+pub fn pending_batches() -> &'static str {
+    "dex/pending_batches"
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use penumbra_crypto::{
+    dex::{lp::position, BatchSwapOutputData, TradingPair},
+    FlowCommittee, FlowEncryptionKey,
+};
+use penumbra_storage::StateExt;
+
+use super::{
+    flow::PendingBatch,
+    list::{PendingBatchList, PositionList, TradingPairList},
+    state_key,
+};
+
+impl<T: StateExt> View for T {}
+
+#[async_trait]
+pub trait View: StateExt {
+    /// Get the output data for the batch swap on the given trading pair at the given height, if
+    /// any swaps were submitted for that pair in that block.
+    async fn output_data(
+        &self,
+        height: u64,
+        trading_pair: TradingPair,
+    ) -> Result<Option<BatchSwapOutputData>> {
+        self.get_domain(state_key::output_data(height, trading_pair).into())
+            .await
+    }
+
+    /// Record the output data for the batch swap on the given trading pair at the given height.
+    async fn set_output_data(
+        &self,
+        height: u64,
+        trading_pair: TradingPair,
+        output_data: BatchSwapOutputData,
+    ) {
+        self.put_domain(
+            state_key::output_data(height, trading_pair).into(),
+            output_data,
+        )
+        .await
+    }
+
+    /// Get the metadata recorded for the position with the given ID, if it has been opened.
+    async fn position_by_id(&self, id: &position::Id) -> Result<Option<position::Metadata>> {
+        self.get_domain(state_key::position_by_id(id).into()).await
+    }
+
+    /// Record the metadata for a position, overwriting any previous metadata for it.
+    async fn put_position(&self, metadata: position::Metadata) {
+        self.put_domain(
+            state_key::position_by_id(&metadata.position.id()).into(),
+            metadata,
+        )
+        .await
+    }
+
+    /// Get the IDs of the open positions on the given trading pair.
+    async fn open_positions(&self, trading_pair: TradingPair) -> Result<Vec<position::Id>> {
+        Ok(self
+            .get_domain(state_key::open_positions(trading_pair).into())
+            .await?
+            .map(|list: PositionList| list.0)
+            .unwrap_or_default())
+    }
+
+    async fn set_open_positions(&self, trading_pair: TradingPair, positions: Vec<position::Id>) {
+        self.put_domain(
+            state_key::open_positions(trading_pair).into(),
+            PositionList(positions),
+        )
+        .await
+    }
+
+    /// Get the trading pairs which have at least one open position.
+    async fn liquid_trading_pairs(&self) -> Result<Vec<TradingPair>> {
+        Ok(self
+            .get_domain(state_key::liquid_trading_pairs().into())
+            .await?
+            .map(|list: TradingPairList| list.0)
+            .unwrap_or_default())
+    }
+
+    async fn set_liquid_trading_pairs(&self, trading_pairs: Vec<TradingPair>) {
+        self.put_domain(
+            state_key::liquid_trading_pairs().into(),
+            TradingPairList(trading_pairs),
+        )
+        .await
+    }
+
+    /// Get the key that swap inputs are encrypted to, if swaps are enabled.
+    async fn flow_encryption_key(&self) -> Result<Option<FlowEncryptionKey>> {
+        self.get_domain(state_key::flow_encryption_key().into())
+            .await
+    }
+
+    async fn set_flow_encryption_key(&self, key: FlowEncryptionKey) {
+        self.put_domain(state_key::flow_encryption_key().into(), key)
+            .await
+    }
+
+    /// Get the committee of validators who jointly decrypt each batch of swaps, if swaps are
+    /// enabled.
+    async fn flow_committee(&self) -> Result<Option<FlowCommittee>> {
+        self.get_domain(state_key::flow_committee().into()).await
+    }
+
+    async fn set_flow_committee(&self, committee: FlowCommittee) {
+        self.put_domain(state_key::flow_committee().into(), committee)
+            .await
+    }
+
+    /// Get the batch swaps whose total inputs are awaiting decryption, in the order their swaps
+    /// were submitted.
+    async fn pending_batches(&self) -> Result<Vec<PendingBatch>> {
+        Ok(self
+            .get_domain(state_key::pending_batches().into())
+            .await?
+            .map(|list: PendingBatchList| list.0)
+            .unwrap_or_default())
+    }
+
+    async fn set_pending_batches(&self, batches: Vec<PendingBatch>) {
+        self.put_domain(
+            state_key::pending_batches().into(),
+            PendingBatchList(batches),
+        )
+        .await
+    }
+}
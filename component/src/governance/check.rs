@@ -2,12 +2,15 @@ use anyhow::{Context as _, Result};
 
 use super::proposal;
 use penumbra_transaction::action::{
-    ProposalSubmit, ProposalWithdraw, ProposalWithdrawBody, ValidatorVote, ValidatorVoteBody,
+    DelegatorVote, DelegatorVoteBody, ProposalSubmit, ProposalWithdraw, ProposalWithdrawBody,
+    ValidatorVote, ValidatorVoteBody,
 };
 
 pub mod stateless {
+    use penumbra_chain::params::ChainParameters;
     use penumbra_proto::Protobuf;
-    use penumbra_transaction::action::Proposal;
+    use penumbra_tct as tct;
+    use penumbra_transaction::{action::Proposal, AuthHash};
 
     use super::*;
 
@@ -38,21 +41,55 @@ pub mod stateless {
 
         use penumbra_transaction::action::ProposalPayload::*;
         match payload {
-            Signaling { commit: _ } => { /* all signaling proposals are valid */ }
+            Signaling { commit: _, upgrade } => {
+                if let Some(upgrade) = upgrade {
+                    if upgrade.migration_id.is_empty() {
+                        anyhow::bail!("upgrade plans must name a migration");
+                    }
+                }
+            }
             Emergency { halt_chain: _ } => { /* all emergency proposals are valid */ }
             ParameterChange {
                 effective_height: _,
-                new_parameters: _,
+                new_parameters,
             } => {
-                // TODO: check that new parameters are marked as mutable and within valid bounds
-                anyhow::bail!("parameter change proposals are not yet supported")
+                if new_parameters.is_empty() {
+                    anyhow::bail!("parameter change proposals must change at least one parameter");
+                }
+
+                // Check that each new parameter is mutable and its value is of the right type and
+                // within bounds: whether the changes are consistent with the rest of the chain
+                // parameters can only be checked statefully
+                let mut chain_params = ChainParameters::default();
+                for (name, value) in new_parameters {
+                    chain_params
+                        .set(name, value)
+                        .context("invalid parameter change")?;
+                }
             }
             DaoSpend {
-                schedule_transactions: _,
-                cancel_transactions: _,
+                schedule_transactions,
+                cancel_transactions,
             } => {
-                // TODO: check that scheduled transactions are valid without any witness or auth data
-                anyhow::bail!("DAO spend proposals are not yet supported")
+                if schedule_transactions.is_empty() && cancel_transactions.is_empty() {
+                    anyhow::bail!(
+                        "DAO spend proposals must schedule or cancel at least one transaction"
+                    );
+                }
+
+                // The DAO has no keys, so it can only execute transactions which need no witness
+                // or auth data, whose outputs are minted directly from the treasury
+                for (_, transaction) in schedule_transactions {
+                    if transaction.actions.is_empty() {
+                        anyhow::bail!("scheduled DAO transactions must have at least one output");
+                    }
+                    if transaction.output_plans().count() != transaction.actions.len() {
+                        anyhow::bail!("scheduled DAO transactions may only contain outputs");
+                    }
+                    if transaction.fee.0.amount != 0 {
+                        anyhow::bail!("scheduled DAO transactions may not pay fees");
+                    }
+                }
             }
         }
 
@@ -77,6 +114,38 @@ pub mod stateless {
 
         Ok(())
     }
+
+    pub fn delegator_vote(
+        DelegatorVote {
+            body,
+            auth_sig,
+            proof,
+        }: &DelegatorVote,
+        auth_hash: &AuthHash,
+        anchor: tct::Root,
+    ) -> Result<()> {
+        // Check the signature using the randomized spend authorization key:
+        body.rk
+            .verify(auth_hash.as_ref(), auth_sig)
+            .context("delegator vote auth signature failed to verify")?;
+
+        // Check that the voter held the voted note before voting started:
+        proof
+            .verify(
+                anchor,
+                body.start_position,
+                body.value,
+                body.nullifier,
+                body.rk,
+            )
+            .context("a delegator vote proof did not verify")?;
+
+        // This is stateless verification, so we still need to check that the proposal being voted
+        // on exists and started at the claimed position, that the note hasn't been spent or used to
+        // vote on it already, and that the note is a delegation token.
+
+        Ok(())
+    }
 }
 
 pub mod stateful {
@@ -84,8 +153,9 @@ pub mod stateful {
     use super::*;
     use crate::stake::View as _;
     use penumbra_chain::View as _;
-    use penumbra_crypto::{GovernanceKey, IdentityKey, STAKING_TOKEN_DENOM};
+    use penumbra_crypto::{asset, GovernanceKey, IdentityKey, Nullifier, STAKING_TOKEN_DENOM};
     use penumbra_storage::State;
+    use penumbra_tct as tct;
     use penumbra_transaction::{action::ProposalPayload, AuthHash};
 
     pub async fn proposal_submit(
@@ -110,13 +180,25 @@ pub mod stateful {
         }
 
         match &proposal.payload {
-            ProposalPayload::Signaling { .. } => { /* no stateful checks for signaling */ }
+            ProposalPayload::Signaling { commit: _, upgrade } => {
+                if let Some(upgrade) = upgrade {
+                    height_in_future_of_voting_end(state, upgrade.height).await?;
+                }
+            }
             ProposalPayload::Emergency { .. } => { /* no stateful checks for emergency */ }
             ProposalPayload::ParameterChange {
                 effective_height,
-                new_parameters: _,
+                new_parameters,
             } => {
                 height_in_future_of_voting_end(state, *effective_height).await?;
+
+                // Check that the new parameters are consistent with the current chain parameters
+                // and the changes already scheduled by other proposals (this is checked again
+                // when the proposal passes, since other proposals may pass in the meantime)
+                state
+                    .check_parameter_changes(*effective_height, new_parameters)
+                    .await
+                    .context("invalid parameter change")?;
             }
             ProposalPayload::DaoSpend {
                 schedule_transactions,
@@ -125,10 +207,21 @@ pub mod stateful {
                 for (effective_height, _) in schedule_transactions.iter() {
                     height_in_future_of_voting_end(state, *effective_height).await?;
                 }
+                for (_, transaction) in schedule_transactions.iter() {
+                    if transaction.chain_id != chain_parameters.chain_id {
+                        anyhow::bail!(
+                            "scheduled DAO transaction has chain ID {}, expected {}",
+                            transaction.chain_id,
+                            chain_parameters.chain_id
+                        );
+                    }
+                }
                 for (scheduled_height, _) in cancel_transactions.iter() {
+                    // The transaction to cancel need not be scheduled yet, since another proposal
+                    // still in voting may schedule it; if it isn't scheduled when this proposal is
+                    // enacted, the cancellation is skipped with a warning.
                     height_in_future_of_voting_end(state, *scheduled_height).await?;
                 }
-                // TODO: check that all transactions to cancel exist already and match auth hash
             }
         }
 
@@ -279,4 +372,80 @@ pub mod stateful {
 
         Ok(())
     }
+
+    pub async fn delegator_vote(
+        state: &State,
+        DelegatorVote {
+            body:
+                DelegatorVoteBody {
+                    proposal,
+                    start_position,
+                    vote: _, // All votes are valid, so we don't need to do anything with this
+                    value,
+                    nullifier,
+                    rk: _, // We already checked this in stateless verification
+                },
+            auth_sig: _, // We already checked this in stateless verification
+            proof: _,    // We already checked this in stateless verification
+        }: &DelegatorVote,
+    ) -> Result<()> {
+        proposal_voteable(state, *proposal).await?;
+        start_position_matches_proposal(state, *proposal, *start_position).await?;
+        nullifier_has_not_voted(state, *proposal, *nullifier).await?;
+        value_is_delegation_token(state, value.asset_id).await?;
+        Ok(())
+    }
+
+    async fn start_position_matches_proposal(
+        state: &State,
+        proposal_id: u64,
+        start_position: tct::Position,
+    ) -> Result<()> {
+        if let Some(proposal_start_position) =
+            state.proposal_voting_start_position(proposal_id).await?
+        {
+            if start_position != proposal_start_position {
+                anyhow::bail!(
+                    "delegator vote start position {} does not match start position {} of proposal {}",
+                    u64::from(start_position),
+                    u64::from(proposal_start_position),
+                    proposal_id
+                );
+            }
+        } else {
+            anyhow::bail!("proposal {} has no start position", proposal_id);
+        }
+
+        Ok(())
+    }
+
+    async fn nullifier_has_not_voted(
+        state: &State,
+        proposal_id: u64,
+        nullifier: Nullifier,
+    ) -> Result<()> {
+        if let Some(_vote) = state
+            .delegator_vote_by_nullifier(proposal_id, nullifier)
+            .await?
+        {
+            anyhow::bail!(
+                "nullifier {} has already been used to vote on proposal {}",
+                nullifier,
+                proposal_id
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn value_is_delegation_token(state: &State, asset_id: asset::Id) -> Result<()> {
+        if state.delegation_token_validator(asset_id).await?.is_none() {
+            anyhow::bail!(
+                "asset {} is not the delegation token of any validator",
+                asset_id
+            );
+        }
+
+        Ok(())
+    }
 }
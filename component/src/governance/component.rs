@@ -1,14 +1,18 @@
+use std::collections::BTreeSet;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use penumbra_chain::genesis;
+use penumbra_crypto::{asset, Value};
 use penumbra_storage::State;
 use penumbra_transaction::Transaction;
 use tendermint::abci;
 use tracing::instrument;
 
+use crate::shielded_pool::View as _;
 use crate::{Component, Context};
 
-use super::{check, execute};
+use super::{check, execute, View as _};
 
 pub struct Governance {
     state: State,
@@ -22,8 +26,25 @@ impl Governance {
 
 #[async_trait]
 impl Component for Governance {
-    #[instrument(name = "governance", skip(self, _app_state))]
-    async fn init_chain(&mut self, _app_state: &genesis::AppState) {}
+    #[instrument(name = "governance", skip(self, app_state))]
+    async fn init_chain(&mut self, app_state: &genesis::AppState) {
+        for balance in &app_state.dao_balances {
+            let denom = asset::REGISTRY
+                .parse_denom(&balance.denom)
+                .expect("genesis DAO balances must have valid denominations");
+            self.state
+                .register_denom(&denom)
+                .await
+                .expect("can register DAO balance denomination");
+            self.state
+                .dao_deposit(Value {
+                    amount: balance.amount,
+                    asset_id: denom.id(),
+                })
+                .await
+                .expect("can deposit genesis DAO balance");
+        }
+    }
 
     #[instrument(name = "governance", skip(self, _ctx, _begin_block))]
     async fn begin_block(&mut self, _ctx: Context, _begin_block: &abci::request::BeginBlock) {}
@@ -39,10 +60,17 @@ impl Component for Governance {
         for validator_vote in tx.validator_votes() {
             check::stateless::validator_vote(validator_vote)?;
         }
-        // TODO: fill in when delegator votes happen
-        // for delegator_vote in tx.delegator_votes() {
-        //     check::stateless::delegator_vote(delegator_vote)?;
-        // }
+
+        let auth_hash = tx.transaction_body().auth_hash();
+        let mut voted_nullifiers = BTreeSet::new();
+        for delegator_vote in tx.delegator_votes() {
+            check::stateless::delegator_vote(delegator_vote, &auth_hash, tx.anchor)?;
+
+            // Check the note has not been used to vote already in this transaction.
+            if !voted_nullifiers.insert(delegator_vote.body.nullifier) {
+                anyhow::bail!("note used to vote more than once in the same transaction");
+            }
+        }
 
         Ok(())
     }
@@ -60,10 +88,9 @@ impl Component for Governance {
         for validator_vote in tx.validator_votes() {
             check::stateful::validator_vote(&self.state, validator_vote).await?;
         }
-        // TODO: fill in when delegator votes happen
-        // for delegator_vote in tx.delegator_votes() {
-        //     check::stateful::delegator_vote(&self.state, delegator_vote).await?;
-        // }
+        for delegator_vote in tx.delegator_votes() {
+            check::stateful::delegator_vote(&self.state, delegator_vote).await?;
+        }
 
         Ok(())
     }
@@ -79,10 +106,9 @@ impl Component for Governance {
         for validator_vote in tx.validator_votes() {
             execute::validator_vote(&self.state, validator_vote).await;
         }
-        // TODO: fill in when delegator votes happen
-        // for delegator_vote in tx.delegator_votes() {
-        //     execute::delegator_vote(&self.state, delegator_vote).await;
-        // }
+        for delegator_vote in tx.delegator_votes() {
+            execute::delegator_vote(&self.state, delegator_vote).await;
+        }
     }
 
     #[instrument(name = "governance", skip(self, _ctx, _end_block))]
@@ -90,5 +116,6 @@ impl Component for Governance {
         // TODO: compute intermediate tallies at epoch boundaries (with threshold delegator voting)
         execute::enact_all_passed_proposals(&self.state).await;
         execute::enact_pending_parameter_changes(&self.state).await;
+        execute::halt_for_scheduled_upgrade(&self.state).await;
     }
 }
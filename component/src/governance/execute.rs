@@ -2,10 +2,11 @@ use crate::governance::proposal::Outcome;
 
 use super::{proposal, tally, View as _};
 use penumbra_chain::View as _;
+use penumbra_crypto::{Value, STAKING_TOKEN_ASSET_ID};
 use penumbra_storage::State;
 use penumbra_transaction::action::{
-    ProposalPayload, ProposalSubmit, ProposalWithdraw, ProposalWithdrawBody, ValidatorVote,
-    ValidatorVoteBody,
+    DelegatorVote, DelegatorVoteBody, ProposalPayload, ProposalSubmit, ProposalWithdraw,
+    ProposalWithdrawBody, ValidatorVote, ValidatorVoteBody,
 };
 use tracing::instrument;
 
@@ -59,6 +60,10 @@ pub async fn proposal_submit(
         .put_proposal_voting_start(proposal_id, current_block)
         .await;
     state.put_proposal_voting_end(proposal_id, voting_end).await;
+    state
+        .put_proposal_start_rates(proposal_id)
+        .await
+        .expect("can record rates at the start of voting");
 
     tracing::debug!(proposal = %proposal_id, "created proposal");
 }
@@ -105,8 +110,35 @@ pub async fn validator_vote(
     tracing::debug!(proposal = %proposal, "cast validator vote");
 }
 
-// TODO: fill in when delegator votes happen
-// pub async fn delegator_vote(state: &State, delegator_vote: &DelegatorVote) {}
+#[instrument(skip(state))]
+pub async fn delegator_vote(
+    state: &State,
+    DelegatorVote {
+        auth_sig: _,
+        proof: _,
+        body:
+            DelegatorVoteBody {
+                proposal,
+                start_position: _, // This is only used for checks that the note predates the proposal
+                vote,
+                value,
+                nullifier,
+                rk: _, // This is only used for checks so that stateless verification can be done on the signature
+            },
+    }: &DelegatorVote,
+) {
+    let identity_key = state
+        .delegation_token_validator(value.asset_id)
+        .await
+        .expect("can look up delegation token")
+        .expect("delegator vote is for a delegation token");
+
+    state
+        .cast_delegator_vote(*proposal, identity_key, *vote, value.amount, *nullifier)
+        .await;
+
+    tracing::debug!(proposal = %proposal, "cast delegator vote");
+}
 
 #[instrument(skip(state))]
 pub async fn enact_all_passed_proposals(state: &State) {
@@ -146,7 +178,19 @@ pub async fn enact_all_passed_proposals(state: &State) {
                     .await
                     .expect("can add proposal refund");
             } else {
-                tracing::debug!(proposal = %proposal_id, "burning proposal deposit for vetoed proposal");
+                tracing::debug!(proposal = %proposal_id, "sending proposal deposit for vetoed proposal to the DAO");
+                let amount = state
+                    .proposal_deposit_amount(proposal_id)
+                    .await
+                    .expect("can get proposal deposit amount")
+                    .expect("deposit amount must exist for proposal");
+                state
+                    .dao_deposit(Value {
+                        asset_id: *STAKING_TOKEN_ASSET_ID,
+                        amount,
+                    })
+                    .await
+                    .expect("can deposit into DAO treasury");
             }
 
             // If the proposal passes, enact it now
@@ -180,58 +224,130 @@ async fn enact_proposal(state: &State, proposal_id: u64) {
         .expect("proposal payload is present");
 
     match payload {
-        ProposalPayload::Signaling { .. } => {
-            // Nothing to do for signaling proposals
+        ProposalPayload::Signaling { commit: _, upgrade } => {
+            // Signaling proposals have no effect, except to schedule an upgrade if they include one
+            if let Some(upgrade) = upgrade {
+                tracing::info!(proposal = %proposal_id, height = %upgrade.height, migration_id = %upgrade.migration_id, "scheduling upgrade");
+                state.schedule_upgrade(upgrade).await;
+            }
         }
         ProposalPayload::Emergency { halt_chain } => {
-            let height = state
-                .get_block_height()
-                .await
-                .expect("can get block height");
-
-            // If the proposal calls to halt the chain...
+            // If the proposal calls to halt the chain, mark the chain as halted: the current block
+            // will still be committed, but no further blocks will be executed until the operator
+            // explicitly resumes the chain
             if halt_chain {
-                // Check to see if the operator has set the environment variable indicating they
-                // wish to resume from this particular chain halt, i.e. the chain has already halted
-                // and they are bringing it back up again
-                if std::env::var("PD_RESUME_FROM_EMERGENCY_HALT_PROPOSAL")
-                    .ok()
-                    .and_then(|v| v.parse::<u64>().ok()) // value of var must be number
-                    .filter(|&resume_from| resume_from == proposal_id) // number must be this proposal's id (to prevent an always-on resume functionality)
-                    .is_some()
-                {
-                    // If so, just print an information message, and don't halt the chain
-                    tracing::info!(proposal = %proposal_id, %height, "resuming from emergency chain halt");
-                } else {
-                    // If not, print an informational message and immediately exit the process
-                    tracing::error!(proposal = %proposal_id, %height, "emergency proposal passed, calling for immediate chain halt");
-                    std::process::exit(0);
-                }
+                let height = state
+                    .get_block_height()
+                    .await
+                    .expect("can get block height");
+                tracing::error!(proposal = %proposal_id, %height, "emergency proposal passed, halting chain after this block");
+                state.put_chain_halted(true).await;
             }
         }
         ProposalPayload::ParameterChange {
-            effective_height: _,
-            new_parameters: _,
+            effective_height,
+            new_parameters,
         } => {
-            // TODO: schedule the parameter change for the effective height
-            todo!("implement parameter change execution")
+            // Don't schedule changes which conflict with those scheduled by proposals that passed
+            // since this one was submitted, so that the earlier changes are still enacted
+            if let Err(error) = state
+                .check_parameter_changes(effective_height, &new_parameters)
+                .await
+            {
+                tracing::warn!(proposal = %proposal_id, %effective_height, %error, "not scheduling conflicting parameter changes");
+                return;
+            }
+
+            // Schedule the parameter changes to be enacted at the end of the effective height
+            tracing::debug!(proposal = %proposal_id, %effective_height, "scheduling parameter changes");
+            state
+                .schedule_parameter_changes(effective_height, new_parameters)
+                .await
+                .expect("can schedule parameter changes");
         }
         ProposalPayload::DaoSpend {
-            schedule_transactions: _,
-            cancel_transactions: _,
+            schedule_transactions,
+            cancel_transactions,
         } => {
-            // TODO: schedule transaction cancellations by removing the first matching one from the
-            // front of the schedule for their effective block
-            // TODO: schedule new transactions by appending them to the end of the schedule for their
-            // effective block
-            // TODO: don't forget to fill in the part in the shielded pool where the transactions
-            // actually get included in a block
-            todo!("implement daospend execution")
+            // Cancel transactions by removing the first matching one from the front of the schedule
+            // for their effective block
+            for (scheduled_height, auth_hash) in cancel_transactions {
+                if !state
+                    .cancel_dao_transaction(scheduled_height, auth_hash)
+                    .await
+                    .expect("can cancel DAO transaction")
+                {
+                    tracing::warn!(proposal = %proposal_id, %scheduled_height, ?auth_hash, "DAO transaction to cancel is not scheduled");
+                }
+            }
+
+            // Schedule new transactions by appending them to the end of the schedule for their
+            // effective block: the shielded pool executes them at the end of that block
+            for (effective_height, transaction) in schedule_transactions {
+                tracing::debug!(proposal = %proposal_id, %effective_height, "scheduling DAO transaction");
+                state
+                    .schedule_dao_transaction(effective_height, transaction)
+                    .await
+                    .expect("can schedule DAO transaction");
+            }
         }
     }
 }
 
-pub async fn enact_pending_parameter_changes(_state: &State) {
-    // TODO: read the new parameters for this block, if any, and change the chain params to reflect
-    // them. Parameters should be stored in the state as a map from name to value string.
+#[instrument(skip(state))]
+pub async fn enact_pending_parameter_changes(state: &State) {
+    let height = state
+        .get_block_height()
+        .await
+        .expect("can get block height");
+
+    let changes = state
+        .parameter_changes(height)
+        .await
+        .expect("can get parameter changes");
+    if changes.is_empty() {
+        return;
+    }
+    state.clear_parameter_changes(height).await;
+
+    let chain_params = state
+        .get_chain_params()
+        .await
+        .expect("can get chain params");
+
+    // Apply all the changes for this block at once, or none of them: changes are checked against
+    // those already scheduled when their proposals are submitted and pass, so this should only fail
+    // for changes scheduled before those checks were made
+    match chain_params.with_changes(
+        changes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    ) {
+        Ok(new_chain_params) => {
+            tracing::info!(%height, ?changes, "enacting parameter changes");
+            state.put_chain_params(new_chain_params).await;
+        }
+        Err(error) => {
+            tracing::error!(%height, ?changes, %error, "not enacting invalid parameter changes");
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn halt_for_scheduled_upgrade(state: &State) {
+    let height = state
+        .get_block_height()
+        .await
+        .expect("can get block height");
+
+    // If an upgrade is scheduled for this block, halt the chain after it is committed: each node
+    // operator must then run the upgrade's migration with `pd migrate` to resume the chain
+    if let Some(upgrade) = state
+        .upgrade_plan(height)
+        .await
+        .expect("can get upgrade plan")
+    {
+        tracing::error!(%height, migration_id = %upgrade.migration_id, "halting chain after this block for scheduled upgrade");
+        state.put_chain_halted(true).await;
+    }
 }
@@ -1,4 +1,4 @@
-use penumbra_crypto::IdentityKey;
+use penumbra_crypto::{asset, IdentityKey, Nullifier};
 
 pub fn latest_proposal_id() -> &'static str {
     "governance/latest_proposal_id"
@@ -36,14 +36,51 @@ pub fn proposal_voting_end(proposal_id: u64) -> String {
     format!("governance/proposal/{}/voting_end", proposal_id)
 }
 
+pub fn proposal_base_rate(proposal_id: u64) -> String {
+    format!("governance/proposal/{}/base_rate", proposal_id)
+}
+
+pub fn proposal_validator_rate(proposal_id: u64, identity_key: IdentityKey) -> String {
+    format!(
+        "governance/proposal/{}/validator_rate/{}",
+        proposal_id, identity_key
+    )
+}
+
+/// The prefix of the indexed keys of unfinished proposals.
 pub fn unfinished_proposals() -> &'static str {
-    "governance/unfinished_proposals"
+    "governance/unfinished_proposals/"
+}
+
+pub fn unfinished_proposal(proposal_id: u64) -> String {
+    // Zero-pad the proposal ID, so that the keys are ordered by it.
+    format!("{}{:020}", unfinished_proposals(), proposal_id)
 }
 
 pub fn proposal_refunds(block_height: u64) -> String {
     format!("governance/proposal_refunds/{}", block_height)
 }
 
+pub fn all_parameter_changes() -> &'static str {
+    "governance/parameter_changes/"
+}
+
+pub fn parameter_changes(block_height: u64) -> String {
+    format!("{}{}", all_parameter_changes(), block_height)
+}
+
+pub fn upgrade_plan(block_height: u64) -> String {
+    format!("governance/upgrade_plan/{}", block_height)
+}
+
+pub fn dao_balance(asset_id: &asset::Id) -> String {
+    format!("governance/dao/balance/{}", asset_id)
+}
+
+pub fn dao_transactions(block_height: u64) -> String {
+    format!("governance/dao/transactions/{}", block_height)
+}
+
 pub fn proposal_withdrawal_key(proposal_id: u64) -> String {
     format!("governance/proposal/{}/withdraw_key", proposal_id)
 }
@@ -58,3 +95,24 @@ pub fn validator_vote(proposal_id: u64, identity_key: IdentityKey) -> String {
         proposal_id, identity_key
     )
 }
+
+pub fn delegator_voting_validators(proposal_id: u64) -> String {
+    format!(
+        "governance/proposal/{}/delegator_voting_validators",
+        proposal_id
+    )
+}
+
+pub fn delegator_votes(proposal_id: u64, identity_key: IdentityKey) -> String {
+    format!(
+        "governance/proposal/{}/delegator_votes/{}",
+        proposal_id, identity_key
+    )
+}
+
+pub fn voted_nullifier(proposal_id: u64, nullifier: Nullifier) -> String {
+    format!(
+        "governance/proposal/{}/voted_nullifiers/{}",
+        proposal_id, nullifier
+    )
+}
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use num_rational::Ratio;
 use penumbra_chain::params::ChainParameters;
+use penumbra_crypto::IdentityKey;
+use penumbra_proto::{governance as pb, Protobuf};
 use penumbra_transaction::action::Vote;
+use serde::{Deserialize, Serialize};
 
 use super::{proposal::Withdrawn, View as _};
 use crate::stake::View as _;
@@ -50,14 +53,19 @@ impl Circumstance {
     }
 }
 
-/// Intermediate structure used for tallying votes for a particular proposal.
-#[derive(Debug, Clone)]
+/// The total weight of the votes cast for each option on a proposal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "pb::Tally", into = "pb::Tally")]
 pub struct Tally {
-    yes: u64,
-    no: u64,
-    no_with_veto: u64,
-    abstain: u64,
-    // Constant during tallying:
+    pub yes: u64,
+    pub no: u64,
+    pub no_with_veto: u64,
+    pub abstain: u64,
+}
+
+/// The context in which a proposal's tally is evaluated, which is constant during tallying.
+#[derive(Debug, Clone)]
+struct Evaluation {
     circumstance: Circumstance,
     ending_block: u64,
     withdrawn: Withdrawn,
@@ -65,64 +73,66 @@ pub struct Tally {
 }
 
 impl Tally {
-    pub fn new(
-        circumstance: Circumstance,
-        ending_block: u64,
-        withdrawn: Withdrawn,
-        emergency: bool,
-    ) -> Self {
-        Self {
-            yes: 0,
-            no: 0,
-            no_with_veto: 0,
-            abstain: 0,
-            circumstance,
-            ending_block,
-            withdrawn,
-            emergency,
-        }
-    }
-
+    /// Add the given weight to the total for a vote.
+    ///
+    /// Totals saturate rather than overflow, since no honest tally can exceed the total supply
+    /// of the staking token.
     pub fn add(&mut self, vote: Vote, power: u64) {
-        *match vote {
+        let total = match vote {
             Vote::Yes => &mut self.yes,
             Vote::No => &mut self.no,
             Vote::NoWithVeto => &mut self.no_with_veto,
             Vote::Abstain => &mut self.abstain,
-        } += power;
+        };
+        *total = total.saturating_add(power);
     }
 
     pub fn total(&self) -> u64 {
-        self.yes + self.no + self.no_with_veto + self.abstain
+        self.total_without_abstain().saturating_add(self.abstain)
     }
 
     pub fn total_without_abstain(&self) -> u64 {
-        self.yes + self.no + self.no_with_veto
+        self.yes
+            .saturating_add(self.no)
+            .saturating_add(self.no_with_veto)
+    }
+
+    /// Iterate over the weight cast for each vote.
+    pub fn votes(&self) -> impl Iterator<Item = (Vote, u64)> {
+        [
+            (Vote::Yes, self.yes),
+            (Vote::No, self.no),
+            (Vote::Abstain, self.abstain),
+            (Vote::NoWithVeto, self.no_with_veto),
+        ]
+        .into_iter()
     }
 
-    pub fn evaluate(self, parameters: &Parameters) -> Option<Outcome> {
+    fn evaluate(self, evaluation: Evaluation, parameters: &Parameters) -> Option<Outcome> {
+        let Evaluation {
+            circumstance,
+            ending_block,
+            withdrawn,
+            emergency,
+        } = evaluation;
+
         // Are we before the end of normal voting?
-        let before_end = self.circumstance.current_block < self.ending_block;
+        let before_end = circumstance.current_block < ending_block;
 
         // Check to see if proposal is an emergency proposal or if it's at the right height to
         // render an outcome
-        if !self.emergency && before_end {
+        if !emergency && before_end {
             return None;
         }
 
         // Check to see if we've met quorum
-        if Ratio::new(self.total(), self.circumstance.total_voting_power) < parameters.valid_quorum
-        {
-            return Some(Outcome::Failed {
-                withdrawn: self.withdrawn,
-            });
+        if Ratio::new(self.total(), circumstance.total_voting_power) < parameters.valid_quorum {
+            return Some(Outcome::Failed { withdrawn });
         }
 
         // Check to see if it has been vetoed
         if Ratio::new(self.no_with_veto, self.total()) > parameters.veto_threshold {
-            return Some(Outcome::Vetoed {
-                withdrawn: self.withdrawn,
-            });
+            return Some(Outcome::Vetoed { withdrawn });
         }
 
         // Calculate the current yes/total-without-abstain ratio, which will be used to determine if
@@ -133,7 +143,7 @@ impl Tally {
         // desired in that situation
 
         // Different logic is used to determine pass threshold depending on emergency/not
-        if self.emergency && before_end {
+        if emergency && before_end {
             // A 2/3 supermajority is required to pass an emergency proposal before it ends normally
             if Ratio::new(2, 3) < ratio_without_abstain {
                 // We might yet reach 2/3 supermajority, but we're not there yet
@@ -144,17 +154,13 @@ impl Tally {
             if parameters.pass_threshold < ratio_without_abstain {
                 // The proposal has failed at this point because it's non-emergency, so we are not
                 // evaluating it mid-proposal
-                return Some(Outcome::Failed {
-                    withdrawn: self.withdrawn,
-                });
+                return Some(Outcome::Failed { withdrawn });
             }
         };
 
         // Ensure that we never pass a withdrawn proposal
-        if matches!(self.withdrawn, Withdrawn::WithReason { .. }) {
-            return Some(Outcome::Failed {
-                withdrawn: self.withdrawn,
-            });
+        if matches!(withdrawn, Withdrawn::WithReason { .. }) {
+            return Some(Outcome::Failed { withdrawn });
         }
 
         // If all the checked above didn't return early, only now can we pass the proposal
@@ -162,6 +168,32 @@ impl Tally {
     }
 }
 
+impl Protobuf<pb::Tally> for Tally {}
+
+impl From<Tally> for pb::Tally {
+    fn from(tally: Tally) -> Self {
+        pb::Tally {
+            yes: tally.yes,
+            no: tally.no,
+            abstain: tally.abstain,
+            no_with_veto: tally.no_with_veto,
+        }
+    }
+}
+
+impl TryFrom<pb::Tally> for Tally {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::Tally) -> Result<Self, Self::Error> {
+        Ok(Tally {
+            yes: msg.yes,
+            no: msg.no,
+            abstain: msg.abstain,
+            no_with_veto: msg.no_with_veto,
+        })
+    }
+}
+
 impl Parameters {
     pub async fn tally(
         &self,
@@ -189,21 +221,96 @@ impl Parameters {
             .await?
             .ok_or_else(|| anyhow::anyhow!("missing proposal end block"))?;
 
-        // Initialize a tally for this proposal
-        let mut tally = Tally::new(circumstance, ending_block, withdrawn, emergency);
-
-        for identity_key in state.voting_validators(proposal_id).await? {
-            let vote = state
-                .validator_vote(proposal_id, identity_key)
-                .await?
-                .expect("validator voted");
-            let power = state
-                .validator_power(&identity_key)
-                .await?
-                .expect("validator has a power");
+        let evaluation = Evaluation {
+            circumstance,
+            ending_block,
+            withdrawn,
+            emergency,
+        };
+
+        Ok(votes(state, proposal_id).await?.evaluate(evaluation, self))
+    }
+}
+
+/// Tally the votes cast on a proposal so far, in units of voting power.
+///
+/// Delegators who voted override the vote of their validator for the portion of its power that
+/// they delegated.
+pub async fn votes(state: &State, proposal_id: u64) -> Result<Tally> {
+    let mut tally = Tally::default();
+
+    // Tally the votes of validators, less the power of any of their delegators who voted
+    // themselves, overriding their validator's vote for that portion of its power
+    for identity_key in state.voting_validators(proposal_id).await? {
+        let vote = state
+            .validator_vote(proposal_id, identity_key)
+            .await?
+            .expect("validator voted");
+        let power = state
+            .validator_power(&identity_key)
+            .await?
+            .expect("validator has a power");
+        let delegator_power = delegator_power(state, proposal_id, identity_key)
+            .await?
+            .total();
+        tally.add(vote, power.saturating_sub(delegator_power));
+    }
+
+    // Tally the votes of delegators, whether or not their validator voted
+    for identity_key in state.delegator_voting_validators(proposal_id).await? {
+        for (vote, power) in delegator_power(state, proposal_id, identity_key)
+            .await?
+            .votes()
+        {
             tally.add(vote, power);
         }
+    }
+
+    Ok(tally)
+}
+
+/// Convert the votes cast by the delegators of a validator on a proposal from amounts of delegation
+/// tokens into voting power.
+///
+/// Delegators of a validator with no voting power (i.e. one not in the active set) have no voting
+/// power either, just as their validator's vote wouldn't count.
+async fn delegator_power(
+    state: &State,
+    proposal_id: u64,
+    identity_key: IdentityKey,
+) -> Result<Tally> {
+    let mut power = Tally::default();
 
-        Ok(tally.evaluate(self))
+    let validator_power = state.validator_power(&identity_key).await?.unwrap_or(0);
+    if validator_power == 0 {
+        return Ok(power);
     }
+
+    // Delegation tokens are converted at the rates in effect when voting started, since only
+    // notes which existed then can vote. Proposals submitted before the rates were recorded use
+    // the current rates instead.
+    let rate_data = match state
+        .proposal_validator_rate(proposal_id, identity_key)
+        .await?
+    {
+        Some(rate_data) => rate_data,
+        None => state
+            .current_validator_rate(&identity_key)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("missing rate data for validator {}", identity_key))?,
+    };
+    let base_rate_data = match state.proposal_base_rate(proposal_id).await? {
+        Some(base_rate_data) => base_rate_data,
+        None => state.current_base_rate().await?,
+    };
+
+    for (vote, amount) in state
+        .delegator_votes(proposal_id, identity_key)
+        .await?
+        .votes()
+    {
+        power.add(vote, rate_data.voting_power(amount, &base_rate_data));
+    }
+
+    Ok(power)
 }
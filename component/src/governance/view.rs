@@ -1,17 +1,26 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use async_trait::async_trait;
+use penumbra_chain::View as _;
 use penumbra_crypto::{
+    asset,
     rdsa::{SpendAuth, VerificationKey},
-    Address, IdentityKey, Value, STAKING_TOKEN_ASSET_ID,
+    Address, DelegationToken, IdentityKey, Nullifier, Value, STAKING_TOKEN_ASSET_ID,
 };
+use penumbra_proto::transaction as pb;
 use penumbra_storage::StateExt;
-use penumbra_transaction::action::{Proposal, ProposalPayload, Vote};
+use penumbra_tct as tct;
+use penumbra_transaction::{
+    action::{Proposal, ProposalPayload, UpgradePlan, Vote},
+    plan::TransactionPlan,
+    AuthHash,
+};
 
+use crate::shielded_pool::View as _;
 use crate::stake::{self, validator, View as _};
 
-use super::{proposal, state_key};
+use super::{proposal, state_key, tally::Tally};
 
 impl<T: StateExt> View for T {}
 
@@ -62,10 +71,37 @@ pub trait View: StateExt {
         )
         .await;
 
+        // Set the list of validators whose delegators have voted to the empty list
+        self.put_domain(
+            state_key::delegator_voting_validators(proposal_id).into(),
+            validator::List::default(),
+        )
+        .await;
+
         // Return the new proposal id
         Ok(proposal_id)
     }
 
+    /// Get a proposal, as submitted.
+    async fn proposal(&self, proposal_id: u64) -> Result<Option<Proposal>> {
+        let title = self
+            .get_proto::<String>(state_key::proposal_title(proposal_id).into())
+            .await?;
+        let description = self
+            .get_proto::<String>(state_key::proposal_description(proposal_id).into())
+            .await?;
+        let payload = self.proposal_payload(proposal_id).await?;
+
+        Ok(match (title, description, payload) {
+            (Some(title), Some(description), Some(payload)) => Some(Proposal {
+                title,
+                description,
+                payload,
+            }),
+            _ => None,
+        })
+    }
+
     /// Get the proposal payload for a proposal.
     async fn proposal_payload(&self, proposal_id: u64) -> Result<Option<ProposalPayload>> {
         self.get_domain(state_key::proposal_payload(proposal_id).into())
@@ -163,6 +199,234 @@ pub trait View: StateExt {
         Ok(result)
     }
 
+    /// Schedule parameter changes to be enacted at the given block height, merging them with any
+    /// changes already scheduled for that height, which they override.
+    async fn schedule_parameter_changes(
+        &self,
+        block_height: u64,
+        new_parameters: BTreeMap<String, String>,
+    ) -> Result<()> {
+        let mut changes = self.parameter_changes(block_height).await?;
+        changes.extend(new_parameters);
+        self.put_proto_indexed(
+            state_key::parameter_changes(block_height),
+            pb::proposal::ParameterChange {
+                effective_height: block_height,
+                new_parameters: changes
+                    .into_iter()
+                    .map(
+                        |(parameter, value)| pb::proposal::parameter_change::SetParameter {
+                            parameter,
+                            value,
+                        },
+                    )
+                    .collect(),
+            },
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Get the parameter changes scheduled to be enacted at the given block height.
+    async fn parameter_changes(&self, block_height: u64) -> Result<BTreeMap<String, String>> {
+        Ok(self
+            .get_proto::<pb::proposal::ParameterChange>(
+                state_key::parameter_changes(block_height).into(),
+            )
+            .await?
+            .map(|changes| {
+                changes
+                    .new_parameters
+                    .into_iter()
+                    .map(|change| (change.parameter, change.value))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Get all the parameter changes which are scheduled but not yet enacted, by the block height
+    /// at which they will be enacted.
+    async fn scheduled_parameter_changes(&self) -> Result<BTreeMap<u64, BTreeMap<String, String>>> {
+        Ok(self
+            .prefix_proto::<pb::proposal::ParameterChange>(state_key::all_parameter_changes())
+            .await?
+            .into_iter()
+            .map(|(_, changes)| {
+                (
+                    changes.effective_height,
+                    changes
+                        .new_parameters
+                        .into_iter()
+                        .map(|change| (change.parameter, change.value))
+                        .collect(),
+                )
+            })
+            .collect())
+    }
+
+    /// Remove the parameter changes scheduled at the given block height, once they have been
+    /// enacted (or found to be invalid).
+    async fn clear_parameter_changes(&self, block_height: u64) {
+        self.delete_indexed(state_key::parameter_changes(block_height))
+            .await
+    }
+
+    /// Check that scheduling the given parameter changes at the given block height would keep the
+    /// chain parameters consistent: starting from the current parameters, applying each block's
+    /// scheduled changes in order, with these merged into those for their block, must never
+    /// produce invalid parameters.
+    async fn check_parameter_changes(
+        &self,
+        block_height: u64,
+        new_parameters: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        let mut scheduled = self.scheduled_parameter_changes().await?;
+        scheduled
+            .entry(block_height)
+            .or_default()
+            .extend(new_parameters.clone());
+
+        let mut params = self.get_chain_params().await?;
+        for (height, changes) in scheduled {
+            params = params
+                .with_changes(
+                    changes
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_str())),
+                )
+                .with_context(|| {
+                    format!("invalid parameter changes scheduled for height {}", height)
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Schedule an upgrade, replacing any upgrade previously scheduled at the same height.
+    async fn schedule_upgrade(&self, upgrade: UpgradePlan) {
+        self.put_domain(state_key::upgrade_plan(upgrade.height).into(), upgrade)
+            .await;
+    }
+
+    /// Get the upgrade scheduled to halt the chain after the given block height, if any.
+    async fn upgrade_plan(&self, block_height: u64) -> Result<Option<UpgradePlan>> {
+        self.get_domain(state_key::upgrade_plan(block_height).into())
+            .await
+    }
+
+    /// Get the balance of the DAO treasury in the given asset.
+    async fn dao_balance(&self, asset_id: &asset::Id) -> Result<u64> {
+        Ok(self
+            .get_proto(state_key::dao_balance(asset_id).into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Deposit the given value into the DAO treasury.
+    async fn dao_deposit(&self, value: Value) -> Result<()> {
+        let balance = self
+            .dao_balance(&value.asset_id)
+            .await?
+            .checked_add(value.amount)
+            .ok_or_else(|| anyhow::anyhow!("DAO treasury balance overflowed"))?;
+        self.put_proto(state_key::dao_balance(&value.asset_id).into(), balance)
+            .await;
+        Ok(())
+    }
+
+    /// Withdraw the given value from the DAO treasury, failing if its balance is insufficient.
+    async fn dao_withdraw(&self, value: Value) -> Result<()> {
+        let balance = self
+            .dao_balance(&value.asset_id)
+            .await?
+            .checked_sub(value.amount)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "insufficient DAO treasury balance to withdraw {} of asset {}",
+                    value.amount,
+                    value.asset_id
+                )
+            })?;
+        self.put_proto(state_key::dao_balance(&value.asset_id).into(), balance)
+            .await;
+        Ok(())
+    }
+
+    /// Get the DAO transactions scheduled to be executed at the given block height, in order.
+    async fn scheduled_dao_transactions(&self, block_height: u64) -> Result<Vec<TransactionPlan>> {
+        self.get_proto::<pb::proposal::DaoSpend>(state_key::dao_transactions(block_height).into())
+            .await?
+            .map(|schedule| schedule.schedule_transactions)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|scheduled| {
+                scheduled
+                    .transaction
+                    .ok_or_else(|| anyhow::anyhow!("missing scheduled DAO transaction"))?
+                    .try_into()
+            })
+            .collect()
+    }
+
+    /// Write the DAO transactions scheduled to be executed at the given block height.
+    async fn put_scheduled_dao_transactions(
+        &self,
+        block_height: u64,
+        transactions: Vec<TransactionPlan>,
+    ) {
+        self.put_proto(
+            state_key::dao_transactions(block_height).into(),
+            pb::proposal::DaoSpend {
+                schedule_transactions: transactions
+                    .into_iter()
+                    .map(|transaction| pb::proposal::dao_spend::ScheduleTransaction {
+                        execute_at_height: block_height,
+                        transaction: Some(transaction.into()),
+                    })
+                    .collect(),
+                cancel_transactions: Vec::new(),
+            },
+        )
+        .await
+    }
+
+    /// Remove the DAO transactions scheduled at the given block height, once they have run.
+    async fn clear_scheduled_dao_transactions(&self, block_height: u64) {
+        self.delete(state_key::dao_transactions(block_height).into())
+            .await
+    }
+
+    /// Schedule a DAO transaction to be executed at the given block height, after any others
+    /// already scheduled for that height.
+    async fn schedule_dao_transaction(
+        &self,
+        block_height: u64,
+        transaction: TransactionPlan,
+    ) -> Result<()> {
+        let mut transactions = self.scheduled_dao_transactions(block_height).await?;
+        transactions.push(transaction);
+        self.put_scheduled_dao_transactions(block_height, transactions)
+            .await;
+        Ok(())
+    }
+
+    /// Cancel the first DAO transaction scheduled at the given block height with the given auth
+    /// hash, returning whether there was one to cancel.
+    async fn cancel_dao_transaction(&self, block_height: u64, auth_hash: AuthHash) -> Result<bool> {
+        let mut transactions = self.scheduled_dao_transactions(block_height).await?;
+        if let Some(index) = transactions
+            .iter()
+            .position(|transaction| transaction.dao_auth_hash() == auth_hash)
+        {
+            transactions.remove(index);
+            self.put_scheduled_dao_transactions(block_height, transactions)
+                .await;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
     /// Get the state of a proposal.
     async fn proposal_state(&self, proposal_id: u64) -> Result<Option<proposal::State>> {
         Ok(self
@@ -173,10 +437,11 @@ pub trait View: StateExt {
     /// Get all the unfinished proposal ids.
     async fn unfinished_proposals(&self) -> Result<BTreeSet<u64>> {
         Ok(self
-            .get_domain::<proposal::ProposalList, _>(state_key::unfinished_proposals().into())
+            .prefix_proto::<u64>(state_key::unfinished_proposals())
             .await?
-            .unwrap_or_default()
-            .proposals)
+            .into_iter()
+            .map(|(_, proposal_id)| proposal_id)
+            .collect())
     }
 
     /// Set the state of a proposal.
@@ -186,30 +451,21 @@ pub trait View: StateExt {
             .await;
 
         // Track the index
-        let mut unfinished_proposals = self
-            .get_domain::<proposal::ProposalList, _>(state_key::unfinished_proposals().into())
-            .await?
-            .unwrap_or_default();
         match &state {
             proposal::State::Voting | proposal::State::Withdrawn { .. } => {
-                // If we're setting the proposal to a non-finished state, track it in our list of
+                // If we're setting the proposal to a non-finished state, track it in our index of
                 // proposals that are not finished
-                unfinished_proposals.proposals.insert(proposal_id);
+                self.put_proto_indexed(state_key::unfinished_proposal(proposal_id), proposal_id)
+                    .await;
             }
             proposal::State::Finished { .. } => {
-                // If we're setting the proposal to a finished state, remove it from our list of
+                // If we're setting the proposal to a finished state, remove it from our index of
                 // proposals that are not finished
-                unfinished_proposals.proposals.remove(&proposal_id);
+                self.delete_indexed(state_key::unfinished_proposal(proposal_id))
+                    .await;
             }
         }
 
-        // Put the modified list back into the state
-        self.put_domain(
-            state_key::unfinished_proposals().into(),
-            unfinished_proposals,
-        )
-        .await;
-
         Ok(())
     }
 
@@ -272,6 +528,107 @@ pub trait View: StateExt {
         .await;
     }
 
+    /// Get the list of validators whose delegators voted on a proposal.
+    async fn delegator_voting_validators(&self, proposal_id: u64) -> Result<Vec<IdentityKey>> {
+        Ok(self
+            .get_domain::<stake::validator::List, _>(
+                state_key::delegator_voting_validators(proposal_id).into(),
+            )
+            .await?
+            .unwrap_or_default()
+            .0)
+    }
+
+    /// Get the votes cast on a proposal by the delegators of a particular validator, weighted by
+    /// their amount of delegation tokens.
+    async fn delegator_votes(&self, proposal_id: u64, identity_key: IdentityKey) -> Result<Tally> {
+        Ok(self
+            .get_domain::<Tally, _>(state_key::delegator_votes(proposal_id, identity_key).into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Get the vote cast on a proposal using the note with the given nullifier, if any.
+    async fn delegator_vote_by_nullifier(
+        &self,
+        proposal_id: u64,
+        nullifier: Nullifier,
+    ) -> Result<Option<Vote>> {
+        Ok(self
+            .get_domain::<Vote, _>(state_key::voted_nullifier(proposal_id, nullifier).into())
+            .await?)
+    }
+
+    /// Record a delegator vote for a proposal, cast with `amount` of the delegation tokens of the
+    /// given validator.
+    async fn cast_delegator_vote(
+        &self,
+        proposal_id: u64,
+        identity_key: IdentityKey,
+        vote: Vote,
+        amount: u64,
+        nullifier: Nullifier,
+    ) {
+        // Record the nullifier, so the same note can't be used to vote again
+        self.put_domain(
+            state_key::voted_nullifier(proposal_id, nullifier).into(),
+            vote,
+        )
+        .await;
+
+        // Add the vote to the running tally for this validator's delegators
+        let mut delegator_votes = self
+            .delegator_votes(proposal_id, identity_key)
+            .await
+            .expect("can fetch delegator votes");
+        delegator_votes.add(vote, amount);
+        self.put_domain(
+            state_key::delegator_votes(proposal_id, identity_key).into(),
+            delegator_votes,
+        )
+        .await;
+
+        // Record the fact that this validator's delegators have voted on this proposal
+        let mut delegator_voting_validators = self
+            .get_domain::<stake::validator::List, _>(
+                state_key::delegator_voting_validators(proposal_id).into(),
+            )
+            .await
+            .expect("can fetch delegator voting validators")
+            .unwrap_or_default();
+        if !delegator_voting_validators.0.contains(&identity_key) {
+            delegator_voting_validators.0.push(identity_key);
+            self.put_domain(
+                state_key::delegator_voting_validators(proposal_id).into(),
+                delegator_voting_validators,
+            )
+            .await;
+        }
+    }
+
+    /// Get the validator whose delegation token has the given asset id, if any.
+    async fn delegation_token_validator(&self, asset_id: asset::Id) -> Result<Option<IdentityKey>> {
+        Ok(self
+            .validator_list()
+            .await?
+            .into_iter()
+            .find(|identity_key| DelegationToken::new(*identity_key).id() == asset_id))
+    }
+
+    /// Get the position of the note commitment tree when voting started on a proposal.
+    ///
+    /// Only notes committed before this position can be used to vote as a delegator.
+    async fn proposal_voting_start_position(
+        &self,
+        proposal_id: u64,
+    ) -> Result<Option<tct::Position>> {
+        if let Some(voting_start) = self.proposal_voting_start(proposal_id).await? {
+            self.nct_position(voting_start).await
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Get the proposal voting end block for a given proposal.
     async fn proposal_voting_start(&self, proposal_id: u64) -> Result<Option<u64>> {
         Ok(self
@@ -288,6 +645,46 @@ pub trait View: StateExt {
         .await
     }
 
+    /// Record the base rate and the rates of all the validators in effect when voting started on a
+    /// proposal, which are used to convert delegator votes into voting power.
+    async fn put_proposal_start_rates(&self, proposal_id: u64) -> Result<()> {
+        self.put_domain(
+            state_key::proposal_base_rate(proposal_id).into(),
+            self.current_base_rate().await?,
+        )
+        .await;
+        for identity_key in self.validator_list().await? {
+            if let Some(rate_data) = self.current_validator_rate(&identity_key).await? {
+                self.put_domain(
+                    state_key::proposal_validator_rate(proposal_id, identity_key).into(),
+                    rate_data,
+                )
+                .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the base rate in effect when voting started on a proposal.
+    async fn proposal_base_rate(
+        &self,
+        proposal_id: u64,
+    ) -> Result<Option<stake::rate::BaseRateData>> {
+        self.get_domain(state_key::proposal_base_rate(proposal_id).into())
+            .await
+    }
+
+    /// Get the rate of a validator in effect when voting started on a proposal, if the validator
+    /// existed then.
+    async fn proposal_validator_rate(
+        &self,
+        proposal_id: u64,
+        identity_key: IdentityKey,
+    ) -> Result<Option<stake::rate::RateData>> {
+        self.get_domain(state_key::proposal_validator_rate(proposal_id, identity_key).into())
+            .await
+    }
+
     /// Get the proposal voting end block for a given proposal.
     async fn proposal_voting_end(&self, proposal_id: u64) -> Result<Option<u64>> {
         Ok(self
@@ -318,3 +715,68 @@ pub trait View: StateExt {
         Ok(total)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use penumbra_chain::params::ChainParameters;
+    use penumbra_storage::Storage;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn parameter_changes_are_checked_against_scheduled_changes() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().to_owned()).await.unwrap();
+        let state = storage.state().await.unwrap();
+        state.put_chain_params(ChainParameters::default()).await;
+        state.put_block_height(0).await;
+
+        let changes = |changes: &[(&str, &str)]| -> BTreeMap<String, String> {
+            changes
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        };
+
+        // Shrink the signing window, leaving room for the current maximum number of missed blocks
+        state
+            .schedule_parameter_changes(10, changes(&[("signed_blocks_window_len", "9600")]))
+            .await
+            .unwrap();
+        assert_eq!(state.scheduled_parameter_changes().await.unwrap().len(), 1);
+
+        // A change which is valid now, but not after the scheduled changes, is rejected, whether
+        // it would be enacted before or after them
+        let too_many_missed = changes(&[("missed_blocks_maximum", "9700")]);
+        assert!(state
+            .check_parameter_changes(5, &too_many_missed)
+            .await
+            .is_err());
+        assert!(state
+            .check_parameter_changes(20, &too_many_missed)
+            .await
+            .is_err());
+
+        // Changes at the same height override the scheduled ones
+        assert!(state
+            .check_parameter_changes(10, &changes(&[("signed_blocks_window_len", "10000")]))
+            .await
+            .is_ok());
+        assert!(state
+            .check_parameter_changes(10, &too_many_missed)
+            .await
+            .is_err());
+
+        state.clear_parameter_changes(10).await;
+        assert!(state
+            .scheduled_parameter_changes()
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(state
+            .check_parameter_changes(20, &too_many_missed)
+            .await
+            .is_ok());
+    }
+}
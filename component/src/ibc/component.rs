@@ -13,11 +13,13 @@ use crate::ibc::transfer::ICS20Transfer;
 use crate::{Component, Context};
 use anyhow::Result;
 use async_trait::async_trait;
+use channel::View as _;
 use client::Ics2Client;
-use ibc::core::ics24_host::identifier::PortId;
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
 use penumbra_chain::{genesis, View as _};
-use penumbra_storage::State;
+use penumbra_storage::{State, StateExt};
 use penumbra_transaction::{Action, Transaction};
+use std::str::FromStr;
 use tendermint::abci;
 use tracing::instrument;
 
@@ -51,6 +53,24 @@ impl IBCComponent {
     }
 }
 
+/// Reads the tokens escrowed on each ICS20 channel, for export to a new genesis.
+pub async fn escrow_balances(state: &State) -> Result<Vec<genesis::EscrowBalance>> {
+    let mut balances = Vec::new();
+    for index in 0..state.get_channel_counter().await? {
+        let channel_id = ChannelId::new(index);
+        if let Some(amount) = state
+            .get_proto::<u64>(state_key::ics20_value_balance(&channel_id).into())
+            .await?
+        {
+            balances.push(genesis::EscrowBalance {
+                channel_id: channel_id.to_string(),
+                amount,
+            });
+        }
+    }
+    Ok(balances)
+}
+
 #[async_trait]
 impl Component for IBCComponent {
     #[instrument(name = "ibc", skip(self, app_state))]
@@ -58,6 +78,32 @@ impl Component for IBCComponent {
         self.client.init_chain(app_state).await;
         self.connection.init_chain(app_state).await;
         self.channel.init_chain(app_state).await;
+
+        // The channels themselves aren't carried over, but their IDs are reserved, so that a
+        // channel opened on the new chain doesn't reset an escrowed balance, and the balances are
+        // found when the chain is exported again.
+        let mut channel_counter = self
+            .state
+            .get_channel_counter()
+            .await
+            .expect("can read channel counter");
+        for escrow in &app_state.ibc_escrow_balances {
+            let channel_id = ChannelId::from_str(&escrow.channel_id)
+                .expect("genesis escrow balances must have valid channel IDs");
+            let index = escrow
+                .channel_id
+                .strip_prefix("channel-")
+                .and_then(|index| index.parse::<u64>().ok())
+                .expect("genesis escrow balances must have channel IDs of the form channel-N");
+            channel_counter = channel_counter.max(index + 1);
+            self.state
+                .put_proto(
+                    state_key::ics20_value_balance(&channel_id).into(),
+                    escrow.amount,
+                )
+                .await;
+        }
+        self.state.put_channel_counter(channel_counter).await;
     }
 
     #[instrument(name = "ibc", skip(self, begin_block, ctx))]
@@ -88,3 +88,7 @@ pub fn packet_commitment_by_port(
         port_id, channel_id, sequence
     )
 }
+
+pub fn ics20_value_balance(channel_id: &ChannelId) -> String {
+    format!("ics20-value-balance/{}", channel_id)
+}
@@ -18,5 +18,5 @@ pub use self::metrics::register_metrics;
 pub use client::{
     validate_penumbra_client_state, ClientConnections, ClientCounter, VerifiedHeights,
 };
-pub use component::IBCComponent;
+pub use component::{escrow_balances, IBCComponent};
 pub use connection::{ConnectionCounter, SUPPORTED_VERSIONS};
@@ -1,3 +1,4 @@
+use crate::ibc::component::state_key;
 use crate::ibc::ibc_handler::{AppHandler, AppHandlerCheck, AppHandlerExecute};
 use crate::Context;
 use anyhow::Result;
@@ -108,7 +109,7 @@ impl AppHandlerCheck for ICS20Transfer {
             let value_balance: u64 = self
                 .state
                 .get_proto::<u64>(
-                    format!("ics20-value-balance/{}", msg.packet.destination_channel).into(),
+                    state_key::ics20_value_balance(&msg.packet.destination_channel).into(),
                 )
                 .await?
                 .ok_or(anyhow::anyhow!("value balance not found"))?;
@@ -136,7 +137,7 @@ impl AppHandlerCheck for ICS20Transfer {
             let value_balance: u64 = self
                 .state
                 .get_proto::<u64>(
-                    format!("ics20-value-balance/{}", msg.packet.destination_channel).into(),
+                    state_key::ics20_value_balance(&msg.packet.destination_channel).into(),
                 )
                 .await?
                 .ok_or(anyhow::anyhow!("value balance not found"))?;
@@ -169,12 +170,12 @@ impl AppHandlerExecute for ICS20Transfer {
     async fn chan_open_try_execute(&mut self, _ctx: Context, _msg: &MsgChannelOpenTry) {}
     async fn chan_open_ack_execute(&mut self, _ctx: Context, msg: &MsgChannelOpenAck) {
         self.state
-            .put_proto::<u64>(format!("ics20-value-balance/{}", msg.channel_id).into(), 0)
+            .put_proto::<u64>(state_key::ics20_value_balance(&msg.channel_id).into(), 0)
             .await;
     }
     async fn chan_open_confirm_execute(&mut self, _ctx: Context, msg: &MsgChannelOpenConfirm) {
         self.state
-            .put_proto::<u64>(format!("ics20-value-balance/{}", msg.channel_id).into(), 0)
+            .put_proto::<u64>(state_key::ics20_value_balance(&msg.channel_id).into(), 0)
             .await;
     }
     async fn chan_close_confirm_execute(&mut self, _ctx: Context, _msg: &MsgChannelCloseConfirm) {}
@@ -6,7 +6,7 @@ use penumbra_chain::genesis;
 use penumbra_transaction::Transaction;
 use std::sync::Arc;
 use std::sync::Mutex;
-use tendermint::abci;
+use tendermint::abci::{self, EventAttributeIndexExt};
 
 pub mod app;
 pub mod dex;
@@ -137,11 +137,20 @@ pub trait Component: Sized {
 /// A context is created by the [`Context::new()`] method. Events can be
 /// accumulated using [`Context::record()`], and finally collected (consuming
 /// the underlying data) in [`Context::into_events()`].
+///
+/// A handle obtained with [`Context::for_component()`] tags the events
+/// recorded through it with the [`EVENT_COMPONENT_KEY`] attribute, so that
+/// clients can tell which component recorded them.
 #[derive(Clone)]
 pub struct Context {
     inner: Arc<Mutex<Option<Vec<abci::Event>>>>,
+    component: Option<&'static str>,
 }
 
+/// The key of the event attribute naming the component which recorded the
+/// event.
+pub const EVENT_COMPONENT_KEY: &str = "component";
+
 impl Default for Context {
     fn default() -> Self {
         Self::new()
@@ -152,10 +161,23 @@ impl Context {
     pub fn new() -> Self {
         Self {
             inner: Arc::new(Mutex::new(Some(Vec::new()))),
+            component: None,
         }
     }
 
-    pub fn record(&self, e: abci::Event) {
+    /// Returns a handle to the same context which tags the events recorded
+    /// through it as recorded by `component`.
+    pub fn for_component(&self, component: &'static str) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            component: Some(component),
+        }
+    }
+
+    pub fn record(&self, mut e: abci::Event) {
+        if let Some(component) = self.component {
+            e.attributes.push((EVENT_COMPONENT_KEY, component).index());
+        }
         self.inner
             .lock()
             .expect("record called after into_events")
@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::{
     governance::View as _,
@@ -160,6 +160,15 @@ impl Component for ShieldedPool {
 
                     spent_nullifiers.insert(spend.body.nullifier);
                 }
+                Action::SwapClaim(swap_claim) => {
+                    // The swap claim proof is verified by the dex, but claiming a swap NFT
+                    // reveals its nullifier, so it can't be double spent in this transaction.
+                    if spent_nullifiers.contains(&swap_claim.body.nullifier) {
+                        return Err(anyhow::anyhow!("Double spend"));
+                    }
+
+                    spent_nullifiers.insert(swap_claim.body.nullifier);
+                }
                 // other actions are handled by other components.
                 _ => {}
             }
@@ -277,7 +286,8 @@ impl Component for ShieldedPool {
             .unwrap();
         }
 
-        // TODO: execute any scheduled DAO spend transactions for this block
+        // Execute any DAO transactions scheduled for this block
+        self.process_dao_transactions().await;
 
         // Schedule all unquarantining that was set up in this block
         self.schedule_unquarantine().await;
@@ -482,6 +492,13 @@ impl ShieldedPool {
         self.state
             .set_nct_block_anchor(height, compact_block.block_root)
             .await;
+        // Write the position of the NCT, if a proposal started in this block, so that delegators
+        // can prove their notes existed before voting started:
+        if compact_block.proposal_started {
+            if let Some(position) = self.note_commitment_tree.position() {
+                self.state.set_nct_position(height, position).await;
+            }
+        }
         // Write the current epoch anchor, if on an epoch boundary:
         if let Some(epoch_root) = compact_block.epoch_root {
             let epoch_duration = self.epoch_duration().await;
@@ -688,6 +705,70 @@ impl ShieldedPool {
             .expect("can mint proposal deposit refund");
         }
     }
+
+    #[instrument(skip(self))]
+    async fn process_dao_transactions(&mut self) {
+        let height = self.height().await;
+
+        for transaction in self
+            .state
+            .scheduled_dao_transactions(height)
+            .await
+            .expect("scheduled DAO transactions can be fetched")
+        {
+            // Total the outputs of the transaction by asset, so that it is executed entirely or not
+            // at all, depending on whether the treasury can fund it
+            let mut totals = BTreeMap::<asset::Id, u64>::new();
+            for output in transaction.output_plans() {
+                let total = totals.entry(output.value.asset_id).or_default();
+                *total = total.saturating_add(output.value.amount);
+            }
+
+            let mut funded = true;
+            for (&asset_id, &amount) in totals.iter() {
+                let balance = self
+                    .state
+                    .dao_balance(&asset_id)
+                    .await
+                    .expect("DAO balance can be fetched");
+                if balance < amount {
+                    tracing::error!(
+                        auth_hash = ?transaction.dao_auth_hash(),
+                        %asset_id,
+                        %amount,
+                        %balance,
+                        "insufficient DAO treasury balance, skipping scheduled DAO transaction"
+                    );
+                    funded = false;
+                    break;
+                }
+            }
+            if !funded {
+                continue;
+            }
+
+            for (asset_id, amount) in totals {
+                self.state
+                    .dao_withdraw(Value { asset_id, amount })
+                    .await
+                    .expect("DAO treasury balance was checked");
+            }
+            for output in transaction.output_plans() {
+                self.mint_note(
+                    output.value,
+                    &output.dest_address,
+                    NoteSource::DaoSpend { height },
+                )
+                .await
+                .expect("can mint DAO transaction output");
+            }
+
+            tracing::debug!(auth_hash = ?transaction.dao_auth_hash(), "executed scheduled DAO transaction");
+        }
+
+        // Skipped transactions are not retried, so nothing scheduled for this height is needed
+        self.state.clear_scheduled_dao_transactions(height).await;
+    }
 }
 
 /// Extension trait providing read/write access to shielded pool data.
@@ -834,6 +915,25 @@ pub trait View: StateExt {
         .await;
     }
 
+    async fn set_nct_position(&self, height: u64, position: tct::Position) {
+        tracing::debug!(?height, ?position, "writing position");
+        self.put_proto(
+            state_key::nct_position_by_height(height).into(),
+            u64::from(position),
+        )
+        .await;
+    }
+
+    /// Get the position of the NCT at the end of the given height, if it was recorded.
+    ///
+    /// This is only recorded for heights at which a proposal started.
+    async fn nct_position(&self, height: u64) -> Result<Option<tct::Position>> {
+        Ok(self
+            .get_proto::<u64>(state_key::nct_position_by_height(height).into())
+            .await?
+            .map(Into::into))
+    }
+
     async fn set_nct_block_anchor(&self, height: u64, nct_block_anchor: tct::builder::block::Root) {
         tracing::debug!(?height, ?nct_block_anchor, "writing block anchor");
 
@@ -1017,3 +1117,227 @@ pub trait View: StateExt {
 }
 
 impl<T: StateExt> View for T {}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::{
+        keys::{SpendKey, SpendKeyBytes},
+        memo::MemoPlaintext,
+    };
+    use penumbra_storage::Storage;
+    use penumbra_transaction::plan::{ActionPlan, OutputPlan, TransactionPlan};
+    use rand_core::OsRng;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::governance::state_key as governance_state_key;
+
+    const HEIGHT: u64 = 10;
+
+    fn address() -> Address {
+        SpendKey::from(SpendKeyBytes([1; 32]))
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into())
+            .0
+    }
+
+    fn gm() -> asset::Id {
+        asset::REGISTRY.parse_denom("gm").unwrap().id()
+    }
+
+    /// Builds a DAO transaction plan which sends each of the given values to the same address.
+    fn dao_transaction(values: &[Value]) -> TransactionPlan {
+        TransactionPlan {
+            actions: values
+                .iter()
+                .map(|&value| {
+                    ActionPlan::Output(OutputPlan::new(
+                        &mut OsRng,
+                        value,
+                        address(),
+                        MemoPlaintext::default(),
+                    ))
+                })
+                .collect(),
+            chain_id: "penumbra-dao-test".to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn state_at_height(dir: &tempfile::TempDir) -> State {
+        let storage = Storage::load(dir.path().to_owned()).await.unwrap();
+        let state = storage.state().await.unwrap();
+        state.put_block_height(HEIGHT).await;
+        state
+    }
+
+    async fn execute(state: &State) {
+        ShieldedPool::new(state.clone(), tct::Tree::new())
+            .await
+            .process_dao_transactions()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn cancel_matches_dao_auth_hash() {
+        let dir = tempdir().unwrap();
+        let state = state_at_height(&dir).await;
+
+        let first = dao_transaction(&[Value {
+            amount: 1,
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        }]);
+        let second = dao_transaction(&[Value {
+            amount: 2,
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        }]);
+        state
+            .schedule_dao_transaction(HEIGHT, first.clone())
+            .await
+            .unwrap();
+        state
+            .schedule_dao_transaction(HEIGHT, second.clone())
+            .await
+            .unwrap();
+
+        // Only a transaction scheduled at the given height with the given auth hash is cancelled
+        assert!(!state
+            .cancel_dao_transaction(HEIGHT + 1, first.dao_auth_hash())
+            .await
+            .unwrap());
+        assert!(state
+            .cancel_dao_transaction(HEIGHT, first.dao_auth_hash())
+            .await
+            .unwrap());
+        assert!(!state
+            .cancel_dao_transaction(HEIGHT, first.dao_auth_hash())
+            .await
+            .unwrap());
+
+        let scheduled = state.scheduled_dao_transactions(HEIGHT).await.unwrap();
+        assert_eq!(scheduled.len(), 1);
+        assert_eq!(scheduled[0].dao_auth_hash(), second.dao_auth_hash());
+    }
+
+    #[tokio::test]
+    async fn executes_funded_transactions_and_clears_schedule() {
+        let dir = tempdir().unwrap();
+        let state = state_at_height(&dir).await;
+
+        state
+            .dao_deposit(Value {
+                amount: 100,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            })
+            .await
+            .unwrap();
+        state
+            .schedule_dao_transaction(
+                HEIGHT,
+                dao_transaction(&[
+                    Value {
+                        amount: 30,
+                        asset_id: *STAKING_TOKEN_ASSET_ID,
+                    },
+                    Value {
+                        amount: 40,
+                        asset_id: *STAKING_TOKEN_ASSET_ID,
+                    },
+                ]),
+            )
+            .await
+            .unwrap();
+
+        execute(&state).await;
+
+        assert_eq!(
+            state.dao_balance(&STAKING_TOKEN_ASSET_ID).await.unwrap(),
+            30
+        );
+        assert_eq!(
+            state.token_supply(&STAKING_TOKEN_ASSET_ID).await.unwrap(),
+            Some(70)
+        );
+        assert!(state
+            .scheduled_dao_transactions(HEIGHT)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(state
+            .get_proto::<penumbra_proto::transaction::proposal::DaoSpend>(
+                governance_state_key::dao_transactions(HEIGHT).into()
+            )
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn skips_underfunded_transactions_entirely() {
+        let dir = tempdir().unwrap();
+        let state = state_at_height(&dir).await;
+
+        state
+            .dao_deposit(Value {
+                amount: 100,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            })
+            .await
+            .unwrap();
+        state
+            .dao_deposit(Value {
+                amount: 5,
+                asset_id: gm(),
+            })
+            .await
+            .unwrap();
+
+        // The treasury can fund the staking token output, but not the other, so neither is paid
+        state
+            .schedule_dao_transaction(
+                HEIGHT,
+                dao_transaction(&[
+                    Value {
+                        amount: 50,
+                        asset_id: *STAKING_TOKEN_ASSET_ID,
+                    },
+                    Value {
+                        amount: 10,
+                        asset_id: gm(),
+                    },
+                ]),
+            )
+            .await
+            .unwrap();
+        // A later transaction at the same height is still executed
+        state
+            .schedule_dao_transaction(
+                HEIGHT,
+                dao_transaction(&[Value {
+                    amount: 5,
+                    asset_id: gm(),
+                }]),
+            )
+            .await
+            .unwrap();
+
+        execute(&state).await;
+
+        assert_eq!(
+            state.dao_balance(&STAKING_TOKEN_ASSET_ID).await.unwrap(),
+            100
+        );
+        assert_eq!(state.dao_balance(&gm()).await.unwrap(), 0);
+        assert_eq!(
+            state.token_supply(&STAKING_TOKEN_ASSET_ID).await.unwrap(),
+            None
+        );
+        assert_eq!(state.token_supply(&gm()).await.unwrap(), Some(5));
+        assert!(state
+            .scheduled_dao_transactions(HEIGHT)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
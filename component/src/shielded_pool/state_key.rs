@@ -49,6 +49,10 @@ pub fn block_anchor_lookup(anchor: block::Root) -> String {
     format!("shielded_pool/valid_block_anchors/{}", anchor)
 }
 
+pub fn nct_position_by_height(height: u64) -> String {
+    format!("shielded_pool/nct_position/{}", height)
+}
+
 pub fn spent_nullifier_lookup(nullifier: Nullifier) -> String {
     format!("shielded_pool/spent_nullifiers/{}", nullifier)
 }
@@ -190,7 +190,8 @@ impl Staking {
             }
             (Jailed, Inactive) => {
                 // We don't really have to do anything here; the validator was already
-                // slashed, and we're just allowing it to return to society.
+                // slashed, and we're just allowing it to return to society. Its uptime is
+                // reset if it becomes active again.
                 tracing::debug!("releasing validator from jail");
                 self.state.put_domain(state_key, Inactive).await;
 
@@ -238,6 +239,13 @@ impl Staking {
                     )
                     .await;
 
+                // Record when the validator was jailed, so that we can check that it has waited
+                // out its sentence before it's unjailed.
+                let current_epoch = self.state.get_current_epoch().await?.index;
+                self.state
+                    .set_validator_jailed_epoch(identity_key, current_epoch)
+                    .await;
+
                 // Finally, set the validator to be jailed.
                 self.state.put_domain(state_key, Jailed).await;
 
@@ -689,21 +697,32 @@ impl Staking {
         genesis_allocations: &HashMap<&String, u64>,
         genesis_base_rate: &BaseRateData,
         validator: Validator,
+        exported_rate_data: Option<RateData>,
+        exported_status: Option<validator::Status>,
+        exported_jailed_epoch: Option<u64>,
     ) -> Result<()> {
         // Delegations require knowing the rates for the
         // next epoch, so pre-populate with 0 reward => exchange rate 1 for
-        // the current and next epochs.
+        // the current and next epochs, unless the rates were carried over
+        // from an exported chain.
+        let (validator_reward_rate, validator_exchange_rate) = match exported_rate_data {
+            Some(rate_data) => (
+                rate_data.validator_reward_rate,
+                rate_data.validator_exchange_rate,
+            ),
+            None => (0, 1_0000_0000), // 1 represented as 1e8
+        };
         let cur_rate_data = RateData {
             identity_key: validator.identity_key.clone(),
             epoch_index: genesis_base_rate.epoch_index,
-            validator_reward_rate: 0,
-            validator_exchange_rate: 1_0000_0000, // 1 represented as 1e8
+            validator_reward_rate,
+            validator_exchange_rate,
         };
         let next_rate_data = RateData {
             identity_key: validator.identity_key.clone(),
             epoch_index: genesis_base_rate.epoch_index + 1,
-            validator_reward_rate: 0,
-            validator_exchange_rate: 1_0000_0000, // 1 represented as 1e8
+            validator_reward_rate,
+            validator_exchange_rate,
         };
 
         // The initial allocations to the validator are specified in `genesis_allocations`.
@@ -715,30 +734,48 @@ impl Staking {
             .get(&delegation_denom)
             .copied()
             .unwrap_or(0);
-        let power = cur_rate_data.voting_power(total_delegation_tokens, genesis_base_rate);
+        // Genesis validators start in the "Active" state and the "Bonded" bonding state, unless
+        // their states were carried over from an exported chain.
+        let (state, bonding_state) = match exported_status {
+            Some(status) => (status.state, status.bonding_state),
+            None => (validator::State::Active, validator::BondingState::Bonded),
+        };
+        // Jailed and disabled validators have no power, as after the transition into those
+        // states.
+        let power = match state {
+            validator::State::Active | validator::State::Inactive => {
+                cur_rate_data.voting_power(total_delegation_tokens, genesis_base_rate)
+            }
+            _ => 0,
+        };
 
         self.state
             .add_validator_inner(
                 validator.clone(),
                 cur_rate_data,
                 next_rate_data,
-                // All genesis validators start in the "Active" state:
-                validator::State::Active,
-                // All genesis validators start in the "Bonded" bonding state:
-                validator::BondingState::Bonded,
+                state,
+                bonding_state,
                 power,
             )
             .await?;
 
-        // We also need to start tracking uptime of new validators, because they
-        // start in the active state, so we need to bundle in the effects of the
-        // Inactive -> Active state transition.
-        self.state
-            .set_validator_uptime(
-                &validator.identity_key,
-                Uptime::new(0, self.state.signed_blocks_window_len().await? as usize),
-            )
-            .await;
+        if let Some(jailed_epoch) = exported_jailed_epoch {
+            self.state
+                .set_validator_jailed_epoch(&validator.identity_key, jailed_epoch)
+                .await;
+        }
+
+        // We also need to start tracking uptime of validators which start in the active state, so
+        // we need to bundle in the effects of the Inactive -> Active state transition.
+        if state == validator::State::Active {
+            self.state
+                .set_validator_uptime(
+                    &validator.identity_key,
+                    Uptime::new(0, self.state.signed_blocks_window_len().await? as usize),
+                )
+                .await;
+        }
 
         Ok(())
     }
@@ -790,7 +827,8 @@ impl Staking {
                 self.set_validator_state(id, Inactive).await?;
             }
             (Jailed, true) => {
-                // Treat updates to jailed validators as unjail requests.
+                // Treat updates to jailed validators as unjail requests, which
+                // `check_tx_stateful` only allows once the jail duration has passed.
                 self.set_validator_state(id, Inactive).await?;
             }
             (Active | Inactive | Jailed | Disabled, false) => {
@@ -812,7 +850,7 @@ impl Staking {
             .await;
 
         self.state
-            .put_domain(state_key::validator_by_id(id).into(), validator)
+            .put_domain_indexed(state_key::validator_by_id(id), validator)
             .await;
 
         Ok(())
@@ -843,16 +881,21 @@ impl Component for Staking {
 
         // Delegations require knowing the rates for the next epoch, so
         // pre-populate with 0 reward => exchange rate 1 for the current
-        // (index 0) and next (index 1) epochs for base rate data.
+        // (index 0) and next (index 1) epochs for base rate data, unless the
+        // base rate was carried over from an exported chain.
+        let (base_reward_rate, base_exchange_rate) = match &app_state.base_rate {
+            Some(base_rate) => (base_rate.base_reward_rate, base_rate.base_exchange_rate),
+            None => (0, 1_0000_0000),
+        };
         let genesis_base_rate = BaseRateData {
             epoch_index,
-            base_reward_rate: 0,
-            base_exchange_rate: 1_0000_0000,
+            base_reward_rate,
+            base_exchange_rate,
         };
         let next_base_rate = BaseRateData {
             epoch_index: epoch_index + 1,
-            base_reward_rate: 0,
-            base_exchange_rate: 1_0000_0000,
+            base_reward_rate,
+            base_exchange_rate,
         };
         self.state
             .set_base_rates(genesis_base_rate.clone(), next_base_rate)
@@ -871,10 +914,42 @@ impl Component for Staking {
         for validator in &app_state.validators {
             // Parse the proto into a domain type.
             let validator = Validator::try_from(validator.clone()).unwrap();
+            let rate_data = app_state
+                .validator_rates
+                .iter()
+                .map(|rate_data| RateData::try_from(rate_data.clone()).unwrap())
+                .find(|rate_data| rate_data.identity_key == validator.identity_key);
+            let status = app_state
+                .validator_statuses
+                .iter()
+                .map(|status| validator::Status::try_from(status.clone()).unwrap())
+                .find(|status| status.identity_key == validator.identity_key);
+            let jailed_epoch = app_state
+                .jailed_epochs
+                .iter()
+                .map(|jailed| {
+                    let identity_key = IdentityKey::try_from(
+                        jailed
+                            .identity_key
+                            .clone()
+                            .expect("genesis jailed epochs must have identity keys"),
+                    )
+                    .unwrap();
+                    (identity_key, jailed.epoch_index)
+                })
+                .find(|(identity_key, _)| *identity_key == validator.identity_key)
+                .map(|(_, epoch_index)| epoch_index);
 
-            self.add_genesis_validator(&genesis_allocations, &genesis_base_rate, validator)
-                .await
-                .unwrap();
+            self.add_genesis_validator(
+                &genesis_allocations,
+                &genesis_base_rate,
+                validator,
+                rate_data,
+                status,
+                jailed_epoch,
+            )
+            .await
+            .unwrap();
         }
 
         // Finally, record that there were no delegations in this block, so the data
@@ -1117,6 +1192,42 @@ impl Component for Staking {
                         current_seq
                     ));
                 }
+
+                // A validator jailed for downtime can only be re-enabled once it has been jailed
+                // for the jail duration, including if it was disabled while jailed.
+                //
+                // Its uptime doesn't need to be checked: an unjailed validator becomes Inactive,
+                // and only returns to the active set through the Inactive -> Active transition,
+                // which starts tracking its uptime afresh. So the blocks it missed before it was
+                // jailed can't count against it, and it will be jailed again if it keeps missing
+                // blocks once it's active.
+                let state = self
+                    .state
+                    .validator_state(&v.validator.identity_key)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("missing state for validator"))?;
+                if v.validator.enabled
+                    && matches!(state, validator::State::Jailed | validator::State::Disabled)
+                {
+                    if let Some(jailed_epoch) = self
+                        .state
+                        .validator_jailed_epoch(&v.validator.identity_key)
+                        .await?
+                    {
+                        let current_epoch = self.state.get_current_epoch().await?.index;
+                        let release_epoch = jailed_epoch
+                            + self.state.get_chain_params().await?.jail_duration_epochs;
+                        if current_epoch < release_epoch {
+                            return Err(anyhow::anyhow!(
+                                "validator {} was jailed in epoch {} and cannot be unjailed until epoch {}, but the current epoch is {}",
+                                v.validator.identity_key,
+                                jailed_epoch,
+                                release_epoch,
+                                current_epoch,
+                            ));
+                        }
+                    }
+                }
             }
 
             // Check whether the consensus key has already been used by another validator.
@@ -1419,7 +1530,7 @@ pub trait View: StateExt {
         tracing::debug!(?validator);
         let id = validator.identity_key.clone();
 
-        self.put_domain(state_key::validator_by_id(&id).into(), validator.clone())
+        self.put_domain_indexed(state_key::validator_by_id(&id), validator.clone())
             .await;
         self.register_consensus_key(&validator.identity_key, &validator.consensus_key)
             .await;
@@ -1436,11 +1547,6 @@ pub trait View: StateExt {
         self.set_validator_power(&id, power).await?;
         self.set_validator_bonding_state(&id, bonding_state).await;
 
-        let mut validator_list = self.validator_list().await?;
-        validator_list.push(id.clone());
-        tracing::debug!(?validator_list);
-        self.set_validator_list(validator_list).await;
-
         // Lastly, update metrics for the new validator.
         match state {
             validator::State::Active => {
@@ -1449,6 +1555,14 @@ pub trait View: StateExt {
             validator::State::Inactive => {
                 increment_gauge!(metrics::INACTIVE_VALIDATORS, 1.0);
             }
+            // Only genesis validators carried over from an exported chain can start jailed or
+            // disabled.
+            validator::State::Jailed => {
+                increment_gauge!(metrics::JAILED_VALIDATORS, 1.0);
+            }
+            validator::State::Disabled => {
+                increment_gauge!(metrics::DISABLED_VALIDATORS, 1.0);
+            }
             _ => unreachable!(),
         };
         gauge!(metrics::MISSED_BLOCKS, 0.0, "identity_key" => id.to_string());
@@ -1506,20 +1620,14 @@ pub trait View: StateExt {
         }
     }
 
+    /// Returns the identity keys of all known validators, in order.
     async fn validator_list(&self) -> Result<Vec<IdentityKey>> {
         Ok(self
-            .get_domain(state_key::validator_list().into())
+            .prefix_domain::<Validator, _>(state_key::validators())
             .await?
-            .map(|list: validator::List| list.0)
-            .unwrap_or_default())
-    }
-
-    async fn set_validator_list(&self, validators: Vec<IdentityKey>) {
-        self.put_domain(
-            state_key::validator_list().into(),
-            validator::List(validators),
-        )
-        .await;
+            .into_iter()
+            .map(|(_, validator)| validator.identity_key)
+            .collect())
     }
 
     async fn delegation_changes(&self, height: block::Height) -> Result<DelegationChanges> {
@@ -1547,6 +1655,20 @@ pub trait View: StateExt {
             .await
     }
 
+    /// Returns the epoch in which the validator was last jailed, if it ever was.
+    async fn validator_jailed_epoch(&self, identity_key: &IdentityKey) -> Result<Option<u64>> {
+        self.get_proto(state_key::jailed_epoch_by_validator(identity_key).into())
+            .await
+    }
+
+    async fn set_validator_jailed_epoch(&self, identity_key: &IdentityKey, epoch_index: u64) {
+        self.put_proto(
+            state_key::jailed_epoch_by_validator(identity_key).into(),
+            epoch_index,
+        )
+        .await
+    }
+
     async fn set_validator_bonding_state(
         &self,
         identity_key: &IdentityKey,
@@ -1577,3 +1699,108 @@ pub trait View: StateExt {
 }
 
 impl<T: StateExt + Send + Sync> View for T {}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_chain::params::ChainParameters;
+    use penumbra_crypto::{
+        keys::SpendKey,
+        rdsa::{Binding, SigningKey},
+        Fr, Zero,
+    };
+    use penumbra_storage::Storage;
+    use penumbra_tct as tct;
+    use penumbra_transaction::TransactionBody;
+    use rand_core::OsRng;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    const EPOCH_DURATION: u64 = 10;
+
+    /// Starts a chain with a single active validator, returning its spend key and definition.
+    async fn genesis(state: &State) -> (SpendKey, Validator) {
+        let (spend_key, _, validator) = validator::test_validator();
+
+        state
+            .put_chain_params(ChainParameters {
+                chain_id: "penumbra-unjail-test".to_string(),
+                epoch_duration: EPOCH_DURATION,
+                jail_duration_epochs: 2,
+                ..Default::default()
+            })
+            .await;
+        state.put_block_height(0).await;
+        Staking::new(state.clone())
+            .await
+            .init_chain(&genesis::AppState {
+                validators: vec![validator.clone().into()],
+                ..Default::default()
+            })
+            .await;
+
+        (spend_key, validator)
+    }
+
+    /// Builds a transaction which redefines the validator, re-enabling it.
+    fn unjail_tx(spend_key: &SpendKey, mut validator: Validator) -> Transaction {
+        validator.enabled = true;
+        validator.sequence_number += 1;
+        let validator_bytes = validator.encode_to_vec();
+        let definition = validator::Definition {
+            auth_sig: spend_key
+                .spend_auth_key()
+                .sign(&mut OsRng, &validator_bytes),
+            validator,
+        };
+
+        Transaction {
+            transaction_body: TransactionBody {
+                actions: vec![Action::ValidatorDefinition(definition.into())],
+                expiry_height: 0,
+                chain_id: "penumbra-unjail-test".to_string(),
+                fee: Default::default(),
+                fmd_clues: vec![],
+            },
+            // The transaction has no value balance, so it's bound with a zero blinding factor.
+            binding_sig: SigningKey::<Binding>::from(Fr::zero()).sign(OsRng, &[]),
+            anchor: tct::Tree::new().root(),
+        }
+    }
+
+    #[tokio::test]
+    async fn unjail_after_jail_duration() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().to_owned()).await.unwrap();
+        let state = storage.state().await.unwrap();
+        let (spend_key, validator) = genesis(&state).await;
+        let identity_key = validator.identity_key.clone();
+
+        // Jail the validator in epoch 1.
+        state.put_block_height(EPOCH_DURATION).await;
+        let mut staking = Staking::new(state.clone()).await;
+        staking
+            .set_validator_state(&identity_key, validator::State::Jailed)
+            .await
+            .unwrap();
+
+        // It can't be unjailed until epoch 3.
+        let tx = unjail_tx(&spend_key, validator);
+        state.put_block_height(3 * EPOCH_DURATION - 1).await;
+        assert!(staking
+            .check_tx_stateful(Context::new(), &tx)
+            .await
+            .is_err());
+
+        state.put_block_height(3 * EPOCH_DURATION).await;
+        staking
+            .check_tx_stateful(Context::new(), &tx)
+            .await
+            .unwrap();
+        staking.execute_tx(Context::new(), &tx).await;
+        assert_eq!(
+            state.validator_state(&identity_key).await.unwrap(),
+            Some(validator::State::Inactive)
+        );
+    }
+}
@@ -2,8 +2,10 @@ use penumbra_crypto::IdentityKey;
 use std::string::String;
 use tendermint::PublicKey;
 
-pub fn validator_list() -> &'static str {
-    "staking/validators"
+/// The prefix of the keys of validator definitions, which are indexed so that validators can be
+/// listed.
+pub fn validators() -> &'static str {
+    "staking/validator/"
 }
 
 pub fn current_base_rate() -> &'static str {
@@ -42,6 +44,10 @@ pub fn uptime_by_validator(id: &IdentityKey) -> String {
     format!("staking/validator/{}/uptime", id)
 }
 
+pub fn jailed_epoch_by_validator(id: &IdentityKey) -> String {
+    format!("staking/validator/{}/jailed_epoch", id)
+}
+
 pub fn slashed_validators(height: u64) -> String {
     format!("staking/slashed_validators/{}", height)
 }
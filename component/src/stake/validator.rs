@@ -113,3 +113,48 @@ impl TryFrom<pb::Validator> for Validator {
         })
     }
 }
+
+/// Builds the validator used in tests: its identity and governance keys are derived from a fixed
+/// spend key, and its single funding stream pays 100 basis points to that key's first address.
+///
+/// Returns the spend key and funding stream address along with the validator.
+#[cfg(test)]
+pub(crate) fn test_validator() -> (
+    penumbra_crypto::keys::SpendKey,
+    penumbra_crypto::Address,
+    Validator,
+) {
+    use penumbra_crypto::{
+        keys::{SpendKey, SpendKeyBytes},
+        rdsa::VerificationKey,
+    };
+
+    let spend_key = SpendKey::from(SpendKeyBytes([1; 32]));
+    let identity_vk = VerificationKey::from(spend_key.spend_auth_key());
+    let (address, _) = spend_key
+        .full_viewing_key()
+        .incoming()
+        .payment_address(0u64.into());
+    let validator = Validator {
+        identity_key: IdentityKey(identity_vk),
+        governance_key: GovernanceKey(identity_vk),
+        consensus_key: tendermint::PublicKey::from_raw_ed25519(
+            ed25519_consensus::SigningKey::from([2; 32])
+                .verification_key()
+                .as_bytes(),
+        )
+        .unwrap(),
+        name: "validator".to_string(),
+        website: String::new(),
+        description: String::new(),
+        enabled: true,
+        funding_streams: FundingStreams::try_from(vec![FundingStream {
+            address,
+            rate_bps: 100,
+        }])
+        .unwrap(),
+        sequence_number: 0,
+    };
+
+    (spend_key, address, validator)
+}
@@ -150,6 +150,7 @@ mod tests {
     use super::*;
 
     use super::super::{super::TradingPair, position::*, TradingFunction};
+    use crate::fixpoint::U64x64;
 
     #[test]
     fn lpnft_denom_parsing_roundtrip() {
@@ -160,10 +161,10 @@ mod tests {
             asset_2: crate::asset::REGISTRY.parse_denom("cube").unwrap().id(),
         };
         let phi = TradingFunction {
-            fee: 0.0001,
-            k: 1.0,
-            p: 1.0,
-            q: 1.0,
+            fee: U64x64::ratio(1, 10_000).unwrap(),
+            k: U64x64::ONE,
+            p: U64x64::ONE,
+            q: U64x64::ONE,
         };
         let position = Position {
             phi,
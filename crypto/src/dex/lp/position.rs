@@ -2,7 +2,7 @@ use anyhow::Context;
 use penumbra_proto::{dex as pb, serializers::bech32str, Protobuf};
 use serde::{Deserialize, Serialize};
 
-use super::{super::TradingPair, TradingFunction};
+use super::{super::TradingPair, Reserves, TradingFunction};
 
 /// Data identifying a position.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,10 +28,10 @@ impl Position {
         state.update(&self.nonce);
         state.update(&self.pair.asset_1.to_bytes());
         state.update(&self.pair.asset_2.to_bytes());
-        state.update(&self.phi.fee.to_le_bytes());
-        state.update(&self.phi.k.to_le_bytes());
-        state.update(&self.phi.p.to_le_bytes());
-        state.update(&self.phi.q.to_le_bytes());
+        state.update(&self.phi.fee.to_bytes());
+        state.update(&self.phi.k.to_bytes());
+        state.update(&self.phi.p.to_bytes());
+        state.update(&self.phi.q.to_bytes());
 
         let hash = state.finalize();
         let mut bytes = [0; 32];
@@ -117,6 +117,16 @@ impl std::str::FromStr for State {
     }
 }
 
+/// The metadata the chain records for a position: its immutable definition,
+/// together with its current state and reserves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "pb::PositionMetadata", into = "pb::PositionMetadata")]
+pub struct Metadata {
+    pub position: Position,
+    pub state: State,
+    pub reserves: Reserves,
+}
+
 // ==== Protobuf impls
 
 impl Protobuf<pb::Position> for Position {}
@@ -207,3 +217,36 @@ impl TryFrom<pb::PositionState> for State {
         )
     }
 }
+
+impl Protobuf<pb::PositionMetadata> for Metadata {}
+
+impl TryFrom<pb::PositionMetadata> for Metadata {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::PositionMetadata) -> Result<Self, Self::Error> {
+        Ok(Self {
+            position: value
+                .position
+                .ok_or_else(|| anyhow::anyhow!("missing position"))?
+                .try_into()?,
+            state: value
+                .state
+                .ok_or_else(|| anyhow::anyhow!("missing position state"))?
+                .try_into()?,
+            reserves: value
+                .reserves
+                .ok_or_else(|| anyhow::anyhow!("missing reserves"))?
+                .try_into()?,
+        })
+    }
+}
+
+impl From<Metadata> for pb::PositionMetadata {
+    fn from(value: Metadata) -> Self {
+        Self {
+            position: Some(value.position.into()),
+            state: Some(value.state.into()),
+            reserves: Some(value.reserves.into()),
+        }
+    }
+}
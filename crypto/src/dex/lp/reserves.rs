@@ -6,7 +6,7 @@ use penumbra_proto::{dex as pb, Protobuf};
 /// between assets 1 and 2, without specifying what those assets are, to avoid
 /// duplicating data (each asset ID alone is four times the size of the
 /// reserves).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Reserves {
     pub r1: u64,
     pub r2: u64,
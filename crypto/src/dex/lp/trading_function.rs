@@ -1,6 +1,10 @@
+use anyhow::{anyhow, Result};
 use penumbra_proto::{dex as pb, Protobuf};
 use serde::{Deserialize, Serialize};
 
+use super::Reserves;
+use crate::fixpoint::U64x64;
+
 /// The data describing a trading function.
 ///
 /// This implicitly treats the trading function as being between assets 1 and 2,
@@ -10,16 +14,85 @@ use serde::{Deserialize, Serialize};
 /// The trading function is `phi(R) = p*R_1 + q*R_2`.
 /// This is used as a CFMM with constant `k` and fee `fee` (gamma).
 ///
-/// NOTE: the use of floats here is a placeholder ONLY, so we can stub out the implementation,
-/// and then decide what type of fixed-point, deterministic arithmetic should be used.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// All parameters are deterministic fixed-point numbers, so that trading against
+/// a position gives the same result on every node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(try_from = "pb::TradingFunction", into = "pb::TradingFunction")]
-
 pub struct TradingFunction {
-    pub fee: f64,
-    pub k: f64,
-    pub p: f64,
-    pub q: f64,
+    /// The fee charged on trades, as a fraction of the input.
+    pub fee: U64x64,
+    pub k: U64x64,
+    pub p: U64x64,
+    pub q: U64x64,
+}
+
+impl TradingFunction {
+    /// The amount of asset 2 output for each unit of asset 1 input, net of fees.
+    pub fn rate_1_to_2(&self) -> Result<U64x64> {
+        self.rate(&self.p, &self.q)
+    }
+
+    /// The amount of asset 1 output for each unit of asset 2 input, net of fees.
+    pub fn rate_2_to_1(&self) -> Result<U64x64> {
+        self.rate(&self.q, &self.p)
+    }
+
+    fn rate(&self, numerator: &U64x64, denominator: &U64x64) -> Result<U64x64> {
+        let gamma = U64x64::ONE
+            .checked_sub(&self.fee)
+            .ok_or_else(|| anyhow!("trading function fee exceeds 1"))?;
+        numerator
+            .checked_div(denominator)
+            .and_then(|price| gamma.checked_mul(&price))
+            .ok_or_else(|| anyhow!("trading function price is out of range"))
+    }
+
+    /// Quote the outputs `(lambda_1, lambda_2)` of trading the inputs `(delta_1, delta_2)`
+    /// against this trading function, without regard to the reserves available.
+    ///
+    /// Since `phi` is linear, trading preserves `phi(R)` exactly when an input of
+    /// `delta_1` (net of fees) is exchanged for `delta_1 * p / q` of asset 2, and an
+    /// input of `delta_2` (net of fees) for `delta_2 * q / p` of asset 1. Outputs are
+    /// rounded down, in favor of the position.
+    pub fn quote(&self, (delta_1, delta_2): (u64, u64)) -> Result<(u64, u64)> {
+        let output = |input: u64, rate: U64x64| -> Result<u64> {
+            rate.checked_mul_u64(input)
+                .ok_or_else(|| anyhow!("trade output overflows"))
+        };
+
+        Ok((
+            output(delta_2, self.rate_2_to_1()?)?,
+            output(delta_1, self.rate_1_to_2()?)?,
+        ))
+    }
+
+    /// Fill the inputs `(delta_1, delta_2)` against a position with this trading function
+    /// and the given `reserves`, returning the outputs `(lambda_1, lambda_2)` and the
+    /// reserves of the position after the fill.
+    ///
+    /// Returns an error if the position's reserves can't cover the outputs.
+    pub fn fill(
+        &self,
+        (delta_1, delta_2): (u64, u64),
+        reserves: &Reserves,
+    ) -> Result<((u64, u64), Reserves)> {
+        let (lambda_1, lambda_2) = self.quote((delta_1, delta_2))?;
+
+        let r1 = reserves
+            .r1
+            .checked_add(delta_1)
+            .ok_or_else(|| anyhow!("reserves of asset 1 overflow"))?
+            .checked_sub(lambda_1)
+            .ok_or_else(|| anyhow!("insufficient reserves of asset 1"))?;
+        let r2 = reserves
+            .r2
+            .checked_add(delta_2)
+            .ok_or_else(|| anyhow!("reserves of asset 2 overflow"))?
+            .checked_sub(lambda_2)
+            .ok_or_else(|| anyhow!("insufficient reserves of asset 2"))?;
+
+        Ok(((lambda_1, lambda_2), Reserves { r1, r2 }))
+    }
 }
 
 impl Protobuf<pb::TradingFunction> for TradingFunction {}
@@ -28,11 +101,34 @@ impl TryFrom<pb::TradingFunction> for TradingFunction {
     type Error = anyhow::Error;
 
     fn try_from(value: pb::TradingFunction) -> Result<Self, Self::Error> {
+        let fee: U64x64 = value
+            .fee
+            .ok_or_else(|| anyhow!("missing trading function fee"))?
+            .try_into()?;
+        let p: U64x64 = value
+            .p
+            .ok_or_else(|| anyhow!("missing trading function p"))?
+            .try_into()?;
+        let q: U64x64 = value
+            .q
+            .ok_or_else(|| anyhow!("missing trading function q"))?
+            .try_into()?;
+
+        if fee > U64x64::ONE {
+            return Err(anyhow!("trading function fee must be at most 1"));
+        }
+        if p == U64x64::ZERO || q == U64x64::ZERO {
+            return Err(anyhow!("trading function coefficients must be nonzero"));
+        }
+
         Ok(Self {
-            fee: value.fee,
-            k: value.k,
-            p: value.p,
-            q: value.q,
+            fee,
+            k: value
+                .k
+                .ok_or_else(|| anyhow!("missing trading function k"))?
+                .try_into()?,
+            p,
+            q,
         })
     }
 }
@@ -40,10 +136,37 @@ impl TryFrom<pb::TradingFunction> for TradingFunction {
 impl From<TradingFunction> for pb::TradingFunction {
     fn from(value: TradingFunction) -> Self {
         Self {
-            fee: value.fee,
-            k: value.k,
-            p: value.p,
-            q: value.q,
+            fee: Some(value.fee.into()),
+            k: Some(value.k.into()),
+            p: Some(value.p.into()),
+            q: Some(value.q.into()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_preserves_phi_and_charges_fee() {
+        // A position selling asset 2 at a price of 2 units of asset 2 per unit of
+        // asset 1, with a 1% fee.
+        let phi = TradingFunction {
+            fee: U64x64::ratio(1, 100).unwrap(),
+            k: U64x64::ZERO,
+            p: U64x64::from(2),
+            q: U64x64::from(1),
+        };
+        let reserves = Reserves { r1: 0, r2: 1_000 };
+
+        assert_eq!(phi.quote((100, 0)).unwrap(), (0, 198));
+
+        let ((lambda_1, lambda_2), reserves) = phi.fill((100, 0), &reserves).unwrap();
+        assert_eq!((lambda_1, lambda_2), (0, 198));
+        assert_eq!(reserves, Reserves { r1: 100, r2: 802 });
+
+        // The position can't output more than its reserves.
+        assert!(phi.fill((1_000, 0), &reserves).is_err());
+    }
+}
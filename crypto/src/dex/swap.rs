@@ -12,22 +12,51 @@ use once_cell::sync::Lazy;
 use penumbra_proto::{dex as pb, Protobuf};
 
 // Swap ciphertext byte length
-pub const SWAP_CIPHERTEXT_BYTES: usize = 216;
+pub const SWAP_CIPHERTEXT_BYTES: usize = 280;
 // Swap plaintext byte length
-pub const SWAP_LEN_BYTES: usize = 200;
+pub const SWAP_LEN_BYTES: usize = 264;
 
 pub const OVK_WRAPPED_LEN_BYTES: usize = 80;
 
 pub static DOMAIN_SEPARATOR: Lazy<Fq> =
     Lazy::new(|| Fq::from_le_bytes_mod_order(blake2b_simd::blake2b(b"penumbra.swap").as_bytes()));
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub struct BatchSwapOutputData {
     pub delta_1: u64,
     pub delta_2: u64,
     pub lambda_1: u64,
     pub lambda_2: u64,
     pub success: bool,
+    pub height: u64,
+}
+
+impl BatchSwapOutputData {
+    /// Given a user's inputs `(delta_1_i, delta_2_i)`, compute their pro rata share
+    /// `(lambda_1_i, lambda_2_i)` of the batch outputs.
+    ///
+    /// Users who input asset 2 receive a share of the asset 1 output, and vice versa.
+    /// If the batch swap failed, the user's inputs are returned to them instead.
+    pub fn pro_rata_outputs(&self, (delta_1_i, delta_2_i): (u64, u64)) -> (u64, u64) {
+        if !self.success {
+            return (delta_1_i, delta_2_i);
+        }
+
+        // The share is computed with 128-bit intermediates to avoid overflow, and rounds
+        // down, so the sum of all users' outputs never exceeds the batch output.
+        let share = |output: u64, input_i: u64, input: u64| -> u64 {
+            if input == 0 {
+                0
+            } else {
+                ((output as u128 * input_i as u128) / input as u128) as u64
+            }
+        };
+
+        (
+            share(self.lambda_1, delta_2_i, self.delta_2),
+            share(self.lambda_2, delta_1_i, self.delta_1),
+        )
+    }
 }
 
 impl Protobuf<pb::BatchSwapOutputData> for BatchSwapOutputData {}
@@ -40,6 +69,7 @@ impl From<BatchSwapOutputData> for pb::BatchSwapOutputData {
             lambda_1: s.lambda_1,
             lambda_2: s.lambda_2,
             success: s.success,
+            height: s.height,
         }
     }
 }
@@ -53,6 +83,43 @@ impl TryFrom<pb::BatchSwapOutputData> for BatchSwapOutputData {
             lambda_1: s.lambda_1,
             lambda_2: s.lambda_2,
             success: s.success,
+            height: s.height,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pro_rata_outputs_split_batch_output() {
+        let output_data = BatchSwapOutputData {
+            delta_1: 100,
+            delta_2: 30,
+            lambda_1: 60,
+            lambda_2: 200,
+            success: true,
+            height: 1,
+        };
+
+        // A user who contributed 10 of the 30 units of asset 2 gets a third of the asset 1 output.
+        assert_eq!(output_data.pro_rata_outputs((0, 10)), (20, 0));
+        // A user who contributed 25 of the 100 units of asset 1 gets a quarter of the asset 2 output.
+        assert_eq!(output_data.pro_rata_outputs((25, 0)), (0, 50));
+    }
+
+    #[test]
+    fn pro_rata_outputs_refund_failed_batch() {
+        let output_data = BatchSwapOutputData {
+            delta_1: 100,
+            delta_2: 30,
+            lambda_1: 0,
+            lambda_2: 0,
+            success: false,
+            height: 1,
+        };
+
+        assert_eq!(output_data.pro_rata_outputs((25, 10)), (25, 10));
+    }
+}
@@ -2,10 +2,11 @@ use crate::symmetric::OutgoingCipherKey;
 use crate::transaction::Fee;
 use crate::{asset, ka, Address, Value};
 use anyhow::{anyhow, Error, Result};
-use ark_ff::PrimeField;
-use decaf377::Fq;
+use ark_ff::{PrimeField, UniformRand};
+use decaf377::{FieldExt, Fq, Fr};
 use penumbra_proto::{crypto as pb_crypto, dex as pb, Protobuf};
-use poseidon377::hash_5;
+use poseidon377::{hash_2, hash_6};
+use rand_core::{CryptoRng, RngCore};
 
 use crate::dex::TradingPair;
 use crate::{
@@ -31,38 +32,106 @@ pub struct SwapPlaintext {
     pub fee: Fee,
     // Address to receive the Swap NFT and SwapClaim outputs
     pub claim_address: Address,
+    // Blinding factor for the commitment to the input amount of asset 1
+    pub delta_1_blinding: Fr,
+    // Blinding factor for the commitment to the input amount of asset 2
+    pub delta_2_blinding: Fr,
 }
 
 impl SwapPlaintext {
+    /// Create a new swap plaintext, choosing fresh blinding factors for the
+    /// commitments to the input amounts.
+    pub fn new<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        trading_pair: TradingPair,
+        delta_1: u64,
+        delta_2: u64,
+        fee: Fee,
+        claim_address: Address,
+    ) -> Self {
+        SwapPlaintext {
+            trading_pair,
+            delta_1,
+            delta_2,
+            fee,
+            claim_address,
+            delta_1_blinding: Fr::rand(rng),
+            delta_2_blinding: Fr::rand(rng),
+        }
+    }
+
     // Constructs the unique asset ID for a swap as a poseidon hash of the input data for the swap.
     //
     // https://protocol.penumbra.zone/main/zswap/swap.html#swap-actions
     pub fn asset_id(&self) -> asset::Id {
-        let packed_values = {
-            let mut bytes = [0u8; 56];
-            bytes[0..8].copy_from_slice(&self.delta_1.to_le_bytes());
-            bytes[8..16].copy_from_slice(&self.delta_2.to_le_bytes());
-            bytes[16..24].copy_from_slice(&self.fee.0.amount.to_le_bytes());
-            bytes[24..56].copy_from_slice(&self.fee.0.asset_id.to_bytes());
+        Self::asset_id_from_commitments(
+            self.trading_pair,
+            self.delta_1_commitment(),
+            self.delta_2_commitment(),
+            &self.fee,
+            &self.claim_address,
+        )
+    }
+
+    /// Constructs the unique asset ID for a swap from the commitments to its
+    /// input amounts, rather than the amounts themselves, so that it can be
+    /// checked against a swap's public data without revealing its inputs.
+    pub fn asset_id_from_commitments(
+        trading_pair: TradingPair,
+        delta_1_commitment: value::Commitment,
+        delta_2_commitment: value::Commitment,
+        fee: &Fee,
+        claim_address: &Address,
+    ) -> asset::Id {
+        let packed_fee = {
+            let mut bytes = [0u8; 40];
+            bytes[0..8].copy_from_slice(&fee.0.amount.to_le_bytes());
+            bytes[8..40].copy_from_slice(&fee.0.asset_id.to_bytes());
             Fq::from_le_bytes_mod_order(&bytes)
         };
+        let delta_commitments = hash_2(
+            &DOMAIN_SEPARATOR,
+            (
+                delta_1_commitment.0.vartime_compress_to_field(),
+                delta_2_commitment.0.vartime_compress_to_field(),
+            ),
+        );
 
-        let asset_id_hash = hash_5(
+        let asset_id_hash = hash_6(
             &DOMAIN_SEPARATOR,
             (
-                self.trading_pair.asset_1().0,
-                self.trading_pair.asset_2().0,
-                packed_values,
-                self.claim_address
+                trading_pair.asset_1().0,
+                trading_pair.asset_2().0,
+                delta_commitments,
+                packed_fee,
+                claim_address
                     .diversified_generator()
                     .vartime_compress_to_field(),
-                *self.claim_address.transmission_key_s(),
+                *claim_address.transmission_key_s(),
             ),
         );
 
         asset::Id(asset_id_hash)
     }
 
+    /// The commitment to the input amount of asset 1.
+    pub fn delta_1_commitment(&self) -> value::Commitment {
+        Value {
+            amount: self.delta_1,
+            asset_id: self.trading_pair.asset_1(),
+        }
+        .commit(self.delta_1_blinding)
+    }
+
+    /// The commitment to the input amount of asset 2.
+    pub fn delta_2_commitment(&self) -> value::Commitment {
+        Value {
+            amount: self.delta_2,
+            asset_id: self.trading_pair.asset_2(),
+        }
+        .commit(self.delta_2_blinding)
+    }
+
     pub fn diversified_generator(&self) -> &decaf377::Element {
         self.claim_address.diversified_generator()
     }
@@ -118,6 +187,8 @@ impl SwapPlaintext {
         delta_2: u64,
         fee: Fee,
         claim_address: Address,
+        delta_1_blinding: Fr,
+        delta_2_blinding: Fr,
     ) -> Result<Self, Error> {
         Ok(SwapPlaintext {
             trading_pair,
@@ -125,6 +196,8 @@ impl SwapPlaintext {
             delta_2,
             fee,
             claim_address,
+            delta_1_blinding,
+            delta_2_blinding,
         })
     }
 }
@@ -150,6 +223,10 @@ impl TryFrom<pb::SwapPlaintext> for SwapPlaintext {
                 .trading_pair
                 .ok_or_else(|| anyhow::anyhow!("missing trading pair in SwapPlaintext"))?
                 .try_into()?,
+            delta_1_blinding: Fr::from_bytes(plaintext.delta_1_blinding[..].try_into()?)
+                .map_err(|_| anyhow::anyhow!("invalid delta 1 blinding in SwapPlaintext"))?,
+            delta_2_blinding: Fr::from_bytes(plaintext.delta_2_blinding[..].try_into()?)
+                .map_err(|_| anyhow::anyhow!("invalid delta 2 blinding in SwapPlaintext"))?,
         })
     }
 }
@@ -162,6 +239,8 @@ impl From<SwapPlaintext> for pb::SwapPlaintext {
             fee: Some(plaintext.fee.into()),
             claim_address: Some(plaintext.claim_address.into()),
             trading_pair: Some(plaintext.trading_pair.into()),
+            delta_1_blinding: plaintext.delta_1_blinding.to_bytes().to_vec(),
+            delta_2_blinding: plaintext.delta_2_blinding.to_bytes().to_vec(),
         }
     }
 }
@@ -176,6 +255,8 @@ impl From<&SwapPlaintext> for [u8; SWAP_LEN_BYTES] {
         bytes[88..120].copy_from_slice(&swap.fee.0.asset_id.to_bytes());
         let pb_address = pb_crypto::Address::from(swap.claim_address);
         bytes[120..200].copy_from_slice(&pb_address.inner);
+        bytes[200..232].copy_from_slice(&swap.delta_1_blinding.to_bytes());
+        bytes[232..264].copy_from_slice(&swap.delta_2_blinding.to_bytes());
         bytes
     }
 }
@@ -215,6 +296,12 @@ impl TryFrom<&[u8]> for SwapPlaintext {
         let pb_address = pb_crypto::Address {
             inner: address_bytes.to_vec(),
         };
+        let delta_1_blinding_bytes: [u8; 32] = bytes[200..232]
+            .try_into()
+            .map_err(|_| anyhow!("error fetching delta1 blinding bytes"))?;
+        let delta_2_blinding_bytes: [u8; 32] = bytes[232..264]
+            .try_into()
+            .map_err(|_| anyhow!("error fetching delta2 blinding bytes"))?;
 
         SwapPlaintext::from_parts(
             tp_bytes
@@ -227,6 +314,10 @@ impl TryFrom<&[u8]> for SwapPlaintext {
                 asset_id: asset::Id::try_from(fee_asset_id_bytes)?,
             }),
             pb_address.try_into()?,
+            Fr::from_bytes(delta_1_blinding_bytes)
+                .map_err(|_| anyhow!("error deserializing delta1 blinding"))?,
+            Fr::from_bytes(delta_2_blinding_bytes)
+                .map_err(|_| anyhow!("error deserializing delta2 blinding"))?,
         )
     }
 }
@@ -273,6 +364,8 @@ mod tests {
                 asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
             }),
             claim_address: dest,
+            delta_1_blinding: Fr::rand(&mut rng),
+            delta_2_blinding: Fr::rand(&mut rng),
         };
         let esk = ka::Secret::new(&mut rng);
 
@@ -0,0 +1,192 @@
+//! Deterministic fixed-point arithmetic, for use in consensus-critical code.
+
+use penumbra_proto::{crypto as pb, Protobuf};
+use serde::{Deserialize, Serialize};
+
+/// An unsigned 64.64 fixed-point number, with 64 integer bits and 64 fractional bits.
+///
+/// All arithmetic is checked, and rounds down: operations that would overflow
+/// (or divide by zero) return `None` rather than panicking or wrapping.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "pb::U64x64", into = "pb::U64x64")]
+pub struct U64x64(u128);
+
+const FRACTIONAL_BITS: u32 = 64;
+const LOW_MASK: u128 = u64::MAX as u128;
+
+impl U64x64 {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1 << FRACTIONAL_BITS);
+
+    /// Construct a fixed-point number from its raw 128-bit representation.
+    pub fn from_bits(bits: u128) -> Self {
+        Self(bits)
+    }
+
+    /// The raw 128-bit representation of this number.
+    pub fn to_bits(&self) -> u128 {
+        self.0
+    }
+
+    /// Construct the fixed-point number closest to (but not exceeding) `numerator / denominator`.
+    pub fn ratio(numerator: u64, denominator: u64) -> Option<Self> {
+        Self::from(numerator).checked_div(&Self::from(denominator))
+    }
+
+    /// The integer part of this number, discarding the fractional part.
+    pub fn round_down(&self) -> u64 {
+        (self.0 >> FRACTIONAL_BITS) as u64
+    }
+
+    /// The fractional part of this number, as a numerator over `2^64`.
+    pub fn fractional_bits(&self) -> u64 {
+        (self.0 & LOW_MASK) as u64
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        // Split each operand into its integer and fractional halves, so that each
+        // partial product fits in a u128, and compute (self * rhs) >> 64 without
+        // needing 256-bit intermediates.
+        let (a_hi, a_lo) = (self.0 >> FRACTIONAL_BITS, self.0 & LOW_MASK);
+        let (b_hi, b_lo) = (rhs.0 >> FRACTIONAL_BITS, rhs.0 & LOW_MASK);
+
+        let hi_hi = a_hi * b_hi;
+        if hi_hi > LOW_MASK {
+            return None;
+        }
+
+        (hi_hi << FRACTIONAL_BITS)
+            .checked_add(a_hi * b_lo)?
+            .checked_add(a_lo * b_hi)?
+            .checked_add((a_lo * b_lo) >> FRACTIONAL_BITS)
+            .map(Self)
+    }
+
+    pub fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        if rhs.0 == 0 {
+            return None;
+        }
+
+        // The integer part of the quotient must fit in 64 bits.
+        let integer = self.0 / rhs.0;
+        if integer > LOW_MASK {
+            return None;
+        }
+
+        // Compute the fractional part of the quotient by binary long division of
+        // the remainder, one bit at a time.
+        let mut remainder = self.0 % rhs.0;
+        let mut fractional = 0u128;
+        for _ in 0..FRACTIONAL_BITS {
+            // If the top bit of the remainder is set, doubling it overflows a u128,
+            // but the doubled remainder is then certainly at least `rhs`.
+            let carry = remainder >> 127 == 1;
+            remainder <<= 1;
+            fractional <<= 1;
+            if carry || remainder >= rhs.0 {
+                remainder = remainder.wrapping_sub(rhs.0);
+                fractional |= 1;
+            }
+        }
+
+        Some(Self((integer << FRACTIONAL_BITS) | fractional))
+    }
+
+    /// Multiply this number by an integer amount, rounding the result down to an integer.
+    pub fn checked_mul_u64(&self, amount: u64) -> Option<u64> {
+        self.checked_mul(&Self::from(amount))
+            .map(|product| product.round_down())
+    }
+
+    pub fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_le_bytes()
+    }
+}
+
+impl From<u64> for U64x64 {
+    fn from(value: u64) -> Self {
+        Self((value as u128) << FRACTIONAL_BITS)
+    }
+}
+
+impl std::fmt::Debug for U64x64 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "U64x64({} + {}/2^64)",
+            self.round_down(),
+            self.fractional_bits()
+        )
+    }
+}
+
+impl Protobuf<pb::U64x64> for U64x64 {}
+
+impl From<U64x64> for pb::U64x64 {
+    fn from(value: U64x64) -> Self {
+        Self {
+            hi: value.round_down(),
+            lo: value.fractional_bits(),
+        }
+    }
+}
+
+impl TryFrom<pb::U64x64> for U64x64 {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::U64x64) -> Result<Self, Self::Error> {
+        Ok(Self(
+            ((value.hi as u128) << FRACTIONAL_BITS) | value.lo as u128,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn simple_arithmetic() {
+        let half = U64x64::ratio(1, 2).unwrap();
+        let three = U64x64::from(3);
+
+        assert_eq!(half.checked_add(&half), Some(U64x64::ONE));
+        assert_eq!(three.checked_mul(&half).unwrap().round_down(), 1);
+        assert_eq!(three.checked_div(&half), Some(U64x64::from(6)));
+        assert_eq!(three.checked_sub(&U64x64::from(4)), None);
+        assert_eq!(three.checked_div(&U64x64::ZERO), None);
+        assert_eq!(U64x64::from(u64::MAX).checked_mul(&three), None);
+    }
+
+    proptest! {
+        #[test]
+        fn mul_matches_integer_arithmetic(a in any::<u32>(), b in any::<u32>()) {
+            let product = U64x64::from(a as u64).checked_mul(&U64x64::from(b as u64)).unwrap();
+            prop_assert_eq!(product, U64x64::from(a as u64 * b as u64));
+        }
+
+        #[test]
+        fn div_rounds_down(a in any::<u64>(), b in 1u64..) {
+            let quotient = U64x64::ratio(a, b).unwrap();
+            prop_assert_eq!(quotient.round_down(), a / b);
+            // Multiplying back never exceeds the original numerator.
+            prop_assert!(quotient.checked_mul_u64(b).unwrap() <= a);
+        }
+
+        #[test]
+        fn proto_roundtrip(bits in any::<u128>()) {
+            let value = U64x64::from_bits(bits);
+            let proto: pb::U64x64 = value.into();
+            prop_assert_eq!(U64x64::try_from(proto).unwrap(), value);
+        }
+    }
+}
@@ -1,45 +1,512 @@
-use std::ops::Add;
+use std::ops::{Add, Deref};
 
+use ark_ff::UniformRand;
+use decaf377::{FieldExt, Fr};
+use penumbra_eddy::{
+    dkg::Committee,
+    proofs::{CommitmentProof, EncryptionProof},
+    Ciphertext, DecryptionShare, EncryptionKey, PrivateKeyShare, Unverified, Verified,
+};
 use penumbra_proto::{dex as pb, Protobuf};
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(try_from = "pb::MockFlowCiphertext", into = "pb::MockFlowCiphertext")]
-pub struct MockFlowCiphertext(u64);
+use crate::{value, Value};
+
+/// An encryption of a flow's value under the validators' [`FlowEncryptionKey`].
+///
+/// Flow ciphertexts are additively homomorphic, so the ciphertexts for every
+/// swap in a batch can be summed, and only the batch total decrypted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "pb::FlowCiphertext", into = "pb::FlowCiphertext")]
+pub struct FlowCiphertext(pub Ciphertext);
+
+impl Add for FlowCiphertext {
+    type Output = Self;
 
-// Fake implementation for now, TODO: replace w/ additively homomorphic encryption impl
-// once Eddy impl available
-impl MockFlowCiphertext {
-    pub fn new(plaintext: u64) -> Self {
-        // TODO: do encryption stuff here
-        Self(plaintext)
+    fn add(self, other: Self) -> Self {
+        Self(&self.0 + &other.0)
     }
+}
 
-    pub fn mock_decrypt(&self) -> u64 {
-        // TODO: do decryption stuff here
-        self.0
+impl Protobuf<pb::FlowCiphertext> for FlowCiphertext {}
+
+impl From<FlowCiphertext> for pb::FlowCiphertext {
+    fn from(ct: FlowCiphertext) -> Self {
+        pb::FlowCiphertext {
+            inner: ct.0.to_bytes().to_vec(),
+        }
     }
 }
 
-impl Add for MockFlowCiphertext {
-    type Output = Self;
+impl TryFrom<pb::FlowCiphertext> for FlowCiphertext {
+    type Error = anyhow::Error;
+    fn try_from(ct: pb::FlowCiphertext) -> Result<Self, Self::Error> {
+        Ok(Self(ct.inner.as_slice().try_into()?))
+    }
+}
 
-    fn add(self, other: Self) -> Self {
-        Self(self.0 + other.0)
+/// The domain separator for the transcripts of flow encryption proofs.
+const ENCRYPTION_TRANSCRIPT_LABEL: &[u8] = b"penumbra_flow_encryption";
+
+/// A proof that a [`FlowCiphertext`] is a well-formed encryption of a 64-bit
+/// value under the [`FlowEncryptionKey`], which reveals nothing about the value.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "pb::FlowEncryptionProof", into = "pb::FlowEncryptionProof")]
+pub struct FlowEncryptionProof(pub EncryptionProof);
+
+impl FlowEncryptionProof {
+    /// Verify that `ciphertext` encrypts a 64-bit value under `encryption_key`.
+    pub fn verify(
+        &self,
+        ciphertext: &FlowCiphertext,
+        encryption_key: &FlowEncryptionKey,
+    ) -> anyhow::Result<()> {
+        self.0.verify(
+            &ciphertext.0,
+            &encryption_key.0,
+            &mut merlin::Transcript::new(ENCRYPTION_TRANSCRIPT_LABEL),
+        )
+    }
+}
+
+impl Protobuf<pb::FlowEncryptionProof> for FlowEncryptionProof {}
+
+impl From<FlowEncryptionProof> for pb::FlowEncryptionProof {
+    fn from(proof: FlowEncryptionProof) -> Self {
+        pb::FlowEncryptionProof {
+            inner: Some(proof.0.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::FlowEncryptionProof> for FlowEncryptionProof {
+    type Error = anyhow::Error;
+    fn try_from(proof: pb::FlowEncryptionProof) -> Result<Self, Self::Error> {
+        Ok(Self(
+            proof
+                .inner
+                .ok_or_else(|| anyhow::anyhow!("missing encryption proof"))?
+                .try_into()?,
+        ))
     }
 }
 
-impl Protobuf<pb::MockFlowCiphertext> for MockFlowCiphertext {}
+/// The domain separator for the transcripts of flow commitment proofs.
+const COMMITMENT_TRANSCRIPT_LABEL: &[u8] = b"penumbra_flow_commitment";
+
+/// A proof that a [`FlowCiphertext`] encrypts the same amount that a value
+/// commitment commits to, which reveals nothing about the amount.
+///
+/// This links a swap's encrypted input to its contribution to the
+/// transaction's balance, without revealing the input amount.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "pb::FlowCommitmentProof", into = "pb::FlowCommitmentProof")]
+pub struct FlowCommitmentProof(pub CommitmentProof);
 
-impl From<MockFlowCiphertext> for pb::MockFlowCiphertext {
-    fn from(ik: MockFlowCiphertext) -> Self {
-        pb::MockFlowCiphertext { value: ik.0 }
+impl FlowCommitmentProof {
+    /// Verify that `ciphertext` encrypts, under `encryption_key`, the amount of
+    /// `asset_id` that `commitment` commits to.
+    pub fn verify(
+        &self,
+        ciphertext: &FlowCiphertext,
+        encryption_key: &FlowEncryptionKey,
+        commitment: value::Commitment,
+        asset_id: crate::asset::Id,
+    ) -> anyhow::Result<()> {
+        self.0.verify(
+            &ciphertext.0,
+            &encryption_key.0,
+            commitment.0,
+            asset_id.value_generator(),
+            *value::VALUE_BLINDING_GENERATOR.deref(),
+            &mut merlin::Transcript::new(COMMITMENT_TRANSCRIPT_LABEL),
+        )
     }
 }
 
-impl TryFrom<pb::MockFlowCiphertext> for MockFlowCiphertext {
+impl Protobuf<pb::FlowCommitmentProof> for FlowCommitmentProof {}
+
+impl From<FlowCommitmentProof> for pb::FlowCommitmentProof {
+    fn from(proof: FlowCommitmentProof) -> Self {
+        pb::FlowCommitmentProof {
+            inner: Some(proof.0.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::FlowCommitmentProof> for FlowCommitmentProof {
     type Error = anyhow::Error;
-    fn try_from(ct: pb::MockFlowCiphertext) -> Result<Self, Self::Error> {
-        Ok(Self(ct.value))
+    fn try_from(proof: pb::FlowCommitmentProof) -> Result<Self, Self::Error> {
+        Ok(Self(
+            proof
+                .inner
+                .ok_or_else(|| anyhow::anyhow!("missing commitment proof"))?
+                .try_into()?,
+        ))
+    }
+}
+
+/// The key that flows are encrypted to, whose decryption key is shared among
+/// the validators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "pb::FlowEncryptionKey", into = "pb::FlowEncryptionKey")]
+pub struct FlowEncryptionKey(pub EncryptionKey);
+
+impl FlowEncryptionKey {
+    /// Encrypt `amount` to this key, using the given blinding factors.
+    pub fn encrypt(&self, amount: u64, blindings: &FlowBlindings) -> FlowCiphertext {
+        let (ciphertext, _proof) = penumbra_eddy::Value::from(amount)
+            .transparent_encrypt_with_blindings(&self.0, blindings.0)
+            .expect("64-bit values can always be encrypted");
+        FlowCiphertext(ciphertext)
+    }
+
+    /// Encrypt `amount` to this key, using the given blinding factors, and
+    /// prove that the ciphertext is well-formed.
+    ///
+    /// The ciphertext is the same as the one produced by [`Self::encrypt`].
+    pub fn encrypt_with_proof<R: RngCore + CryptoRng>(
+        &self,
+        amount: u64,
+        blindings: &FlowBlindings,
+        rng: &mut R,
+    ) -> (FlowCiphertext, FlowEncryptionProof) {
+        let (ciphertext, proof) = penumbra_eddy::Value::from(amount)
+            .encrypt_with_blindings(
+                &self.0,
+                blindings.0,
+                &mut merlin::Transcript::new(ENCRYPTION_TRANSCRIPT_LABEL),
+                rng,
+            )
+            .expect("64-bit values can always be encrypted");
+        (FlowCiphertext(ciphertext), FlowEncryptionProof(proof))
+    }
+
+    /// Prove that the encryption of `value`'s amount with the given blinding
+    /// factors encrypts the same amount as `value.commit(commitment_blinding)`.
+    pub fn prove_commitment<R: RngCore + CryptoRng>(
+        &self,
+        value: Value,
+        blindings: &FlowBlindings,
+        commitment_blinding: Fr,
+        rng: &mut R,
+    ) -> FlowCommitmentProof {
+        let proof = penumbra_eddy::Value::from(value.amount)
+            .prove_commitment(
+                &self.0,
+                blindings.0,
+                value.asset_id.value_generator(),
+                *value::VALUE_BLINDING_GENERATOR.deref(),
+                commitment_blinding,
+                &mut merlin::Transcript::new(COMMITMENT_TRANSCRIPT_LABEL),
+                rng,
+            )
+            .expect("64-bit values can always be encrypted");
+        FlowCommitmentProof(proof)
+    }
+}
+
+impl Protobuf<pb::FlowEncryptionKey> for FlowEncryptionKey {}
+
+impl From<FlowEncryptionKey> for pb::FlowEncryptionKey {
+    fn from(key: FlowEncryptionKey) -> Self {
+        pb::FlowEncryptionKey {
+            inner: key.0.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::FlowEncryptionKey> for FlowEncryptionKey {
+    type Error = anyhow::Error;
+    fn try_from(key: pb::FlowEncryptionKey) -> Result<Self, Self::Error> {
+        Ok(Self(key.inner.as_slice().try_into()?))
+    }
+}
+
+/// The blinding factors used to encrypt each of the four limbs of a flow's value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlowBlindings(pub [Fr; 4]);
+
+impl FlowBlindings {
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self([Fr::rand(rng), Fr::rand(rng), Fr::rand(rng), Fr::rand(rng)])
+    }
+
+    pub fn to_bytes(&self) -> [u8; 128] {
+        let mut bytes = [0u8; 128];
+        for (chunk, blinding) in bytes.chunks_exact_mut(32).zip(self.0.iter()) {
+            chunk.copy_from_slice(&blinding.to_bytes());
+        }
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for FlowBlindings {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 128 {
+            return Err(anyhow::anyhow!("flow blindings must be 128 bytes"));
+        }
+        let mut blindings = [Fr::default(); 4];
+        for (blinding, chunk) in blindings.iter_mut().zip(bytes.chunks_exact(32)) {
+            *blinding = Fr::from_bytes(chunk.try_into()?)
+                .map_err(|_| anyhow::anyhow!("invalid flow blinding encoding"))?;
+        }
+        Ok(Self(blindings))
+    }
+}
+
+/// The domain separator for the transcripts of flow decryption shares.
+const DECRYPTION_TRANSCRIPT_LABEL: &[u8] = b"penumbra_flow_decryption";
+
+/// A validator's share of the flow decryption key.
+///
+/// Each validator keeps its key share locally, and never publishes it: the
+/// chain only records the [`FlowCommittee`] of public key shares, against which
+/// the validators' [`FlowDecryptionShare`]s are verified.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "pb::FlowKeyShare", into = "pb::FlowKeyShare")]
+pub struct FlowKeyShare(pub PrivateKeyShare);
+
+impl FlowKeyShare {
+    /// The index of the validator holding this share in the [`FlowCommittee`].
+    pub fn participant_index(&self) -> u32 {
+        self.0.participant_index()
+    }
+
+    /// Compute this key share's share of the decryption of `ciphertext`, with a
+    /// proof that it was computed with this key share.
+    pub fn decryption_share<R: RngCore + CryptoRng>(
+        &self,
+        ciphertext: &FlowCiphertext,
+        rng: &mut R,
+    ) -> FlowDecryptionShare {
+        FlowDecryptionShare(self.0.decryption_share(
+            &ciphertext.0,
+            &mut merlin::Transcript::new(DECRYPTION_TRANSCRIPT_LABEL),
+            rng,
+        ))
+    }
+}
+
+impl std::fmt::Debug for FlowKeyShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Don't print the key material itself.
+        f.debug_struct("FlowKeyShare")
+            .field("participant_index", &self.participant_index())
+            .finish()
+    }
+}
+
+impl Protobuf<pb::FlowKeyShare> for FlowKeyShare {}
+
+impl From<FlowKeyShare> for pb::FlowKeyShare {
+    fn from(share: FlowKeyShare) -> Self {
+        pb::FlowKeyShare {
+            inner: share.0.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::FlowKeyShare> for FlowKeyShare {
+    type Error = anyhow::Error;
+    fn try_from(share: pb::FlowKeyShare) -> Result<Self, Self::Error> {
+        Ok(Self(share.inner.as_slice().try_into()?))
+    }
+}
+
+/// The public key shares of the validators holding the shares of the flow
+/// decryption key, and the number of their decryption shares needed to decrypt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "pb::FlowCommittee", into = "pb::FlowCommittee")]
+pub struct FlowCommittee(pub Committee);
+
+impl FlowCommittee {
+    /// The number of decryption shares needed to decrypt.
+    pub fn threshold(&self) -> u32 {
+        self.0.threshold
+    }
+
+    /// Whether the committee has a member with the given index.
+    pub fn contains(&self, participant_index: u32) -> bool {
+        self.0.share(participant_index).is_some()
+    }
+}
+
+impl Protobuf<pb::FlowCommittee> for FlowCommittee {}
+
+impl From<FlowCommittee> for pb::FlowCommittee {
+    fn from(committee: FlowCommittee) -> Self {
+        pb::FlowCommittee {
+            shares: committee.0.shares.into_iter().map(Into::into).collect(),
+            threshold: committee.0.threshold,
+        }
+    }
+}
+
+impl TryFrom<pb::FlowCommittee> for FlowCommittee {
+    type Error = anyhow::Error;
+    fn try_from(committee: pb::FlowCommittee) -> Result<Self, Self::Error> {
+        let shares = committee
+            .shares
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+        if committee.threshold == 0 || committee.threshold as usize > shares.len() {
+            return Err(anyhow::anyhow!(
+                "threshold {} must be between 1 and the committee size {}",
+                committee.threshold,
+                shares.len()
+            ));
+        }
+        Ok(Self(Committee {
+            shares,
+            threshold: committee.threshold,
+        }))
+    }
+}
+
+/// A validator's share of the decryption of a [`FlowCiphertext`], with a proof
+/// that it was computed with the validator's [`FlowKeyShare`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "pb::FlowDecryptionShare", into = "pb::FlowDecryptionShare")]
+pub struct FlowDecryptionShare(pub DecryptionShare<Unverified>);
+
+impl FlowDecryptionShare {
+    /// The index of the validator who computed this share in the [`FlowCommittee`].
+    pub fn participant_index(&self) -> u32 {
+        self.0.participant_index()
+    }
+
+    /// Verify that this is a share of the decryption of `ciphertext`, computed
+    /// by a member of `committee`, so that it can be used to decrypt.
+    pub fn verify(
+        &self,
+        ciphertext: &FlowCiphertext,
+        committee: &FlowCommittee,
+    ) -> anyhow::Result<DecryptionShare<Verified>> {
+        let pub_key_share = committee.0.share(self.participant_index()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "participant {} is not a member of the flow committee",
+                self.participant_index()
+            )
+        })?;
+        self.0.verify(
+            &ciphertext.0,
+            pub_key_share,
+            &mut merlin::Transcript::new(DECRYPTION_TRANSCRIPT_LABEL),
+        )
+    }
+}
+
+impl Protobuf<pb::FlowDecryptionShare> for FlowDecryptionShare {}
+
+impl From<FlowDecryptionShare> for pb::FlowDecryptionShare {
+    fn from(share: FlowDecryptionShare) -> Self {
+        pb::FlowDecryptionShare {
+            inner: Some(share.0.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::FlowDecryptionShare> for FlowDecryptionShare {
+    type Error = anyhow::Error;
+    fn try_from(share: pb::FlowDecryptionShare) -> Result<Self, Self::Error> {
+        Ok(Self(
+            share
+                .inner
+                .ok_or_else(|| anyhow::anyhow!("missing decryption share"))?
+                .try_into()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn encryption_proof_verifies() {
+        let (encryption_key, _) = penumbra_eddy::dkg::deal(1, 1, OsRng).unwrap();
+        let key = FlowEncryptionKey(encryption_key);
+        let blindings = FlowBlindings::random(&mut OsRng);
+
+        let (ciphertext, proof) = key.encrypt_with_proof(1_000_000, &blindings, &mut OsRng);
+        assert_eq!(ciphertext, key.encrypt(1_000_000, &blindings));
+        proof.verify(&ciphertext, &key).unwrap();
+
+        // The proof doesn't verify against any other ciphertext.
+        let other = key.encrypt(1_000_000, &FlowBlindings::random(&mut OsRng));
+        assert!(proof.verify(&other, &key).is_err());
+    }
+
+    #[test]
+    fn commitment_proof_verifies() {
+        let (encryption_key, _) = penumbra_eddy::dkg::deal(1, 1, OsRng).unwrap();
+        let key = FlowEncryptionKey(encryption_key);
+        let blindings = FlowBlindings::random(&mut OsRng);
+        let commitment_blinding = Fr::rand(&mut OsRng);
+        let value = Value {
+            amount: 1_000_000,
+            asset_id: crate::asset::REGISTRY
+                .parse_denom("upenumbra")
+                .unwrap()
+                .id(),
+        };
+
+        let ciphertext = key.encrypt(value.amount, &blindings);
+        let commitment = value.commit(commitment_blinding);
+        let proof = key.prove_commitment(value, &blindings, commitment_blinding, &mut OsRng);
+        proof
+            .verify(&ciphertext, &key, commitment, value.asset_id)
+            .unwrap();
+
+        // The proof doesn't link the ciphertext to a commitment to any other amount.
+        let other = Value {
+            amount: value.amount + 1,
+            ..value
+        };
+        assert!(proof
+            .verify(
+                &ciphertext,
+                &key,
+                other.commit(commitment_blinding),
+                value.asset_id
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn decryption_shares_verify_against_committee() {
+        let (encryption_key, key_shares) = penumbra_eddy::dkg::deal(2, 3, OsRng).unwrap();
+        let key = FlowEncryptionKey(encryption_key);
+        let committee = FlowCommittee(Committee {
+            shares: key_shares
+                .iter()
+                .map(|share| *share.public_key_share())
+                .collect(),
+            threshold: 2,
+        });
+        let ciphertext = key.encrypt(1_000_000, &FlowBlindings::random(&mut OsRng));
+
+        let key_share = FlowKeyShare(key_shares[0].clone());
+        let share = key_share.decryption_share(&ciphertext, &mut OsRng);
+        share.verify(&ciphertext, &committee).unwrap();
+
+        // The share doesn't verify as a decryption of any other ciphertext.
+        let other = key.encrypt(1_000_000, &FlowBlindings::random(&mut OsRng));
+        assert!(share.verify(&other, &committee).is_err());
+
+        // Nor does a share from outside the committee.
+        let (_, outsiders) = penumbra_eddy::dkg::deal(1, 4, OsRng).unwrap();
+        let outsider = FlowKeyShare(outsiders[3].clone());
+        assert!(outsider
+            .decryption_share(&ciphertext, &mut OsRng)
+            .verify(&ciphertext, &committee)
+            .is_err());
     }
 }
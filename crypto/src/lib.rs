@@ -10,6 +10,7 @@ pub mod asset;
 mod delegation_token;
 pub mod dex;
 pub mod eddy;
+pub mod fixpoint;
 mod flow;
 mod governance_key;
 mod identity_key;
@@ -27,7 +28,10 @@ pub mod value;
 pub use address::Address;
 pub use asset::Asset;
 pub use delegation_token::DelegationToken;
-pub use flow::MockFlowCiphertext;
+pub use flow::{
+    FlowBlindings, FlowCiphertext, FlowCommitmentProof, FlowCommittee, FlowDecryptionShare,
+    FlowEncryptionKey, FlowEncryptionProof, FlowKeyShare,
+};
 pub use governance_key::GovernanceKey;
 pub use identity_key::IdentityKey;
 pub use keys::FullViewingKey;
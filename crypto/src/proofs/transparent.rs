@@ -11,10 +11,11 @@ use penumbra_tct as tct;
 
 use crate::{
     asset,
-    dex::{swap::SwapPlaintext, TradingPair},
+    dex::{swap::SwapPlaintext, BatchSwapOutputData, TradingPair},
     fmd, ka, keys, note,
     transaction::Fee,
-    value, Address, Fq, Fr, Nullifier, Value,
+    value, Address, FlowCiphertext, FlowCommitmentProof, FlowEncryptionKey, Fq, Fr, Nullifier,
+    Value,
 };
 
 /// Transparent proof for spending existing notes.
@@ -123,6 +124,116 @@ impl SpendProof {
     }
 }
 
+/// Transparent proof for voting on a proposal with delegation tokens.
+///
+/// This is like a [`SpendProof`], except that the value of the note is public,
+/// since it determines the weight of the vote, and the note must have been
+/// committed before voting on the proposal started.
+///
+/// This structure keeps track of the auxiliary (private) inputs.
+#[derive(Clone, Debug)]
+pub struct DelegatorVoteProof {
+    // Inclusion proof for the note commitment.
+    pub note_commitment_proof: tct::Proof,
+    // The diversified base for the address.
+    pub g_d: decaf377::Element,
+    // The transmission key for the address.
+    pub pk_d: ka::Public,
+    // The clue key for the address.
+    pub ck_d: fmd::ClueKey,
+    // The value of the note.
+    pub value: Value,
+    // The blinding factor used for generating the note commitment.
+    pub note_blinding: Fq,
+    // The randomizer used for generating the randomized spend auth key.
+    pub spend_auth_randomizer: Fr,
+    // The spend authorization key.
+    pub ak: VerificationKey<SpendAuth>,
+    // The nullifier deriving key.
+    pub nk: keys::NullifierKey,
+}
+
+impl DelegatorVoteProof {
+    /// Called to verify the proof using the provided public inputs.
+    ///
+    /// The public inputs are:
+    /// * the merkle root of the note commitment tree,
+    /// * the position of the note commitment tree when voting started,
+    /// * value of the note voted with,
+    /// * nullifier of the note voted with,
+    /// * the randomized verification spend key,
+    pub fn verify(
+        &self,
+        anchor: tct::Root,
+        start_position: tct::Position,
+        value: Value,
+        nullifier: Nullifier,
+        rk: VerificationKey<SpendAuth>,
+    ) -> anyhow::Result<()> {
+        // Note commitment integrity.
+        let s_component_transmission_key = Fq::from_bytes(self.pk_d.0);
+        if let Ok(transmission_key_s) = s_component_transmission_key {
+            let note_commitment_test = note::commitment(
+                self.note_blinding,
+                value,
+                self.g_d,
+                transmission_key_s,
+                &self.ck_d,
+            );
+
+            if self.note_commitment_proof.commitment() != note_commitment_test {
+                return Err(anyhow!("note commitment mismatch"));
+            }
+        } else {
+            return Err(anyhow!("transmission key mismatch"));
+        }
+
+        // Merkle path integrity.
+        self.note_commitment_proof
+            .verify(anchor)
+            .map_err(|_| anyhow!("merkle root mismatch"))?;
+
+        // The note must have existed before voting started.
+        if self.note_commitment_proof.position() >= start_position {
+            return Err(anyhow!("note was created after voting started"));
+        }
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        if self.g_d.is_identity() || self.ak.is_identity() {
+            return Err(anyhow!("unexpected identity"));
+        }
+
+        // Nullifier integrity.
+        if nullifier
+            != self.nk.derive_nullifier(
+                self.note_commitment_proof.position(),
+                &self.note_commitment_proof.commitment(),
+            )
+        {
+            return Err(anyhow!("bad nullifier"));
+        }
+
+        // Spend authority.
+        let rk_bytes: [u8; 32] = rk.into();
+        let rk_test = self.ak.randomize(&self.spend_auth_randomizer);
+        let rk_test_bytes: [u8; 32] = rk_test.into();
+        if rk_bytes != rk_test_bytes {
+            return Err(anyhow!("invalid spend auth randomizer"));
+        }
+
+        // Diversified address integrity.
+        let fvk = keys::FullViewingKey::from_components(self.ak, self.nk);
+        let ivk = fvk.incoming();
+        if self.pk_d != ivk.diversified_public(&self.g_d) {
+            return Err(anyhow!("invalid diversified address"));
+        }
+
+        Ok(())
+    }
+}
+
 /// Transparent proof for new note creation.
 ///
 /// This structure keeps track of the auxiliary (private) inputs.
@@ -301,6 +412,104 @@ impl TryFrom<transparent_proofs::SpendProof> for SpendProof {
     }
 }
 
+impl Protobuf<transparent_proofs::DelegatorVoteProof> for DelegatorVoteProof {}
+
+impl From<DelegatorVoteProof> for transparent_proofs::DelegatorVoteProof {
+    fn from(msg: DelegatorVoteProof) -> Self {
+        let ak_bytes: [u8; 32] = msg.ak.into();
+        let nk_bytes: [u8; 32] = msg.nk.0.to_bytes();
+        transparent_proofs::DelegatorVoteProof {
+            note_commitment_proof: Some(msg.note_commitment_proof.into()),
+            g_d: msg.g_d.vartime_compress().0.to_vec(),
+            pk_d: msg.pk_d.0.to_vec(),
+            value_amount: msg.value.amount,
+            value_asset_id: msg.value.asset_id.0.to_bytes().to_vec(),
+            note_blinding: msg.note_blinding.to_bytes().to_vec(),
+            spend_auth_randomizer: msg.spend_auth_randomizer.to_bytes().to_vec(),
+            ak: ak_bytes.into(),
+            nk: nk_bytes.into(),
+            ck_d: msg.ck_d.0.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<transparent_proofs::DelegatorVoteProof> for DelegatorVoteProof {
+    type Error = Error;
+
+    fn try_from(
+        proto: transparent_proofs::DelegatorVoteProof,
+    ) -> anyhow::Result<Self, Self::Error> {
+        let g_d_bytes: [u8; 32] = proto
+            .g_d
+            .try_into()
+            .map_err(|_| anyhow!("proto malformed"))?;
+        let g_d_encoding = decaf377::Encoding(g_d_bytes);
+
+        let ck_d_bytes: [u8; 32] = proto
+            .ck_d
+            .try_into()
+            .map_err(|_| anyhow!("proto malformed"))?;
+
+        let ak_bytes: [u8; 32] = (proto.ak[..])
+            .try_into()
+            .map_err(|_| anyhow!("proto malformed"))?;
+        let ak = ak_bytes
+            .try_into()
+            .map_err(|_| anyhow!("proto malformed"))?;
+
+        Ok(DelegatorVoteProof {
+            note_commitment_proof: proto
+                .note_commitment_proof
+                .ok_or_else(|| anyhow!("proto malformed"))?
+                .try_into()
+                .map_err(|_| anyhow!("proto malformed"))?,
+            g_d: g_d_encoding
+                .vartime_decompress()
+                .map_err(|_| anyhow!("proto malformed"))?,
+            pk_d: ka::Public(
+                proto
+                    .pk_d
+                    .try_into()
+                    .map_err(|_| anyhow!("proto malformed"))?,
+            ),
+            ck_d: fmd::ClueKey(ck_d_bytes),
+            value: Value {
+                amount: proto.value_amount,
+                asset_id: asset::Id(
+                    Fq::from_bytes(
+                        proto
+                            .value_asset_id
+                            .try_into()
+                            .map_err(|_| anyhow!("proto malformed"))?,
+                    )
+                    .map_err(|_| anyhow!("proto malformed"))?,
+                ),
+            },
+            note_blinding: Fq::from_bytes(
+                proto.note_blinding[..]
+                    .try_into()
+                    .map_err(|_| anyhow!("proto malformed"))?,
+            )
+            .map_err(|_| anyhow!("proto malformed"))?,
+            spend_auth_randomizer: Fr::from_bytes(
+                proto.spend_auth_randomizer[..]
+                    .try_into()
+                    .map_err(|_| anyhow!("proto malformed"))?,
+            )
+            .map_err(|_| anyhow!("proto malformed"))?,
+            ak,
+            nk: keys::NullifierKey(
+                Fq::from_bytes(
+                    proto.nk[..]
+                        .try_into()
+                        .map_err(|_| anyhow!("proto malformed"))?,
+                )
+                .map_err(|_| anyhow!("proto malformed"))?,
+            ),
+        })
+    }
+}
+
 impl Protobuf<transparent_proofs::OutputProof> for OutputProof {}
 
 impl From<OutputProof> for transparent_proofs::OutputProof {
@@ -397,6 +606,26 @@ impl TryFrom<&[u8]> for SpendProof {
     }
 }
 
+impl From<DelegatorVoteProof> for Vec<u8> {
+    fn from(delegator_vote_proof: DelegatorVoteProof) -> Vec<u8> {
+        let protobuf_serialized_proof: transparent_proofs::DelegatorVoteProof =
+            delegator_vote_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for DelegatorVoteProof {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<DelegatorVoteProof, Self::Error> {
+        let protobuf_serialized_proof = transparent_proofs::DelegatorVoteProof::decode(bytes)
+            .map_err(|_| anyhow!("proto malformed"))?;
+        protobuf_serialized_proof
+            .try_into()
+            .map_err(|_| anyhow!("proto malformed"))
+    }
+}
+
 impl From<OutputProof> for Vec<u8> {
     fn from(output_proof: OutputProof) -> Vec<u8> {
         let protobuf_serialized_proof: transparent_proofs::OutputProof = output_proof.into();
@@ -442,6 +671,8 @@ pub struct SwapClaimProof {
     pub trading_pair: TradingPair,
     pub delta_1: u64,
     pub delta_2: u64,
+    pub delta_1_blinding: Fr,
+    pub delta_2_blinding: Fr,
 
     // Describes output amounts
     pub lambda_1: u64,
@@ -461,22 +692,28 @@ impl SwapClaimProof {
     ///
     /// The public inputs are:
     /// * the merkle root of the note commitment tree,
-    /// * value commitment of the note to be spent,
-    /// * nullifier of the note to be spent,
-    /// * the randomized verification spend key,
+    /// * value commitment of the pre-paid fee for the swap claim,
+    /// * nullifier of the swap NFT note to be spent,
+    /// * the trading pair of the swap,
+    /// * the output data of the batch swap the swap was included in,
+    /// * the epoch duration of the chain,
     /// * the pre-paid fee amount for the swap,
+    /// * the note commitments and ephemeral public keys of the two output notes.
+    #[allow(clippy::too_many_arguments)]
     pub fn verify(
         &self,
         anchor: tct::Root,
         // Value commitment to the fees for the swap claim
         value_commitment: value::Commitment,
         nullifier: Nullifier,
-        _clearing_price_1: u64,
-        _clearing_price_2: u64,
-        clearing_price_height: u64,
-        _success: bool,
+        trading_pair: TradingPair,
+        output_data: BatchSwapOutputData,
         epoch_duration: u64,
         fee: Fee,
+        note_commitment_1: note::Commitment,
+        epk_1: ka::Public,
+        note_commitment_2: note::Commitment,
+        epk_2: ka::Public,
     ) -> anyhow::Result<()> {
         // Swap NFT note commitment integrity.
         let swap_nft_value = Value {
@@ -496,6 +733,11 @@ impl SwapClaimProof {
             return Err(anyhow!("note commitment mismatch"));
         }
 
+        // The swap must have been made on the claimed trading pair.
+        if self.trading_pair != trading_pair {
+            return Err(anyhow!("trading pair mismatch"));
+        }
+
         // check the swap NFT Asset ID is properly constructed
         let asset_id = self.swap_nft_asset_id;
         let expected_plaintext = SwapPlaintext::from_parts(
@@ -506,6 +748,8 @@ impl SwapClaimProof {
             // This should ensure that the claim address matches the address
             // used to construct the Swap NFT.
             self.claim_address,
+            self.delta_1_blinding,
+            self.delta_2_blinding,
         )
         .map_err(|_| anyhow!("error generating expected swap plaintext"))?;
         let expected_asset_id = expected_plaintext.asset_id();
@@ -524,7 +768,7 @@ impl SwapClaimProof {
         let epoch = position.epoch();
         let note_commitment_block_height: u64 =
             epoch_duration * u64::from(epoch) + u64::from(block);
-        if note_commitment_block_height != clearing_price_height {
+        if note_commitment_block_height != output_data.height {
             return Err(anyhow::anyhow!(
                 "note commitment was not for clearing price height"
             ));
@@ -547,22 +791,59 @@ impl SwapClaimProof {
             return Err(anyhow!("bad nullifier"));
         }
 
-        // TODO:
-        // The address should be the same for the Swap NFT and SwapClaim outputs
-        // Need output notes here, and to validate the amounts and addresses.
-        // instructions here: https://github.com/penumbra-zone/penumbra/issues/1126
-        // let lambda_1 = success.into() * (clearing_price_1 * self.delta_2)
-        //     + (1 - success.into()) * self.delta_1;
-        // let lambda_2 = success.into() * (clearing_price_2 * self.delta_1)
-        //     + (1 - success.into()) * self.delta_2;
-        // let proof_1 = OutputProof {
-        //     g_d: self.b_d,
-        //     pk_d: self.pk_d,
-        //     value: lambda_1,
-        //     v_blinding,
-        //     note_blinding: self.note_blinding_1,
-        //     esk,
-        // };
+        // Output amount integrity: the outputs must be the swapper's pro rata share of the
+        // batch swap outputs (or a refund of their inputs, if the batch swap failed).
+        let (lambda_1, lambda_2) = output_data.pro_rata_outputs((self.delta_1, self.delta_2));
+        if (self.lambda_1, self.lambda_2) != (lambda_1, lambda_2) {
+            return Err(anyhow!("output amounts do not match batch swap outputs"));
+        }
+
+        // Output note integrity. Both outputs are sent to the same address as the swap NFT.
+        let output_notes = [
+            (
+                self.note_blinding_1,
+                Value {
+                    amount: self.lambda_1,
+                    asset_id: self.trading_pair.asset_1(),
+                },
+                &self.esk_1,
+                note_commitment_1,
+                epk_1,
+            ),
+            (
+                self.note_blinding_2,
+                Value {
+                    amount: self.lambda_2,
+                    asset_id: self.trading_pair.asset_2(),
+                },
+                &self.esk_2,
+                note_commitment_2,
+                epk_2,
+            ),
+        ];
+        for (note_blinding, value, esk, note_commitment, epk) in output_notes {
+            let note_commitment_test = note::commitment(
+                note_blinding,
+                value,
+                *self.claim_address.diversified_generator(),
+                *transmission_key_s,
+                &self.claim_address.clue_key(),
+            );
+            if note_commitment != note_commitment_test {
+                return Err(anyhow!("output note commitment mismatch"));
+            }
+
+            if esk.diversified_public(self.claim_address.diversified_generator()) != epk {
+                return Err(anyhow!("ephemeral public key mismatch"));
+            }
+        }
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        if self.claim_address.diversified_generator().is_identity() {
+            return Err(anyhow!("unexpected identity"));
+        }
 
         Ok(())
     }
@@ -598,6 +879,8 @@ impl From<SwapClaimProof> for transparent_proofs::SwapClaimProof {
             trading_pair: Some(msg.trading_pair.into()),
             delta_1: msg.delta_1,
             delta_2: msg.delta_2,
+            delta_1_blinding: msg.delta_1_blinding.to_bytes().to_vec(),
+            delta_2_blinding: msg.delta_2_blinding.to_bytes().to_vec(),
             lambda_1: msg.lambda_1,
             lambda_2: msg.lambda_2,
             note_blinding_1: msg.note_blinding_1.to_bytes().to_vec(),
@@ -637,6 +920,18 @@ impl TryFrom<transparent_proofs::SwapClaimProof> for SwapClaimProof {
             lambda_1: proto.lambda_1,
             delta_2: proto.delta_2,
             delta_1: proto.delta_1,
+            delta_1_blinding: Fr::from_bytes(
+                proto.delta_1_blinding[..]
+                    .try_into()
+                    .map_err(|_| anyhow!("proto malformed"))?,
+            )
+            .map_err(|_| anyhow!("proto malformed"))?,
+            delta_2_blinding: Fr::from_bytes(
+                proto.delta_2_blinding[..]
+                    .try_into()
+                    .map_err(|_| anyhow!("proto malformed"))?,
+            )
+            .map_err(|_| anyhow!("proto malformed"))?,
             trading_pair: proto
                 .trading_pair
                 .ok_or_else(|| anyhow!("proto malformed"))?
@@ -683,46 +978,59 @@ impl TryFrom<transparent_proofs::SwapClaimProof> for SwapClaimProof {
 ///
 /// Swaps create an output NFT encoding the swap data so they are most similar to Output operations.
 ///
-/// This structure keeps track of the auxiliary (private) inputs.
+/// This structure keeps track of the auxiliary (private) inputs.  Unlike the other transparent
+/// proofs, it doesn't include the swap's input amounts or the blinding factors of their
+/// commitments and encryptions: the swap NFT's asset ID is derived from the commitments to the
+/// inputs, and each input's encryption is linked to its commitment by a [`FlowCommitmentProof`],
+/// so that the inputs are only revealed in aggregate with the rest of the batch.
 #[derive(Clone, Debug)]
 pub struct SwapProof {
     // The address associated with the swap.
     pub claim_address: Address,
-    // The value of asset 1 in the swap.
-    pub value_t1: Value,
-    // The value of asset 2 in the swap.
-    pub value_t2: Value,
     // The fee amount associated with the swap.
     pub fee_delta: Fee,
+    // The blinding factor used for generating the value commitment for the fee.
+    pub fee_blinding: Fr,
     // The asset ID of the Swap NFT.
     pub swap_nft_asset_id: asset::Id,
     // The blinding factor used for generating the note commitment for the Swap NFT.
     pub note_blinding: Fq,
     // The ephemeral secret key that corresponds to the public key.
     pub esk: ka::Secret,
-    // TODO: no value commitments for delta 1/delta 2 until flow encryption is available
-    // // The blinding factor used for generating the value commitment for delta 1.
-    // pub delta_1_blinding: Fr,
-    // // The blinding factor used for generating the value commitment for delta 2.
-    // pub delta_2_blinding: Fr,
+    // Proves that the encryption of delta 1 encrypts the amount in its value commitment.
+    pub delta_1_proof: FlowCommitmentProof,
+    // Proves that the encryption of delta 2 encrypts the amount in its value commitment.
+    pub delta_2_proof: FlowCommitmentProof,
 }
 
 impl SwapProof {
     /// Called to verify the proof using the provided public inputs.
     ///
     /// The public inputs are:
+    /// * the trading pair of the swap,
     /// * value commitment of the asset 1's contribution to the transaction,
     /// * value commitment of the asset 2's contribution to the transaction,
     /// * value commitment of the fee's contribution to the transaction,
     /// * note commitment of the new swap NFT note,
-    /// * the ephemeral public key used to generate the new swap NFT note.
+    /// * the ephemeral public key used to generate the new swap NFT note,
+    /// * the encryptions of the amounts of asset 1 and asset 2,
+    /// * the flow encryption key they're encrypted to.
+    ///
+    /// This doesn't check that the encryptions are of 64-bit amounts, which is shown by their
+    /// [`FlowEncryptionProof`](crate::FlowEncryptionProof)s.  Since the amounts are hidden, it
+    /// also can't check that the swap has a nonzero input: a swap of nothing just costs its fee.
+    #[allow(clippy::too_many_arguments)]
     pub fn verify(
         &self,
-        _value_1_commitment: value::Commitment,
-        _value_2_commitment: value::Commitment,
+        trading_pair: TradingPair,
+        value_1_commitment: value::Commitment,
+        value_2_commitment: value::Commitment,
         value_fee_commitment: value::Commitment,
         note_commitment: note::Commitment,
         epk: ka::Public,
+        enc_amount_1: FlowCiphertext,
+        enc_amount_2: FlowCiphertext,
+        flow_encryption_key: &FlowEncryptionKey,
     ) -> anyhow::Result<(), Error> {
         // Note commitment integrity.
         let transmission_key_s = self.claim_address.transmission_key_s();
@@ -743,18 +1051,42 @@ impl SwapProof {
             return Err(anyhow!("note commitment mismatch"));
         }
 
-        // TODO: no value commitment checks until flow encryption is available
-        // // Value commitment integrity.
-        // if value_1_commitment != -self.value_t1.commit(self.delta_1_blinding) {
-        //     return Err(anyhow!("value commitment mismatch"));
-        // }
+        // The value commitments are for the swap's contribution to the transaction, so they
+        // commit to the negated inputs.
+        let delta_1_commitment = -value_1_commitment;
+        let delta_2_commitment = -value_2_commitment;
+
+        // Check the swap NFT asset ID is properly constructed from the committed swap inputs.
+        let expected_asset_id = SwapPlaintext::asset_id_from_commitments(
+            trading_pair,
+            delta_1_commitment,
+            delta_2_commitment,
+            &self.fee_delta,
+            &self.claim_address,
+        );
+        if expected_asset_id != self.swap_nft_asset_id {
+            return Err(anyhow!("improper swap NFT asset id"));
+        }
 
-        // if value_2_commitment != -self.value_t2.commit(self.delta_2_blinding) {
-        //     return Err(anyhow!("value commitment mismatch"));
-        // }
+        // Swap amount encryption integrity: each encryption must be of the committed amount.
+        self.delta_1_proof
+            .verify(
+                &enc_amount_1,
+                flow_encryption_key,
+                delta_1_commitment,
+                trading_pair.asset_1(),
+            )
+            .map_err(|_| anyhow!("swap amount encryption mismatch"))?;
+        self.delta_2_proof
+            .verify(
+                &enc_amount_2,
+                flow_encryption_key,
+                delta_2_commitment,
+                trading_pair.asset_2(),
+            )
+            .map_err(|_| anyhow!("swap amount encryption mismatch"))?;
 
-        let fee_blinding = Fr::zero();
-        if value_fee_commitment != -self.fee_delta.commit(fee_blinding) {
+        if value_fee_commitment != -self.fee_delta.commit(self.fee_blinding) {
             return Err(anyhow!("value commitment mismatch"));
         }
 
@@ -784,15 +1116,11 @@ impl From<SwapProof> for transparent_proofs::SwapProof {
     fn from(msg: SwapProof) -> Self {
         transparent_proofs::SwapProof {
             claim_address: Some(msg.claim_address.into()),
-            delta_1: msg.value_t1.amount,
-            t1: msg.value_t1.asset_id.0.to_bytes().to_vec(),
-            delta_2: msg.value_t2.amount,
-            t2: msg.value_t2.asset_id.0.to_bytes().to_vec(),
             fee: Some(msg.fee_delta.into()),
+            fee_blinding: msg.fee_blinding.to_bytes().to_vec(),
             swap_nft_asset_id: msg.swap_nft_asset_id.0.to_bytes().to_vec(),
-            // TODO: no value commitments for delta 1/delta 2 until flow encryption is available
-            // delta_1_blinding: msg.delta_1_blinding.to_bytes().to_vec(),
-            // delta_2_blinding: msg.delta_2_blinding.to_bytes().to_vec(),
+            delta_1_proof: Some(msg.delta_1_proof.into()),
+            delta_2_proof: Some(msg.delta_2_proof.into()),
             note_blinding: msg.note_blinding.to_bytes().to_vec(),
             esk: msg.esk.to_bytes().to_vec(),
         }
@@ -803,13 +1131,6 @@ impl TryFrom<transparent_proofs::SwapProof> for SwapProof {
     type Error = Error;
 
     fn try_from(proto: transparent_proofs::SwapProof) -> anyhow::Result<Self, Self::Error> {
-        // let delta_1_blinding_bytes: [u8; 32] = proto.delta_1_blinding[..]
-        //     .try_into()
-        //     .map_err(|_| anyhow!("proto malformed"))?;
-        // let delta_2_blinding_bytes: [u8; 32] = proto.delta_2_blinding[..]
-        //     .try_into()
-        //     .map_err(|_| anyhow!("proto malformed"))?;
-
         let esk_bytes: [u8; 32] = proto.esk[..]
             .try_into()
             .map_err(|_| anyhow!("proto malformed"))?;
@@ -817,43 +1138,23 @@ impl TryFrom<transparent_proofs::SwapProof> for SwapProof {
             Fr::from_bytes(esk_bytes).map_err(|_| anyhow!("proto malformed"))?,
         );
 
-        let _pen_denom = asset::REGISTRY.parse_denom("upenumbra").unwrap();
-
         Ok(SwapProof {
             claim_address: proto
                 .claim_address
                 .ok_or(anyhow!("proto malformed"))?
                 .try_into()
                 .map_err(|_| anyhow!("proto malformed"))?,
-            value_t1: Value {
-                amount: proto.delta_1,
-                asset_id: asset::Id(
-                    Fq::from_bytes(
-                        proto
-                            .t1
-                            .try_into()
-                            .map_err(|_| anyhow!("proto malformed"))?,
-                    )
-                    .map_err(|_| anyhow!("proto malformed"))?,
-                ),
-            },
-            value_t2: Value {
-                amount: proto.delta_2,
-                asset_id: asset::Id(
-                    Fq::from_bytes(
-                        proto
-                            .t2
-                            .try_into()
-                            .map_err(|_| anyhow!("proto malformed"))?,
-                    )
-                    .map_err(|_| anyhow!("proto malformed"))?,
-                ),
-            },
             fee_delta: proto
                 .fee
                 .ok_or_else(|| anyhow::anyhow!("proto malformed"))?
                 .try_into()
                 .map_err(|_| anyhow!("proto malformed"))?,
+            fee_blinding: Fr::from_bytes(
+                proto.fee_blinding[..]
+                    .try_into()
+                    .map_err(|_| anyhow!("proto malformed"))?,
+            )
+            .map_err(|_| anyhow!("proto malformed"))?,
             swap_nft_asset_id: asset::Id(
                 Fq::from_bytes(
                     proto
@@ -863,11 +1164,16 @@ impl TryFrom<transparent_proofs::SwapProof> for SwapProof {
                 )
                 .map_err(|_| anyhow!("proto malformed"))?,
             ),
-            // TODO: no value commitment checks until flow encryption is available
-            // delta_1_blinding: Fr::from_bytes(delta_1_blinding_bytes)
-            //     .map_err(|_| anyhow!("proto malformed"))?,
-            // delta_2_blinding: Fr::from_bytes(delta_2_blinding_bytes)
-            //     .map_err(|_| anyhow!("proto malformed"))?,
+            delta_1_proof: proto
+                .delta_1_proof
+                .ok_or_else(|| anyhow!("proto malformed"))?
+                .try_into()
+                .map_err(|_| anyhow!("proto malformed"))?,
+            delta_2_proof: proto
+                .delta_2_proof
+                .ok_or_else(|| anyhow!("proto malformed"))?
+                .try_into()
+                .map_err(|_| anyhow!("proto malformed"))?,
             note_blinding: Fq::from_bytes(
                 proto.note_blinding[..]
                     .try_into()
@@ -906,7 +1212,7 @@ mod tests {
     use super::*;
     use crate::{
         keys::{SeedPhrase, SpendKey},
-        note, Note, Value,
+        note, FlowBlindings, Note, Value,
     };
 
     #[test]
@@ -1279,4 +1585,189 @@ mod tests {
             .verify(anchor, value_to_send.commit(v_blinding), incorrect_nf, rk)
             .is_err());
     }
+
+    #[test]
+    fn test_delegator_vote_proof_verification_success() {
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::generate(&mut rng);
+        let sk_sender = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let fvk_sender = sk_sender.full_viewing_key();
+        let ivk_sender = fvk_sender.incoming();
+        let (sender, _dtk_d) = ivk_sender.payment_address(0u64.into());
+
+        let value_to_vote = Value {
+            amount: 10,
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+
+        let note = Note::generate(&mut rng, &sender, value_to_vote);
+        let note_commitment = note.commit();
+        let spend_auth_randomizer = Fr::rand(&mut rng);
+        let rsk = sk_sender.spend_auth_key().randomize(&spend_auth_randomizer);
+        let nk = *sk_sender.nullifier_key();
+        let ak = sk_sender.spend_auth_key().into();
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
+
+        let proof = DelegatorVoteProof {
+            note_commitment_proof,
+            g_d: *sender.diversified_generator(),
+            pk_d: *sender.transmission_key(),
+            ck_d: *sender.clue_key(),
+            value: value_to_vote,
+            note_blinding: note.note_blinding(),
+            spend_auth_randomizer,
+            ak,
+            nk,
+        };
+
+        let rk: VerificationKey<SpendAuth> = rsk.into();
+        let nf = nk.derive_nullifier(0.into(), &note_commitment);
+        assert!(proof
+            .verify(anchor, 1.into(), value_to_vote, nf, rk)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_delegator_vote_proof_verification_start_position_failure() {
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::generate(&mut rng);
+        let sk_sender = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let fvk_sender = sk_sender.full_viewing_key();
+        let ivk_sender = fvk_sender.incoming();
+        let (sender, _dtk_d) = ivk_sender.payment_address(0u64.into());
+
+        let value_to_vote = Value {
+            amount: 10,
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+
+        let note = Note::generate(&mut rng, &sender, value_to_vote);
+        let note_commitment = note.commit();
+        let spend_auth_randomizer = Fr::rand(&mut rng);
+        let rsk = sk_sender.spend_auth_key().randomize(&spend_auth_randomizer);
+        let nk = *sk_sender.nullifier_key();
+        let ak = sk_sender.spend_auth_key().into();
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
+
+        let proof = DelegatorVoteProof {
+            note_commitment_proof,
+            g_d: *sender.diversified_generator(),
+            pk_d: *sender.transmission_key(),
+            ck_d: *sender.clue_key(),
+            value: value_to_vote,
+            note_blinding: note.note_blinding(),
+            spend_auth_randomizer,
+            ak,
+            nk,
+        };
+
+        // The note was committed at position 0, so it can't vote on a proposal
+        // that started when the tree was empty.
+        let rk: VerificationKey<SpendAuth> = rsk.into();
+        let nf = nk.derive_nullifier(0.into(), &note_commitment);
+        assert!(proof
+            .verify(anchor, 0.into(), value_to_vote, nf, rk)
+            .is_err());
+    }
+
+    #[test]
+    fn test_swap_proof_verification_encryption_integrity_failure() {
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::generate(&mut rng);
+        let sk_claimer = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let fvk_claimer = sk_claimer.full_viewing_key();
+        let ivk_claimer = fvk_claimer.incoming();
+        let (claim_address, _dtk_d) = ivk_claimer.payment_address(0u64.into());
+
+        let (encryption_key, _shares) = penumbra_eddy::dkg::deal(1, 1, &mut rng).unwrap();
+        let flow_encryption_key = FlowEncryptionKey(encryption_key);
+
+        let gm = asset::REGISTRY.parse_denom("gm").unwrap().id();
+        let gn = asset::REGISTRY.parse_denom("gn").unwrap().id();
+        let trading_pair = TradingPair::canonical_order_for((gm, gn)).unwrap();
+        let fee_delta = Fee::from_staking_token_amount(1);
+        let swap_plaintext = SwapPlaintext::new(
+            &mut rng,
+            trading_pair,
+            100,
+            0,
+            fee_delta.clone(),
+            claim_address,
+        );
+        let value_t1 = Value {
+            amount: swap_plaintext.delta_1,
+            asset_id: trading_pair.asset_1(),
+        };
+        let value_t2 = Value {
+            amount: swap_plaintext.delta_2,
+            asset_id: trading_pair.asset_2(),
+        };
+
+        let swap_nft = Note::from_parts(
+            claim_address,
+            Value {
+                amount: 1,
+                asset_id: swap_plaintext.asset_id(),
+            },
+            Fq::rand(&mut rng),
+        )
+        .unwrap();
+        let esk = ka::Secret::new(&mut rng);
+        let epk = esk.diversified_public(claim_address.diversified_generator());
+
+        let enc_amount_1_blindings = FlowBlindings::random(&mut rng);
+        let enc_amount_2_blindings = FlowBlindings::random(&mut rng);
+        let proof = SwapProof {
+            claim_address,
+            fee_delta: fee_delta.clone(),
+            fee_blinding: Fr::rand(&mut rng),
+            swap_nft_asset_id: swap_plaintext.asset_id(),
+            note_blinding: swap_nft.note_blinding(),
+            esk,
+            delta_1_proof: flow_encryption_key.prove_commitment(
+                value_t1,
+                &enc_amount_1_blindings,
+                swap_plaintext.delta_1_blinding,
+                &mut rng,
+            ),
+            delta_2_proof: flow_encryption_key.prove_commitment(
+                value_t2,
+                &enc_amount_2_blindings,
+                swap_plaintext.delta_2_blinding,
+                &mut rng,
+            ),
+        };
+
+        let verify = |enc_amount_1| {
+            proof.verify(
+                trading_pair,
+                -swap_plaintext.delta_1_commitment(),
+                -swap_plaintext.delta_2_commitment(),
+                -fee_delta.commit(proof.fee_blinding),
+                swap_nft.commit(),
+                epk,
+                enc_amount_1,
+                flow_encryption_key.encrypt(value_t2.amount, &enc_amount_2_blindings),
+                &flow_encryption_key,
+            )
+        };
+
+        assert!(
+            verify(flow_encryption_key.encrypt(value_t1.amount, &enc_amount_1_blindings)).is_ok()
+        );
+        // An encryption of a different amount doesn't match the committed amount.
+        assert!(
+            verify(flow_encryption_key.encrypt(value_t1.amount + 1, &enc_amount_1_blindings))
+                .is_err()
+        );
+    }
 }
@@ -1,5 +1,7 @@
 use std::ops::{Add, AddAssign};
 
+use penumbra_proto::{eddy as pb, Protobuf};
+
 use crate::{
     decryption_share::Verified, limb, DecryptionShare, DecryptionTable, TableLookupError, Value,
 };
@@ -11,7 +13,7 @@ use crate::{
 pub struct InsufficientSharesError {}
 
 /// A flow encryption ciphertext.
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Ciphertext {
     pub(crate) c0: limb::Ciphertext,
     pub(crate) c1: limb::Ciphertext,
@@ -20,6 +22,39 @@ pub struct Ciphertext {
 }
 
 impl Ciphertext {
+    /// The canonical encoding of this ciphertext: the encodings of each limb
+    /// ciphertext, from least to most significant.
+    pub fn to_bytes(&self) -> [u8; 256] {
+        let mut bytes = [0u8; 256];
+        for (chunk, limb) in bytes
+            .chunks_exact_mut(64)
+            .zip([&self.c0, &self.c1, &self.c2, &self.c3])
+        {
+            chunk.copy_from_slice(&limb.to_bytes());
+        }
+        bytes
+    }
+
+    /// Combine the limb ciphertexts, weighted by their place values, into a
+    /// single ElGamal ciphertext `(r * B, r * PK + v * B)` of the whole value.
+    ///
+    /// The combined ciphertext can't be decrypted by table lookup, but it can
+    /// be related to other representations of the value in proofs.
+    pub(crate) fn combine(&self) -> limb::Ciphertext {
+        let mut combined = limb::Ciphertext::default();
+        for (i, limb) in [&self.c0, &self.c1, &self.c2, &self.c3]
+            .into_iter()
+            .enumerate()
+        {
+            let weight = decaf377::Fr::from(1u64 << (16 * i));
+            combined += &limb::Ciphertext {
+                c1: limb.c1 * weight,
+                c2: limb.c2 * weight,
+            };
+        }
+        combined
+    }
+
     /// Use the provided [`DecryptionShare`]s to decrypt the ciphertext,
     /// recovering the value with the given [`DecryptionTable`].
     ///
@@ -72,6 +107,60 @@ impl Ciphertext {
     }
 }
 
+impl TryFrom<&[u8]> for Ciphertext {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 256 {
+            return Err(anyhow::anyhow!("ciphertext must be 256 bytes"));
+        }
+        let limb = |i: usize| {
+            limb::Ciphertext::from_bytes(
+                bytes[64 * i..64 * (i + 1)]
+                    .try_into()
+                    .expect("slice is 64 bytes"),
+            )
+        };
+        Ok(Self {
+            c0: limb(0)?,
+            c1: limb(1)?,
+            c2: limb(2)?,
+            c3: limb(3)?,
+        })
+    }
+}
+
+impl Protobuf<pb::Ciphertext> for Ciphertext {}
+
+impl From<Ciphertext> for pb::Ciphertext {
+    fn from(ciphertext: Ciphertext) -> Self {
+        pb::Ciphertext {
+            c0: Some(ciphertext.c0.into()),
+            c1: Some(ciphertext.c1.into()),
+            c2: Some(ciphertext.c2.into()),
+            c3: Some(ciphertext.c3.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::Ciphertext> for Ciphertext {
+    type Error = anyhow::Error;
+
+    fn try_from(ciphertext: pb::Ciphertext) -> Result<Self, Self::Error> {
+        let limb = |ciphertext: Option<pb::LimbCiphertext>| -> anyhow::Result<limb::Ciphertext> {
+            ciphertext
+                .ok_or_else(|| anyhow::anyhow!("missing limb ciphertext"))?
+                .try_into()
+        };
+        Ok(Self {
+            c0: limb(ciphertext.c0)?,
+            c1: limb(ciphertext.c1)?,
+            c2: limb(ciphertext.c2)?,
+            c3: limb(ciphertext.c3)?,
+        })
+    }
+}
+
 impl Add<&Ciphertext> for &Ciphertext {
     type Output = Ciphertext;
     fn add(self, rhs: &Ciphertext) -> Self::Output {
@@ -92,3 +181,31 @@ impl AddAssign<&Ciphertext> for Ciphertext {
         self.c3 += &rhs.c3;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ark_ff::UniformRand;
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::EncryptionKey;
+
+    proptest! {
+        #[test]
+        fn ciphertext_encoding_roundtrip(value: u64, seed: u64) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let encryption_key = EncryptionKey(decaf377::basepoint() * decaf377::Fr::rand(&mut rng));
+            let (ciphertext, _) = Value::from(value)
+                .transparent_encrypt(&encryption_key, &mut rng)
+                .unwrap();
+
+            let from_bytes = Ciphertext::try_from(&ciphertext.to_bytes()[..]).unwrap();
+            assert_eq!(from_bytes, ciphertext);
+
+            let from_proto = Ciphertext::decode(ciphertext.encode_to_vec().as_slice()).unwrap();
+            assert_eq!(from_proto, ciphertext);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use penumbra_proto::{eddy as pb, Protobuf};
 use rand_core::{CryptoRng, RngCore};
 
 use crate::{limb, Ciphertext, PrivateKeyShare, PublicKeyShare, TranscriptProtocol};
@@ -103,3 +104,143 @@ impl DecryptionShare<Unverified> {
         })
     }
 }
+
+impl<S: VerificationStatus> DecryptionShare<S> {
+    /// The index of the participant who generated this share.
+    pub fn participant_index(&self) -> u32 {
+        self.participant_index
+    }
+
+    /// The canonical encoding of this share: the participant index
+    /// (little-endian), followed by the share of each limb, from least to most
+    /// significant.
+    pub fn to_bytes(&self) -> [u8; 388] {
+        let mut bytes = [0u8; 388];
+        bytes[0..4].copy_from_slice(&self.participant_index.to_le_bytes());
+        for (chunk, share) in bytes[4..].chunks_exact_mut(96).zip([
+            &self.share0,
+            &self.share1,
+            &self.share2,
+            &self.share3,
+        ]) {
+            chunk.copy_from_slice(&share.to_bytes());
+        }
+        bytes
+    }
+}
+
+/// Decoded shares are always [`Unverified`], since there's no way to know
+/// whether the encoded share had been verified.
+impl TryFrom<&[u8]> for DecryptionShare<Unverified> {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 388 {
+            return Err(anyhow::anyhow!("decryption share must be 388 bytes"));
+        }
+        let participant_index = u32::from_le_bytes(bytes[0..4].try_into()?);
+        let limb = |i: usize| {
+            limb::DecryptionShare::from_bytes(
+                participant_index,
+                &bytes[4 + 96 * i..4 + 96 * (i + 1)],
+            )
+        };
+        Ok(Self {
+            participant_index,
+            share0: limb(0)?,
+            share1: limb(1)?,
+            share2: limb(2)?,
+            share3: limb(3)?,
+        })
+    }
+}
+
+impl Protobuf<pb::DecryptionShare> for DecryptionShare<Unverified> {}
+
+impl<S: VerificationStatus> From<DecryptionShare<S>> for pb::DecryptionShare {
+    fn from(share: DecryptionShare<S>) -> Self {
+        pb::DecryptionShare {
+            participant_index: share.participant_index,
+            share0: Some((&share.share0).into()),
+            share1: Some((&share.share1).into()),
+            share2: Some((&share.share2).into()),
+            share3: Some((&share.share3).into()),
+        }
+    }
+}
+
+impl TryFrom<pb::DecryptionShare> for DecryptionShare<Unverified> {
+    type Error = anyhow::Error;
+
+    fn try_from(share: pb::DecryptionShare) -> Result<Self, Self::Error> {
+        let participant_index = share.participant_index;
+        let limb = |limb_share: Option<pb::LimbDecryptionShare>| {
+            limb::DecryptionShare::from_proto(
+                participant_index,
+                limb_share.ok_or_else(|| anyhow::anyhow!("missing limb decryption share"))?,
+            )
+        };
+        Ok(Self {
+            participant_index,
+            share0: limb(share.share0)?,
+            share1: limb(share.share1)?,
+            share2: limb(share.share2)?,
+            share3: limb(share.share3)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ark_ff::UniformRand;
+    use penumbra_proto::Message;
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{EncryptionKey, Value};
+
+    proptest! {
+        #[test]
+        fn decryption_share_encoding_roundtrip(value: u64, participant_index: u32, seed: u64) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let key_share = PrivateKeyShare::new(participant_index, decaf377::Fr::rand(&mut rng));
+            let encryption_key = EncryptionKey(decaf377::basepoint() * decaf377::Fr::rand(&mut rng));
+            let (ciphertext, _) = Value::from(value)
+                .transparent_encrypt(&encryption_key, &mut rng)
+                .unwrap();
+
+            let unverified = key_share.decryption_share(
+                &ciphertext,
+                &mut merlin::Transcript::new(b"test"),
+                &mut rng,
+            );
+            let verified = unverified
+                .verify(
+                    &ciphertext,
+                    key_share.public_key_share(),
+                    &mut merlin::Transcript::new(b"test"),
+                )
+                .unwrap();
+            assert_eq!(verified.to_bytes(), unverified.to_bytes());
+
+            let from_bytes = DecryptionShare::<Unverified>::try_from(&unverified.to_bytes()[..]).unwrap();
+            assert_eq!(from_bytes.to_bytes(), unverified.to_bytes());
+
+            // A verified share is decoded as unverified, and still verifies.
+            let from_proto = DecryptionShare::<Unverified>::decode(
+                pb::DecryptionShare::from(verified).encode_to_vec().as_slice(),
+            )
+            .unwrap();
+            assert_eq!(from_proto.to_bytes(), unverified.to_bytes());
+            assert!(from_proto
+                .verify(
+                    &ciphertext,
+                    key_share.public_key_share(),
+                    &mut merlin::Transcript::new(b"test"),
+                )
+                .is_ok());
+        }
+    }
+}
@@ -1,8 +1,189 @@
-//! Types used to perform distributed key generation (WIP).
+//! Distributed key generation.
+//!
+//! The DKG is a joint-Feldman protocol, in which each participant deals a
+//! Shamir sharing of a random secret, and the decryption key is the sum of the
+//! secrets of every dealer who dealt honestly.  No participant learns the
+//! decryption key, and any `threshold` of the participants' key shares suffice
+//! to decrypt.
+//!
+//! Each [`Participant`] runs the protocol in three rounds:
+//!
+//! 1. **Dealing:** each participant broadcasts a [`Dealing`], containing
+//!    Feldman commitments to the coefficients of its secret polynomial and a
+//!    proof of knowledge of its secret, and sends a [`SecretShare`] of the
+//!    polynomial privately to each other participant.
+//! 2. **Complaints:** each participant checks the shares it received against
+//!    the dealers' commitments, and broadcasts a [`Complaint`] against each
+//!    dealer whose share was invalid or missing.
+//! 3. **Justifications:** each dealer answers each complaint against it by
+//!    broadcasting a [`Justification`] revealing the disputed share, which
+//!    every participant checks against the dealer's commitments.
+//!
+//! A dealer whose dealing was invalid, or who failed to justify a complaint
+//! against it, is disqualified.  Finally, each participant combines the shares
+//! dealt by the qualified dealers into its [`PrivateKeyShare`], and computes
+//! the combined [`EncryptionKey`] and the [`Committee`] of public key shares.
+//!
+//! The protocol assumes that broadcasts are reliable, so that every
+//! participant sees the same dealings, complaints and justifications, and that
+//! secret shares are sent over authenticated, encrypted channels.  The
+//! [`simulation`] module runs a ceremony in-process, without any network.
 
-use crate::PublicKeyShare;
+use ark_ff::{UniformRand, Zero};
+use rand_core::{CryptoRng, RngCore};
 
+use crate::{EncryptionKey, PrivateKeyShare, PublicKeyShare};
+
+mod messages;
+mod participant;
+pub mod simulation;
+
+pub use messages::{Complaint, Dealing, Justification, SecretShare};
+pub use participant::{Output, Participant};
+
+/// The public parameters of a DKG ceremony.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parameters {
+    /// The number of participants, who are indexed from `1` to `participants`.
+    pub participants: u32,
+    /// The number of key shares required to decrypt.
+    pub threshold: u32,
+}
+
+impl Parameters {
+    pub fn new(participants: u32, threshold: u32) -> anyhow::Result<Self> {
+        if threshold == 0 || threshold > participants {
+            return Err(anyhow::anyhow!(
+                "threshold {} must be between 1 and the number of participants {}",
+                threshold,
+                participants
+            ));
+        }
+        Ok(Self {
+            participants,
+            threshold,
+        })
+    }
+
+    /// The indices of the participants in the ceremony.
+    pub fn indices(&self) -> impl Iterator<Item = u32> {
+        1..=self.participants
+    }
+
+    fn contains(&self, index: u32) -> bool {
+        (1..=self.participants).contains(&index)
+    }
+}
+
+/// The public key shares of every member of a decryption committee.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Committee {
     pub shares: Vec<PublicKeyShare>,
     pub threshold: u32,
 }
+
+impl Committee {
+    /// The public key share of the participant with the given index, if any.
+    pub fn share(&self, participant_index: u32) -> Option<&PublicKeyShare> {
+        self.shares
+            .iter()
+            .find(|share| share.participant_index == participant_index)
+    }
+}
+
+/// Evaluate the polynomial with the given coefficients (constant term first) at `x`.
+fn evaluate(coefficients: &[decaf377::Fr], x: u32) -> decaf377::Fr {
+    let x = decaf377::Fr::from(x);
+    coefficients
+        .iter()
+        .rev()
+        .fold(decaf377::Fr::zero(), |acc, coefficient| {
+            acc * x + *coefficient
+        })
+}
+
+/// Evaluate "in the exponent" the polynomial whose coefficients are committed to by
+/// `commitments`, giving a commitment to the polynomial's value at `x`.
+fn evaluate_commitments(commitments: &[decaf377::Element], x: u32) -> decaf377::Element {
+    let x = decaf377::Fr::from(x);
+    commitments
+        .iter()
+        .rev()
+        .fold(decaf377::Element::default(), |acc, commitment| {
+            acc * x + *commitment
+        })
+}
+
+/// Generate key shares for a committee of `participants`, any `threshold` of
+/// whom can decrypt, using a single trusted dealer.
+///
+/// Unlike a distributed key generation, the dealer learns the decryption key,
+/// so this should only be used where that's acceptable, such as in tests or to
+/// bootstrap a chain before its validators can run a DKG.
+pub fn deal<R: RngCore + CryptoRng>(
+    threshold: u32,
+    participants: u32,
+    mut rng: R,
+) -> anyhow::Result<(EncryptionKey, Vec<PrivateKeyShare>)> {
+    let parameters = Parameters::new(participants, threshold)?;
+
+    // Shamir-share the decryption key, which is the constant term of a random
+    // polynomial of degree `threshold - 1`.
+    let coefficients = (0..threshold)
+        .map(|_| decaf377::Fr::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let encryption_key = EncryptionKey(coefficients[0] * decaf377::basepoint());
+
+    // Participant indices start from 1, since the share at 0 is the key itself.
+    let shares = parameters
+        .indices()
+        .map(|participant_index| {
+            PrivateKeyShare::new(
+                participant_index,
+                evaluate(&coefficients, participant_index),
+            )
+        })
+        .collect();
+
+    Ok((encryption_key, shares))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DecryptionTable, MockDecryptionTable, Value};
+
+    #[tokio::test]
+    async fn threshold_of_dealt_shares_decrypts() {
+        let mut rng = rand::thread_rng();
+        let (encryption_key, shares) = deal(3, 5, &mut rng).unwrap();
+
+        let (ciphertext_1, _) = Value::from(1_000_000u64)
+            .transparent_encrypt(&encryption_key, &mut rng)
+            .unwrap();
+        let (ciphertext_2, _) = Value::from(234_567u64)
+            .transparent_encrypt(&encryption_key, &mut rng)
+            .unwrap();
+        let ciphertext = &ciphertext_1 + &ciphertext_2;
+
+        let table = MockDecryptionTable::default();
+        table.initialize(17).await.unwrap();
+
+        let decryption_shares = shares[1..4]
+            .iter()
+            .map(|share| {
+                share
+                    .decryption_share(&ciphertext, &mut merlin::Transcript::new(b"test"), &mut rng)
+                    .verify(
+                        &ciphertext,
+                        share.public_key_share(),
+                        &mut merlin::Transcript::new(b"test"),
+                    )
+                    .unwrap()
+            })
+            .collect();
+
+        let value = ciphertext.decrypt(decryption_shares, &table).await.unwrap();
+        assert_eq!(value, Value::from(1_234_567u64));
+    }
+}
@@ -0,0 +1,154 @@
+use ark_std::UniformRand;
+use decaf377::{FieldExt, Fr};
+use rand_core::{CryptoRng, RngCore};
+
+use crate::TranscriptProtocol;
+
+/// A dealer's broadcast message in the first round of the DKG.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dealing {
+    /// The index of the dealer.
+    pub dealer: u32,
+    /// Feldman commitments `a_k * B` to each coefficient `a_k` of the dealer's
+    /// secret polynomial, constant term first.
+    pub commitments: Vec<decaf377::Element>,
+    /// A proof of knowledge of the dealer's secret `a_0`.
+    pub(crate) proof: DealingProof,
+}
+
+/// A Schnorr proof of knowledge of the discrete log of a dealing's first
+/// commitment, which prevents a dealer from choosing its contribution to the
+/// encryption key as a function of the other dealers' contributions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DealingProof {
+    /// The challenge scalar
+    c: Fr,
+    /// The response to the challenge
+    r: Fr,
+}
+
+impl Dealing {
+    /// Deal the polynomial with the given coefficients, proving knowledge of its
+    /// constant term.
+    #[allow(non_snake_case)]
+    pub(crate) fn new<R: RngCore + CryptoRng>(
+        dealer: u32,
+        coefficients: &[Fr],
+        transcript: &mut merlin::Transcript,
+        mut rng: R,
+    ) -> Self {
+        let commitments = coefficients
+            .iter()
+            .map(|coefficient| *coefficient * decaf377::basepoint())
+            .collect::<Vec<_>>();
+
+        transcript.begin_dealing(dealer);
+        transcript.append_dealing_commitments(&commitments);
+
+        let k = Fr::rand(
+            &mut transcript
+                .build_rng()
+                .rekey_with_witness_bytes(b"secret", &coefficients[0].to_bytes())
+                .finalize(&mut rng),
+        );
+        let kB = k * decaf377::basepoint();
+        transcript.append_blinding_commitment(b"kB", &kB);
+
+        let challenge = transcript.challenge_scalar(b"c");
+        let response = k - coefficients[0] * challenge;
+
+        Self {
+            dealer,
+            commitments,
+            proof: DealingProof {
+                c: challenge,
+                r: response,
+            },
+        }
+    }
+
+    /// Verify the dealing's proof of knowledge, and that it commits to a
+    /// polynomial of the expected degree.
+    #[allow(non_snake_case)]
+    pub fn verify(
+        &self,
+        threshold: u32,
+        transcript: &mut merlin::Transcript,
+    ) -> anyhow::Result<()> {
+        if self.commitments.len() != threshold as usize {
+            return Err(anyhow::anyhow!(
+                "dealing from {} has {} commitments, expected {}",
+                self.dealer,
+                self.commitments.len(),
+                threshold
+            ));
+        }
+
+        let kB = decaf377::basepoint() * self.proof.r + self.commitments[0] * self.proof.c;
+
+        transcript.begin_dealing(self.dealer);
+        transcript.append_dealing_commitments(&self.commitments);
+        transcript.append_blinding_commitment(b"kB", &kB);
+
+        let challenge = transcript.challenge_scalar(b"c");
+
+        if self.proof.c == challenge {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Recomputed challenge {:?} did not match expected challenge {:?}",
+                challenge,
+                self.proof.c
+            ))
+        }
+    }
+
+    /// Check a share of this dealing against its commitments.
+    pub fn verify_share(&self, recipient: u32, share: &Fr) -> bool {
+        *share * decaf377::basepoint() == super::evaluate_commitments(&self.commitments, recipient)
+    }
+}
+
+/// A dealer's share of its secret polynomial for a single recipient, sent over
+/// a private channel in the first round of the DKG.
+#[derive(Clone)]
+pub struct SecretShare {
+    /// The index of the dealer.
+    pub dealer: u32,
+    /// The index of the recipient.
+    pub recipient: u32,
+    /// The dealer's polynomial evaluated at the recipient's index.
+    pub share: Fr,
+}
+
+impl std::fmt::Debug for SecretShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Don't print the share itself.
+        f.debug_struct("SecretShare")
+            .field("dealer", &self.dealer)
+            .field("recipient", &self.recipient)
+            .finish()
+    }
+}
+
+/// A participant's broadcast accusation, in the second round of the DKG, that
+/// a dealer sent it an invalid share or no share at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Complaint {
+    /// The index of the participant making the complaint.
+    pub accuser: u32,
+    /// The index of the dealer being complained about.
+    pub dealer: u32,
+}
+
+/// A dealer's broadcast answer to a complaint, in the third round of the DKG,
+/// revealing the accuser's share so that everyone can check it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Justification {
+    /// The index of the dealer.
+    pub dealer: u32,
+    /// The index of the participant who complained.
+    pub accuser: u32,
+    /// The dealer's polynomial evaluated at the accuser's index.
+    pub share: Fr,
+}
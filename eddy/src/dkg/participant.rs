@@ -0,0 +1,322 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ark_ff::Zero;
+use ark_std::UniformRand;
+use decaf377::Fr;
+use rand_core::{CryptoRng, RngCore};
+
+use super::{
+    evaluate, evaluate_commitments, Committee, Complaint, Dealing, Justification, Parameters,
+    SecretShare,
+};
+use crate::{EncryptionKey, PrivateKeyShare, PublicKeyShare};
+
+/// A single participant's view of a DKG ceremony.
+///
+/// Every broadcast message, including the participant's own, should be passed
+/// to the corresponding `receive_` method of every participant, and each round
+/// should be complete before the next begins.
+pub struct Participant {
+    index: u32,
+    parameters: Parameters,
+    /// The coefficients of this participant's secret polynomial.
+    coefficients: Vec<Fr>,
+    /// The valid dealings received, by dealer.
+    dealings: BTreeMap<u32, Dealing>,
+    /// The shares received, by dealer.
+    shares: BTreeMap<u32, Fr>,
+    complaints: BTreeSet<Complaint>,
+    /// The shares revealed in answer to complaints, by dealer and accuser.
+    justifications: BTreeMap<(u32, u32), Fr>,
+    disqualified: BTreeSet<u32>,
+}
+
+/// The result of a DKG ceremony for a single participant.
+pub struct Output {
+    /// The combined encryption key.
+    pub encryption_key: EncryptionKey,
+    /// This participant's share of the decryption key.
+    pub private_key_share: PrivateKeyShare,
+    /// The public key shares of every participant.
+    pub committee: Committee,
+    /// The indices of the dealers whose secrets make up the decryption key.
+    pub qualified: Vec<u32>,
+}
+
+impl Participant {
+    /// Join a ceremony as the participant with the given index, choosing a
+    /// random secret polynomial to deal.
+    pub fn new<R: RngCore + CryptoRng>(
+        index: u32,
+        parameters: Parameters,
+        mut rng: R,
+    ) -> anyhow::Result<Self> {
+        let parameters = Parameters::new(parameters.participants, parameters.threshold)?;
+        if !parameters.contains(index) {
+            return Err(anyhow::anyhow!(
+                "participant index {} is not between 1 and {}",
+                index,
+                parameters.participants
+            ));
+        }
+
+        let coefficients = (0..parameters.threshold)
+            .map(|_| Fr::rand(&mut rng))
+            .collect::<Vec<_>>();
+
+        // We deal a share to ourselves, but there's no need to send it.
+        let mut shares = BTreeMap::new();
+        shares.insert(index, evaluate(&coefficients, index));
+
+        Ok(Self {
+            index,
+            parameters,
+            coefficients,
+            dealings: BTreeMap::new(),
+            shares,
+            complaints: BTreeSet::new(),
+            justifications: BTreeMap::new(),
+            disqualified: BTreeSet::new(),
+        })
+    }
+
+    /// The index of this participant.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Round 1: the dealing to broadcast to every participant.
+    pub fn dealing<R: RngCore + CryptoRng>(
+        &self,
+        transcript: &mut merlin::Transcript,
+        rng: R,
+    ) -> Dealing {
+        Dealing::new(self.index, &self.coefficients, transcript, rng)
+    }
+
+    /// Round 1: the shares to send privately to each other participant.
+    pub fn secret_shares(&self) -> Vec<SecretShare> {
+        self.parameters
+            .indices()
+            .filter(|&recipient| recipient != self.index)
+            .map(|recipient| SecretShare {
+                dealer: self.index,
+                recipient,
+                share: evaluate(&self.coefficients, recipient),
+            })
+            .collect()
+    }
+
+    /// Round 1: record a broadcast dealing.
+    ///
+    /// If the dealing is invalid, its dealer is disqualified.
+    pub fn receive_dealing(
+        &mut self,
+        dealing: &Dealing,
+        transcript: &mut merlin::Transcript,
+    ) -> anyhow::Result<()> {
+        self.check_index(dealing.dealer)?;
+        if self.dealings.contains_key(&dealing.dealer) {
+            return Err(anyhow::anyhow!(
+                "already received a dealing from {}",
+                dealing.dealer
+            ));
+        }
+
+        if let Err(e) = dealing.verify(self.parameters.threshold, transcript) {
+            self.disqualified.insert(dealing.dealer);
+            return Err(e);
+        }
+
+        self.dealings.insert(dealing.dealer, dealing.clone());
+        Ok(())
+    }
+
+    /// Round 1: record a share sent to this participant.
+    ///
+    /// Shares aren't checked until [`Participant::complaints`], so that they
+    /// can arrive before the dealing they belong to.
+    pub fn receive_share(&mut self, share: &SecretShare) -> anyhow::Result<()> {
+        self.check_index(share.dealer)?;
+        if share.recipient != self.index {
+            return Err(anyhow::anyhow!(
+                "share for {} was sent to {}",
+                share.recipient,
+                self.index
+            ));
+        }
+        if self.shares.contains_key(&share.dealer) {
+            return Err(anyhow::anyhow!(
+                "already received a share from {}",
+                share.dealer
+            ));
+        }
+
+        self.shares.insert(share.dealer, share.share);
+        Ok(())
+    }
+
+    /// Round 2: the complaints to broadcast, against every dealer whose share
+    /// to this participant was missing or inconsistent with its dealing.
+    pub fn complaints(&self) -> Vec<Complaint> {
+        self.dealings
+            .values()
+            .filter(|dealing| match self.shares.get(&dealing.dealer) {
+                Some(share) => !dealing.verify_share(self.index, share),
+                None => true,
+            })
+            .map(|dealing| Complaint {
+                accuser: self.index,
+                dealer: dealing.dealer,
+            })
+            .collect()
+    }
+
+    /// Round 2: record a broadcast complaint.
+    pub fn receive_complaint(&mut self, complaint: &Complaint) -> anyhow::Result<()> {
+        self.check_index(complaint.accuser)?;
+        self.check_index(complaint.dealer)?;
+        self.complaints.insert(*complaint);
+        Ok(())
+    }
+
+    /// Round 3: the justifications to broadcast, revealing the share of every
+    /// participant who complained about this participant's dealing.
+    pub fn justifications(&self) -> Vec<Justification> {
+        self.complaints
+            .iter()
+            .filter(|complaint| complaint.dealer == self.index)
+            .map(|complaint| Justification {
+                dealer: self.index,
+                accuser: complaint.accuser,
+                share: evaluate(&self.coefficients, complaint.accuser),
+            })
+            .collect()
+    }
+
+    /// Round 3: record a broadcast justification.
+    ///
+    /// If the revealed share is inconsistent with the dealing, its dealer is
+    /// disqualified.
+    pub fn receive_justification(&mut self, justification: &Justification) -> anyhow::Result<()> {
+        let complaint = Complaint {
+            accuser: justification.accuser,
+            dealer: justification.dealer,
+        };
+        if !self.complaints.contains(&complaint) {
+            return Err(anyhow::anyhow!(
+                "justification from {} answers no complaint by {}",
+                justification.dealer,
+                justification.accuser
+            ));
+        }
+        let dealing = self
+            .dealings
+            .get(&justification.dealer)
+            .ok_or_else(|| anyhow::anyhow!("no valid dealing from {}", justification.dealer))?;
+
+        if !dealing.verify_share(justification.accuser, &justification.share) {
+            self.disqualified.insert(justification.dealer);
+            return Err(anyhow::anyhow!(
+                "justification from {} for {} is inconsistent with its dealing",
+                justification.dealer,
+                justification.accuser
+            ));
+        }
+
+        self.justifications.insert(
+            (justification.dealer, justification.accuser),
+            justification.share,
+        );
+        Ok(())
+    }
+
+    /// The dealers who dealt validly and justified every complaint against
+    /// them, in ascending order.
+    pub fn qualified(&self) -> Vec<u32> {
+        self.dealings
+            .keys()
+            .copied()
+            .filter(|dealer| !self.disqualified.contains(dealer))
+            .filter(|&dealer| {
+                self.complaints
+                    .iter()
+                    .filter(|complaint| complaint.dealer == dealer)
+                    .all(|complaint| {
+                        self.justifications
+                            .contains_key(&(dealer, complaint.accuser))
+                    })
+            })
+            .collect()
+    }
+
+    /// Finish the ceremony, combining the shares dealt by the qualified
+    /// dealers.
+    ///
+    /// At least `threshold` dealers must qualify, so that the decryption key
+    /// isn't known to any coalition smaller than the threshold.
+    pub fn finish(self) -> anyhow::Result<Output> {
+        let qualified = self.qualified();
+        if qualified.len() < self.parameters.threshold as usize {
+            return Err(anyhow::anyhow!(
+                "only {} dealers qualified, but the threshold is {}",
+                qualified.len(),
+                self.parameters.threshold
+            ));
+        }
+
+        let mut key_share = Fr::zero();
+        let mut encryption_key = decaf377::Element::default();
+        for dealer in &qualified {
+            let dealing = &self.dealings[dealer];
+            // If we complained, the justified share replaces the one we were sent.
+            let share = self
+                .justifications
+                .get(&(*dealer, self.index))
+                .or_else(|| self.shares.get(dealer))
+                .ok_or_else(|| anyhow::anyhow!("missing share from {}", dealer))?;
+            if !dealing.verify_share(self.index, share) {
+                return Err(anyhow::anyhow!("invalid share from {}", dealer));
+            }
+
+            key_share += *share;
+            encryption_key += dealing.commitments[0];
+        }
+
+        let shares = self
+            .parameters
+            .indices()
+            .map(|participant_index| PublicKeyShare {
+                participant_index,
+                pub_key_share: qualified
+                    .iter()
+                    .map(|dealer| {
+                        evaluate_commitments(&self.dealings[dealer].commitments, participant_index)
+                    })
+                    .fold(decaf377::Element::default(), |acc, point| acc + point),
+            })
+            .collect();
+
+        Ok(Output {
+            encryption_key: EncryptionKey(encryption_key),
+            private_key_share: PrivateKeyShare::new(self.index, key_share),
+            committee: Committee {
+                shares,
+                threshold: self.parameters.threshold,
+            },
+            qualified,
+        })
+    }
+
+    fn check_index(&self, index: u32) -> anyhow::Result<()> {
+        if self.parameters.contains(index) {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "participant index {} is not between 1 and {}",
+                index,
+                self.parameters.participants
+            ))
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! An in-process simulation of a DKG ceremony, for testing.
+//!
+//! Every participant runs in the same process, and messages are delivered
+//! reliably and in order of participant index, so that a simulation driven by
+//! a seeded RNG is deterministic.  Individual participants can be made to
+//! misbehave, to exercise the complaint and justification rounds.
+
+use std::collections::BTreeMap;
+
+use rand_core::{CryptoRng, RngCore};
+
+use super::{Output, Parameters, Participant};
+
+const TRANSCRIPT_LABEL: &[u8] = b"eddy-dkg-simulation";
+
+/// How a simulated participant behaves during the ceremony.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Behavior {
+    /// Follow the protocol.
+    Honest,
+    /// Send no messages at all.
+    Absent,
+    /// Broadcast a dealing whose proof of knowledge doesn't verify.
+    InvalidDealing,
+    /// Send invalid shares to the given recipients, and, if `justify` is set,
+    /// reveal the correct shares when they complain.
+    CorruptShares { recipients: Vec<u32>, justify: bool },
+    /// Complain about the given dealer, even if its share was valid.
+    FalseComplaint { dealer: u32 },
+}
+
+/// A simulated DKG ceremony.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    parameters: Parameters,
+    behaviors: BTreeMap<u32, Behavior>,
+}
+
+impl Simulation {
+    /// Simulate a ceremony in which every participant is honest.
+    pub fn new(parameters: Parameters) -> Self {
+        Self {
+            parameters,
+            behaviors: BTreeMap::new(),
+        }
+    }
+
+    /// Set the behavior of the participant with the given index.
+    pub fn with_behavior(mut self, participant: u32, behavior: Behavior) -> Self {
+        self.behaviors.insert(participant, behavior);
+        self
+    }
+
+    fn behavior(&self, participant: u32) -> &Behavior {
+        self.behaviors
+            .get(&participant)
+            .unwrap_or(&Behavior::Honest)
+    }
+
+    /// Run the ceremony, returning the output of every participant who wasn't
+    /// absent, by index.
+    pub fn run<R: RngCore + CryptoRng>(&self, mut rng: R) -> anyhow::Result<BTreeMap<u32, Output>> {
+        let mut participants = self
+            .parameters
+            .indices()
+            .filter(|&index| self.behavior(index) != &Behavior::Absent)
+            .map(|index| Ok((index, Participant::new(index, self.parameters, &mut rng)?)))
+            .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+
+        // Round 1: dealings and secret shares.
+        let mut dealings = Vec::new();
+        let mut shares = Vec::new();
+        for (index, participant) in &participants {
+            let mut dealing =
+                participant.dealing(&mut merlin::Transcript::new(TRANSCRIPT_LABEL), &mut rng);
+            let mut secret_shares = participant.secret_shares();
+
+            match self.behavior(*index) {
+                Behavior::InvalidDealing => {
+                    dealing.commitments[0] += decaf377::basepoint();
+                }
+                Behavior::CorruptShares { recipients, .. } => {
+                    for share in &mut secret_shares {
+                        if recipients.contains(&share.recipient) {
+                            share.share += decaf377::Fr::from(1u32);
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            dealings.push(dealing);
+            shares.extend(secret_shares);
+        }
+        for participant in participants.values_mut() {
+            // An invalid dealing disqualifies its dealer, which the participant
+            // records itself, so there's nothing to do with the error here.
+            for dealing in &dealings {
+                let _ = participant
+                    .receive_dealing(dealing, &mut merlin::Transcript::new(TRANSCRIPT_LABEL));
+            }
+        }
+        for share in &shares {
+            if let Some(recipient) = participants.get_mut(&share.recipient) {
+                recipient.receive_share(share)?;
+            }
+        }
+
+        // Round 2: complaints.
+        let mut complaints = Vec::new();
+        for (index, participant) in &participants {
+            complaints.extend(participant.complaints());
+            if let Behavior::FalseComplaint { dealer } = self.behavior(*index) {
+                complaints.push(super::Complaint {
+                    accuser: *index,
+                    dealer: *dealer,
+                });
+            }
+        }
+        for participant in participants.values_mut() {
+            for complaint in &complaints {
+                participant.receive_complaint(complaint)?;
+            }
+        }
+
+        // Round 3: justifications.
+        let mut justifications = Vec::new();
+        for (index, participant) in &participants {
+            if let Behavior::CorruptShares { justify: false, .. } = self.behavior(*index) {
+                continue;
+            }
+            justifications.extend(participant.justifications());
+        }
+        for participant in participants.values_mut() {
+            // As with dealings, an invalid justification disqualifies its dealer.
+            for justification in &justifications {
+                let _ = participant.receive_justification(justification);
+            }
+        }
+
+        participants
+            .into_iter()
+            .map(|(index, participant)| Ok((index, participant.finish()?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{DecryptionTable, MockDecryptionTable, Value};
+
+    fn parameters() -> Parameters {
+        Parameters::new(5, 3).unwrap()
+    }
+
+    /// Check that every participant agrees on the outcome of the ceremony.
+    fn assert_consistent(outputs: &BTreeMap<u32, Output>, qualified: &[u32]) {
+        let first = outputs.values().next().unwrap();
+        for output in outputs.values() {
+            assert_eq!(output.encryption_key, first.encryption_key);
+            assert_eq!(output.committee, first.committee);
+            assert_eq!(output.qualified, qualified);
+            assert_eq!(
+                output
+                    .committee
+                    .share(output.private_key_share.participant_index()),
+                Some(output.private_key_share.public_key_share())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn honest_ceremony_threshold_decrypts() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let outputs = Simulation::new(parameters()).run(&mut rng).unwrap();
+        assert_eq!(outputs.len(), 5);
+        assert_consistent(&outputs, &[1, 2, 3, 4, 5]);
+
+        let encryption_key = outputs[&1].encryption_key;
+        let (ciphertext, _) = Value::from(54_321u64)
+            .transparent_encrypt(&encryption_key, &mut rng)
+            .unwrap();
+
+        let table = MockDecryptionTable::default();
+        table.initialize(16).await.unwrap();
+
+        let decryption_shares = [2, 4, 5]
+            .iter()
+            .map(|index| {
+                let share = &outputs[index].private_key_share;
+                share
+                    .decryption_share(&ciphertext, &mut merlin::Transcript::new(b"test"), &mut rng)
+                    .verify(
+                        &ciphertext,
+                        outputs[&1].committee.share(*index).unwrap(),
+                        &mut merlin::Transcript::new(b"test"),
+                    )
+                    .unwrap()
+            })
+            .collect();
+
+        let value = ciphertext.decrypt(decryption_shares, &table).await.unwrap();
+        assert_eq!(value, Value::from(54_321u64));
+    }
+
+    #[test]
+    fn ceremony_is_deterministic() {
+        let first = Simulation::new(parameters())
+            .run(StdRng::seed_from_u64(1))
+            .unwrap();
+        let second = Simulation::new(parameters())
+            .run(StdRng::seed_from_u64(1))
+            .unwrap();
+        assert_eq!(first[&1].encryption_key, second[&1].encryption_key);
+        assert_eq!(first[&1].committee, second[&1].committee);
+    }
+
+    #[test]
+    fn misbehaving_dealers_are_disqualified() {
+        let outputs = Simulation::new(parameters())
+            .with_behavior(2, Behavior::InvalidDealing)
+            .with_behavior(
+                4,
+                Behavior::CorruptShares {
+                    recipients: vec![1, 3],
+                    justify: false,
+                },
+            )
+            .run(StdRng::seed_from_u64(2))
+            .unwrap();
+        assert_consistent(&outputs, &[1, 3, 5]);
+    }
+
+    #[test]
+    fn justified_complaints_do_not_disqualify() {
+        let outputs = Simulation::new(parameters())
+            .with_behavior(
+                2,
+                Behavior::CorruptShares {
+                    recipients: vec![5],
+                    justify: true,
+                },
+            )
+            .with_behavior(3, Behavior::FalseComplaint { dealer: 1 })
+            .run(StdRng::seed_from_u64(3))
+            .unwrap();
+        assert_consistent(&outputs, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn absent_participants_are_disqualified() {
+        let outputs = Simulation::new(parameters())
+            .with_behavior(3, Behavior::Absent)
+            .run(StdRng::seed_from_u64(4))
+            .unwrap();
+        assert_eq!(outputs.len(), 4);
+        assert_consistent(&outputs, &[1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn too_few_qualified_dealers_fails() {
+        let result = Simulation::new(parameters())
+            .with_behavior(1, Behavior::Absent)
+            .with_behavior(2, Behavior::Absent)
+            .with_behavior(3, Behavior::InvalidDealing)
+            .run(StdRng::seed_from_u64(5));
+        assert!(result.is_err());
+    }
+}
@@ -1,3 +1,25 @@
 /// The key used to encrypt ciphertexts (the public key of the encryption
 /// scheme).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EncryptionKey(pub(crate) decaf377::Element);
+
+impl EncryptionKey {
+    /// The canonical encoding of this key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.vartime_compress().0
+    }
+}
+
+impl TryFrom<&[u8]> for EncryptionKey {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("encryption key must be 32 bytes"))?;
+        let element = decaf377::Encoding(bytes)
+            .vartime_decompress()
+            .map_err(|_| anyhow::anyhow!("invalid encryption key encoding"))?;
+        Ok(Self(element))
+    }
+}
@@ -1,4 +1,8 @@
+use decaf377::FieldExt;
+use penumbra_proto::{eddy as pb, Protobuf};
+
 /// A decryptor's private key share.
+#[derive(Clone)]
 pub struct PrivateKeyShare {
     pub(crate) participant_index: u32,
     pub(crate) key_share: decaf377::Fr,
@@ -6,7 +10,142 @@ pub struct PrivateKeyShare {
 }
 
 /// A decryptor's public key share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PublicKeyShare {
     pub(crate) participant_index: u32,
     pub(crate) pub_key_share: decaf377::Element,
 }
+
+impl PrivateKeyShare {
+    pub(crate) fn new(participant_index: u32, key_share: decaf377::Fr) -> Self {
+        Self {
+            participant_index,
+            key_share,
+            cached_pub: PublicKeyShare {
+                participant_index,
+                pub_key_share: key_share * decaf377::basepoint(),
+            },
+        }
+    }
+
+    /// The index of the participant holding this share.
+    pub fn participant_index(&self) -> u32 {
+        self.participant_index
+    }
+
+    /// The public key share corresponding to this private key share.
+    pub fn public_key_share(&self) -> &PublicKeyShare {
+        &self.cached_pub
+    }
+
+    /// The encoding of this share: the participant index (little-endian),
+    /// followed by the key share.
+    pub fn to_bytes(&self) -> [u8; 36] {
+        let mut bytes = [0u8; 36];
+        bytes[0..4].copy_from_slice(&self.participant_index.to_le_bytes());
+        bytes[4..36].copy_from_slice(&self.key_share.to_bytes());
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for PrivateKeyShare {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 36 {
+            return Err(anyhow::anyhow!("private key share must be 36 bytes"));
+        }
+        let participant_index = u32::from_le_bytes(bytes[0..4].try_into()?);
+        let key_share = decaf377::Fr::from_bytes(bytes[4..36].try_into()?)
+            .map_err(|_| anyhow::anyhow!("invalid private key share encoding"))?;
+        Ok(Self::new(participant_index, key_share))
+    }
+}
+
+impl PublicKeyShare {
+    /// The index of the participant holding this share.
+    pub fn participant_index(&self) -> u32 {
+        self.participant_index
+    }
+
+    /// The encoding of this share: the participant index (little-endian),
+    /// followed by the public key share.
+    pub fn to_bytes(&self) -> [u8; 36] {
+        let mut bytes = [0u8; 36];
+        bytes[0..4].copy_from_slice(&self.participant_index.to_le_bytes());
+        bytes[4..36].copy_from_slice(&self.pub_key_share.vartime_compress().0);
+        bytes
+    }
+
+    fn from_parts(participant_index: u32, pub_key_share: &[u8]) -> anyhow::Result<Self> {
+        let pub_key_share = decaf377::Encoding(
+            pub_key_share
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("public key share point must be 32 bytes"))?,
+        )
+        .vartime_decompress()
+        .map_err(|_| anyhow::anyhow!("invalid public key share encoding"))?;
+        Ok(Self {
+            participant_index,
+            pub_key_share,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKeyShare {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 36 {
+            return Err(anyhow::anyhow!("public key share must be 36 bytes"));
+        }
+        let participant_index = u32::from_le_bytes(bytes[0..4].try_into()?);
+        Self::from_parts(participant_index, &bytes[4..36])
+    }
+}
+
+impl Protobuf<pb::PublicKeyShare> for PublicKeyShare {}
+
+impl From<PublicKeyShare> for pb::PublicKeyShare {
+    fn from(share: PublicKeyShare) -> Self {
+        pb::PublicKeyShare {
+            participant_index: share.participant_index,
+            pub_key_share: share.pub_key_share.vartime_compress().0.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::PublicKeyShare> for PublicKeyShare {
+    type Error = anyhow::Error;
+
+    fn try_from(share: pb::PublicKeyShare) -> Result<Self, Self::Error> {
+        Self::from_parts(share.participant_index, &share.pub_key_share)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ark_ff::UniformRand;
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    proptest! {
+        #[test]
+        fn public_key_share_encoding_roundtrip(participant_index: u32, seed: u64) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let key_share = PrivateKeyShare::new(participant_index, decaf377::Fr::rand(&mut rng));
+            let share = *key_share.public_key_share();
+
+            let from_bytes = PublicKeyShare::try_from(&share.to_bytes()[..]).unwrap();
+            assert_eq!(from_bytes, share);
+
+            let from_proto = PublicKeyShare::decode(share.encode_to_vec().as_slice()).unwrap();
+            assert_eq!(from_proto, share);
+
+            let private_from_bytes = PrivateKeyShare::try_from(&key_share.to_bytes()[..]).unwrap();
+            assert_eq!(private_from_bytes.public_key_share(), &share);
+        }
+    }
+}
@@ -6,9 +6,9 @@
 //! - [x] Decryption Proofs
 //! - [x] Lookup table interface
 //! - [ ] Error on insufficient shares
-//! - [ ] Distributed key generation
-//! - [ ] Serialization
-//! - [ ] Encryption Proofs
+//! - [x] Distributed key generation
+//! - [x] Serialization
+//! - [x] Encryption Proofs
 //!
 //! [protocol-batching]: https://protocol.penumbra.zone/main/concepts/batching_flows.html
 
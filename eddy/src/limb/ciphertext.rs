@@ -3,9 +3,10 @@ use std::ops::{Add, AddAssign};
 use crate::decryption_share::Verified;
 use crate::limb::DecryptionShare;
 use ark_ff::One;
+use penumbra_proto::eddy as pb;
 
 /// an Elgamal ciphertext (c1, c2).
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Ciphertext {
     pub(crate) c1: decaf377::Element,
     pub(crate) c2: decaf377::Element,
@@ -26,6 +27,25 @@ fn lagrange_coefficient(participant_index: u32, participant_indices: &[u32]) ->
 }
 
 impl Ciphertext {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[0..32].copy_from_slice(&self.c1.vartime_compress().0);
+        bytes[32..64].copy_from_slice(&self.c2.vartime_compress().0);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 64]) -> anyhow::Result<Self> {
+        let decompress = |bytes: &[u8]| {
+            decaf377::Encoding(bytes.try_into().expect("slice is 32 bytes"))
+                .vartime_decompress()
+                .map_err(|_| anyhow::anyhow!("invalid limb ciphertext encoding"))
+        };
+        Ok(Self {
+            c1: decompress(&bytes[0..32])?,
+            c2: decompress(&bytes[32..64])?,
+        })
+    }
+
     pub fn decrypt(&self, shares: Vec<&DecryptionShare<Verified>>) -> decaf377::Element {
         let indices = shares
             .iter()
@@ -41,6 +61,35 @@ impl Ciphertext {
     }
 }
 
+impl From<Ciphertext> for pb::LimbCiphertext {
+    fn from(ciphertext: Ciphertext) -> Self {
+        pb::LimbCiphertext {
+            c1: ciphertext.c1.vartime_compress().0.to_vec(),
+            c2: ciphertext.c2.vartime_compress().0.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::LimbCiphertext> for Ciphertext {
+    type Error = anyhow::Error;
+
+    fn try_from(ciphertext: pb::LimbCiphertext) -> Result<Self, Self::Error> {
+        let decompress = |bytes: &[u8]| -> anyhow::Result<decaf377::Element> {
+            decaf377::Encoding(
+                bytes
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("limb ciphertext elements must be 32 bytes"))?,
+            )
+            .vartime_decompress()
+            .map_err(|_| anyhow::anyhow!("invalid limb ciphertext encoding"))
+        };
+        Ok(Self {
+            c1: decompress(&ciphertext.c1)?,
+            c2: decompress(&ciphertext.c2)?,
+        })
+    }
+}
+
 impl Add<&Ciphertext> for &Ciphertext {
     type Output = Ciphertext;
     fn add(self, rhs: &Ciphertext) -> Self::Output {
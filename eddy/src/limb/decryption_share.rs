@@ -1,5 +1,6 @@
 use ark_std::UniformRand;
 use decaf377::{FieldExt, Fr};
+use penumbra_proto::eddy as pb;
 use rand_core::{CryptoRng, RngCore};
 
 use super::Ciphertext;
@@ -121,3 +122,85 @@ impl DecryptionShare<Unverified> {
         }
     }
 }
+
+impl<S: VerificationStatus> DecryptionShare<S> {
+    /// The canonical encoding of this share: the decryption share point,
+    /// followed by the proof's challenge and response.
+    pub(crate) fn to_bytes(&self) -> [u8; 96] {
+        let mut bytes = [0u8; 96];
+        bytes[0..32].copy_from_slice(&self.decryption_share.vartime_compress().0);
+        bytes[32..64].copy_from_slice(&self.proof.c.to_bytes());
+        bytes[64..96].copy_from_slice(&self.proof.r.to_bytes());
+        bytes
+    }
+}
+
+impl DecryptionShare<Unverified> {
+    /// Decode a limb decryption share from its canonical encoding.  The
+    /// participant index is shared by every limb, so it's encoded separately.
+    pub(crate) fn from_bytes(participant_index: u32, bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.len() != 96 {
+            return Err(anyhow::anyhow!("limb decryption share must be 96 bytes"));
+        }
+        Self::from_parts(
+            participant_index,
+            &bytes[0..32],
+            &bytes[32..64],
+            &bytes[64..96],
+        )
+    }
+
+    pub(crate) fn from_proto(
+        participant_index: u32,
+        share: pb::LimbDecryptionShare,
+    ) -> anyhow::Result<Self> {
+        Self::from_parts(
+            participant_index,
+            &share.decryption_share,
+            &share.challenge,
+            &share.response,
+        )
+    }
+
+    fn from_parts(
+        participant_index: u32,
+        decryption_share: &[u8],
+        challenge: &[u8],
+        response: &[u8],
+    ) -> anyhow::Result<Self> {
+        let decryption_share = decaf377::Encoding(
+            decryption_share
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("decryption share point must be 32 bytes"))?,
+        )
+        .vartime_decompress()
+        .map_err(|_| anyhow::anyhow!("invalid decryption share point encoding"))?;
+        let scalar =
+            |bytes: &[u8]| -> anyhow::Result<Fr> {
+                Fr::from_bytes(bytes.try_into().map_err(|_| {
+                    anyhow::anyhow!("decryption share proof scalars must be 32 bytes")
+                })?)
+                .map_err(|_| anyhow::anyhow!("invalid decryption share proof scalar encoding"))
+            };
+
+        Ok(Self {
+            decryption_share,
+            proof: DecryptionShareProof {
+                c: scalar(challenge)?,
+                r: scalar(response)?,
+            },
+            participant_index,
+            _marker: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<S: VerificationStatus> From<&DecryptionShare<S>> for pb::LimbDecryptionShare {
+    fn from(share: &DecryptionShare<S>) -> Self {
+        pb::LimbDecryptionShare {
+            decryption_share: share.decryption_share.vartime_compress().0.to_vec(),
+            challenge: share.proof.c.to_bytes().to_vec(),
+            response: share.proof.r.to_bytes().to_vec(),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use ark_ff::Field;
+use ark_std::UniformRand;
+use decaf377::{FieldExt, Fr};
+use penumbra_proto::eddy as pb;
+use rand_core::{CryptoRng, RngCore};
+
+use super::{Ciphertext, Value};
+use crate::{EncryptionKey, TranscriptProtocol};
+
+/// The number of bits in a limb.
+const LIMB_BITS: usize = 16;
+
+/// A proof that a limb ciphertext encrypts a 16-bit value.
+///
+/// The prover splits the limb's blinding factor across an encryption of each
+/// bit of the value, so that the bit ciphertexts, weighted by powers of two,
+/// sum to the limb ciphertext, and proves that each bit ciphertext encrypts
+/// either 0 or 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionProof {
+    bits: Vec<BitProof>,
+}
+
+/// A disjunctive Chaum-Pedersen proof that a ciphertext `(C_1, C_2)` encrypts
+/// `v` in `{0, 1}`, i.e., that `log_B(C_1) = log_PK(C_2 - v * B)` for one of
+/// the two values of `v`.
+///
+/// The prover answers the challenge for the true value honestly, and simulates
+/// the proof for the other; the two challenges must sum to the transcript's
+/// challenge, so the prover can simulate at most one of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitProof {
+    ciphertext: Ciphertext,
+    /// The challenge scalar for each value of the bit
+    c: [Fr; 2],
+    /// The response to each challenge
+    r: [Fr; 2],
+}
+
+impl Value {
+    /// Encrypt this limb with the given blinding factor, proving that the
+    /// ciphertext encrypts a 16-bit value.
+    #[allow(non_snake_case)]
+    pub(crate) fn encrypt_with_proof<R: RngCore + CryptoRng>(
+        &self,
+        encryption_key: &EncryptionKey,
+        blinding: Fr,
+        transcript: &mut merlin::Transcript,
+        mut rng: R,
+    ) -> anyhow::Result<(Ciphertext, EncryptionProof)> {
+        if self.0 >> LIMB_BITS != 0 {
+            return Err(anyhow::anyhow!("limb value {} exceeds 16 bits", self.0));
+        }
+        let ciphertext = self.encrypt_with_blinding(encryption_key, blinding);
+
+        transcript.begin_limb_encryption();
+        transcript.append_limb_ciphertext(&ciphertext);
+
+        // Choose random blindings for every bit but the last, and then solve
+        // for the last, so that sum(2^j * r_j) = blinding.
+        let mut bit_blindings = (0..LIMB_BITS - 1)
+            .map(|_| Fr::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let partial_sum = bit_blindings
+            .iter()
+            .enumerate()
+            .fold(Fr::from(0u32), |acc, (j, r)| acc + Fr::from(1u32 << j) * r);
+        let top_weight_inverse = Fr::from(1u32 << (LIMB_BITS - 1))
+            .inverse()
+            .expect("powers of two are nonzero");
+        bit_blindings.push((blinding - partial_sum) * top_weight_inverse);
+
+        let B = decaf377::basepoint();
+        let PK = encryption_key.0;
+
+        let mut bits = Vec::with_capacity(LIMB_BITS);
+        for (j, r) in bit_blindings.into_iter().enumerate() {
+            let bit = ((self.0 >> j) & 1) as usize;
+            let bit_ciphertext = Value(bit as u32).encrypt_with_blinding(encryption_key, r);
+            transcript.append_limb_ciphertext(&bit_ciphertext);
+
+            // Use the Merlin transcript RNG to generate the blinding factor and
+            // the simulated branch of the proof, binding them to the public
+            // context, the bit's blinding factor, and fresh randomness.
+            let mut proof_rng = transcript
+                .build_rng()
+                .rekey_with_witness_bytes(b"bit_blinding", &r.to_bytes())
+                .finalize(&mut rng);
+            let k = Fr::rand(&mut proof_rng);
+
+            let mut c = [Fr::from(0u32); 2];
+            let mut responses = [Fr::from(0u32); 2];
+            let mut kB = [decaf377::Element::default(); 2];
+            let mut kPK = [decaf377::Element::default(); 2];
+
+            // The true branch commits to k honestly...
+            kB[bit] = k * B;
+            kPK[bit] = k * PK;
+            // ... while the other branch is simulated from a random challenge
+            // and response.
+            let other = 1 - bit;
+            c[other] = Fr::rand(&mut proof_rng);
+            responses[other] = Fr::rand(&mut proof_rng);
+            kB[other] = B * responses[other] + bit_ciphertext.c1 * c[other];
+            kPK[other] =
+                PK * responses[other] + (bit_ciphertext.c2 - Fr::from(other as u32) * B) * c[other];
+
+            transcript.append_blinding_commitment(b"kB_0", &kB[0]);
+            transcript.append_blinding_commitment(b"kPK_0", &kPK[0]);
+            transcript.append_blinding_commitment(b"kB_1", &kB[1]);
+            transcript.append_blinding_commitment(b"kPK_1", &kPK[1]);
+
+            let challenge = transcript.challenge_scalar(b"c");
+            c[bit] = challenge - c[other];
+            responses[bit] = k - r * c[bit];
+
+            bits.push(BitProof {
+                ciphertext: bit_ciphertext,
+                c,
+                r: responses,
+            });
+        }
+
+        Ok((ciphertext, EncryptionProof { bits }))
+    }
+}
+
+impl EncryptionProof {
+    /// Verify that `ciphertext` encrypts a 16-bit value under `encryption_key`.
+    #[allow(non_snake_case)]
+    pub fn verify(
+        &self,
+        ciphertext: &Ciphertext,
+        encryption_key: &EncryptionKey,
+        transcript: &mut merlin::Transcript,
+    ) -> anyhow::Result<()> {
+        if self.bits.len() != LIMB_BITS {
+            return Err(anyhow::anyhow!(
+                "limb encryption proof has {} bits, expected {}",
+                self.bits.len(),
+                LIMB_BITS
+            ));
+        }
+
+        // The bit ciphertexts must recombine into the limb ciphertext.
+        let mut recombined = Ciphertext::default();
+        for (j, bit) in self.bits.iter().enumerate() {
+            let weight = Fr::from(1u32 << j);
+            recombined += &Ciphertext {
+                c1: bit.ciphertext.c1 * weight,
+                c2: bit.ciphertext.c2 * weight,
+            };
+        }
+        if &recombined != ciphertext {
+            return Err(anyhow::anyhow!(
+                "bit ciphertexts do not recombine into the limb ciphertext"
+            ));
+        }
+
+        let B = decaf377::basepoint();
+        let PK = encryption_key.0;
+
+        transcript.begin_limb_encryption();
+        transcript.append_limb_ciphertext(ciphertext);
+
+        for bit in &self.bits {
+            transcript.append_limb_ciphertext(&bit.ciphertext);
+
+            let commitments = |v: usize| {
+                let kB = B * bit.r[v] + bit.ciphertext.c1 * bit.c[v];
+                let kPK = PK * bit.r[v] + (bit.ciphertext.c2 - Fr::from(v as u32) * B) * bit.c[v];
+                (kB, kPK)
+            };
+            let (kB_0, kPK_0) = commitments(0);
+            let (kB_1, kPK_1) = commitments(1);
+
+            transcript.append_blinding_commitment(b"kB_0", &kB_0);
+            transcript.append_blinding_commitment(b"kPK_0", &kPK_0);
+            transcript.append_blinding_commitment(b"kB_1", &kB_1);
+            transcript.append_blinding_commitment(b"kPK_1", &kPK_1);
+
+            let challenge = transcript.challenge_scalar(b"c");
+
+            if bit.c[0] + bit.c[1] != challenge {
+                return Err(anyhow::anyhow!(
+                    "Recomputed challenge {:?} did not match expected challenge {:?}",
+                    challenge,
+                    bit.c[0] + bit.c[1]
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&EncryptionProof> for pb::LimbEncryptionProof {
+    fn from(proof: &EncryptionProof) -> Self {
+        pb::LimbEncryptionProof {
+            bits: proof
+                .bits
+                .iter()
+                .map(|bit| pb::BitEncryptionProof {
+                    ciphertext: Some(bit.ciphertext.into()),
+                    challenge_0: bit.c[0].to_bytes().to_vec(),
+                    challenge_1: bit.c[1].to_bytes().to_vec(),
+                    response_0: bit.r[0].to_bytes().to_vec(),
+                    response_1: bit.r[1].to_bytes().to_vec(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::LimbEncryptionProof> for EncryptionProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: pb::LimbEncryptionProof) -> Result<Self, Self::Error> {
+        let scalar = |bytes: &[u8]| -> anyhow::Result<Fr> {
+            Fr::from_bytes(
+                bytes
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("encryption proof scalars must be 32 bytes"))?,
+            )
+            .map_err(|_| anyhow::anyhow!("invalid encryption proof scalar encoding"))
+        };
+
+        let bits = proof
+            .bits
+            .into_iter()
+            .map(|bit| {
+                Ok(BitProof {
+                    ciphertext: bit
+                        .ciphertext
+                        .ok_or_else(|| anyhow::anyhow!("missing bit ciphertext"))?
+                        .try_into()?,
+                    c: [scalar(&bit.challenge_0)?, scalar(&bit.challenge_1)?],
+                    r: [scalar(&bit.response_0)?, scalar(&bit.response_1)?],
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self { bits })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn limb_encryption_proof_rejects_out_of_range_values() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let encryption_key = EncryptionKey(decaf377::basepoint() * Fr::rand(&mut rng));
+
+        let result = Value(1 << 16).encrypt_with_proof(
+            &encryption_key,
+            Fr::rand(&mut rng),
+            &mut merlin::Transcript::new(b"test"),
+            &mut rng,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn limb_encryption_proof_rejects_other_ciphertexts() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let encryption_key = EncryptionKey(decaf377::basepoint() * Fr::rand(&mut rng));
+
+        let (ciphertext, proof) = Value(0xbeef)
+            .encrypt_with_proof(
+                &encryption_key,
+                Fr::rand(&mut rng),
+                &mut merlin::Transcript::new(b"test"),
+                &mut rng,
+            )
+            .unwrap();
+        assert!(proof
+            .verify(
+                &ciphertext,
+                &encryption_key,
+                &mut merlin::Transcript::new(b"test")
+            )
+            .is_ok());
+
+        let other = Value(0xbeef).encrypt_with_blinding(&encryption_key, Fr::rand(&mut rng));
+        assert!(proof
+            .verify(
+                &other,
+                &encryption_key,
+                &mut merlin::Transcript::new(b"test")
+            )
+            .is_err());
+    }
+}
@@ -1,7 +1,5 @@
 use crate::limb::Ciphertext;
 use crate::EncryptionKey;
-use ark_ff::UniformRand;
-use rand_core::{CryptoRng, RngCore};
 
 /// An individual limb value.
 ///
@@ -20,16 +18,15 @@ impl From<u32> for Value {
 type Blinding = decaf377::Fr;
 
 impl Value {
-    pub fn transparent_encrypt<R: RngCore + CryptoRng>(
+    pub fn encrypt_with_blinding(
         &self,
         encryption_key: &EncryptionKey,
-        mut rng: R,
-    ) -> (Ciphertext, Blinding) {
-        let elgamal_blind = decaf377::Fr::rand(&mut rng);
+        elgamal_blind: Blinding,
+    ) -> Ciphertext {
         let c1 = elgamal_blind * decaf377::basepoint();
         let c2 =
             elgamal_blind * encryption_key.0 + decaf377::Fr::from(self.0) * decaf377::basepoint();
 
-        (Ciphertext { c1, c2 }, elgamal_blind)
+        Ciphertext { c1, c2 }
     }
 }
@@ -1,11 +1,208 @@
-//! Encryption correctness proofs (WIP: currently, only placeholder "transparent
-//! proofs").
+//! Encryption correctness proofs.
+//!
+//! An [`EncryptionProof`] proves that a ciphertext encrypts a 64-bit value
+//! without revealing it, and a [`CommitmentProof`] proves that it encrypts the
+//! same value as a Pedersen commitment, while the placeholder
+//! [`TransparentEncryptionProof`] reveals the value and its blinding factors.
 
-use crate::{Ciphertext, EncryptionKey, Value};
+use decaf377::{FieldExt, Fr};
+use penumbra_proto::{eddy as pb, Protobuf};
+
+use crate::{limb, Ciphertext, EncryptionKey, TranscriptProtocol, Value};
+
+/// A proof that a [`Ciphertext`] is a well-formed encryption of a 64-bit value
+/// under a particular [`EncryptionKey`], which reveals nothing about the value.
+///
+/// The proof consists of a Sigma-protocol range proof for each limb, showing
+/// that it encrypts a 16-bit value.  Without it, a ciphertext could encrypt an
+/// arbitrary (e.g., "negative") scalar in a limb, corrupting any sum it's
+/// added to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionProof {
+    pub(crate) proof0: limb::EncryptionProof,
+    pub(crate) proof1: limb::EncryptionProof,
+    pub(crate) proof2: limb::EncryptionProof,
+    pub(crate) proof3: limb::EncryptionProof,
+}
+
+impl EncryptionProof {
+    /// Verify that `ctxt` encrypts a 64-bit value under `encryption_key`.
+    pub fn verify(
+        &self,
+        ctxt: &Ciphertext,
+        encryption_key: &EncryptionKey,
+        transcript: &mut merlin::Transcript,
+    ) -> anyhow::Result<()> {
+        transcript.begin_encryption();
+        transcript.append_encryption_key(encryption_key);
+
+        self.proof0.verify(&ctxt.c0, encryption_key, transcript)?;
+        self.proof1.verify(&ctxt.c1, encryption_key, transcript)?;
+        self.proof2.verify(&ctxt.c2, encryption_key, transcript)?;
+        self.proof3.verify(&ctxt.c3, encryption_key, transcript)?;
+
+        Ok(())
+    }
+}
+
+impl Protobuf<pb::EncryptionProof> for EncryptionProof {}
+
+impl From<EncryptionProof> for pb::EncryptionProof {
+    fn from(proof: EncryptionProof) -> Self {
+        pb::EncryptionProof {
+            proof0: Some((&proof.proof0).into()),
+            proof1: Some((&proof.proof1).into()),
+            proof2: Some((&proof.proof2).into()),
+            proof3: Some((&proof.proof3).into()),
+        }
+    }
+}
+
+impl TryFrom<pb::EncryptionProof> for EncryptionProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: pb::EncryptionProof) -> Result<Self, Self::Error> {
+        let limb =
+            |limb_proof: Option<pb::LimbEncryptionProof>| -> anyhow::Result<limb::EncryptionProof> {
+                limb_proof
+                    .ok_or_else(|| anyhow::anyhow!("missing limb encryption proof"))?
+                    .try_into()
+            };
+        Ok(Self {
+            proof0: limb(proof.proof0)?,
+            proof1: limb(proof.proof1)?,
+            proof2: limb(proof.proof2)?,
+            proof3: limb(proof.proof3)?,
+        })
+    }
+}
+
+/// A proof that a [`Ciphertext`] encrypts the same value `v` that a Pedersen
+/// commitment `C = v * G + b * H` commits to, for caller-chosen generators `G`
+/// and `H`, which reveals nothing about the value.
+///
+/// The limb ciphertexts, weighted by their place values, combine into a single
+/// ElGamal ciphertext `(r * B, r * PK + v * B)`, and the proof is a Sigma
+/// protocol showing knowledge of `(v, r, b)` opening both it and the
+/// commitment.  This doesn't show that `v` fits in 64 bits, so it should be
+/// checked alongside an [`EncryptionProof`] for the same ciphertext.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitmentProof {
+    pub(crate) challenge: Fr,
+    pub(crate) value_response: Fr,
+    pub(crate) encryption_blinding_response: Fr,
+    pub(crate) commitment_blinding_response: Fr,
+}
+
+impl CommitmentProof {
+    /// Verify that `ctxt` encrypts, under `encryption_key`, the value that
+    /// `commitment` commits to with the generators `value_generator` and
+    /// `blinding_generator`.
+    #[allow(non_snake_case)]
+    pub fn verify(
+        &self,
+        ctxt: &Ciphertext,
+        encryption_key: &EncryptionKey,
+        commitment: decaf377::Element,
+        value_generator: decaf377::Element,
+        blinding_generator: decaf377::Element,
+        transcript: &mut merlin::Transcript,
+    ) -> anyhow::Result<()> {
+        let combined = ctxt.combine();
+        let B = decaf377::basepoint();
+        let PK = encryption_key.0;
+
+        transcript.begin_commitment_proof();
+        transcript.append_encryption_key(encryption_key);
+        transcript.append_limb_ciphertext(&combined);
+        transcript.append_commitment_point(b"G", &value_generator);
+        transcript.append_commitment_point(b"H", &blinding_generator);
+        transcript.append_commitment_point(b"C", &commitment);
+
+        let e = self.challenge;
+        let kB = B * self.encryption_blinding_response + combined.c1 * e;
+        let kPK =
+            B * self.value_response + PK * self.encryption_blinding_response + combined.c2 * e;
+        let kC = value_generator * self.value_response
+            + blinding_generator * self.commitment_blinding_response
+            + commitment * e;
+
+        transcript.append_blinding_commitment(b"kB", &kB);
+        transcript.append_blinding_commitment(b"kPK", &kPK);
+        transcript.append_blinding_commitment(b"kC", &kC);
+
+        if transcript.challenge_scalar(b"c") != e {
+            return Err(anyhow::anyhow!(
+                "commitment proof challenge did not match the recomputed challenge"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The canonical encoding of this proof: the challenge, followed by the
+    /// responses for the value, the encryption blinding factor, and the
+    /// commitment blinding factor.
+    pub fn to_bytes(&self) -> [u8; 128] {
+        let mut bytes = [0u8; 128];
+        for (chunk, scalar) in bytes.chunks_exact_mut(32).zip([
+            &self.challenge,
+            &self.value_response,
+            &self.encryption_blinding_response,
+            &self.commitment_blinding_response,
+        ]) {
+            chunk.copy_from_slice(&scalar.to_bytes());
+        }
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for CommitmentProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 128 {
+            return Err(anyhow::anyhow!("commitment proof must be 128 bytes"));
+        }
+        let scalar = |i: usize| {
+            Fr::from_bytes(
+                bytes[32 * i..32 * (i + 1)]
+                    .try_into()
+                    .expect("slice is 32 bytes"),
+            )
+            .map_err(|_| anyhow::anyhow!("invalid commitment proof scalar encoding"))
+        };
+        Ok(Self {
+            challenge: scalar(0)?,
+            value_response: scalar(1)?,
+            encryption_blinding_response: scalar(2)?,
+            commitment_blinding_response: scalar(3)?,
+        })
+    }
+}
+
+impl Protobuf<pb::CommitmentProof> for CommitmentProof {}
+
+impl From<CommitmentProof> for pb::CommitmentProof {
+    fn from(proof: CommitmentProof) -> Self {
+        pb::CommitmentProof {
+            inner: proof.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::CommitmentProof> for CommitmentProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: pb::CommitmentProof) -> Result<Self, Self::Error> {
+        proof.inner.as_slice().try_into()
+    }
+}
 
 /// Placeholder for a zk-SNARK proof that the encryption is well-formed.
 ///
 /// Note: this proof reveals the ciphertext!!!
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TransparentEncryptionProof {
     value: u64,
     blindings: [decaf377::Fr; 4],
@@ -38,4 +235,158 @@ impl TransparentEncryptionProof {
 
         Ok(())
     }
+
+    /// The canonical encoding of this proof: the value (little-endian),
+    /// followed by the blinding factor of each limb, from least to most
+    /// significant.
+    pub fn to_bytes(&self) -> [u8; 136] {
+        let mut bytes = [0u8; 136];
+        bytes[0..8].copy_from_slice(&self.value.to_le_bytes());
+        for (chunk, blinding) in bytes[8..].chunks_exact_mut(32).zip(self.blindings.iter()) {
+            chunk.copy_from_slice(&blinding.to_bytes());
+        }
+        bytes
+    }
+
+    fn from_parts<'a>(
+        value: u64,
+        blindings: impl ExactSizeIterator<Item = &'a [u8]>,
+    ) -> anyhow::Result<Self> {
+        if blindings.len() != 4 {
+            return Err(anyhow::anyhow!(
+                "transparent encryption proof must have 4 blindings"
+            ));
+        }
+        let mut parsed = [decaf377::Fr::default(); 4];
+        for (parsed, blinding) in parsed.iter_mut().zip(blindings) {
+            *parsed = decaf377::Fr::from_bytes(
+                blinding
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("blindings must be 32 bytes"))?,
+            )
+            .map_err(|_| anyhow::anyhow!("invalid blinding encoding"))?;
+        }
+        Ok(Self::new(value, parsed))
+    }
+}
+
+impl TryFrom<&[u8]> for TransparentEncryptionProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 136 {
+            return Err(anyhow::anyhow!(
+                "transparent encryption proof must be 136 bytes"
+            ));
+        }
+        let value = u64::from_le_bytes(bytes[0..8].try_into()?);
+        Self::from_parts(value, bytes[8..].chunks_exact(32))
+    }
+}
+
+impl Protobuf<pb::TransparentEncryptionProof> for TransparentEncryptionProof {}
+
+impl From<TransparentEncryptionProof> for pb::TransparentEncryptionProof {
+    fn from(proof: TransparentEncryptionProof) -> Self {
+        pb::TransparentEncryptionProof {
+            value: proof.value,
+            blindings: proof
+                .blindings
+                .iter()
+                .map(|blinding| blinding.to_bytes().to_vec())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::TransparentEncryptionProof> for TransparentEncryptionProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: pb::TransparentEncryptionProof) -> Result<Self, Self::Error> {
+        Self::from_parts(proof.value, proof.blindings.iter().map(Vec::as_slice))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ark_ff::UniformRand;
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    proptest! {
+        #[test]
+        fn encryption_proof_verify_roundtrip(value: u64, seed: u64) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let encryption_key = EncryptionKey(decaf377::basepoint() * decaf377::Fr::rand(&mut rng));
+            let (ciphertext, proof) = Value::from(value)
+                .encrypt(&encryption_key, &mut merlin::Transcript::new(b"test"), &mut rng)
+                .unwrap();
+
+            let from_proto = EncryptionProof::decode(proof.encode_to_vec().as_slice()).unwrap();
+            assert_eq!(from_proto, proof);
+            assert!(from_proto
+                .verify(&ciphertext, &encryption_key, &mut merlin::Transcript::new(b"test"))
+                .is_ok());
+
+            let other_key = EncryptionKey(decaf377::basepoint() * decaf377::Fr::rand(&mut rng));
+            assert!(proof
+                .verify(&ciphertext, &other_key, &mut merlin::Transcript::new(b"test"))
+                .is_err());
+        }
+
+        #[test]
+        fn commitment_proof_verify_roundtrip(value: u64, seed: u64) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let encryption_key = EncryptionKey(decaf377::basepoint() * decaf377::Fr::rand(&mut rng));
+            let blindings = [
+                decaf377::Fr::rand(&mut rng),
+                decaf377::Fr::rand(&mut rng),
+                decaf377::Fr::rand(&mut rng),
+                decaf377::Fr::rand(&mut rng),
+            ];
+            let (ciphertext, _) = Value::from(value)
+                .transparent_encrypt_with_blindings(&encryption_key, blindings)
+                .unwrap();
+
+            let g = decaf377::basepoint() * decaf377::Fr::rand(&mut rng);
+            let h = decaf377::basepoint() * decaf377::Fr::rand(&mut rng);
+            let b = decaf377::Fr::rand(&mut rng);
+            let commitment = g * decaf377::Fr::from(value) + h * b;
+
+            let proof = Value::from(value)
+                .prove_commitment(&encryption_key, blindings, g, h, b, &mut merlin::Transcript::new(b"test"), &mut rng)
+                .unwrap();
+
+            let from_proto = CommitmentProof::decode(proof.encode_to_vec().as_slice()).unwrap();
+            assert_eq!(from_proto, proof);
+            assert!(from_proto
+                .verify(&ciphertext, &encryption_key, commitment, g, h, &mut merlin::Transcript::new(b"test"))
+                .is_ok());
+
+            // A commitment to a different value doesn't match the ciphertext.
+            let other_commitment = commitment + g;
+            assert!(proof
+                .verify(&ciphertext, &encryption_key, other_commitment, g, h, &mut merlin::Transcript::new(b"test"))
+                .is_err());
+        }
+
+        #[test]
+        fn transparent_encryption_proof_encoding_roundtrip(value: u64, seed: u64) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let encryption_key = EncryptionKey(decaf377::basepoint() * decaf377::Fr::rand(&mut rng));
+            let (ciphertext, proof) = Value::from(value)
+                .transparent_encrypt(&encryption_key, &mut rng)
+                .unwrap();
+
+            let from_bytes = TransparentEncryptionProof::try_from(&proof.to_bytes()[..]).unwrap();
+            assert_eq!(from_bytes, proof);
+
+            let from_proto =
+                TransparentEncryptionProof::decode(proof.encode_to_vec().as_slice()).unwrap();
+            assert_eq!(from_proto, proof);
+            assert!(from_proto.verify(&ciphertext, &encryption_key).is_ok());
+        }
+    }
 }
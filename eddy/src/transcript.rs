@@ -1,4 +1,4 @@
-use crate::{limb, PublicKeyShare};
+use crate::{limb, EncryptionKey, PublicKeyShare};
 
 pub trait TranscriptProtocol {
     fn begin_decryption(&mut self);
@@ -7,6 +7,13 @@ pub trait TranscriptProtocol {
     fn append_limb_ciphertext(&mut self, ciphertext: &limb::Ciphertext);
     fn append_decryption_share_point(&mut self, point: &decaf377::Element);
     fn append_blinding_commitment(&mut self, label: &'static [u8], point: &decaf377::Element);
+    fn begin_encryption(&mut self);
+    fn begin_limb_encryption(&mut self);
+    fn append_encryption_key(&mut self, key: &EncryptionKey);
+    fn begin_dealing(&mut self, dealer: u32);
+    fn append_dealing_commitments(&mut self, commitments: &[decaf377::Element]);
+    fn begin_commitment_proof(&mut self);
+    fn append_commitment_point(&mut self, label: &'static [u8], point: &decaf377::Element);
 
     fn challenge_scalar(&mut self, label: &'static [u8]) -> decaf377::Fr;
 }
@@ -38,6 +45,33 @@ impl TranscriptProtocol for merlin::Transcript {
         self.append_message(b"dom-sep", label);
         self.append_message(b"blinding-commitment", &point.vartime_compress().0);
     }
+    fn begin_encryption(&mut self) {
+        self.append_message(b"dom-sep", b"eddy-decaf377-encrypt");
+    }
+    fn begin_limb_encryption(&mut self) {
+        self.append_message(b"dom-sep", b"begin-limb-encryption");
+    }
+    fn append_encryption_key(&mut self, key: &EncryptionKey) {
+        self.append_message(b"dom-sep", b"encryption-key");
+        self.append_message(b"encryption-key", &key.to_bytes());
+    }
+    fn begin_dealing(&mut self, dealer: u32) {
+        self.append_message(b"dom-sep", b"eddy-decaf377-dkg-dealing");
+        self.append_message(b"dealer", &dealer.to_le_bytes());
+    }
+    fn append_dealing_commitments(&mut self, commitments: &[decaf377::Element]) {
+        self.append_message(b"dom-sep", b"dealing-commitments");
+        self.append_message(b"len", &(commitments.len() as u64).to_le_bytes());
+        for commitment in commitments {
+            self.append_message(b"commitment", &commitment.vartime_compress().0);
+        }
+    }
+    fn begin_commitment_proof(&mut self) {
+        self.append_message(b"dom-sep", b"eddy-decaf377-commitment");
+    }
+    fn append_commitment_point(&mut self, label: &'static [u8], point: &decaf377::Element) {
+        self.append_message(label, &point.vartime_compress().0);
+    }
 
     fn challenge_scalar(&mut self, label: &'static [u8]) -> decaf377::Fr {
         use ark_ff::fields::PrimeField;
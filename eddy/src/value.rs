@@ -1,13 +1,15 @@
+use ark_ff::UniformRand;
+use decaf377::FieldExt;
 use rand_core::{CryptoRng, RngCore};
 
-use crate::{limb, proofs, Ciphertext, EncryptionKey};
+use crate::{limb, proofs, Ciphertext, EncryptionKey, TranscriptProtocol};
 
 /// A plaintext integer value.
 ///
 /// While only encryptions of 64-bit values are supported, the `Value` type
 /// holds a `u128` internally, because the sum of 64-bit values may exceed 64
 /// bits.  Attempting to encrypt a `Value` bigger than 64 bits will fail.
-#[derive(Default, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Value(pub u128);
 
 impl From<u64> for Value {
@@ -40,6 +42,127 @@ impl Value {
         Value(x0 + (x1 << 16) + (x2 << 32) + (x3 << 48))
     }
 
+    /// Encrypt this value to the given [`EncryptionKey`], producing a
+    /// [`Ciphertext`] and a proof that it encrypts a 64-bit value.
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        &self,
+        encryption_key: &EncryptionKey,
+        transcript: &mut merlin::Transcript,
+        mut rng: R,
+    ) -> anyhow::Result<(Ciphertext, proofs::EncryptionProof)> {
+        let blindings = [
+            decaf377::Fr::rand(&mut rng),
+            decaf377::Fr::rand(&mut rng),
+            decaf377::Fr::rand(&mut rng),
+            decaf377::Fr::rand(&mut rng),
+        ];
+        self.encrypt_with_blindings(encryption_key, blindings, transcript, rng)
+    }
+
+    /// Encrypt this value to the given [`EncryptionKey`] using the given
+    /// blinding factor for each limb, producing a [`Ciphertext`] and a proof
+    /// that it encrypts a 64-bit value.
+    ///
+    /// This allows the ciphertext to be planned ahead of time and
+    /// reconstructed deterministically.  Each blinding factor must be chosen
+    /// uniformly at random and never reused.
+    pub fn encrypt_with_blindings<R: RngCore + CryptoRng>(
+        &self,
+        encryption_key: &EncryptionKey,
+        blindings: [decaf377::Fr; 4],
+        transcript: &mut merlin::Transcript,
+        mut rng: R,
+    ) -> anyhow::Result<(Ciphertext, proofs::EncryptionProof)> {
+        let limbs = self.to_limbs()?;
+
+        transcript.begin_encryption();
+        transcript.append_encryption_key(encryption_key);
+
+        let (c0, proof0) =
+            limbs[0].encrypt_with_proof(encryption_key, blindings[0], transcript, &mut rng)?;
+        let (c1, proof1) =
+            limbs[1].encrypt_with_proof(encryption_key, blindings[1], transcript, &mut rng)?;
+        let (c2, proof2) =
+            limbs[2].encrypt_with_proof(encryption_key, blindings[2], transcript, &mut rng)?;
+        let (c3, proof3) =
+            limbs[3].encrypt_with_proof(encryption_key, blindings[3], transcript, &mut rng)?;
+
+        Ok((
+            Ciphertext { c0, c1, c2, c3 },
+            proofs::EncryptionProof {
+                proof0,
+                proof1,
+                proof2,
+                proof3,
+            },
+        ))
+    }
+
+    /// Prove that the encryption of this value with the given limb blinding
+    /// factors encrypts the same value as the Pedersen commitment
+    /// `self * value_generator + commitment_blinding * blinding_generator`.
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    pub fn prove_commitment<R: RngCore + CryptoRng>(
+        &self,
+        encryption_key: &EncryptionKey,
+        blindings: [decaf377::Fr; 4],
+        value_generator: decaf377::Element,
+        blinding_generator: decaf377::Element,
+        commitment_blinding: decaf377::Fr,
+        transcript: &mut merlin::Transcript,
+        mut rng: R,
+    ) -> anyhow::Result<proofs::CommitmentProof> {
+        let v = decaf377::Fr::from(u64::try_from(self.0)?);
+        // The blinding factor of the combined ciphertext is the weighted sum
+        // of the limb blinding factors.
+        let r = blindings
+            .iter()
+            .enumerate()
+            .fold(decaf377::Fr::from(0u64), |acc, (i, r)| {
+                acc + decaf377::Fr::from(1u64 << (16 * i)) * r
+            });
+        let b = commitment_blinding;
+
+        let B = decaf377::basepoint();
+        let PK = encryption_key.0;
+        let combined = limb::Ciphertext {
+            c1: r * B,
+            c2: r * PK + v * B,
+        };
+        let commitment = v * value_generator + b * blinding_generator;
+
+        transcript.begin_commitment_proof();
+        transcript.append_encryption_key(encryption_key);
+        transcript.append_limb_ciphertext(&combined);
+        transcript.append_commitment_point(b"G", &value_generator);
+        transcript.append_commitment_point(b"H", &blinding_generator);
+        transcript.append_commitment_point(b"C", &commitment);
+
+        let mut proof_rng = transcript
+            .build_rng()
+            .rekey_with_witness_bytes(b"value", &v.to_bytes())
+            .rekey_with_witness_bytes(b"encryption_blinding", &r.to_bytes())
+            .rekey_with_witness_bytes(b"commitment_blinding", &b.to_bytes())
+            .finalize(&mut rng);
+        let k_v = decaf377::Fr::rand(&mut proof_rng);
+        let k_r = decaf377::Fr::rand(&mut proof_rng);
+        let k_b = decaf377::Fr::rand(&mut proof_rng);
+
+        transcript.append_blinding_commitment(b"kB", &(k_r * B));
+        transcript.append_blinding_commitment(b"kPK", &(k_v * B + k_r * PK));
+        transcript
+            .append_blinding_commitment(b"kC", &(k_v * value_generator + k_b * blinding_generator));
+
+        let challenge = transcript.challenge_scalar(b"c");
+
+        Ok(proofs::CommitmentProof {
+            challenge,
+            value_response: k_v - challenge * v,
+            encryption_blinding_response: k_r - challenge * r,
+            commitment_blinding_response: k_b - challenge * b,
+        })
+    }
+
     /// Encrypt this value to the given [`EncryptionKey`], producing a
     /// [`Ciphertext`] and a (transparent) encryption proof.
     ///
@@ -51,22 +174,34 @@ impl Value {
         encryption_key: &EncryptionKey,
         mut rng: R,
     ) -> anyhow::Result<(Ciphertext, proofs::TransparentEncryptionProof)> {
-        let encrypted_limbs = self
-            .to_limbs()?
-            .iter()
-            .map(|limb| limb.transparent_encrypt(encryption_key, &mut rng))
-            .collect::<Vec<_>>();
+        let blindings = [
+            decaf377::Fr::rand(&mut rng),
+            decaf377::Fr::rand(&mut rng),
+            decaf377::Fr::rand(&mut rng),
+            decaf377::Fr::rand(&mut rng),
+        ];
+        self.transparent_encrypt_with_blindings(encryption_key, blindings)
+    }
 
-        let mut blindings: [decaf377::Fr; 4] = Default::default();
-        for (i, (_, blinding)) in encrypted_limbs.iter().enumerate() {
-            blindings[i] = *blinding;
-        }
+    /// Encrypt this value to the given [`EncryptionKey`] using the given
+    /// blinding factor for each limb, producing a [`Ciphertext`] and a
+    /// (transparent) encryption proof.
+    ///
+    /// This allows the encryption to be planned ahead of time and
+    /// reconstructed deterministically.  Each blinding factor must be chosen
+    /// uniformly at random and never reused.
+    pub fn transparent_encrypt_with_blindings(
+        &self,
+        encryption_key: &EncryptionKey,
+        blindings: [decaf377::Fr; 4],
+    ) -> anyhow::Result<(Ciphertext, proofs::TransparentEncryptionProof)> {
+        let limbs = self.to_limbs()?;
 
         let ciphertext = Ciphertext {
-            c0: encrypted_limbs[0].0,
-            c1: encrypted_limbs[1].0,
-            c2: encrypted_limbs[2].0,
-            c3: encrypted_limbs[3].0,
+            c0: limbs[0].encrypt_with_blinding(encryption_key, blindings[0]),
+            c1: limbs[1].encrypt_with_blinding(encryption_key, blindings[1]),
+            c2: limbs[2].encrypt_with_blinding(encryption_key, blindings[2]),
+            c3: limbs[3].encrypt_with_blinding(encryption_key, blindings[3]),
         };
 
         let proof = proofs::TransparentEncryptionProof::new(self.0 as u64, blindings);
@@ -6,8 +6,10 @@ mod tx;
 use tx::Tx;
 mod chain;
 use chain::ChainCmd;
+mod dex;
+use dex::DexCmd;
 mod proposal;
-use proposal::ProposalCmd;
+use proposal::{ProposalCmd, ProposalStateFilter};
 mod validator;
 pub(super) use validator::ValidatorCmd;
 
@@ -19,6 +21,9 @@ pub enum QueryCmd {
     Key {
         /// The key to query.
         key: String,
+        /// The height at which to query the key, rather than the latest height.
+        #[clap(long)]
+        height: Option<u64>,
     },
     /// Queries shielded pool data.
     #[clap(subcommand)]
@@ -32,13 +37,22 @@ pub enum QueryCmd {
     #[clap(subcommand)]
     Validator(ValidatorCmd),
     /// Queries information about governance proposals.
-    #[clap(flatten)]
+    #[clap(subcommand)]
     Proposal(ProposalCmd),
+    /// Lists governance proposals: an alias for `proposal list`, kept for compatibility.
+    #[clap(hide = true)]
+    Proposals {
+        /// Whether to include proposals which have already finished voting.
+        #[clap(short, long)]
+        inactive: bool,
+    },
+    /// Queries information about the decentralized exchange.
+    #[clap(subcommand)]
+    Dex(DexCmd),
 }
 
 impl QueryCmd {
     pub async fn exec(&self, app: &mut App) -> Result<()> {
-        // Special-case: this is a Tendermint query
         if let QueryCmd::Tx(tx) = self {
             return tx.exec(app).await;
         }
@@ -55,20 +69,36 @@ impl QueryCmd {
             return proposal.exec(app).await;
         }
 
-        let key = match self {
+        if let QueryCmd::Proposals { inactive } = self {
+            let state = if *inactive {
+                ProposalStateFilter::All
+            } else {
+                ProposalStateFilter::Voting
+            };
+            return ProposalCmd::List { state }.exec(app).await;
+        }
+
+        if let QueryCmd::Dex(dex) = self {
+            return dex.exec(app).await;
+        }
+
+        let (key, height) = match self {
             QueryCmd::Tx(_)
             | QueryCmd::Chain(_)
             | QueryCmd::Validator(_)
-            | QueryCmd::Proposal(_) => {
+            | QueryCmd::Proposal(_)
+            | QueryCmd::Proposals { .. }
+            | QueryCmd::Dex(_) => {
                 unreachable!("query handled in guard");
             }
-            QueryCmd::ShieldedPool(p) => p.key().as_bytes().to_vec(),
-            QueryCmd::Key { key } => key.as_bytes().to_vec(),
+            QueryCmd::ShieldedPool(p) => (p.key().as_bytes().to_vec(), None),
+            QueryCmd::Key { key, height } => (key.as_bytes().to_vec(), *height),
         };
 
         let mut client = app.specific_client().await?;
         let req = penumbra_proto::client::specific::KeyValueRequest {
             key,
+            height: height.unwrap_or_default(),
             ..Default::default()
         };
 
@@ -89,7 +119,9 @@ impl QueryCmd {
             QueryCmd::Tx { .. }
             | QueryCmd::Chain { .. }
             | QueryCmd::Validator { .. }
-            | QueryCmd::Proposal { .. } => {
+            | QueryCmd::Proposal { .. }
+            | QueryCmd::Proposals { .. }
+            | QueryCmd::Dex { .. } => {
                 unreachable!("query is special cased")
             }
         }
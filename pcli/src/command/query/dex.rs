@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use comfy_table::{presets, Table};
+use futures::TryStreamExt;
+use penumbra_crypto::{
+    asset,
+    dex::{
+        lp::position::{self, Position},
+        BatchSwapOutputData, TradingPair,
+    },
+    fixpoint::U64x64,
+    Value,
+};
+use penumbra_proto::client::specific::{
+    BatchSwapOutputDataRequest, OpenPositionsRequest, PositionByIdRequest, SimulateSwapRequest,
+};
+use penumbra_view::ViewClient;
+
+use crate::App;
+
+#[derive(Debug, clap::Subcommand)]
+pub enum DexCmd {
+    /// Display the result of the batch swap on a trading pair at a given height.
+    BatchOutputs {
+        /// The height of the batch.
+        height: u64,
+        /// The denomination of one asset of the trading pair.
+        asset_1: String,
+        /// The denomination of the other asset of the trading pair.
+        asset_2: String,
+    },
+    /// Display a liquidity position.
+    Position {
+        /// The ID of the position.
+        id: position::Id,
+    },
+    /// List the open liquidity positions on a trading pair.
+    Positions {
+        /// The denomination of one asset of the trading pair.
+        asset_1: String,
+        /// The denomination of the other asset of the trading pair.
+        asset_2: String,
+    },
+    /// Simulate swapping an input through the current liquidity.
+    Simulate {
+        /// The input amount to swap, written as a typed value 1.87penumbra, 12cubes, etc.
+        input: String,
+        /// The denomination to swap the input into.
+        #[clap(long)]
+        into: String,
+    },
+}
+
+impl DexCmd {
+    pub async fn exec(&self, app: &mut App) -> Result<()> {
+        let mut client = app.specific_client().await?;
+        let asset_cache = app.view().assets().await?;
+        let format_value = |value: Value| {
+            value
+                .try_format(&asset_cache)
+                .unwrap_or_else(|| format!("{}{}", value.amount, value.asset_id))
+        };
+
+        match self {
+            DexCmd::BatchOutputs {
+                height,
+                asset_1,
+                asset_2,
+            } => {
+                let trading_pair = parse_trading_pair(asset_1, asset_2)?;
+                let output_data: BatchSwapOutputData = client
+                    .batch_swap_output_data(BatchSwapOutputDataRequest {
+                        height: *height,
+                        trading_pair: Some(trading_pair.into()),
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner()
+                    .try_into()
+                    .context("cannot parse batch swap output data")?;
+
+                let value_1 = |amount| Value {
+                    amount,
+                    asset_id: trading_pair.asset_1(),
+                };
+                let value_2 = |amount| Value {
+                    amount,
+                    asset_id: trading_pair.asset_2(),
+                };
+
+                let mut table = Table::new();
+                table.load_preset(presets::NOTHING);
+                table.set_header(vec!["", "Input", "Output"]);
+                table.add_row(vec![
+                    "Asset 1".to_string(),
+                    format_value(value_1(output_data.delta_1)),
+                    format_value(value_1(output_data.lambda_1)),
+                ]);
+                table.add_row(vec![
+                    "Asset 2".to_string(),
+                    format_value(value_2(output_data.delta_2)),
+                    format_value(value_2(output_data.lambda_2)),
+                ]);
+
+                println!("{}", table);
+                if !output_data.success {
+                    println!("The batch could not be filled, so its inputs were refunded.");
+                }
+            }
+            DexCmd::Position { id } => {
+                let metadata: position::Metadata = client
+                    .position_by_id(PositionByIdRequest {
+                        position_id: Some((*id).into()),
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner()
+                    .try_into()
+                    .context("cannot parse position")?;
+
+                println!("{}", serde_json::to_string_pretty(&metadata)?);
+            }
+            DexCmd::Positions { asset_1, asset_2 } => {
+                let trading_pair = parse_trading_pair(asset_1, asset_2)?;
+                let positions = client
+                    .open_positions(OpenPositionsRequest {
+                        trading_pair: Some(trading_pair.into()),
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner()
+                    .try_collect::<Vec<_>>()
+                    .await?
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<position::Metadata>, _>>()?;
+
+                let mut table = Table::new();
+                table.load_preset(presets::NOTHING);
+                table.set_header(vec!["ID", "Reserves 1", "Reserves 2", "Price", "Fee"]);
+
+                for metadata in positions {
+                    let Position { pair, phi, .. } = &metadata.position;
+                    table.add_row(vec![
+                        metadata.position.id().to_string(),
+                        format_value(Value {
+                            amount: metadata.reserves.r1,
+                            asset_id: pair.asset_1(),
+                        }),
+                        format_value(Value {
+                            amount: metadata.reserves.r2,
+                            asset_id: pair.asset_2(),
+                        }),
+                        // The price of asset 1 in units of asset 2, before fees.
+                        phi.p
+                            .checked_div(&phi.q)
+                            .map(|price| format!("{:.6}", to_f64(price)))
+                            .unwrap_or_else(|| "-".to_string()),
+                        format!("{:.2}%", to_f64(phi.fee) * 100.0),
+                    ]);
+                }
+
+                println!("{}", table);
+            }
+            DexCmd::Simulate { input, into } => {
+                let input = input.parse::<Value>()?;
+                let into = asset::REGISTRY.parse_unit(into.as_str()).base();
+
+                let output: Value = client
+                    .simulate_swap(SimulateSwapRequest {
+                        input: Some(input.into()),
+                        output_id: Some(into.id().into()),
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner()
+                    .output
+                    .ok_or_else(|| anyhow::anyhow!("missing output"))?
+                    .try_into()?;
+
+                println!("{} => {}", format_value(input), format_value(output));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_trading_pair(asset_1: &str, asset_2: &str) -> Result<TradingPair> {
+    TradingPair::canonical_order_for((
+        asset::REGISTRY.parse_unit(asset_1).base().id(),
+        asset::REGISTRY.parse_unit(asset_2).base().id(),
+    ))
+}
+
+/// Approximate a fixed-point number for display.
+fn to_f64(value: U64x64) -> f64 {
+    value.to_bits() as f64 / 2f64.powi(64)
+}
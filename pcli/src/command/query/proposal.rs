@@ -4,14 +4,16 @@ use std::{
 };
 
 use anyhow::Result;
+use comfy_table::{presets, Table};
+use futures::TryStreamExt;
 use penumbra_component::{
-    governance::{
-        proposal::{self, ProposalList},
-        state_key::*,
-    },
+    governance::{proposal, state_key::*, tally::Tally},
     stake::validator,
 };
 use penumbra_crypto::IdentityKey;
+use penumbra_proto::client::specific::{
+    proposal_list_request::StateFilter, ProposalInfo, ProposalListRequest, ProposalTallyRequest,
+};
 use penumbra_transaction::action::{Proposal, ProposalPayload, Vote};
 use serde::Serialize;
 use serde_json::json;
@@ -20,101 +22,222 @@ use crate::App;
 
 #[derive(Debug, clap::Subcommand)]
 pub enum ProposalCmd {
-    /// List all governance proposals by number.
-    Proposals {
-        /// Whether to include proposals which have already finished voting.
-        #[clap(short, long)]
-        inactive: bool,
+    /// List governance proposals by number.
+    List {
+        /// Only list proposals in this state.
+        #[clap(long, arg_enum, default_value = "voting")]
+        state: ProposalStateFilter,
     },
-    /// Query for information about a particular proposal.
-    Proposal {
+    /// Fetch the details of a proposal, as submitted to the chain.
+    Definition {
         /// The proposal id to query.
         proposal_id: u64,
-        /// The query to ask of it.
-        #[clap(subcommand)]
-        query: PerProposalCmd,
     },
-}
-
-#[derive(Debug, clap::Subcommand)]
-pub enum PerProposalCmd {
-    /// Fetch the details of a proposal, as submitted to the chain.
-    Definition,
     /// Display the current state of a proposal.
-    State,
+    State {
+        /// The proposal id to query.
+        proposal_id: u64,
+    },
     /// Display the voting period of a proposal.
-    Period,
-    /// Display the latest epoch's tally of votes on the proposal, in units of voting power.
-    Tally,
+    Period {
+        /// The proposal id to query.
+        proposal_id: u64,
+    },
+    /// Display the current tally of votes on a proposal, in units of voting power, along with its
+    /// progress towards quorum and the number of blocks left to vote.
+    Tally {
+        /// The proposal id to query.
+        proposal_id: u64,
+    },
     /// List the votes of the validators who have voted on a proposal.
-    ValidatorVotes,
+    ValidatorVotes {
+        /// The proposal id to query.
+        proposal_id: u64,
+    },
+    /// The previous form of the queries about a single proposal, `proposal <PROPOSAL_ID> <QUERY>`,
+    /// which is still accepted.
+    #[clap(external_subcommand)]
+    ByProposalId(Vec<String>),
+}
+
+/// The states of proposals which can be listed.
+#[derive(Debug, Clone, Copy, clap::ArgEnum)]
+pub enum ProposalStateFilter {
+    All,
+    Voting,
+    Withdrawn,
+    Finished,
+}
+
+impl From<ProposalStateFilter> for StateFilter {
+    fn from(filter: ProposalStateFilter) -> Self {
+        match filter {
+            ProposalStateFilter::All => StateFilter::All,
+            ProposalStateFilter::Voting => StateFilter::Voting,
+            ProposalStateFilter::Withdrawn => StateFilter::Withdrawn,
+            ProposalStateFilter::Finished => StateFilter::Finished,
+        }
+    }
 }
 
 impl ProposalCmd {
+    /// Translate a query of the previous form, `proposal <PROPOSAL_ID> <QUERY>`, into the current
+    /// form, `proposal <QUERY> <PROPOSAL_ID>`.
+    fn from_proposal_id_first(args: &[String]) -> Result<Self> {
+        let (proposal_id, query) = match args {
+            [proposal_id, query] => (proposal_id, query),
+            _ => anyhow::bail!(
+                "unrecognized proposal query {:?}, see `pcli query proposal --help`",
+                args.join(" ")
+            ),
+        };
+        let proposal_id = proposal_id
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid proposal id {:?}", proposal_id))?;
+
+        Ok(match query.as_str() {
+            "definition" => ProposalCmd::Definition { proposal_id },
+            "state" => ProposalCmd::State { proposal_id },
+            "period" => ProposalCmd::Period { proposal_id },
+            "tally" => ProposalCmd::Tally { proposal_id },
+            "validator-votes" => ProposalCmd::ValidatorVotes { proposal_id },
+            _ => anyhow::bail!("unrecognized proposal query {:?}", query),
+        })
+    }
+
     pub async fn exec(&self, app: &mut App) -> Result<()> {
-        use PerProposalCmd::*;
+        let translated;
+        let cmd = if let ProposalCmd::ByProposalId(args) = self {
+            translated = Self::from_proposal_id_first(args)?;
+            &translated
+        } else {
+            self
+        };
 
         let mut client = app.specific_client().await?;
 
-        match self {
-            ProposalCmd::Proposals { inactive } => {
-                let list: Vec<u64> = if *inactive {
-                    let latest: u64 = client.key_proto(latest_proposal_id()).await?;
-                    (0..=latest).collect()
-                } else {
-                    let unfinished: ProposalList =
-                        client.key_domain(unfinished_proposals()).await?;
-                    unfinished.proposals.into_iter().collect()
+        match cmd {
+            ProposalCmd::List { state } => {
+                let proposals: Vec<ProposalInfo> = client
+                    .proposal_list(ProposalListRequest {
+                        state: StateFilter::from(*state) as i32,
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner()
+                    .try_collect()
+                    .await?;
+
+                let mut table = Table::new();
+                table.load_preset(presets::NOTHING);
+                table.set_header(vec!["ID", "State", "Voting Ends", "Title"]);
+                for info in proposals {
+                    let proposal_state: proposal::State = info
+                        .state
+                        .ok_or_else(|| anyhow::anyhow!("missing proposal state"))?
+                        .try_into()?;
+                    let proposal: Proposal = info
+                        .proposal
+                        .ok_or_else(|| anyhow::anyhow!("missing proposal"))?
+                        .try_into()?;
+                    table.add_row(vec![
+                        info.proposal_id.to_string(),
+                        match proposal_state {
+                            proposal::State::Voting => "voting".to_string(),
+                            proposal::State::Withdrawn { .. } => "withdrawn".to_string(),
+                            proposal::State::Finished { outcome } => match outcome {
+                                proposal::Outcome::Passed => "passed".to_string(),
+                                proposal::Outcome::Failed { .. } => "failed".to_string(),
+                                proposal::Outcome::Vetoed { .. } => "vetoed".to_string(),
+                            },
+                        },
+                        info.voting_end_block.to_string(),
+                        proposal.title,
+                    ]);
+                }
+                println!("{}", table);
+            }
+            ProposalCmd::Definition { proposal_id } => {
+                let title: String = client.key_proto(proposal_title(*proposal_id)).await?;
+                let description: String =
+                    client.key_proto(proposal_description(*proposal_id)).await?;
+                let payload: ProposalPayload =
+                    client.key_domain(proposal_payload(*proposal_id)).await?;
+                let proposal = Proposal {
+                    title,
+                    description,
+                    payload,
                 };
-                json(&list)?;
+                json(&proposal)?;
             }
-            ProposalCmd::Proposal { proposal_id, query } => match query {
-                Definition => {
-                    let title: String = client.key_proto(proposal_title(*proposal_id)).await?;
-                    let description: String =
-                        client.key_proto(proposal_description(*proposal_id)).await?;
-                    let payload: ProposalPayload =
-                        client.key_domain(proposal_payload(*proposal_id)).await?;
-                    let proposal = Proposal {
-                        title,
-                        description,
-                        payload,
-                    };
-                    json(&proposal)?;
-                }
-                State => {
-                    let state: proposal::State =
-                        client.key_domain(proposal_state(*proposal_id)).await?;
-                    json(&state)?;
-                }
-                Period => {
-                    let start: u64 = client
-                        .key_proto(proposal_voting_start(*proposal_id))
+            ProposalCmd::State { proposal_id } => {
+                let state: proposal::State =
+                    client.key_domain(proposal_state(*proposal_id)).await?;
+                json(&state)?;
+            }
+            ProposalCmd::Period { proposal_id } => {
+                let start: u64 = client
+                    .key_proto(proposal_voting_start(*proposal_id))
+                    .await?;
+                let end: u64 = client.key_proto(proposal_voting_end(*proposal_id)).await?;
+                let period = json!({
+                    "voting_start_block": start,
+                    "voting_end_block": end,
+                });
+                json(&period)?;
+            }
+            ProposalCmd::ValidatorVotes { proposal_id } => {
+                let voting_validators: validator::List =
+                    client.key_domain(voting_validators(*proposal_id)).await?;
+
+                let mut votes: BTreeMap<IdentityKey, Vote> = BTreeMap::new();
+                for identity_key in voting_validators.0.iter() {
+                    let vote: Vote = client
+                        .key_domain(validator_vote(*proposal_id, *identity_key))
                         .await?;
-                    let end: u64 = client.key_proto(proposal_voting_end(*proposal_id)).await?;
-                    let period = json!({
-                        "voting_start_block": start,
-                        "voting_end_block": end,
-                    });
-                    json(&period)?;
-                }
-                ValidatorVotes => {
-                    let voting_validators: validator::List =
-                        client.key_domain(voting_validators(*proposal_id)).await?;
-
-                    let mut votes: BTreeMap<IdentityKey, Vote> = BTreeMap::new();
-                    for identity_key in voting_validators.0.iter() {
-                        let vote: Vote = client
-                            .key_domain(validator_vote(*proposal_id, *identity_key))
-                            .await?;
-                        votes.insert(*identity_key, vote);
-                    }
-                    json(&votes)?;
+                    votes.insert(*identity_key, vote);
                 }
-                Tally => {
-                    todo!("vote tallying not yet implemented");
-                }
-            },
+                json(&votes)?;
+            }
+            ProposalCmd::Tally { proposal_id } => {
+                let rsp = client
+                    .proposal_tally(ProposalTallyRequest {
+                        proposal_id: *proposal_id,
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner();
+
+                let tally: Tally = rsp
+                    .tally
+                    .ok_or_else(|| anyhow::anyhow!("missing tally"))?
+                    .try_into()?;
+                let valid_quorum = rsp
+                    .valid_quorum
+                    .ok_or_else(|| anyhow::anyhow!("missing valid quorum"))?;
+
+                // The fraction of the voting power needed for quorum which has voted so far
+                let quorum_power = rsp.total_voting_power as f64 * valid_quorum.numerator as f64
+                    / valid_quorum.denominator as f64;
+                let quorum_progress = if quorum_power > 0.0 {
+                    tally.total() as f64 / quorum_power
+                } else {
+                    0.0
+                };
+
+                let tally = json!({
+                    "yes": tally.yes,
+                    "no": tally.no,
+                    "abstain": tally.abstain,
+                    "no_with_veto": tally.no_with_veto,
+                    "total_voting_power": rsp.total_voting_power,
+                    "valid_quorum": format!("{}/{}", valid_quorum.numerator, valid_quorum.denominator),
+                    "quorum_progress": format!("{:.2}%", quorum_progress * 100.0),
+                    "blocks_remaining": rsp.blocks_remaining,
+                });
+                json(&tally)?;
+            }
+            ProposalCmd::ByProposalId(_) => unreachable!("query was translated to current form"),
         }
 
         Ok(())
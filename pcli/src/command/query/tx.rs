@@ -1,11 +1,18 @@
 use anyhow::Result;
 use colored_json::prelude::*;
-use penumbra_proto::Protobuf;
+use comfy_table::{presets, Table};
+use penumbra_proto::{
+    client::specific::{IndexedTransaction, TransactionByHashRequest},
+    transaction as pbt, Message,
+};
 use penumbra_transaction::Transaction;
 
 use crate::App;
 
 /// Queries the chain for a transaction by hash.
+///
+/// Transactions are looked up in the node's transaction index, or through Tendermint if the node
+/// isn't indexing transactions.
 #[derive(Debug, clap::Args)]
 pub struct Tx {
     /// The hex-formatted transaction hash to query.
@@ -14,6 +21,56 @@ pub struct Tx {
 
 impl Tx {
     pub async fn exec(&self, app: &mut App) -> Result<()> {
+        let mut client = app.specific_client().await?;
+
+        let rsp = match client
+            .transaction_by_hash(TransactionByHashRequest {
+                tx_hash: hex::decode(&self.hash)?,
+                ..Default::default()
+            })
+            .await
+        {
+            Ok(rsp) => rsp.into_inner(),
+            // The node doesn't index transactions, or hadn't started indexing when this one was
+            // committed, so ask Tendermint instead.
+            Err(status)
+                if matches!(
+                    status.code(),
+                    tonic::Code::Unimplemented | tonic::Code::NotFound
+                ) =>
+            {
+                self.tendermint_tx(app).await?
+            }
+            Err(status) => return Err(status.into()),
+        };
+
+        let mut table = Table::new();
+        table.load_preset(presets::NOTHING);
+        table
+            .add_row(vec!["Hash", &hex::encode(&rsp.tx_hash)])
+            .add_row(vec!["Height", &rsp.height.to_string()])
+            .add_row(vec!["Index", &rsp.index.to_string()])
+            .add_row(vec!["Result Code", &rsp.code.to_string()]);
+        if !rsp.log.is_empty() {
+            table.add_row(vec!["Log", &rsp.log]);
+        }
+        println!("{}", table);
+
+        let tx: Transaction = rsp
+            .transaction
+            .ok_or_else(|| anyhow::anyhow!("transaction could not be decoded"))?
+            .try_into()?;
+        for (index, action) in tx.actions().enumerate() {
+            let action_json = serde_json::to_string_pretty(&pbt::Action::from(action.clone()))?;
+            println!("\nAction {}:", index);
+            println!("{}", action_json.to_colored_json_auto()?);
+        }
+
+        Ok(())
+    }
+
+    /// Looks up the transaction using Tendermint's `tx` RPC.
+    async fn tendermint_tx(&self, app: &App) -> Result<IndexedTransaction> {
         use tendermint_rpc::{Client, HttpClient};
 
         // generic bounds on HttpClient::new are not well-constructed, so we have to
@@ -22,12 +79,14 @@ impl Tx {
 
         let rsp = client.tx(self.hash.parse()?, false).await?;
 
-        let tx = Transaction::decode(rsp.tx.as_bytes())?;
-        let tx_json = serde_json::to_string_pretty(&tx)?;
-
-        println!("{}", tx_json.to_colored_json_auto()?);
-        println!("\nresult code: {:?}", rsp.tx_result.code);
-
-        Ok(())
+        Ok(IndexedTransaction {
+            tx_hash: hex::decode(&self.hash)?,
+            height: rsp.height.value(),
+            index: rsp.index.into(),
+            transaction: pbt::Transaction::decode(rsp.tx.as_bytes()).ok(),
+            code: rsp.tx_result.code.into(),
+            log: rsp.tx_result.log.to_string(),
+            events: Vec::new(),
+        })
     }
 }
@@ -3,9 +3,13 @@ use std::{fs::File, io::Write};
 use anyhow::{anyhow, Context, Result};
 use penumbra_component::stake::rate::RateData;
 use penumbra_crypto::{
-    asset, transaction::Fee, Address, DelegationToken, IdentityKey, Value, STAKING_TOKEN_ASSET_ID,
+    asset, dex::BatchSwapOutputData, transaction::Fee, Address, DelegationToken, FlowEncryptionKey,
+    IdentityKey, Value, STAKING_TOKEN_ASSET_ID,
+};
+use penumbra_proto::{
+    client::specific::{BatchSwapOutputDataRequest, KeyValueRequest},
+    Message, Protobuf,
 };
-use penumbra_proto::{client::specific::KeyValueRequest, Protobuf};
 use penumbra_transaction::action::Proposal;
 use penumbra_view::ViewClient;
 use penumbra_wallet::plan;
@@ -98,7 +102,7 @@ pub enum TxCmd {
         into: String,
         /// The transaction fee (paid in upenumbra).
         ///
-        /// A swap generates two transactions; the fee will be split equally over both.
+        /// A swap generates two transactions; the fee will be paid for each of them.
         #[clap(long, default_value = "0")]
         fee: u64,
         /// Optional. Only spend funds originally received by the given address index.
@@ -192,13 +196,23 @@ impl TxCmd {
                 let input = input.parse::<Value>()?;
                 let into = asset::REGISTRY.parse_unit(into.as_str()).base();
                 let fee = Fee::from_staking_token_amount(*fee);
+
+                // Swap amounts are encrypted to the validators' flow encryption key.
+                let flow_encryption_key: FlowEncryptionKey = app
+                    .specific_client()
+                    .await?
+                    .key_domain(penumbra_component::dex::state_key::flow_encryption_key())
+                    .await
+                    .context("could not fetch the flow encryption key")?;
+
                 let swap_plan = plan::swap(
                     &app.fvk,
                     &mut app.view,
                     OsRng,
                     input,
                     into,
-                    fee.clone(),
+                    fee,
+                    flow_encryption_key,
                     *source,
                 )
                 .await?;
@@ -208,8 +222,6 @@ impl TxCmd {
                     .expect("expected swap plan")
                     .clone();
 
-                let _swap_nft_asset_id = swap_plan_inner.swap_plaintext.asset_id();
-
                 // Submit the `Swap` transaction.
                 app.build_and_submit_transaction(swap_plan).await?;
 
@@ -226,15 +238,42 @@ impl TxCmd {
                 .context("timeout waiting to detect commitment of submitted transaction")?
                 .context("error while waiting for detection of submitted transaction")?;
 
-                // Now that the note commitment is detected, we can submit the `SwapClaim` transaction.
+                // Once the note commitment is detected, the batch swap it was included in is
+                // executed as soon as the validators have decrypted the batch's inputs, which
+                // takes a few blocks, so we wait for its output data and then submit the
+                // `SwapClaim` transaction.
+                let swap_plaintext = swap_plan_inner.swap_plaintext;
+                let mut client = app.specific_client().await?;
+                let output_data: BatchSwapOutputData =
+                    tokio::time::timeout(std::time::Duration::from_secs(60), async {
+                        loop {
+                            match client
+                                .batch_swap_output_data(BatchSwapOutputDataRequest {
+                                    height: swap_nft_note.height_created,
+                                    trading_pair: Some(swap_plaintext.trading_pair.into()),
+                                    ..Default::default()
+                                })
+                                .await
+                            {
+                                Ok(output_data) => return Ok(output_data.into_inner()),
+                                Err(status) if status.code() == tonic::Code::NotFound => {
+                                    tokio::time::sleep(std::time::Duration::from_secs(1)).await
+                                }
+                                Err(status) => return Err(status),
+                            }
+                        }
+                    })
+                    .await
+                    .context("timeout waiting for the batch swap to be executed")?
+                    .context("error fetching batch swap output data")?
+                    .try_into()?;
 
                 let claim_plan = plan::swap_claim(
-                    &app.fvk,
                     &mut app.view,
                     OsRng,
-                    swap_nft_note.note.clone(),
-                    fee,
-                    *source,
+                    swap_plaintext,
+                    swap_nft_note,
+                    output_data,
                 )
                 .await?;
 
@@ -411,6 +450,7 @@ impl TxCmd {
                                 *proposal_id,
                             ).into(),
                             proof: false,
+                            ..Default::default()
                         })
                         .await?
                         .into_inner()
@@ -446,24 +486,60 @@ impl TxCmd {
                 }
             }
             TxCmd::Proposal(ProposalCmd::Vote {
-                proposal_id: _,
-                vote: _,
-                fee: _,
-                source: _,
+                proposal_id,
+                vote,
+                fee,
+                source,
             }) => {
-                println!("Sorry, delegator voting is not yet implemented");
-                // TODO: fill this in for delegator votes
-                // let plan = plan::delegator_vote(
-                //     &app.fvk,
-                //     &mut app.view,
-                //     OsRng,
-                //     *proposal_id,
-                //     *vote,
-                //     *fee,
-                //     *source,
-                // )
-                // .await?;
-                // app.build_and_submit_transaction(plan).await?;
+                // Download the position of the note commitment tree when voting on the proposal
+                // started, since only delegation tokens held at that point are eligible to vote
+                let chain_id = app.view().chain_params().await?.chain_id;
+                let mut client = app.specific_client().await?;
+                let start_height = u64::decode(
+                    &client
+                        .key_value(KeyValueRequest {
+                            chain_id: chain_id.clone(),
+                            key: penumbra_component::governance::state_key::proposal_voting_start(
+                                *proposal_id,
+                            )
+                            .into(),
+                            proof: false,
+                            ..Default::default()
+                        })
+                        .await?
+                        .into_inner()
+                        .value[..],
+                )?;
+                let start_position = u64::decode(
+                    &client
+                        .key_value(KeyValueRequest {
+                            chain_id,
+                            key: penumbra_component::shielded_pool::state_key::nct_position_by_height(
+                                start_height,
+                            )
+                            .into(),
+                            proof: false,
+                            ..Default::default()
+                        })
+                        .await?
+                        .into_inner()
+                        .value[..],
+                )?;
+
+                let fee = Fee::from_staking_token_amount(*fee);
+                let plan = plan::delegator_vote(
+                    &app.fvk,
+                    &mut app.view,
+                    OsRng,
+                    *proposal_id,
+                    start_position.into(),
+                    *vote,
+                    fee,
+                    *source,
+                )
+                .await?;
+
+                app.build_and_submit_transaction(plan).await?;
             }
         }
         Ok(())
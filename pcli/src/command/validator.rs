@@ -1,9 +1,12 @@
 use std::{fs::File, io::Write};
 
 use anyhow::{Context, Result};
+use futures::TryStreamExt;
 use penumbra_component::stake::{validator, validator::Validator, FundingStream, FundingStreams};
-use penumbra_crypto::{transaction::Fee, GovernanceKey, IdentityKey};
-use penumbra_proto::{stake::Validator as ProtoValidator, Message, Protobuf};
+use penumbra_crypto::{keys::SpendKey, transaction::Fee, GovernanceKey, IdentityKey};
+use penumbra_proto::{
+    client::oblivious::ValidatorInfoRequest, stake::Validator as ProtoValidator, Message, Protobuf,
+};
 use penumbra_transaction::action::{ValidatorVote, ValidatorVoteBody, Vote};
 use penumbra_wallet::plan;
 use rand_core::OsRng;
@@ -34,6 +37,18 @@ pub enum ValidatorCmd {
         #[clap(long)]
         source: Option<u64>,
     },
+    /// Release your validator from jail after it was jailed for downtime.
+    ///
+    /// This uploads your validator's current definition with an increased sequence number, which
+    /// the chain accepts once the validator has been jailed for the jail duration.
+    Unjail {
+        /// The transaction fee (paid in upenumbra).
+        #[clap(long, default_value = "0")]
+        fee: u64,
+        /// Optional. Only spend funds originally received by the given address index.
+        #[clap(long)]
+        source: Option<u64>,
+    },
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -76,6 +91,7 @@ impl ValidatorCmd {
                 DefinitionCmd::Template { .. } | DefinitionCmd::Fetch { .. },
             ) => false,
             ValidatorCmd::Vote { .. } => true,
+            ValidatorCmd::Unjail { .. } => true,
         }
     }
 
@@ -105,14 +121,7 @@ impl ValidatorCmd {
                     .map_err(|_| anyhow::anyhow!("Unable to parse validator definition"))?;
                 let fee = Fee::from_staking_token_amount(*fee);
 
-                // Sign the validator definition with the wallet's spend key.
-                let protobuf_serialized: ProtoValidator = new_validator.clone().into();
-                let v_bytes = protobuf_serialized.encode_to_vec();
-                let auth_sig = sk.spend_auth_key().sign(&mut OsRng, &v_bytes);
-                let vd = validator::Definition {
-                    validator: new_validator,
-                    auth_sig,
-                };
+                let vd = sign_definition(&sk, new_validator);
                 // Construct a new transaction and include the validator definition.
                 let plan =
                     plan::validator_definition(&app.fvk, &mut app.view, OsRng, vd, fee, *source)
@@ -159,6 +168,48 @@ impl ValidatorCmd {
 
                 println!("Cast validator vote");
             }
+            ValidatorCmd::Unjail { fee, source } => {
+                let identity_key = IdentityKey(fvk.spend_verification_key().clone());
+
+                let mut client = app.oblivious_client().await?;
+                let info = client
+                    .validator_info(ValidatorInfoRequest {
+                        show_inactive: true,
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner()
+                    .try_collect::<Vec<_>>()
+                    .await?
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<validator::Info>, _>>()?
+                    .into_iter()
+                    .find(|info| info.validator.identity_key == identity_key)
+                    .ok_or_else(|| anyhow::anyhow!("Could not find validator {}", identity_key))?;
+
+                if info.status.state != validator::State::Jailed {
+                    return Err(anyhow::anyhow!(
+                        "validator {} is not jailed, but in state {:?}",
+                        identity_key,
+                        info.status.state
+                    ));
+                }
+
+                // Resubmitting an enabled definition for a jailed validator unjails it.
+                let mut validator = info.validator;
+                validator.enabled = true;
+                validator.sequence_number += 1;
+
+                let vd = sign_definition(&sk, validator);
+                let fee = Fee::from_staking_token_amount(*fee);
+                let plan =
+                    plan::validator_definition(&app.fvk, &mut app.view, OsRng, vd, fee, *source)
+                        .await?;
+                app.build_and_submit_transaction(plan).await?;
+
+                println!("Submitted unjail request; your validator will be Inactive until it re-enters the active set");
+            }
             ValidatorCmd::Definition(DefinitionCmd::Template { file }) => {
                 let (address, _dtk) = fvk.incoming().payment_address(0u64.into());
                 let identity_key = IdentityKey(fvk.spend_verification_key().clone());
@@ -212,3 +263,14 @@ impl ValidatorCmd {
         Ok(())
     }
 }
+
+/// Signs the validator definition with the wallet's spend key.
+fn sign_definition(sk: &SpendKey, validator: Validator) -> validator::Definition {
+    let protobuf_serialized: ProtoValidator = validator.clone().into();
+    let v_bytes = protobuf_serialized.encode_to_vec();
+    let auth_sig = sk.spend_auth_key().sign(&mut OsRng, &v_bytes);
+    validator::Definition {
+        validator,
+        auth_sig,
+    }
+}
@@ -107,3 +107,42 @@ fn transaction_send_from_addr_0_to_addr_1() {
         ])
         .timeout(std::time::Duration::from_secs(TIMEOUT_COMMAND_SECONDS));
 }
+
+#[ignore]
+#[test]
+fn swap_and_claim_round_trip() {
+    let tmpdir = load_wallet_into_tmpdir();
+
+    // Swapping submits the `Swap` transaction, waits for the swap NFT to be
+    // detected, and then claims the outputs of the batch swap with a
+    // `SwapClaim` transaction, so success means the full round trip worked.
+    let mut swap_cmd = Command::cargo_bin("pcli").unwrap();
+    swap_cmd
+        .args(&[
+            "--data-path",
+            tmpdir.path().to_str().unwrap(),
+            "tx",
+            "swap",
+            TEST_ASSET,
+            "--into",
+            "penumbra",
+        ])
+        .timeout(std::time::Duration::from_secs(TIMEOUT_COMMAND_SECONDS));
+    swap_cmd.assert().success();
+
+    // Wait for a couple blocks for the claim to be confirmed.
+    let block_time = time::Duration::from_secs(2 * BLOCK_TIME_SECONDS);
+    thread::sleep(block_time);
+
+    // The claimed outputs should have been detected by the wallet.
+    let mut balance_cmd = Command::cargo_bin("pcli").unwrap();
+    balance_cmd
+        .args(&[
+            "--data-path",
+            tmpdir.path().to_str().unwrap(),
+            "view",
+            "balance",
+        ])
+        .timeout(std::time::Duration::from_secs(TIMEOUT_COMMAND_SECONDS));
+    balance_cmd.assert().success();
+}
@@ -10,13 +10,17 @@ use tendermint::{
     abci::{ConsensusRequest, ConsensusResponse},
     block,
 };
-use tokio::sync::{mpsc, oneshot, watch};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
 use tokio_util::sync::PollSender;
 use tower_abci::BoxError;
 use tracing::error_span;
 
 use super::{Message, Worker};
-use crate::RequestExt;
+use crate::{indexer::BlockEvents, Indexer, RequestExt};
+
+/// The number of committed blocks' events buffered for each event subscriber, beyond which a slow
+/// subscriber misses events.
+const EVENT_BUFFER_BLOCKS: usize = 100;
 
 #[derive(Clone)]
 pub struct Consensus {
@@ -24,23 +28,34 @@ pub struct Consensus {
 }
 
 impl Consensus {
-    pub async fn new(storage: Storage) -> anyhow::Result<(Self, watch::Receiver<block::Height>)> {
+    pub async fn new(
+        storage: Storage,
+        indexer: Option<Indexer>,
+    ) -> anyhow::Result<(
+        Self,
+        watch::Receiver<block::Height>,
+        broadcast::Sender<BlockEvents>,
+    )> {
         let (queue_tx, queue_rx) = mpsc::channel(10);
         let initial_height = match storage.latest_version().await? {
             Some(version) => version.try_into().unwrap(),
             _ => 0u32.into(),
         };
         let (height_tx, height_rx) = watch::channel(initial_height);
+        let (events_tx, _) = broadcast::channel(EVENT_BUFFER_BLOCKS);
 
-        tokio::task::Builder::new()
-            .name("consensus::Worker")
-            .spawn(Worker::new(storage, queue_rx, height_tx).await?.run());
+        tokio::task::Builder::new().name("consensus::Worker").spawn(
+            Worker::new(storage, indexer, queue_rx, height_tx, events_tx.clone())
+                .await?
+                .run(),
+        );
 
         Ok((
             Self {
                 queue: PollSender::new(queue_tx),
             },
             height_rx,
+            events_tx,
         ))
     }
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use anyhow::{anyhow, Result};
 
 use penumbra_proto::Protobuf;
@@ -10,33 +12,52 @@ use tendermint::{
     abci::{self, ConsensusRequest as Request, ConsensusResponse as Response},
     block,
 };
-use tokio::sync::{mpsc, watch};
+use tokio::sync::{broadcast, mpsc, watch};
 use tracing::{instrument, Instrument};
 
 use super::Message;
-use crate::App;
+use crate::{
+    indexer::{self, BlockEvents},
+    App, Indexer,
+};
 
 pub struct Worker {
     queue: mpsc::Receiver<Message>,
     height_tx: watch::Sender<block::Height>,
+    events_tx: broadcast::Sender<BlockEvents>,
     storage: Storage,
     app: App,
+    /// The version of the storage that the app's state was loaded from.
+    version: Option<jmt::Version>,
+    indexer: Option<Indexer>,
+    /// The transactions and events of the block being executed.
+    block: Option<indexer::Block>,
 }
 
 impl Worker {
-    #[instrument(skip(storage, queue, height_tx), name = "consensus::Worker::new")]
+    #[instrument(
+        skip(storage, indexer, queue, height_tx, events_tx),
+        name = "consensus::Worker::new"
+    )]
     pub async fn new(
         storage: Storage,
+        indexer: Option<Indexer>,
         queue: mpsc::Receiver<Message>,
         height_tx: watch::Sender<block::Height>,
+        events_tx: broadcast::Sender<BlockEvents>,
     ) -> Result<Self> {
         let app = App::new(storage.clone()).await;
+        let version = storage.latest_version().await?;
 
         Ok(Self {
             queue,
             height_tx,
+            events_tx,
             storage,
             app,
+            version,
+            indexer,
+            block: None,
         })
     }
 
@@ -57,20 +78,42 @@ impl Worker {
                         .await
                         .expect("init_chain must succeed"),
                 ),
-                Request::BeginBlock(begin_block) => Response::BeginBlock(
-                    self.begin_block(begin_block)
-                        .instrument(span)
+                Request::BeginBlock(begin_block) => {
+                    self.reload_if_restored()
+                        .instrument(span.clone())
                         .await
-                        .expect("begin_block must succeed"),
-                ),
+                        .expect("can check whether the storage was restored");
+
+                    // If the chain has been halted, stop the worker rather than executing any
+                    // further blocks: the last block was already committed, so the storage is
+                    // left consistent, and the halt is still reported over gRPC.
+                    if self
+                        .check_halted()
+                        .instrument(span.clone())
+                        .await
+                        .expect("can check whether the chain is halted")
+                    {
+                        return Err(anyhow!(
+                            "chain is halted, refusing to execute further blocks until resumed with `pd resume` or `pd migrate`"
+                        ));
+                    }
+
+                    Response::BeginBlock(
+                        self.begin_block(begin_block)
+                            .instrument(span)
+                            .await
+                            .expect("begin_block must succeed"),
+                    )
+                }
                 Request::DeliverTx(deliver_tx) => {
                     let ctx = Context::new();
+                    let tx = deliver_tx.tx.clone();
                     let rsp = self
                         .deliver_tx(ctx.clone(), deliver_tx)
                         .instrument(span.clone())
                         .await;
-                    span.in_scope(|| {
-                        Response::DeliverTx(match rsp {
+                    let rsp = span.in_scope(|| {
+                        match rsp {
                             Ok(()) => {
                                 tracing::info!("deliver_tx succeeded");
                                 abci::response::DeliverTx {
@@ -87,8 +130,12 @@ impl Worker {
                                     ..Default::default()
                                 }
                             }
-                        })
-                    })
+                        }
+                    });
+                    if let Some(block) = self.block.as_mut() {
+                        block.record_transaction(&tx, &rsp);
+                    }
+                    Response::DeliverTx(rsp)
                 }
                 Request::EndBlock(end_block) => Response::EndBlock(
                     self.end_block(end_block)
@@ -151,15 +198,56 @@ impl Worker {
         })
     }
 
+    /// Reloads the app if the storage was restored from a state sync snapshot after the worker was
+    /// created, since the app's state would otherwise still be the empty pre-genesis state.
+    async fn reload_if_restored(&mut self) -> Result<()> {
+        let version = self.storage.latest_version().await?;
+        if version != self.version {
+            tracing::info!(
+                ?version,
+                "storage was restored from a snapshot, reloading app"
+            );
+            self.app = App::new(self.storage.clone()).await;
+            self.version = version;
+        }
+        Ok(())
+    }
+
+    /// Checks whether the chain has been halted, resuming it if the operator has chosen to resume
+    /// from the halt at the current height.
+    async fn check_halted(&mut self) -> Result<bool> {
+        if !self.app.is_halted().await? {
+            return Ok(false);
+        }
+
+        let height = self.storage.latest_version().await?;
+        if height.is_some() && self.storage.get_resume_height().await? == height {
+            tracing::info!(?height, "resuming chain from halt");
+            self.app.resume_from_halt().await?;
+            return Ok(false);
+        }
+
+        tracing::error!(
+            ?height,
+            "chain is halted: to resume it, stop pd and run `pd resume` (or `pd migrate`, for an upgrade)"
+        );
+        Ok(true)
+    }
+
     async fn begin_block(
         &mut self,
         begin_block: abci::request::BeginBlock,
     ) -> Result<abci::response::BeginBlock> {
+        self.block = Some(indexer::Block::new(begin_block.header.height.value()));
+
         let ctx = Context::new();
         self.app.begin_block(ctx.clone(), &begin_block).await;
-        Ok(abci::response::BeginBlock {
-            events: ctx.into_events(),
-        })
+        let events = ctx.into_events();
+        if let Some(block) = self.block.as_mut() {
+            block.record_events(&events);
+        }
+
+        Ok(abci::response::BeginBlock { events })
     }
 
     /// Perform full transaction validation via `DeliverTx`.
@@ -207,18 +295,35 @@ impl Worker {
             "sending validator updates to tendermint"
         );
 
+        let events = ctx.into_events();
+        if let Some(block) = self.block.as_mut() {
+            block.record_events(&events);
+        }
+
         Ok(abci::response::EndBlock {
             validator_updates,
             consensus_param_updates: None,
-            events: ctx.into_events(),
+            events,
         })
     }
 
     async fn commit(&mut self) -> Result<abci::response::Commit> {
         // Begin sidecar code
 
+        let block = self.block.take();
+        let events: Option<BlockEvents> = block
+            .as_ref()
+            .map(|block| Arc::new(block.events().to_vec()));
+
+        // Index the block before committing it, so that if we crash in between, the block is
+        // executed and indexed again rather than committed without being indexed.
+        if let (Some(indexer), Some(block)) = (self.indexer.as_ref(), block) {
+            indexer.index_block(block).await?;
+        }
+
         // Note: App::commit resets internal components, so we don't need to do that ourselves.
-        let (jmt_root, _) = self.app.commit(self.storage.clone()).await?;
+        let (jmt_root, version) = self.app.commit(self.storage.clone()).await?;
+        self.version = Some(version);
         let app_hash = jmt_root.0.to_vec();
         let _ = self.height_tx.send(
             self.storage
@@ -231,6 +336,16 @@ impl Worker {
 
         tracing::info!(app_hash = ?hex::encode(&app_hash), "finished block commit");
 
+        // Publish the block's events only once it's committed.  The send only fails if there are
+        // no subscribers, in which case there's no one to publish to.
+        if let Some(events) = events {
+            let _ = self.events_tx.send(events);
+        }
+
+        if self.app.is_halted().await? {
+            tracing::error!("chain halted, no further blocks will be executed");
+        }
+
         Ok(abci::response::Commit {
             data: app_hash.into(),
             retain_height: 0u32.into(),
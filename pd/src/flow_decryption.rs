@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use penumbra_chain::View as _;
+use penumbra_component::{dex::View as _, shielded_pool::state_key};
+use penumbra_crypto::{
+    dex::TradingPair,
+    rdsa::{Binding, SigningKey},
+    FlowKeyShare, Fr, Zero,
+};
+use penumbra_proto::Protobuf;
+use penumbra_storage::{StateExt as _, Storage};
+use penumbra_tct as tct;
+use penumbra_transaction::{action::FlowDecryption, Action, Transaction, TransactionBody};
+use rand::Rng;
+use rand_core::OsRng;
+use tendermint::block;
+use tokio::sync::watch;
+use tracing::Instrument;
+
+/// Contribute decryption shares to a pending batch again if they haven't been recorded after this
+/// many blocks, in case the transaction was dropped.
+const RESUBMIT_INTERVAL: u64 = 10;
+
+/// Contributes this validator's decryption shares of the inputs of each pending batch swap, as
+/// blocks are committed.
+///
+/// The shares are submitted in a transaction with no fee and no value balance, which needs no
+/// authorization beyond the proofs in the decryption shares themselves, to the Tendermint node at
+/// `tendermint_url`.
+pub async fn contribute_flow_decryptions(
+    storage: Storage,
+    key_share: FlowKeyShare,
+    tendermint_url: String,
+    mut height_rx: watch::Receiver<block::Height>,
+) {
+    let client = reqwest::Client::new();
+    // The height at which shares were last submitted for each pending batch.
+    let mut submitted = BTreeMap::<(u64, TradingPair), u64>::new();
+
+    while height_rx.changed().await.is_ok() {
+        let height = height_rx.borrow().value();
+
+        let span = tracing::error_span!("contribute_flow_decryptions", %height);
+        async {
+            let state = storage.state().await?;
+            let committee = match state.flow_committee().await? {
+                Some(committee) => committee,
+                // Swaps aren't enabled, so there's nothing to decrypt.
+                None => return Ok(()),
+            };
+            let participant_index = key_share.participant_index();
+            if !committee.contains(participant_index) {
+                return Err(anyhow::anyhow!(
+                    "participant {} is not a member of the flow committee",
+                    participant_index
+                ));
+            }
+
+            let pending_batches = state.pending_batches().await?;
+            submitted.retain(|(batch_height, trading_pair), _| {
+                pending_batches.iter().any(|batch| {
+                    batch.height == *batch_height && batch.trading_pair == *trading_pair
+                })
+            });
+
+            let mut actions = Vec::new();
+            for batch in pending_batches {
+                let key = (batch.height, batch.trading_pair);
+                let due = submitted
+                    .get(&key)
+                    .map(|submitted_at| height >= submitted_at + RESUBMIT_INTERVAL)
+                    .unwrap_or(true);
+                if batch.has_contributed(participant_index) || !due {
+                    continue;
+                }
+
+                actions.push(Action::FlowDecryption(FlowDecryption {
+                    height: batch.height,
+                    trading_pair: batch.trading_pair,
+                    delta_1_share: key_share.decryption_share(&batch.enc_delta_1, &mut OsRng),
+                    delta_2_share: key_share.decryption_share(&batch.enc_delta_2, &mut OsRng),
+                }));
+                submitted.insert(key, height);
+            }
+            if actions.is_empty() {
+                return Ok(());
+            }
+
+            // The transaction needs a valid anchor, though it has no spends to prove against it.
+            let anchor_height = state.get_block_height().await?;
+            let anchor: tct::Root = state
+                .get_domain(state_key::anchor_by_height(anchor_height).into())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no NCT anchor at height {}", anchor_height))?;
+            let transaction_body = TransactionBody {
+                actions,
+                expiry_height: 0,
+                chain_id: state.get_chain_id().await?,
+                fee: Default::default(),
+                fmd_clues: vec![],
+            };
+            // The transaction has no value balance, so it's bound with a zero blinding factor.
+            let binding_sig = SigningKey::<Binding>::from(Fr::zero())
+                .sign(OsRng, transaction_body.auth_hash().as_ref());
+            let transaction = Transaction {
+                transaction_body,
+                binding_sig,
+                anchor,
+            };
+
+            let req_id: u8 = rand::thread_rng().gen();
+            let rsp: serde_json::Value = client
+                .post(&tendermint_url)
+                .json(&serde_json::json!(
+                    {
+                        "method": "broadcast_tx_sync",
+                        "params": [&transaction.encode_to_vec()],
+                        "id": req_id,
+                    }
+                ))
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            let result = rsp.get("result").unwrap_or(&rsp);
+            let code = result
+                .get("code")
+                .and_then(|c| c.as_i64())
+                .ok_or_else(|| anyhow::anyhow!("could not parse JSON response"))?;
+            if code != 0 {
+                return Err(anyhow::anyhow!(
+                    "transaction was rejected with code {}: {}",
+                    code,
+                    result
+                        .get("log")
+                        .and_then(|l| l.as_str())
+                        .unwrap_or_default()
+                ));
+            }
+
+            tracing::info!("submitted flow decryption shares");
+            Ok::<_, anyhow::Error>(())
+        }
+        .instrument(span)
+        .await
+        .unwrap_or_else(
+            |error| tracing::warn!(%height, %error, "failed to contribute flow decryption shares"),
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Indexing of committed transactions and events, so that clients can look them up.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use penumbra_component::EVENT_COMPONENT_KEY;
+use penumbra_proto::{
+    client::specific::{Event, EventAttribute, IndexedEvent, IndexedTransaction},
+    transaction as pbt, Message,
+};
+use rocksdb::{Direction, IteratorMode, Options, WriteBatch, DB};
+use sha2::{Digest, Sha256};
+use tendermint::abci;
+use tracing::Span;
+
+/// An index of the transactions and events committed to the chain.
+///
+/// Transactions are stored by hash in the "tx" column family, and their hashes by height and
+/// position in the block in the "tx_by_height" column family.  Every event, whether recorded by a
+/// transaction or while beginning or ending a block, is stored by height and position in the
+/// "events" column family.
+#[derive(Clone, Debug)]
+pub struct Indexer(Arc<DB>);
+
+impl Indexer {
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("open_index")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    tracing::info!(?path, "opening transaction index");
+                    let mut opts = Options::default();
+                    opts.create_if_missing(true);
+                    opts.create_missing_column_families(true);
+
+                    Ok(Self(Arc::new(DB::open_cf(
+                        &opts,
+                        path,
+                        ["tx", "tx_by_height", "events"],
+                    )?)))
+                })
+            })
+            .await
+            .unwrap()
+    }
+
+    /// Indexes the transactions and events of a block.
+    ///
+    /// Indexing a block again replaces all of its previous entries, so a block which is executed
+    /// again after a crash before it was committed is indexed consistently, even if it has fewer
+    /// transactions or events the second time.
+    pub async fn index_block(&self, block: Block) -> Result<()> {
+        let db = self.0.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("index_block")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    let tx_cf = db.cf_handle("tx").expect("tx column family not found");
+                    let tx_by_height_cf = db
+                        .cf_handle("tx_by_height")
+                        .expect("tx_by_height column family not found");
+                    let events_cf = db
+                        .cf_handle("events")
+                        .expect("events column family not found");
+
+                    // Clear any entries from a previous execution of the block first; the batch
+                    // applies its writes in order, so the new entries take their place.
+                    let mut batch = WriteBatch::default();
+                    let prefix = block.height.to_be_bytes();
+                    for (_, tx_hash) in db
+                        .iterator_cf(
+                            tx_by_height_cf,
+                            IteratorMode::From(&prefix, Direction::Forward),
+                        )
+                        .take_while(|(key, _)| key.starts_with(&prefix))
+                    {
+                        batch.delete_cf(tx_cf, tx_hash);
+                    }
+                    let (start, end) = (
+                        position_key(block.height, 0),
+                        position_key(block.height + 1, 0),
+                    );
+                    batch.delete_range_cf(tx_by_height_cf, &start, &end);
+                    batch.delete_range_cf(events_cf, &start, &end);
+
+                    for tx in block.transactions.iter() {
+                        batch.put_cf(
+                            tx_by_height_cf,
+                            position_key(tx.height, tx.index),
+                            &tx.tx_hash,
+                        );
+                        batch.put_cf(tx_cf, &tx.tx_hash, tx.encode_to_vec());
+                    }
+                    for (index, event) in block.events.iter().enumerate() {
+                        batch.put_cf(
+                            events_cf,
+                            position_key(block.height, index as u64),
+                            event.encode_to_vec(),
+                        );
+                    }
+                    db.write(batch)?;
+
+                    tracing::debug!(
+                        height = block.height,
+                        transactions = block.transactions.len(),
+                        events = block.events.len(),
+                        "indexed block"
+                    );
+                    Ok(())
+                })
+            })
+            .await?
+    }
+
+    /// Looks up a transaction by the hash of its encoding.
+    pub async fn transaction_by_hash(
+        &self,
+        tx_hash: Vec<u8>,
+    ) -> Result<Option<IndexedTransaction>> {
+        let db = self.0.clone();
+        tokio::task::Builder::new()
+            .name("transaction_by_hash")
+            .spawn_blocking(move || {
+                let tx_cf = db.cf_handle("tx").expect("tx column family not found");
+                db.get_pinned_cf(tx_cf, &tx_hash)?
+                    .map(|bytes| Ok(IndexedTransaction::decode(bytes.as_ref())?))
+                    .transpose()
+            })
+            .await?
+    }
+
+    /// Returns the transactions in the block at `height`, in order.
+    pub async fn transactions_by_height(&self, height: u64) -> Result<Vec<IndexedTransaction>> {
+        let db = self.0.clone();
+        tokio::task::Builder::new()
+            .name("transactions_by_height")
+            .spawn_blocking(move || {
+                let tx_cf = db.cf_handle("tx").expect("tx column family not found");
+                let tx_by_height_cf = db
+                    .cf_handle("tx_by_height")
+                    .expect("tx_by_height column family not found");
+
+                let prefix = height.to_be_bytes();
+                db.iterator_cf(
+                    tx_by_height_cf,
+                    IteratorMode::From(&prefix, Direction::Forward),
+                )
+                .take_while(|(key, _)| key.starts_with(&prefix))
+                .map(|(_, tx_hash)| {
+                    let bytes = db
+                        .get_pinned_cf(tx_cf, &tx_hash)?
+                        .ok_or_else(|| anyhow::anyhow!("missing indexed transaction"))?;
+                    Ok(IndexedTransaction::decode(bytes.as_ref())?)
+                })
+                .collect()
+            })
+            .await?
+    }
+
+    /// Returns the events recorded in the block at `height` which match `filter`, in order.
+    pub async fn events(&self, height: u64, filter: EventFilter) -> Result<Vec<IndexedEvent>> {
+        let db = self.0.clone();
+        tokio::task::Builder::new()
+            .name("events")
+            .spawn_blocking(move || {
+                let events_cf = db
+                    .cf_handle("events")
+                    .expect("events column family not found");
+
+                let prefix = height.to_be_bytes();
+                let mut events = Vec::new();
+                for (key, value) in
+                    db.iterator_cf(events_cf, IteratorMode::From(&prefix, Direction::Forward))
+                {
+                    if !key.starts_with(&prefix) {
+                        break;
+                    }
+                    let event = IndexedEvent::decode(value.as_ref())?;
+                    if event
+                        .event
+                        .as_ref()
+                        .map(|event| filter.matches(event))
+                        .unwrap_or(false)
+                    {
+                        events.push(event);
+                    }
+                }
+                Ok(events)
+            })
+            .await?
+    }
+}
+
+/// The events recorded in a committed block, as published to subscribers.
+pub type BlockEvents = Arc<Vec<IndexedEvent>>;
+
+/// The transactions and events of a block, accumulated as it's executed so that they can be
+/// indexed and published once it's committed.
+#[derive(Debug)]
+pub struct Block {
+    height: u64,
+    transactions: Vec<IndexedTransaction>,
+    events: Vec<IndexedEvent>,
+}
+
+impl Block {
+    pub fn new(height: u64) -> Self {
+        Self {
+            height,
+            transactions: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// The events recorded in the block so far, in order.
+    pub fn events(&self) -> &[IndexedEvent] {
+        &self.events
+    }
+
+    /// Records the events of beginning or ending the block.
+    pub fn record_events(&mut self, events: &[abci::Event]) {
+        for event in events {
+            self.events.push(IndexedEvent {
+                height: self.height,
+                tx_hash: Vec::new(),
+                event: Some(event_to_proto(event)),
+            });
+        }
+    }
+
+    /// Records a transaction delivered in the block, with the result of executing it.
+    pub fn record_transaction(&mut self, tx: &[u8], rsp: &abci::response::DeliverTx) {
+        let tx_hash = Sha256::digest(tx).to_vec();
+        let events = rsp.events.iter().map(event_to_proto).collect::<Vec<_>>();
+
+        for event in events.iter() {
+            self.events.push(IndexedEvent {
+                height: self.height,
+                tx_hash: tx_hash.clone(),
+                event: Some(event.clone()),
+            });
+        }
+        self.transactions.push(IndexedTransaction {
+            tx_hash,
+            height: self.height,
+            index: self.transactions.len() as u64,
+            // A transaction which can't be decoded is still indexed, since it was included in the
+            // block, but only with the result of trying to execute it.
+            transaction: pbt::Transaction::decode(tx).ok(),
+            code: rsp.code,
+            log: rsp.log.clone(),
+            events,
+        });
+    }
+}
+
+/// Selects events by the component which recorded them, type, and attributes.  Unset fields match
+/// any event.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    pub component: Option<String>,
+    pub kind: Option<String>,
+    pub attribute_key: Option<String>,
+    pub attribute_value: Option<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        if let Some(component) = &self.component {
+            if !event.attributes.iter().any(|attribute| {
+                attribute.key == EVENT_COMPONENT_KEY && &attribute.value == component
+            }) {
+                return false;
+            }
+        }
+        if let Some(kind) = &self.kind {
+            if &event.r#type != kind {
+                return false;
+            }
+        }
+        if self.attribute_key.is_none() && self.attribute_value.is_none() {
+            return true;
+        }
+        event.attributes.iter().any(|attribute| {
+            self.attribute_key
+                .as_ref()
+                .map(|key| &attribute.key == key)
+                .unwrap_or(true)
+                && self
+                    .attribute_value
+                    .as_ref()
+                    .map(|value| &attribute.value == value)
+                    .unwrap_or(true)
+        })
+    }
+}
+
+fn event_to_proto(event: &abci::Event) -> Event {
+    Event {
+        r#type: event.kind.clone(),
+        attributes: event
+            .attributes
+            .iter()
+            .map(|attribute| EventAttribute {
+                key: attribute.key.clone(),
+                value: attribute.value.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Entries are keyed by the big-endian height and position within the block, so that they're
+/// ordered by them.
+fn position_key(height: u64, index: u64) -> Vec<u8> {
+    let mut key = height.to_be_bytes().to_vec();
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
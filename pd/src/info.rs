@@ -10,11 +10,11 @@ use tendermint::{
     abci::{self, response::Echo, InfoRequest, InfoResponse},
     block,
 };
-use tokio::sync::watch;
+use tokio::sync::{broadcast, watch};
 use tower_abci::BoxError;
 use tracing::Instrument;
 
-use crate::RequestExt;
+use crate::{BlockEvents, Indexer, RequestExt};
 
 mod oblivious;
 mod specific;
@@ -25,17 +25,47 @@ const ABCI_INFO_VERSION: &str = env!("VERGEN_GIT_SEMVER");
 pub struct Info {
     storage: Storage,
     height_rx: watch::Receiver<block::Height>,
+    events_tx: broadcast::Sender<BlockEvents>,
+    indexer: Option<Indexer>,
 }
 
 impl Info {
-    pub fn new(storage: Storage, height_rx: watch::Receiver<block::Height>) -> Self {
-        Self { storage, height_rx }
+    pub fn new(
+        storage: Storage,
+        height_rx: watch::Receiver<block::Height>,
+        events_tx: broadcast::Sender<BlockEvents>,
+        indexer: Option<Indexer>,
+    ) -> Self {
+        Self {
+            storage,
+            height_rx,
+            events_tx,
+            indexer,
+        }
+    }
+
+    fn indexer_tonic(&self) -> Result<&Indexer, tonic::Status> {
+        self.indexer
+            .as_ref()
+            .ok_or_else(|| tonic::Status::unimplemented("this node does not index transactions"))
     }
 
     async fn state_tonic(&self) -> Result<State, tonic::Status> {
         self.storage.state_tonic().await
     }
 
+    /// Returns the state at the given height, or at the latest height if it is zero, as in
+    /// requests where the height is left unset.
+    async fn state_at_tonic(&self, height: u64) -> Result<State, tonic::Status> {
+        if height == 0 {
+            return self.state_tonic().await;
+        }
+        self.storage
+            .state_at(height)
+            .await
+            .map_err(|e| tonic::Status::not_found(e.to_string()))
+    }
+
     async fn info(&self, info: abci::request::Info) -> Result<abci::response::Info, anyhow::Error> {
         tracing::info!(?info);
 
@@ -65,7 +95,11 @@ impl Info {
 
         match query.path.as_str() {
             "state/key" => {
-                let height: u64 = query.height.into();
+                // A height of zero means the latest height.
+                let height = match u64::from(query.height) {
+                    0 => self.storage.latest_version().await?.unwrap_or(0),
+                    height => height,
+                };
                 let key = hex::decode(&query.data).unwrap_or_else(|_| query.data.to_vec());
                 let store = jmt::JellyfishMerkleTree::new(&self.storage);
 
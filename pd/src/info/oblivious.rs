@@ -12,7 +12,7 @@ use penumbra_proto::{
     chain::{ChainParameters, CompactBlock, KnownAssets},
     client::oblivious::{
         oblivious_query_server::ObliviousQuery, AssetListRequest, ChainParamsRequest,
-        CompactBlockRangeRequest, ValidatorInfoRequest,
+        CompactBlockRangeRequest, HaltStatusRequest, HaltStatusResponse, ValidatorInfoRequest,
     },
     stake::ValidatorInfo,
     Protobuf,
@@ -72,6 +72,25 @@ impl ObliviousQuery for Info {
         Ok(tonic::Response::new(chain_params.into()))
     }
 
+    #[instrument(skip(self, request))]
+    async fn halt_status(
+        &self,
+        request: tonic::Request<HaltStatusRequest>,
+    ) -> Result<tonic::Response<HaltStatusResponse>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let halted = state
+            .is_chain_halted()
+            .await
+            .map_err(|e| tonic::Status::unavailable(format!("error getting halt status: {}", e)))?;
+        let height = state.get_block_height().await.map_err(|e| {
+            tonic::Status::unavailable(format!("error getting block height: {}", e))
+        })?;
+
+        Ok(tonic::Response::new(HaltStatusResponse { halted, height }))
+    }
+
     #[instrument(skip(self, request))]
     async fn asset_list(
         &self,
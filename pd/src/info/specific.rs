@@ -1,16 +1,29 @@
+use std::pin::Pin;
+
+use async_stream::try_stream;
+use futures::stream::{StreamExt, TryStreamExt};
 use penumbra_chain::View as _;
+use penumbra_component::dex::{Book, View as _};
+use penumbra_component::governance::{proposal, tally, View as _};
 use penumbra_component::shielded_pool::View as _;
 use penumbra_component::stake::View as _;
+use penumbra_crypto::{asset, dex::TradingPair, Value};
 use penumbra_proto::{
     self as proto,
     chain::NoteSource,
     client::specific::{
-        specific_query_server::SpecificQuery, KeyValueRequest, KeyValueResponse,
-        ValidatorStatusRequest,
+        proposal_list_request::StateFilter, specific_query_server::SpecificQuery,
+        BatchSwapOutputDataRequest, EventsRequest, IndexedEvent, IndexedTransaction,
+        KeyValueRequest, KeyValueResponse, OpenPositionsRequest, PositionByIdRequest, ProposalInfo,
+        ProposalListRequest, ProposalTallyRequest, ProposalTallyResponse, SimulateSwapRequest,
+        SimulateSwapResponse, SubscribeEventsRequest, TransactionByHashRequest,
+        TransactionsByHeightRequest, ValidatorStatusRequest,
     },
     crypto::NoteCommitment,
+    dex::PositionMetadata,
 };
 
+use tokio::sync::broadcast::error::RecvError;
 use tonic::Status;
 use tracing::instrument;
 
@@ -21,9 +34,21 @@ use tracing::instrument;
 //use tracing_futures::Instrument;
 
 use super::Info;
+use crate::indexer::EventFilter;
 
 #[tonic::async_trait]
 impl SpecificQuery for Info {
+    type TransactionsByHeightStream =
+        Pin<Box<dyn futures::Stream<Item = Result<IndexedTransaction, tonic::Status>> + Send>>;
+    type EventsStream =
+        Pin<Box<dyn futures::Stream<Item = Result<IndexedEvent, tonic::Status>> + Send>>;
+    type SubscribeEventsStream =
+        Pin<Box<dyn futures::Stream<Item = Result<IndexedEvent, tonic::Status>> + Send>>;
+    type OpenPositionsStream =
+        Pin<Box<dyn futures::Stream<Item = Result<PositionMetadata, tonic::Status>> + Send>>;
+    type ProposalListStream =
+        Pin<Box<dyn futures::Stream<Item = Result<ProposalInfo, tonic::Status>> + Send>>;
+
     #[instrument(skip(self, request))]
     async fn transaction_by_note(
         &self,
@@ -44,12 +69,129 @@ impl SpecificQuery for Info {
         Ok(tonic::Response::new(source.into()))
     }
 
+    #[instrument(skip(self, request))]
+    async fn transaction_by_hash(
+        &self,
+        request: tonic::Request<TransactionByHashRequest>,
+    ) -> Result<tonic::Response<IndexedTransaction>, Status> {
+        let indexer = self.indexer_tonic()?;
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let tx = indexer
+            .transaction_by_hash(request.into_inner().tx_hash)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting transaction: {}", e)))?
+            .ok_or_else(|| Status::not_found("transaction not found"))?;
+
+        Ok(tonic::Response::new(tx))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn transactions_by_height(
+        &self,
+        request: tonic::Request<TransactionsByHeightRequest>,
+    ) -> Result<tonic::Response<Self::TransactionsByHeightStream>, Status> {
+        let indexer = self.indexer_tonic()?;
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let txs = indexer
+            .transactions_by_height(request.into_inner().height)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting transactions: {}", e)))?;
+
+        Ok(tonic::Response::new(
+            futures::stream::iter(txs.into_iter().map(Ok)).boxed(),
+        ))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn events(
+        &self,
+        request: tonic::Request<EventsRequest>,
+    ) -> Result<tonic::Response<Self::EventsStream>, Status> {
+        let indexer = self.indexer_tonic()?.clone();
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let request = request.into_inner();
+        let end_height = match request.end_height {
+            0 => state
+                .get_block_height()
+                .await
+                .map_err(|e| Status::unavailable(format!("error getting block height: {}", e)))?,
+            end_height => end_height,
+        };
+        let filter = EventFilter {
+            component: non_empty(request.component),
+            kind: non_empty(request.r#type),
+            attribute_key: non_empty(request.attribute_key),
+            attribute_value: non_empty(request.attribute_value),
+        };
+
+        let s = try_stream! {
+            for height in request.start_height..=end_height {
+                for event in indexer.events(height, filter.clone()).await? {
+                    yield event;
+                }
+            }
+        };
+
+        Ok(tonic::Response::new(
+            s.map_err(|e: anyhow::Error| {
+                tonic::Status::unavailable(format!("error getting events: {}", e))
+            })
+            .boxed(),
+        ))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn subscribe_events(
+        &self,
+        request: tonic::Request<SubscribeEventsRequest>,
+    ) -> Result<tonic::Response<Self::SubscribeEventsStream>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let request = request.into_inner();
+        let filter = EventFilter {
+            component: non_empty(request.component),
+            kind: non_empty(request.r#type),
+            ..Default::default()
+        };
+
+        let mut events_rx = self.events_tx.subscribe();
+        let s = try_stream! {
+            loop {
+                let events = match events_rx.recv().await {
+                    Ok(events) => events,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(blocks)) => Err(anyhow::anyhow!(
+                        "subscriber fell behind, missing the events of {} blocks",
+                        blocks
+                    ))?,
+                };
+                for event in events.iter() {
+                    if event.event.as_ref().map(|e| filter.matches(e)).unwrap_or(false) {
+                        yield event.clone();
+                    }
+                }
+            }
+        };
+
+        Ok(tonic::Response::new(
+            s.map_err(|e: anyhow::Error| tonic::Status::aborted(e.to_string()))
+                .boxed(),
+        ))
+    }
+
     #[instrument(skip(self, request))]
     async fn validator_status(
         &self,
         request: tonic::Request<ValidatorStatusRequest>,
     ) -> Result<tonic::Response<proto::stake::ValidatorStatus>, Status> {
-        let state = self.state_tonic().await?;
+        let state = self.state_at_tonic(request.get_ref().height).await?;
         state.check_chain_id(&request.get_ref().chain_id).await?;
 
         let id = request
@@ -90,12 +232,239 @@ impl SpecificQuery for Info {
         }
     }
 
+    #[instrument(skip(self, request))]
+    async fn batch_swap_output_data(
+        &self,
+        request: tonic::Request<BatchSwapOutputDataRequest>,
+    ) -> Result<tonic::Response<proto::dex::BatchSwapOutputData>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let request = request.into_inner();
+        let trading_pair: TradingPair = request
+            .trading_pair
+            .ok_or_else(|| Status::invalid_argument("missing trading pair"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid trading pair"))?;
+
+        let output_data = state
+            .output_data(request.height, trading_pair)
+            .await
+            .map_err(|e| {
+                Status::unavailable(format!("error getting batch swap output data: {}", e))
+            })?
+            .ok_or_else(|| Status::not_found("batch swap output data not found"))?;
+
+        Ok(tonic::Response::new(output_data.into()))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn position_by_id(
+        &self,
+        request: tonic::Request<PositionByIdRequest>,
+    ) -> Result<tonic::Response<PositionMetadata>, Status> {
+        let state = self.state_at_tonic(request.get_ref().height).await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let id = request
+            .into_inner()
+            .position_id
+            .ok_or_else(|| Status::invalid_argument("missing position id"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid position id"))?;
+
+        let metadata = state
+            .position_by_id(&id)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting position: {}", e)))?
+            .ok_or_else(|| Status::not_found("position not found"))?;
+
+        Ok(tonic::Response::new(metadata.into()))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn open_positions(
+        &self,
+        request: tonic::Request<OpenPositionsRequest>,
+    ) -> Result<tonic::Response<Self::OpenPositionsStream>, Status> {
+        let state = self.state_at_tonic(request.get_ref().height).await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let trading_pair: TradingPair = request
+            .into_inner()
+            .trading_pair
+            .ok_or_else(|| Status::invalid_argument("missing trading pair"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid trading pair"))?;
+
+        let ids = state
+            .open_positions(trading_pair)
+            .await
+            .map_err(|e| Status::unavailable(format!("error listing open positions: {}", e)))?;
+
+        let s = try_stream! {
+            for id in ids {
+                let metadata = state.position_by_id(&id)
+                    .await
+                    .map_err(|e| Status::unavailable(format!("error getting position: {}", e)))?
+                    .ok_or_else(|| Status::internal(format!("open position {} is missing", id)))?;
+                yield PositionMetadata::from(metadata);
+            }
+        };
+
+        Ok(tonic::Response::new(s.map_err(|e: Status| e).boxed()))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn simulate_swap(
+        &self,
+        request: tonic::Request<SimulateSwapRequest>,
+    ) -> Result<tonic::Response<SimulateSwapResponse>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let request = request.into_inner();
+        let input: Value = request
+            .input
+            .ok_or_else(|| Status::invalid_argument("missing input"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid input"))?;
+        let output_id: asset::Id = request
+            .output_id
+            .ok_or_else(|| Status::invalid_argument("missing output asset id"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid output asset id"))?;
+
+        let trading_pair = TradingPair::canonical_order_for((input.asset_id, output_id))
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let mut book = Book::load(&state, trading_pair)
+            .await
+            .map_err(|e| Status::unavailable(format!("error loading positions: {}", e)))?;
+        let amount = book
+            .route(input.asset_id, output_id, input.amount)
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+
+        Ok(tonic::Response::new(SimulateSwapResponse {
+            output: Some(
+                Value {
+                    amount,
+                    asset_id: output_id,
+                }
+                .into(),
+            ),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn proposal_list(
+        &self,
+        request: tonic::Request<ProposalListRequest>,
+    ) -> Result<tonic::Response<Self::ProposalListStream>, Status> {
+        let state = self.state_at_tonic(request.get_ref().height).await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let filter = request.into_inner().state();
+        let next_proposal_id = state
+            .next_proposal_id()
+            .await
+            .map_err(|e| Status::unavailable(format!("error listing proposals: {}", e)))?;
+
+        let s = try_stream! {
+            for proposal_id in 0..next_proposal_id {
+                let proposal_state = state
+                    .proposal_state(proposal_id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("missing state for proposal {}", proposal_id))?;
+
+                let included = match (filter, &proposal_state) {
+                    (StateFilter::All, _) => true,
+                    (StateFilter::Voting, proposal::State::Voting) => true,
+                    (StateFilter::Withdrawn, proposal::State::Withdrawn { .. }) => true,
+                    (StateFilter::Finished, proposal::State::Finished { .. }) => true,
+                    _ => false,
+                };
+                if !included {
+                    continue;
+                }
+
+                let proposal = state
+                    .proposal(proposal_id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("missing proposal {}", proposal_id))?;
+                let voting_start_block = state
+                    .proposal_voting_start(proposal_id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("missing voting start for proposal {}", proposal_id))?;
+                let voting_end_block = state
+                    .proposal_voting_end(proposal_id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("missing voting end for proposal {}", proposal_id))?;
+
+                yield ProposalInfo {
+                    proposal_id,
+                    proposal: Some(proposal.into()),
+                    state: Some(proposal_state.into()),
+                    voting_start_block,
+                    voting_end_block,
+                };
+            }
+        };
+
+        Ok(tonic::Response::new(
+            s.map_err(|e: anyhow::Error| {
+                tonic::Status::unavailable(format!("error getting proposal: {}", e))
+            })
+            .boxed(),
+        ))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn proposal_tally(
+        &self,
+        request: tonic::Request<ProposalTallyRequest>,
+    ) -> Result<tonic::Response<ProposalTallyResponse>, Status> {
+        let state = self.state_at_tonic(request.get_ref().height).await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let proposal_id = request.into_inner().proposal_id;
+        let voting_end = state
+            .proposal_voting_end(proposal_id)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting proposal: {}", e)))?
+            .ok_or_else(|| Status::not_found("proposal not found"))?;
+
+        let tally = tally::votes(&state, proposal_id)
+            .await
+            .map_err(|e| Status::unavailable(format!("error tallying proposal: {}", e)))?;
+        let total_voting_power = state
+            .total_voting_power()
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting voting power: {}", e)))?;
+        let valid_quorum = state
+            .get_chain_params()
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting chain parameters: {}", e)))?
+            .proposal_valid_quorum;
+        let height = state
+            .get_block_height()
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting block height: {}", e)))?;
+
+        Ok(tonic::Response::new(ProposalTallyResponse {
+            tally: Some(tally.into()),
+            total_voting_power,
+            valid_quorum: Some(valid_quorum.into()),
+            blocks_remaining: voting_end.saturating_sub(height),
+        }))
+    }
+
     #[instrument(skip(self, request))]
     async fn key_value(
         &self,
         request: tonic::Request<KeyValueRequest>,
     ) -> Result<tonic::Response<KeyValueResponse>, Status> {
-        let state = self.state_tonic().await?;
+        let state = self.state_at_tonic(request.get_ref().height).await?;
         state.check_chain_id(&request.get_ref().chain_id).await?;
 
         let request = request.into_inner();
@@ -110,7 +479,8 @@ impl SpecificQuery for Info {
             .await
             .get_with_proof(request.key)
             .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?;
+            .map_err(|e| tonic::Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("key not found"))?;
 
         let commitment_proof = ics23::CommitmentProof {
             proof: Some(ics23::commitment_proof::Proof::Exist(proof)),
@@ -126,3 +496,12 @@ impl SpecificQuery for Info {
         }))
     }
 }
+
+/// Interprets an unset (empty) string field in a request as `None`.
+fn non_empty(field: String) -> Option<String> {
+    if field.is_empty() {
+        None
+    } else {
+        Some(field)
+    }
+}
@@ -5,9 +5,12 @@
 #![allow(clippy::clone_on_copy)]
 
 mod consensus;
+mod flow_decryption;
+mod indexer;
 mod info;
 mod mempool;
 mod metrics;
+mod pruning;
 mod request_ext;
 mod snapshot;
 
@@ -17,7 +20,10 @@ use request_ext::RequestExt;
 
 pub use crate::metrics::register_metrics;
 pub use consensus::Consensus;
+pub use flow_decryption::contribute_flow_decryptions;
+pub use indexer::{BlockEvents, Indexer};
 pub use info::Info;
 pub use mempool::Mempool;
 pub use penumbra_component::app::App;
+pub use pruning::prune_state;
 pub use snapshot::Snapshot;
@@ -13,9 +13,13 @@ use anyhow::Context;
 use clap::{Parser, Subcommand};
 use metrics_exporter_prometheus::PrometheusBuilder;
 use pd::testnet::{canonicalize_path, generate_tm_config, write_configs, ValidatorKeys};
-use penumbra_chain::{genesis::Allocation, params::ChainParameters};
-use penumbra_component::stake::{validator::Validator, FundingStream, FundingStreams};
-use penumbra_crypto::{keys::SpendKey, DelegationToken, GovernanceKey};
+use penumbra_chain::{genesis::Allocation, params::ChainParameters, View as _};
+use penumbra_component::{
+    app::{export, migration},
+    governance::View as _,
+    stake::{validator::Validator, FundingStream, FundingStreams},
+};
+use penumbra_crypto::{keys::SpendKey, DelegationToken, FlowKeyShare, GovernanceKey};
 use penumbra_proto::client::{
     oblivious::oblivious_query_server::ObliviousQueryServer,
     specific::specific_query_server::SpecificQueryServer,
@@ -61,6 +65,62 @@ enum RootCommand {
         /// Bind the metrics endpoint to this port.
         #[clap(short, long, default_value = "9000")]
         metrics_port: u16,
+        /// Take a state sync snapshot every this many blocks, or never if zero.
+        #[clap(long, default_value = "1000")]
+        snapshot_interval: u64,
+        /// Keep this many of the most recent versions of the state, deleting older versions other
+        /// than those of stored snapshots, or keep every version if zero.
+        #[clap(long, default_value = "0")]
+        keep_recent_versions: u64,
+        /// Index committed transactions and events, so that they can be queried by hash, height,
+        /// and type.
+        #[clap(long)]
+        index_transactions: bool,
+        /// The path to this validator's share of the flow decryption key, if it's a member of the
+        /// flow committee, in which case it contributes decryption shares of each batch swap
+        /// [default: `<home>/flow_key_share.json`, if it exists].
+        #[clap(long, parse(from_os_str))]
+        flow_key_share: Option<PathBuf>,
+        /// Submit the validator's flow decryption shares to the Tendermint RPC at this URL.
+        #[clap(long, default_value = "http://127.0.0.1:26657")]
+        tendermint_url: String,
+    },
+
+    /// Resume the chain after it was halted by an emergency proposal.
+    ///
+    /// This must be run while `pd` is stopped. Once every validator has done so and restarted `pd`,
+    /// the chain will continue with the next block.
+    Resume {
+        /// The path used to store pd-releated data, including the Rocks database.
+        #[clap(long)]
+        home: PathBuf,
+    },
+
+    /// Migrate the chain state after it was halted for an upgrade, so that it can resume.
+    ///
+    /// This must be run with the upgraded `pd` binary while `pd` is stopped. It checks that the
+    /// migration named by the upgrade is known to this binary; the migration then runs as the chain
+    /// resumes, once every validator has done so and restarted `pd`.
+    Migrate {
+        /// The path used to store pd-releated data, including the Rocks database.
+        #[clap(long)]
+        home: PathBuf,
+    },
+
+    /// Export the chain state at a committed height as the genesis app state of a new chain.
+    ///
+    /// Balances held in notes are shielded, so they can't be exported: the exported app state has
+    /// no allocations, which must be added before it's used.
+    Export {
+        /// The path used to store pd-releated data, including the Rocks database.
+        #[clap(long)]
+        home: PathBuf,
+        /// The height to export the state at [default: the latest height].
+        #[clap(long)]
+        height: Option<u64>,
+        /// The file to write the exported app state to, as JSON [default: stdout].
+        #[clap(long, parse(from_os_str))]
+        output: Option<PathBuf>,
     },
 
     /// Generate, join, or reset a testnet.
@@ -163,6 +223,11 @@ async fn main() -> anyhow::Result<()> {
             abci_port,
             grpc_port,
             metrics_port,
+            snapshot_interval,
+            keep_recent_versions,
+            index_transactions,
+            flow_key_share,
+            tendermint_url,
         } => {
             tracing::info!(?host, ?abci_port, ?grpc_port, "starting pd");
 
@@ -173,10 +238,55 @@ async fn main() -> anyhow::Result<()> {
                 .await
                 .context("Unable to initialize RocksDB storage")?;
 
-            let (consensus, height_rx) = pd::Consensus::new(storage.clone()).await?;
+            let indexer = if index_transactions {
+                Some(
+                    pd::Indexer::load(home.join("index"))
+                        .await
+                        .context("Unable to initialize transaction index")?,
+                )
+            } else {
+                None
+            };
+
+            let (consensus, height_rx, events_tx) =
+                pd::Consensus::new(storage.clone(), indexer.clone()).await?;
             let mempool = pd::Mempool::new(storage.clone(), height_rx.clone()).await?;
-            let info = pd::Info::new(storage.clone(), height_rx);
-            let snapshot = pd::Snapshot {};
+            let snapshot = pd::Snapshot::new(
+                storage.clone(),
+                home.join("snapshots"),
+                snapshot_interval,
+                height_rx.clone(),
+            );
+            if keep_recent_versions > 0 {
+                tokio::task::Builder::new()
+                    .name("prune_state")
+                    .spawn(pd::prune_state(
+                        storage.clone(),
+                        keep_recent_versions,
+                        home.join("snapshots"),
+                        height_rx.clone(),
+                    ));
+            }
+            let flow_key_share = flow_key_share.or_else(|| {
+                Some(home.join(pd::testnet::FLOW_KEY_SHARE_FILE)).filter(|path| path.exists())
+            });
+            if let Some(path) = flow_key_share {
+                let key_share: FlowKeyShare = serde_json::from_reader(
+                    std::fs::File::open(&path)
+                        .with_context(|| format!("cannot open flow key share {:?}", path))?,
+                )
+                .with_context(|| format!("could not parse flow key share {:?}", path))?;
+                tracing::info!(?key_share, "contributing flow decryption shares");
+                tokio::task::Builder::new()
+                    .name("contribute_flow_decryptions")
+                    .spawn(pd::contribute_flow_decryptions(
+                        storage.clone(),
+                        key_share,
+                        tendermint_url,
+                        height_rx.clone(),
+                    ));
+            }
+            let info = pd::Info::new(storage.clone(), height_rx, events_tx, indexer);
 
             let abci_server = tokio::task::Builder::new().name("abci_server").spawn(
                 tower_abci::Server::builder()
@@ -237,6 +347,110 @@ async fn main() -> anyhow::Result<()> {
             };
         }
 
+        RootCommand::Resume { home } => {
+            let mut rocks_path = home.clone();
+            rocks_path.push("rocksdb");
+
+            let storage = Storage::load(rocks_path)
+                .await
+                .context("Unable to initialize RocksDB storage")?;
+
+            let state = storage.state().await?;
+            if !state.is_chain_halted().await? {
+                return Err(anyhow::anyhow!("chain is not halted, so cannot be resumed"));
+            }
+            if let Some(upgrade) = state.upgrade_plan(state.get_block_height().await?).await? {
+                return Err(anyhow::anyhow!(
+                    "chain is halted for an upgrade, which must be resumed with `pd migrate` using the upgraded binary (migration {:?})",
+                    upgrade.migration_id
+                ));
+            }
+
+            let height = storage
+                .latest_version()
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no blocks have been committed"))?;
+            storage.put_resume_height(height).await?;
+
+            tracing::info!(?height, "chain will resume from halt once pd is restarted");
+        }
+
+        RootCommand::Migrate { home } => {
+            let mut rocks_path = home.clone();
+            rocks_path.push("rocksdb");
+
+            let storage = Storage::load(rocks_path)
+                .await
+                .context("Unable to initialize RocksDB storage")?;
+
+            let state = storage.state().await?;
+            if !state.is_chain_halted().await? {
+                return Err(anyhow::anyhow!(
+                    "chain is not halted, so cannot be migrated"
+                ));
+            }
+            let upgrade = state
+                .upgrade_plan(state.get_block_height().await?)
+                .await?
+                .ok_or_else(|| {
+                    anyhow::anyhow!("chain is not halted for an upgrade, so cannot be migrated")
+                })?;
+            if !migration::is_registered(&upgrade.migration_id) {
+                return Err(anyhow::anyhow!(
+                    "migration {:?} is unknown to this version of pd: upgrade pd before migrating",
+                    upgrade.migration_id
+                ));
+            }
+
+            // Run the migration against an ephemeral copy of the state, which is never committed,
+            // to catch any failure now rather than when the chain resumes
+            migration::run(&upgrade.migration_id, &storage.state().await?)
+                .await
+                .context("state migration failed")?;
+
+            let height = storage
+                .latest_version()
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no blocks have been committed"))?;
+            storage.put_resume_height(height).await?;
+
+            tracing::info!(
+                ?height,
+                migration_id = %upgrade.migration_id,
+                "chain will be migrated and resume from halt once pd is restarted"
+            );
+        }
+
+        RootCommand::Export {
+            home,
+            height,
+            output,
+        } => {
+            let mut rocks_path = home.clone();
+            rocks_path.push("rocksdb");
+
+            let storage = Storage::load(rocks_path)
+                .await
+                .context("Unable to initialize RocksDB storage")?;
+
+            let state = match height {
+                Some(height) => storage.state_at(height).await?,
+                None => storage.state().await?,
+            };
+            let app_state = export::app_state(&state)
+                .await
+                .context("failed to export state")?;
+            let json = serde_json::to_string_pretty(&app_state)?;
+
+            match output {
+                Some(output) => {
+                    std::fs::write(&output, json)?;
+                    tracing::info!(?output, "exported app state");
+                }
+                None => println!("{}", json),
+            }
+        }
+
         RootCommand::Testnet {
             tn_cmd: TestnetCommand::UnsafeResetAll {},
             testnet_dir,
@@ -306,7 +520,7 @@ async fn main() -> anyhow::Result<()> {
             let node_name = format!("node-{}", hex::encode(OsRng.gen::<u32>().to_le_bytes()));
             let tm_config = generate_tm_config(&node_name, &[(node_id, node)]);
 
-            write_configs(node_dir, &vk, &genesis, tm_config)?;
+            write_configs(node_dir, &vk, &genesis, tm_config, None)?;
         }
 
         RootCommand::Testnet {
@@ -349,7 +563,7 @@ async fn main() -> anyhow::Result<()> {
 
             use pd::testnet::*;
             use penumbra_chain::genesis;
-            use penumbra_crypto::{Address, IdentityKey};
+            use penumbra_crypto::{Address, FlowCommittee, FlowEncryptionKey, IdentityKey};
             use tendermint::{node, public_key::Algorithm, Genesis, Time};
 
             let genesis_time = Time::from_unix_timestamp(
@@ -498,6 +712,25 @@ async fn main() -> anyhow::Result<()> {
                 })
                 .collect::<Result<Vec<Validator>, anyhow::Error>>()?;
 
+            // Deal the flow decryption key shares, one per validator, such that any two thirds of
+            // the validators can decrypt. Each share is written to its validator's node directory,
+            // and only the committee of public key shares is included in the genesis.
+            //
+            // This uses a trusted dealer, which knows the whole decryption key while it deals
+            // the shares, so it's only suitable for testnets generated by a single party. A
+            // production network must run a DKG among its validators instead.
+            let participants = validators.len() as u32;
+            let threshold = 2 * participants / 3 + 1;
+            let (flow_encryption_key, flow_key_shares) =
+                penumbra_eddy::dkg::deal(threshold, participants, OsRng)?;
+            let flow_committee = FlowCommittee(penumbra_eddy::dkg::Committee {
+                shares: flow_key_shares
+                    .iter()
+                    .map(|share| *share.public_key_share())
+                    .collect(),
+                threshold,
+            });
+
             let app_state = genesis::AppState {
                 allocations: allocations.clone(),
                 chain_params: ChainParameters {
@@ -508,6 +741,9 @@ async fn main() -> anyhow::Result<()> {
                     ..Default::default()
                 },
                 validators: validators.into_iter().map(Into::into).collect(),
+                flow_encryption_key: Some(FlowEncryptionKey(flow_encryption_key)),
+                flow_committee: Some(flow_committee),
+                ..Default::default()
             };
 
             // Create the genesis data shared by all nodes
@@ -548,7 +784,11 @@ async fn main() -> anyhow::Result<()> {
                 validators: vec![],
             };
 
-            for (n, vk) in validator_keys.iter().enumerate() {
+            for (n, (vk, flow_key_share)) in validator_keys
+                .iter()
+                .zip(flow_key_shares.into_iter().map(FlowKeyShare))
+                .enumerate()
+            {
                 let node_name = format!("node{}", n);
 
                 // Create the directory for this node
@@ -575,7 +815,13 @@ async fn main() -> anyhow::Result<()> {
                     .collect::<Vec<_>>();
                 let tm_config = generate_tm_config(&node_name, &ips_minus_mine);
 
-                write_configs(node_dir, vk, &validator_genesis, tm_config)?;
+                write_configs(
+                    node_dir,
+                    vk,
+                    &validator_genesis,
+                    tm_config,
+                    Some(&flow_key_share),
+                )?;
             }
         }
     }
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use penumbra_storage::{Retention, Storage};
+use tendermint::block;
+use tokio::sync::watch;
+use tracing::Instrument;
+
+use crate::snapshot::snapshot_heights;
+
+/// Prune the state every this many blocks.
+const PRUNE_INTERVAL: u64 = 100;
+
+/// Deletes stale state in the background, keeping the `keep_recent` most recent versions and the
+/// versions of the snapshots stored in `snapshot_dir`.
+pub async fn prune_state(
+    storage: Storage,
+    keep_recent: u64,
+    snapshot_dir: PathBuf,
+    mut height_rx: watch::Receiver<block::Height>,
+) {
+    while height_rx.changed().await.is_ok() {
+        let height = height_rx.borrow().value();
+        if height % PRUNE_INTERVAL != 0 {
+            continue;
+        }
+
+        let span = tracing::error_span!("prune_state", %height);
+        async {
+            let keep_versions = snapshot_heights(&snapshot_dir).await?.into_iter().collect();
+            let pruned = storage
+                .prune(Retention {
+                    keep_recent,
+                    keep_versions,
+                })
+                .await?;
+            tracing::info!(pruned, "pruned stale state");
+            Ok::<_, anyhow::Error>(())
+        }
+        .instrument(span)
+        .await
+        .unwrap_or_else(|error| tracing::warn!(%height, %error, "failed to prune state"));
+    }
+}
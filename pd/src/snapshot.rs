@@ -1,15 +1,328 @@
 use std::{
     future::Future,
+    path::{Path, PathBuf},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
+use anyhow::Result;
+use bytes::Bytes;
 use futures::FutureExt;
-use tendermint::abci::{SnapshotRequest, SnapshotResponse};
+use penumbra_component::shielded_pool::state_key;
+use penumbra_storage::{AppHash, StateSnapshot, Storage};
+use penumbra_tct as tct;
+use sha2::{Digest, Sha256};
+use tendermint::{
+    abci::{
+        self,
+        response::{ApplySnapshotChunkResult, OfferSnapshot},
+        types, SnapshotRequest, SnapshotResponse,
+    },
+    block,
+};
+use tokio::sync::{watch, Mutex};
 use tower_abci::BoxError;
+use tracing::Instrument;
+
+use crate::RequestExt;
+
+/// The format of the snapshots produced by this version of `pd`.
+///
+/// Each snapshot is an encoded [`StateSnapshot`], split into chunks. The snapshot's metadata is the
+/// concatenation of the SHA256 hashes of its chunks, and the snapshot's hash is the SHA256 hash of
+/// its metadata, so that each chunk can be checked as soon as it's received.
+const SNAPSHOT_FORMAT: u32 = 1;
+
+/// The maximum size of a snapshot chunk (Tendermint rejects chunks larger than 16 MB).
+const CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+/// The number of most recent snapshots to keep on disk.
+const KEEP_RECENT: usize = 2;
+
+/// The size of the hash of each chunk in the snapshot metadata.
+const CHUNK_HASH_SIZE: usize = 32;
 
 #[derive(Clone, Debug)]
-pub struct Snapshot {}
+pub struct Snapshot {
+    storage: Storage,
+    dir: PathBuf,
+    restore: Arc<Mutex<Option<Restore>>>,
+}
+
+/// A snapshot being restored by state sync.
+#[derive(Debug)]
+struct Restore {
+    snapshot: types::Snapshot,
+    app_hash: Bytes,
+    chunks: Vec<Option<Bytes>>,
+}
+
+impl Snapshot {
+    /// Creates a new snapshot service, which serves the snapshots stored in `dir`, and takes a new
+    /// snapshot every `interval` blocks (or never, if `interval` is zero).
+    pub fn new(
+        storage: Storage,
+        dir: PathBuf,
+        interval: u64,
+        height_rx: watch::Receiver<block::Height>,
+    ) -> Self {
+        if interval > 0 {
+            tokio::task::Builder::new()
+                .name("snapshot::take_snapshots")
+                .spawn(take_snapshots(
+                    storage.clone(),
+                    dir.clone(),
+                    interval,
+                    height_rx,
+                ));
+        }
+
+        Self {
+            storage,
+            dir,
+            restore: Default::default(),
+        }
+    }
+
+    async fn list_snapshots(&self) -> Result<abci::response::ListSnapshots> {
+        let mut snapshots = Vec::new();
+        for height in snapshot_heights(&self.dir).await? {
+            let metadata =
+                tokio::fs::read(self.dir.join(height.to_string()).join("metadata")).await?;
+            snapshots.push(types::Snapshot {
+                height: height.try_into()?,
+                format: SNAPSHOT_FORMAT,
+                chunks: (metadata.len() / CHUNK_HASH_SIZE).try_into()?,
+                hash: Sha256::digest(&metadata).to_vec().into(),
+                metadata: metadata.into(),
+            });
+        }
+
+        Ok(abci::response::ListSnapshots { snapshots })
+    }
+
+    async fn load_snapshot_chunk(
+        &self,
+        load: abci::request::LoadSnapshotChunk,
+    ) -> Result<abci::response::LoadSnapshotChunk> {
+        if load.format != SNAPSHOT_FORMAT {
+            return Ok(Default::default());
+        }
+
+        let height: u64 = load.height.into();
+        let path = self
+            .dir
+            .join(height.to_string())
+            .join(load.chunk.to_string());
+        let chunk = match tokio::fs::read(path).await {
+            Ok(chunk) => chunk.into(),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Default::default(),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(abci::response::LoadSnapshotChunk { chunk })
+    }
+
+    async fn offer_snapshot(&self, offer: abci::request::OfferSnapshot) -> Result<OfferSnapshot> {
+        let abci::request::OfferSnapshot { snapshot, app_hash } = offer;
+
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Ok(OfferSnapshot::RejectFormat);
+        }
+        if snapshot.metadata.len() != snapshot.chunks as usize * CHUNK_HASH_SIZE
+            || Sha256::digest(&snapshot.metadata).as_slice() != snapshot.hash.as_ref()
+        {
+            tracing::warn!(height = ?snapshot.height, "rejecting snapshot with invalid metadata");
+            return Ok(OfferSnapshot::Reject);
+        }
+        if let Some(version) = self.storage.latest_version().await? {
+            tracing::warn!(?version, "can't restore a snapshot over existing state");
+            return Ok(OfferSnapshot::Abort);
+        }
+
+        tracing::info!(height = ?snapshot.height, chunks = snapshot.chunks, "accepting snapshot");
+        *self.restore.lock().await = Some(Restore {
+            chunks: vec![None; snapshot.chunks as usize],
+            snapshot,
+            app_hash,
+        });
+
+        Ok(OfferSnapshot::Accept)
+    }
+
+    async fn apply_snapshot_chunk(
+        &self,
+        apply: abci::request::ApplySnapshotChunk,
+    ) -> Result<abci::response::ApplySnapshotChunk> {
+        let result = |result| abci::response::ApplySnapshotChunk {
+            result,
+            ..Default::default()
+        };
+
+        let mut restore = self.restore.lock().await;
+        let in_progress = match restore.as_mut() {
+            Some(in_progress) => in_progress,
+            None => return Ok(result(ApplySnapshotChunkResult::Abort)),
+        };
+
+        // Check the chunk against its hash in the metadata, so that a bad chunk can be fetched
+        // again from another peer, rather than discarding the whole snapshot.
+        let index = apply.index as usize;
+        if index >= in_progress.chunks.len() {
+            return Ok(result(ApplySnapshotChunkResult::RejectSnapshot));
+        }
+        let expected_hash =
+            &in_progress.snapshot.metadata[index * CHUNK_HASH_SIZE..(index + 1) * CHUNK_HASH_SIZE];
+        if Sha256::digest(&apply.chunk).as_slice() != expected_hash {
+            tracing::warn!(index, sender = %apply.sender, "snapshot chunk has the wrong hash");
+            return Ok(abci::response::ApplySnapshotChunk {
+                result: ApplySnapshotChunkResult::Retry,
+                refetch_chunks: vec![apply.index],
+                reject_senders: vec![apply.sender],
+            });
+        }
+        in_progress.chunks[index] = Some(apply.chunk);
+
+        if in_progress.chunks.iter().any(Option::is_none) {
+            return Ok(result(ApplySnapshotChunkResult::Accept));
+        }
+
+        // All the chunks have been received, so we can reassemble the snapshot, verify it against
+        // the app hash, and restore it.
+        let Restore {
+            snapshot,
+            app_hash,
+            chunks,
+        } = restore.take().expect("restore is in progress");
+        let bytes = chunks.into_iter().flatten().flatten().collect::<Vec<u8>>();
+
+        let height: u64 = snapshot.height.into();
+        let state_snapshot = match verify(&bytes, height, &app_hash).await {
+            Ok(state_snapshot) => state_snapshot,
+            Err(error) => {
+                tracing::warn!(%height, %error, "rejecting invalid snapshot");
+                return Ok(result(ApplySnapshotChunkResult::RejectSnapshot));
+            }
+        };
+        self.storage.restore(state_snapshot).await?;
+
+        Ok(result(ApplySnapshotChunkResult::Accept))
+    }
+}
+
+/// Decodes a snapshot, and checks that it matches the given height and app hash.
+async fn verify(bytes: &[u8], height: u64, app_hash: &[u8]) -> Result<StateSnapshot> {
+    let snapshot = StateSnapshot::decode(bytes)?;
+    if snapshot.version() != height {
+        return Err(anyhow::anyhow!(
+            "snapshot is for version {}, expected {}",
+            snapshot.version(),
+            height
+        ));
+    }
+    if AppHash::from(snapshot.root_hash().await?).0[..] != app_hash[..] {
+        return Err(anyhow::anyhow!("snapshot does not match the app hash"));
+    }
+    verify_nct(&snapshot).await?;
+
+    Ok(snapshot)
+}
+
+/// Checks that the NCT in the snapshot matches the NCT anchor recorded in its state.
+async fn verify_nct(snapshot: &StateSnapshot) -> Result<()> {
+    let anchor: Option<tct::Root> = snapshot
+        .get_domain(state_key::anchor_by_height(snapshot.version()).into())
+        .await?;
+    if anchor != Some(snapshot.nct().root()) {
+        return Err(anyhow::anyhow!(
+            "snapshot NCT does not match the NCT anchor at height {}",
+            snapshot.version()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Takes a snapshot every `interval` blocks, as they are committed.
+async fn take_snapshots(
+    storage: Storage,
+    dir: PathBuf,
+    interval: u64,
+    mut height_rx: watch::Receiver<block::Height>,
+) {
+    while height_rx.changed().await.is_ok() {
+        let height = height_rx.borrow().value();
+        if height % interval != 0 {
+            continue;
+        }
+
+        let span = tracing::error_span!("take_snapshot", %height);
+        if let Err(error) = take_snapshot(&storage, &dir, height).instrument(span).await {
+            tracing::warn!(%height, %error, "failed to take snapshot");
+        }
+    }
+}
+
+async fn take_snapshot(storage: &Storage, dir: &Path, height: u64) -> Result<()> {
+    let snapshot = storage.snapshot(height).await?;
+    // The NCT can be a later version than the tree, if the next block was committed while we were
+    // reading it, in which case we skip this snapshot.
+    verify_nct(&snapshot).await?;
+    let bytes = snapshot.encode()?;
+
+    let mut metadata = Vec::new();
+    let partial_dir = dir.join(format!("{}.partial", height));
+    tokio::fs::create_dir_all(&partial_dir).await?;
+    for (index, chunk) in bytes.chunks(CHUNK_SIZE).enumerate() {
+        metadata.extend_from_slice(&Sha256::digest(chunk));
+        tokio::fs::write(partial_dir.join(index.to_string()), chunk).await?;
+    }
+    tokio::fs::write(partial_dir.join("metadata"), &metadata).await?;
+
+    // Only move the snapshot into place once it's complete, so that we never serve partial ones.
+    tokio::fs::rename(&partial_dir, dir.join(height.to_string())).await?;
+    tracing::info!(
+        chunks = metadata.len() / CHUNK_HASH_SIZE,
+        bytes = bytes.len(),
+        "took snapshot"
+    );
+
+    let heights = snapshot_heights(dir).await?;
+    for height in heights
+        .iter()
+        .take(heights.len().saturating_sub(KEEP_RECENT))
+    {
+        tracing::debug!(%height, "pruning old snapshot");
+        tokio::fs::remove_dir_all(dir.join(height.to_string())).await?;
+    }
+
+    Ok(())
+}
+
+/// Returns the heights of the complete snapshots stored in `dir`, in ascending order.
+pub(crate) async fn snapshot_heights(dir: &Path) -> Result<Vec<u64>> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut heights = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        // Partial snapshots don't parse as heights, so they're skipped here.
+        if let Some(height) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        {
+            heights.push(height);
+        }
+    }
+    heights.sort_unstable();
+
+    Ok(heights)
+}
 
 impl tower::Service<SnapshotRequest> for Snapshot {
     type Response = SnapshotResponse;
@@ -22,17 +335,26 @@ impl tower::Service<SnapshotRequest> for Snapshot {
     }
 
     fn call(&mut self, req: SnapshotRequest) -> Self::Future {
-        // No-op, we don't implement snapshot support
         use SnapshotRequest as Request;
         use SnapshotResponse as Response;
+
+        let span = req.create_span();
+        let this = self.clone();
         async move {
             Ok(match req {
-                Request::ListSnapshots => Response::ListSnapshots(Default::default()),
-                Request::OfferSnapshot(_) => Response::OfferSnapshot(Default::default()),
-                Request::LoadSnapshotChunk(_) => Response::LoadSnapshotChunk(Default::default()),
-                Request::ApplySnapshotChunk(_) => Response::ApplySnapshotChunk(Default::default()),
+                Request::ListSnapshots => Response::ListSnapshots(this.list_snapshots().await?),
+                Request::OfferSnapshot(offer) => {
+                    Response::OfferSnapshot(this.offer_snapshot(offer).await?)
+                }
+                Request::LoadSnapshotChunk(load) => {
+                    Response::LoadSnapshotChunk(this.load_snapshot_chunk(load).await?)
+                }
+                Request::ApplySnapshotChunk(apply) => {
+                    Response::ApplySnapshotChunk(this.apply_snapshot_chunk(apply).await?)
+                }
             })
         }
+        .instrument(span)
         .boxed()
     }
 }
@@ -13,7 +13,7 @@ use penumbra_chain::genesis::{self, AppState};
 use penumbra_crypto::{
     keys::{SpendKey, SpendKeyBytes},
     rdsa::{SigningKey, SpendAuth, VerificationKey},
-    Address,
+    Address, FlowKeyShare,
 };
 use penumbra_wallet::KeyStore;
 use rand::Rng;
@@ -229,11 +229,15 @@ pub fn canonicalize_path(input: &str) -> PathBuf {
     }
 }
 
+/// The name of the file in a node's `pd` directory holding its share of the flow decryption key.
+pub const FLOW_KEY_SHARE_FILE: &str = "flow_key_share.json";
+
 pub fn write_configs(
     node_dir: PathBuf,
     vk: &ValidatorKeys,
     genesis: &Genesis<AppState>,
     tm_config: String,
+    flow_key_share: Option<&FlowKeyShare>,
 ) -> anyhow::Result<()> {
     let mut pd_dir = node_dir.clone();
     let mut tm_dir = node_dir;
@@ -251,6 +255,15 @@ pub fn write_configs(
     fs::create_dir_all(&node_data_dir)?;
     fs::create_dir_all(&pd_dir)?;
 
+    // Write this node's share of the flow decryption key, if it has one. The share is kept by the
+    // node, and never recorded on chain.
+    if let Some(flow_key_share) = flow_key_share {
+        let flow_key_share_file_path = pd_dir.join(FLOW_KEY_SHARE_FILE);
+        tracing::info!(flow_key_share_file_path = %flow_key_share_file_path.display(), "writing flow key share");
+        let mut flow_key_share_file = File::create(flow_key_share_file_path)?;
+        flow_key_share_file.write_all(serde_json::to_string_pretty(flow_key_share)?.as_bytes())?;
+    }
+
     let mut genesis_file_path = node_config_dir.clone();
     genesis_file_path.push("genesis.json");
     tracing::info!(genesis_file_path = %genesis_file_path.display(), "writing genesis");
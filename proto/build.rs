@@ -49,6 +49,7 @@ fn main() -> Result<()> {
             "proto/ibc.proto",
             "proto/dex.proto",
             "proto/governance.proto",
+            "proto/eddy.proto",
         ],
         &["proto/", "ibc-go-vendor/"],
     )?;
@@ -136,6 +137,7 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.crypto.AssetId", SERIALIZE),
     (".penumbra.crypto.AssetId", SERDE_TRANSPARENT),
     (".penumbra.crypto.Value", SERIALIZE),
+    (".penumbra.crypto.U64x64", SERIALIZE),
     (".penumbra.crypto.Denom", SERIALIZE),
     (".penumbra.crypto.Denom", SERDE_TRANSPARENT),
     (".penumbra.crypto.Asset", SERIALIZE),
@@ -208,15 +210,40 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.transaction.DelegatorVoteBody", SERIALIZE),
     (".penumbra.ibc.IBCAction", SERIALIZE),
     (".penumbra.ibc.ICS20Withdrawal", SERIALIZE),
-    (".penumbra.dex.MockFlowCiphertext", SERIALIZE),
-    (".penumbra.dex.MockFlowCiphertext", SERDE_TRANSPARENT),
+    (".penumbra.dex.FlowCiphertext", SERIALIZE),
+    (".penumbra.dex.FlowCiphertext", SERDE_TRANSPARENT),
+    (".penumbra.dex.FlowEncryptionProof", SERIALIZE),
+    (".penumbra.dex.FlowEncryptionProof", SERDE_TRANSPARENT),
+    (".penumbra.dex.FlowCommitmentProof", SERIALIZE),
+    (".penumbra.dex.FlowCommitmentProof", SERDE_TRANSPARENT),
+    (".penumbra.dex.FlowEncryptionKey", SERIALIZE),
+    (".penumbra.dex.FlowEncryptionKey", SERDE_TRANSPARENT),
+    (".penumbra.dex.FlowKeyShare", SERIALIZE),
+    (".penumbra.dex.FlowKeyShare", SERDE_TRANSPARENT),
+    (".penumbra.dex.FlowCommittee", SERIALIZE),
+    (".penumbra.dex.FlowDecryptionShare", SERIALIZE),
+    (".penumbra.dex.FlowDecryptionShare", SERDE_TRANSPARENT),
+    (".penumbra.dex.FlowDecryption", SERIALIZE),
+    (".penumbra.dex.PendingBatch", SERIALIZE),
+    (".penumbra.dex.PendingBatchList", SERIALIZE),
     (".penumbra.dex.TradingPair", SERIALIZE),
+    (".penumbra.eddy.LimbCiphertext", SERIALIZE),
+    (".penumbra.eddy.BitEncryptionProof", SERIALIZE),
+    (".penumbra.eddy.LimbEncryptionProof", SERIALIZE),
+    (".penumbra.eddy.EncryptionProof", SERIALIZE),
+    (".penumbra.eddy.CommitmentProof", SERIALIZE),
+    (".penumbra.eddy.LimbDecryptionShare", SERIALIZE),
+    (".penumbra.eddy.DecryptionShare", SERIALIZE),
+    (".penumbra.eddy.PublicKeyShare", SERIALIZE),
     (".penumbra.dex.TradingFunction", SERIALIZE),
     (".penumbra.dex.Reserves", SERIALIZE),
     (".penumbra.dex.Position", SERIALIZE),
     (".penumbra.dex.PositionId", SERIALIZE),
     (".penumbra.dex.PositionId", SERDE_TRANSPARENT),
+    (".penumbra.dex.PositionIdList", SERIALIZE),
+    (".penumbra.dex.TradingPairList", SERIALIZE),
     (".penumbra.dex.PositionState", SERIALIZE),
+    (".penumbra.dex.PositionMetadata", SERIALIZE),
     (".penumbra.dex.PositionOpen", SERIALIZE),
     (".penumbra.dex.PositionClose", SERIALIZE),
     (".penumbra.dex.PositionWithdraw", SERIALIZE),
@@ -231,6 +258,7 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     ("penumbra.governance.Vote", SERDE_TRANSPARENT),
     (".penumbra.governance.ProposalState", SERIALIZE),
     (".penumbra.governance.ProposalOutcome", SERIALIZE),
+    (".penumbra.governance.Tally", SERIALIZE),
     (".penumbra.governance.ProposalState.state", SERDE_SNAKE_CASE),
     (".penumbra.governance.ProposalState.state", SERDE_TAG_STATE),
     (
@@ -311,6 +339,14 @@ static FIELD_ATTRIBUTES: &[(&str, &str)] = &[
         AS_HEX_FOR_BYTES,
     ),
     (".penumbra.transaction.SwapPlan.esk", AS_HEX_FOR_BYTES),
+    (
+        ".penumbra.transaction.SwapPlan.enc_amount_1_blindings",
+        AS_HEX_FOR_BYTES,
+    ),
+    (
+        ".penumbra.transaction.SwapPlan.enc_amount_2_blindings",
+        AS_HEX_FOR_BYTES,
+    ),
     (
         ".penumbra.transaction.SwapClaimPlan.output_1_blinding",
         AS_HEX_FOR_BYTES,
@@ -347,7 +383,38 @@ static FIELD_ATTRIBUTES: &[(&str, &str)] = &[
         ".penumbra.transaction.SpendBody.nullifier",
         AS_HEX_FOR_BYTES,
     ),
+    (
+        ".penumbra.transaction.DelegatorVote.zkproof",
+        AS_BASE64_FOR_BYTES,
+    ),
+    (
+        ".penumbra.transaction.DelegatorVoteBody.rk",
+        AS_HEX_FOR_BYTES,
+    ),
+    (
+        ".penumbra.transaction.DelegatorVoteBody.nullifier",
+        AS_HEX_FOR_BYTES,
+    ),
     (".penumbra.dex.Position.nonce", AS_HEX),
+    (".penumbra.dex.FlowCiphertext.inner", AS_HEX),
+    (".penumbra.dex.FlowEncryptionKey.inner", AS_HEX),
+    (".penumbra.dex.SwapPlaintext.delta_1_blinding", AS_HEX),
+    (".penumbra.dex.SwapPlaintext.delta_2_blinding", AS_HEX),
+    (".penumbra.dex.FlowKeyShare.inner", AS_HEX),
+    (".penumbra.eddy.CommitmentProof.inner", AS_HEX),
+    (".penumbra.eddy.LimbCiphertext.c1", AS_HEX),
+    (".penumbra.eddy.LimbCiphertext.c2", AS_HEX),
+    (
+        ".penumbra.eddy.LimbDecryptionShare.decryption_share",
+        AS_HEX,
+    ),
+    (".penumbra.eddy.LimbDecryptionShare.challenge", AS_HEX),
+    (".penumbra.eddy.LimbDecryptionShare.response", AS_HEX),
+    (".penumbra.eddy.PublicKeyShare.pub_key_share", AS_HEX),
+    (".penumbra.eddy.BitEncryptionProof.challenge_0", AS_HEX),
+    (".penumbra.eddy.BitEncryptionProof.challenge_1", AS_HEX),
+    (".penumbra.eddy.BitEncryptionProof.response_0", AS_HEX),
+    (".penumbra.eddy.BitEncryptionProof.response_1", AS_HEX),
     (".penumbra.dex.PositionId.inner", AS_BECH32_LP_ID),
     // Proposal JSON formatting
     (".penumbra.transaction.Proposal.payload", SERDE_FLATTEN),
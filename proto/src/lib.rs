@@ -38,6 +38,11 @@ pub mod dex {
     include!(concat!(env!("OUT_DIR"), "/penumbra.dex.rs"));
 }
 
+/// Flow encryption structures.
+pub mod eddy {
+    include!(concat!(env!("OUT_DIR"), "/penumbra.eddy.rs"));
+}
+
 /// Governance structures.
 pub mod governance {
     include!(concat!(env!("OUT_DIR"), "/penumbra.governance.rs"));
@@ -4,6 +4,8 @@ use once_cell::sync::Lazy;
 use penumbra_proto::Message;
 use sha2::{Digest, Sha256};
 
+use crate::index;
+
 /// this is a proof spec for computing Penumbra's AppHash, which is defined as
 /// SHA256("PenumbraAppHash" || jmt.root()). In ICS/IBC terms, this applies a single global prefix
 /// to Penumbra's state. Having a stable merkle prefix is currently required for our IBC
@@ -72,6 +74,10 @@ pub async fn get_with_proof<'a, R: TreeReader>(
     let jmt_root = store.get_root_hash(height).await?;
     let jmt_proof = store.get_with_ics23_proof(key.clone(), height).await?;
     let value = jmt_proof.value.clone();
+    // A deleted key is overwritten with a tombstone, which must not be proven as its value.
+    if value == index::TOMBSTONE {
+        return Err(anyhow::anyhow!("key not found"));
+    }
 
     let jmt_commitment_proof = ics23::CommitmentProof {
         proof: Some(ics23::commitment_proof::Proof::Exist(jmt_proof)),
@@ -0,0 +1,158 @@
+//! The key index, which records the keys written with [`StateExt::put_proto_indexed`], so that the
+//! keys with a given prefix can be found in order.
+//!
+//! The tree only stores hashes of keys, so to find the keys with a given prefix, we keep an index of
+//! them in the "key_index" column family.  So that the index can be derived from the tree (and in
+//! particular, restored along with it from a snapshot), every change to it is also committed into the
+//! tree, as a *marker* stored under the hash of the key prefixed with [`MARKER_PREFIX`], recording
+//! the key and whether it's present.  The index is updated from the markers as they're written.
+//!
+//! So that the keys can be found at past versions as well as the latest one, the index keeps an
+//! entry for each version at which a key's marker was written, keyed by the key, a zero byte, and
+//! the big-endian version, and recording whether the key is present as of that version.  Keys never
+//! contain a zero byte, so the entries for each key are contiguous and ordered by version, and the
+//! keys are in order.
+//!
+//! Every other value in the tree is proto-encoded, so values starting with a zero byte (the tag of a
+//! field numbered zero, which protobuf forbids) are free for us to use: the [`TOMBSTONE`] value marks
+//! a deleted key, and markers start with [`MARKER_TAG`].
+//!
+//! [`StateExt::put_proto_indexed`]: crate::StateExt::put_proto_indexed
+
+use anyhow::{anyhow, Result};
+use jmt::{
+    storage::{Node, NodeKey},
+    KeyHash,
+};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+
+/// The value marking a deleted key.
+pub(crate) const TOMBSTONE: &[u8] = &[0x00];
+
+/// The prefix of the values of markers.
+const MARKER_TAG: &[u8] = &[0x00, 0x01];
+
+/// The prefix of the keys of markers.
+const MARKER_PREFIX: &str = "index/";
+
+/// The key under which the marker for `key` is stored.
+pub(crate) fn marker_key(key: &str) -> KeyHash {
+    format!("{}{}", MARKER_PREFIX, key).into()
+}
+
+/// The marker recording whether `key` is present.
+pub(crate) fn marker(key: &str, present: bool) -> Vec<u8> {
+    let mut value = MARKER_TAG.to_vec();
+    value.push(present as u8);
+    value.extend_from_slice(key.as_bytes());
+    value
+}
+
+/// Parses a value as a marker, returning the key it records and whether that key is present, or
+/// `None` if the value isn't a marker.
+fn parse_marker(value: &[u8]) -> Option<(&[u8], bool)> {
+    let rest = value.strip_prefix(MARKER_TAG)?;
+    let (present, key) = rest.split_first()?;
+    Some((key, *present != 0))
+}
+
+/// Applies the markers among the leaves of the given nodes to the key index.
+///
+/// Each marker is recorded as of the version of the node it's in.
+pub(crate) fn update<'a>(
+    db: &DB,
+    batch: &mut WriteBatch,
+    nodes: impl IntoIterator<Item = (&'a NodeKey, &'a Node)>,
+) {
+    let index_cf = db
+        .cf_handle("key_index")
+        .expect("key_index column family not found");
+
+    for (node_key, node) in nodes {
+        if let Node::Leaf(leaf) = node {
+            if let Some((key, present)) = parse_marker(leaf.value()) {
+                batch.put_cf(
+                    index_cf,
+                    entry_key(key, node_key.version()),
+                    [present as u8],
+                );
+            }
+        }
+    }
+}
+
+/// Removes the index entry recorded by the given node, if it's a marker, when the node is pruned.
+///
+/// A leaf only becomes stale when a newer leaf replaces it, with an index entry of its own, so the
+/// entry for the stale leaf is only needed to read versions which are no longer retained.
+pub(crate) fn prune(db: &DB, batch: &mut WriteBatch, node_key: &NodeKey, node: &Node) {
+    let index_cf = db
+        .cf_handle("key_index")
+        .expect("key_index column family not found");
+
+    if let Node::Leaf(leaf) = node {
+        if let Some((key, _)) = parse_marker(leaf.value()) {
+            batch.delete_cf(index_cf, entry_key(key, node_key.version()));
+        }
+    }
+}
+
+/// The key of the index entry for `key` as of `version`.
+fn entry_key(key: &[u8], version: jmt::Version) -> Vec<u8> {
+    let mut entry_key = Vec::with_capacity(key.len() + 9);
+    entry_key.extend_from_slice(key);
+    entry_key.push(0);
+    entry_key.extend_from_slice(&version.to_be_bytes());
+    entry_key
+}
+
+/// Parses the key of an index entry into the key it records and the version it's as of.
+fn parse_entry_key(entry_key: &[u8]) -> Result<(&[u8], jmt::Version)> {
+    let split = entry_key
+        .len()
+        .checked_sub(9)
+        .filter(|&split| entry_key[split] == 0)
+        .ok_or_else(|| anyhow!("malformed key index entry"))?;
+    let version = jmt::Version::from_be_bytes(entry_key[split + 1..].try_into()?);
+    Ok((&entry_key[..split], version))
+}
+
+/// Returns the indexed keys starting with `prefix` which are present at `version`, in order.
+pub(crate) fn keys_with_prefix(
+    db: &DB,
+    prefix: &str,
+    version: jmt::Version,
+) -> Result<Vec<String>> {
+    let index_cf = db
+        .cf_handle("key_index")
+        .expect("key_index column family not found");
+
+    let mut keys = Vec::new();
+    // The key whose entries we're reading, and whether it was present as of its latest entry at or
+    // before `version`.
+    let mut current: Option<(Vec<u8>, bool)> = None;
+    for (entry_key, present) in db.iterator_cf(
+        index_cf,
+        IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+    ) {
+        if !entry_key.starts_with(prefix.as_bytes()) {
+            break;
+        }
+        let (key, entry_version) = parse_entry_key(&entry_key)?;
+        if current.as_ref().map(|(k, _)| k.as_slice()) != Some(key) {
+            if let Some((key, true)) = current.take() {
+                keys.push(String::from_utf8(key)?);
+            }
+            current = Some((key.to_vec(), false));
+        }
+        if entry_version <= version {
+            if let Some((_, current_present)) = current.as_mut() {
+                *current_present = present.first() == Some(&1);
+            }
+        }
+    }
+    if let Some((key, true)) = current {
+        keys.push(String::from_utf8(key)?);
+    }
+    Ok(keys)
+}
@@ -1,18 +1,25 @@
 // Required to ensure that Rust can infer a Send bound inside the TCT
 #![recursion_limit = "256"]
 
-use jmt::WriteOverlay;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 mod app_hash;
+mod index;
 mod metrics;
+mod nct;
+mod overlay;
 mod overlay_ext;
+mod pruning;
+mod snapshot;
 mod storage;
 
 pub use crate::metrics::register_metrics;
 pub use app_hash::{get_with_proof, AppHash, PENUMBRA_COMMITMENT_PREFIX, PENUMBRA_PROOF_SPECS};
+pub use overlay::Overlay;
 pub use overlay_ext::StateExt;
+pub use pruning::Retention;
+pub use snapshot::StateSnapshot;
 pub use storage::Storage;
 
-pub type State = Arc<RwLock<WriteOverlay<Storage>>>;
+pub type State = Arc<RwLock<Overlay>>;
@@ -15,12 +15,28 @@ pub use metrics::*;
 
 /// Registers all metrics used by this crate.
 pub fn register_metrics() {
-    register_gauge!(TCT_SIZE_BYTES);
+    register_gauge!(TCT_WRITE_BYTES);
     describe_gauge!(
-        TCT_SIZE_BYTES,
+        TCT_WRITE_BYTES,
         Unit::Bytes,
-        "The size of the serialized TCT in bytes"
+        "The number of bytes written to store the changes to the TCT in the last commit"
+    );
+
+    register_counter!(JMT_PRUNED_NODES);
+    describe_counter!(
+        JMT_PRUNED_NODES,
+        Unit::Count,
+        "The number of stale JMT nodes deleted by pruning"
+    );
+
+    register_gauge!(JMT_OLDEST_RECENT_VERSION);
+    describe_gauge!(
+        JMT_OLDEST_RECENT_VERSION,
+        Unit::Count,
+        "The oldest version of the JMT kept by the last pruning, other than snapshot versions"
     );
 }
 
-pub const TCT_SIZE_BYTES: &str = "penumbra_storage_tct_size_bytes";
+pub const TCT_WRITE_BYTES: &str = "penumbra_storage_tct_write_bytes";
+pub const JMT_PRUNED_NODES: &str = "penumbra_storage_jmt_pruned_nodes_total";
+pub const JMT_OLDEST_RECENT_VERSION: &str = "penumbra_storage_jmt_oldest_recent_version";
@@ -0,0 +1,382 @@
+//! Incremental storage of the NCT in the "nct" column family.
+//!
+//! Rather than serializing the whole tree on every commit, we use the TCT's incremental
+//! serialization, so that each commit only writes the hashes and commitments added since the
+//! previous one, and deletes those which have since been forgotten.
+
+use std::{ops::Range, pin::Pin};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::{stream, Stream};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+
+use penumbra_tct::{
+    self as tct,
+    storage::{Read, StoredPosition, Write},
+    structure::Hash,
+    Commitment, Forgotten, Position,
+};
+
+/// The keys under which the parts of the NCT are stored.
+///
+/// Hashes are keyed by height before position, so that the hashes below a given height within a
+/// range of positions can be deleted with one range deletion per height.
+mod key {
+    use penumbra_tct::Position;
+
+    /// The key under which the whole tree was stored, before it was stored incrementally.
+    pub const LEGACY_TREE: &[u8] = b"tct";
+
+    pub const POSITION: &[u8] = b"position";
+    pub const FORGOTTEN: &[u8] = b"forgotten";
+    pub const HASH_PREFIX: &[u8] = b"hash/";
+    pub const COMMITMENT_PREFIX: &[u8] = b"commitment/";
+
+    pub fn hash(height: u8, position: Position) -> Vec<u8> {
+        let mut key = HASH_PREFIX.to_vec();
+        key.push(height);
+        key.extend_from_slice(&u64::from(position).to_be_bytes());
+        key
+    }
+
+    pub fn commitment(position: Position) -> Vec<u8> {
+        let mut key = COMMITMENT_PREFIX.to_vec();
+        key.extend_from_slice(&u64::from(position).to_be_bytes());
+        key
+    }
+}
+
+/// Reads the stored position of the tree, which is absent if nothing has been stored yet.
+fn position(stored: Option<Vec<u8>>) -> Result<StoredPosition> {
+    Ok(match stored {
+        None => StoredPosition::default(),
+        // The position of a full tree is stored as an empty value
+        Some(bytes) if bytes.is_empty() => StoredPosition::Full,
+        Some(bytes) => StoredPosition::Position(u64::from_be_bytes(bytes_8(&bytes)?).into()),
+    })
+}
+
+/// Reads the stored forgotten version of the tree, which is absent if nothing has been stored yet.
+fn forgotten(stored: Option<Vec<u8>>) -> Result<Forgotten> {
+    Ok(match stored {
+        None => Forgotten::default(),
+        Some(bytes) => u64::from_be_bytes(bytes_8(&bytes)?).into(),
+    })
+}
+
+fn bytes_8(bytes: &[u8]) -> Result<[u8; 8]> {
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("stored NCT value must be 8 bytes"))
+}
+
+/// The NCT as loaded from storage.
+pub(crate) enum Loaded {
+    /// The NCT was stored incrementally, and must be deserialized.
+    Incremental(Stored),
+    /// The NCT was stored by an older version as a single serialized tree: it will be stored
+    /// incrementally from the next commit onwards.
+    Legacy(tct::Tree),
+}
+
+impl Loaded {
+    /// Loads the NCT from a consistent view of the database.
+    pub(crate) fn load(db: &DB, snapshot: &rocksdb::Snapshot<'_>) -> Result<Self> {
+        let nct_cf = db.cf_handle("nct").expect("nct column family not found");
+
+        if let Some(tct_bytes) = snapshot.get_cf(nct_cf, key::LEGACY_TREE)? {
+            return Ok(Loaded::Legacy(bincode::deserialize(&tct_bytes)?));
+        }
+
+        let mut hashes = Vec::new();
+        for (key, value) in snapshot.iterator_cf(
+            nct_cf,
+            IteratorMode::From(key::HASH_PREFIX, Direction::Forward),
+        ) {
+            let suffix = match key.strip_prefix(key::HASH_PREFIX) {
+                Some(suffix) => suffix,
+                None => break,
+            };
+            let (height, position) = suffix
+                .split_first()
+                .ok_or_else(|| anyhow::anyhow!("stored NCT hash key is missing its height"))?;
+            hashes.push((
+                u64::from_be_bytes(bytes_8(position)?).into(),
+                *height,
+                Hash::from_bytes(
+                    value
+                        .as_ref()
+                        .try_into()
+                        .map_err(|_| anyhow::anyhow!("stored NCT hash must be 32 bytes"))?,
+                )?,
+            ));
+        }
+
+        let mut commitments = Vec::new();
+        for (key, value) in snapshot.iterator_cf(
+            nct_cf,
+            IteratorMode::From(key::COMMITMENT_PREFIX, Direction::Forward),
+        ) {
+            let position = match key.strip_prefix(key::COMMITMENT_PREFIX) {
+                Some(position) => position,
+                None => break,
+            };
+            commitments.push((
+                u64::from_be_bytes(bytes_8(position)?).into(),
+                Commitment::try_from(
+                    <[u8; 32]>::try_from(value.as_ref())
+                        .map_err(|_| anyhow::anyhow!("stored NCT commitment must be 32 bytes"))?,
+                )?,
+            ));
+        }
+
+        Ok(Loaded::Incremental(Stored {
+            position: position(snapshot.get_cf(nct_cf, key::POSITION)?)?,
+            forgotten: forgotten(snapshot.get_cf(nct_cf, key::FORGOTTEN)?)?,
+            hashes,
+            commitments,
+        }))
+    }
+
+    /// Reconstructs the tree from what was loaded.
+    pub(crate) async fn into_tree(self) -> Result<tct::Tree> {
+        match self {
+            Loaded::Incremental(mut stored) => tct::Tree::deserialize(&mut stored).await,
+            Loaded::Legacy(tree) => Ok(tree),
+        }
+    }
+}
+
+/// A complete copy of the NCT as stored incrementally, from which the tree can be deserialized.
+pub(crate) struct Stored {
+    position: StoredPosition,
+    forgotten: Forgotten,
+    hashes: Vec<(Position, u8, Hash)>,
+    commitments: Vec<(Position, Commitment)>,
+}
+
+#[async_trait]
+impl Read for Stored {
+    type Error = anyhow::Error;
+
+    async fn position(&mut self) -> Result<StoredPosition> {
+        Ok(self.position)
+    }
+
+    async fn forgotten(&mut self) -> Result<Forgotten> {
+        Ok(self.forgotten)
+    }
+
+    fn hashes(&mut self) -> Pin<Box<dyn Stream<Item = Result<(Position, u8, Hash)>> + Send + '_>> {
+        Box::pin(stream::iter(self.hashes.iter().copied().map(Ok)))
+    }
+
+    fn commitments(
+        &mut self,
+    ) -> Pin<Box<dyn Stream<Item = Result<(Position, Commitment)>> + Send + '_>> {
+        Box::pin(stream::iter(self.commitments.iter().copied().map(Ok)))
+    }
+}
+
+/// A single change to the stored NCT.
+enum Change {
+    Put { key: Vec<u8>, value: Vec<u8> },
+    DeleteRange { from: Vec<u8>, to: Vec<u8> },
+}
+
+/// The changes to the stored NCT made by incrementally serializing a newer version of the tree,
+/// which are then written to the database atomically.
+pub(crate) struct Changes {
+    position: StoredPosition,
+    forgotten: Forgotten,
+    changes: Vec<Change>,
+}
+
+impl Changes {
+    /// Begins a new set of changes on top of the stored position and forgotten version, which are
+    /// all that incremental serialization needs to read.
+    pub(crate) fn new(db: &DB) -> Result<Self> {
+        let nct_cf = db.cf_handle("nct").expect("nct column family not found");
+
+        // If the NCT is still stored as a single serialized tree, start from scratch, so that the
+        // whole tree is written incrementally (and the serialized tree is deleted).
+        if db.get_cf(nct_cf, key::LEGACY_TREE)?.is_some() {
+            return Ok(Self::empty());
+        }
+
+        Ok(Self {
+            position: position(db.get_cf(nct_cf, key::POSITION)?)?,
+            forgotten: forgotten(db.get_cf(nct_cf, key::FORGOTTEN)?)?,
+            changes: Vec::new(),
+        })
+    }
+
+    /// Begins a new set of changes on top of an empty stored tree.
+    pub(crate) fn empty() -> Self {
+        Self {
+            position: StoredPosition::default(),
+            forgotten: Forgotten::default(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Adds these changes to a write batch, returning the number of bytes they write.
+    pub(crate) fn write_to(self, db: &DB, batch: &mut WriteBatch) -> usize {
+        let nct_cf = db.cf_handle("nct").expect("nct column family not found");
+
+        batch.delete_cf(nct_cf, key::LEGACY_TREE);
+
+        let mut bytes = 0;
+        for change in self.changes {
+            match change {
+                Change::Put { key, value } => {
+                    bytes += key.len() + value.len();
+                    batch.put_cf(nct_cf, key, value);
+                }
+                Change::DeleteRange { from, to } => batch.delete_range_cf(nct_cf, from, to),
+            }
+        }
+        bytes
+    }
+
+    fn put(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.changes.push(Change::Put { key, value });
+    }
+}
+
+#[async_trait]
+impl Read for Changes {
+    type Error = anyhow::Error;
+
+    async fn position(&mut self) -> Result<StoredPosition> {
+        Ok(self.position)
+    }
+
+    async fn forgotten(&mut self) -> Result<Forgotten> {
+        Ok(self.forgotten)
+    }
+
+    // Serialization only reads the stored position and forgotten version, so there's no need to
+    // load the stored hashes and commitments: use `Stored` to read the whole tree.
+
+    fn hashes(&mut self) -> Pin<Box<dyn Stream<Item = Result<(Position, u8, Hash)>> + Send + '_>> {
+        Box::pin(stream::empty())
+    }
+
+    fn commitments(
+        &mut self,
+    ) -> Pin<Box<dyn Stream<Item = Result<(Position, Commitment)>> + Send + '_>> {
+        Box::pin(stream::empty())
+    }
+}
+
+#[async_trait]
+impl Write for Changes {
+    async fn add_hash(
+        &mut self,
+        position: Position,
+        height: u8,
+        hash: Hash,
+        _essential: bool,
+    ) -> Result<()> {
+        self.put(key::hash(height, position), hash.to_bytes().to_vec());
+        Ok(())
+    }
+
+    async fn add_commitment(&mut self, position: Position, commitment: Commitment) -> Result<()> {
+        self.put(
+            key::commitment(position),
+            <[u8; 32]>::from(commitment).to_vec(),
+        );
+        Ok(())
+    }
+
+    async fn delete_range(&mut self, below_height: u8, positions: Range<Position>) -> Result<()> {
+        for height in 0..below_height {
+            self.changes.push(Change::DeleteRange {
+                from: key::hash(height, positions.start),
+                to: key::hash(height, positions.end),
+            });
+        }
+        self.changes.push(Change::DeleteRange {
+            from: key::commitment(positions.start),
+            to: key::commitment(positions.end),
+        });
+        Ok(())
+    }
+
+    async fn set_position(&mut self, position: StoredPosition) -> Result<()> {
+        if position <= self.position {
+            return Err(anyhow::anyhow!(
+                "NCT position did not increase from {:?} to {:?}",
+                self.position,
+                position
+            ));
+        }
+        self.position = position;
+        let value = match position {
+            StoredPosition::Position(position) => u64::from(position).to_be_bytes().to_vec(),
+            StoredPosition::Full => Vec::new(),
+        };
+        self.put(key::POSITION.to_vec(), value);
+        Ok(())
+    }
+
+    async fn set_forgotten(&mut self, forgotten: Forgotten) -> Result<()> {
+        if forgotten <= self.forgotten {
+            return Err(anyhow::anyhow!(
+                "NCT forgotten version did not increase from {:?} to {:?}",
+                self.forgotten,
+                forgotten
+            ));
+        }
+        self.forgotten = forgotten;
+        self.put(
+            key::FORGOTTEN.to_vec(),
+            u64::from(forgotten).to_be_bytes().to_vec(),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Storage;
+    use tempfile::tempdir;
+
+    fn commitment(i: u8) -> Commitment {
+        let mut bytes = [0; 32];
+        bytes[0] = i;
+        Commitment::try_from(bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn nct_round_trips_incrementally() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().to_owned()).await.unwrap();
+        let mut tree = tct::Tree::new();
+
+        for i in 0..4 {
+            tree.insert(tct::Witness::Keep, commitment(i)).unwrap();
+        }
+        tree.end_block().unwrap();
+        storage.put_nct(&tree).await.unwrap();
+
+        // Forget some commitments and add others, so that the second write both deletes and adds
+        tree.forget(commitment(1));
+        tree.forget(commitment(2));
+        for i in 4..8 {
+            tree.insert(tct::Witness::Keep, commitment(i)).unwrap();
+        }
+        storage.put_nct(&tree).await.unwrap();
+
+        let loaded = storage.get_nct().await.unwrap();
+        assert_eq!(loaded.root(), tree.root());
+        assert_eq!(loaded.position(), tree.position());
+        assert_eq!(loaded.forgotten(), tree.forgotten());
+        assert!(loaded.witness(commitment(1)).is_none());
+        assert!(loaded.witness(commitment(5)).is_some());
+    }
+}
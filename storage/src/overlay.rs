@@ -0,0 +1,175 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use jmt::{KeyHash, RootHash, WriteOverlay};
+
+use crate::{index, Storage};
+
+/// A copy-on-write overlay of changes on top of a version of the [`Storage`].
+///
+/// This wraps a JMT [`WriteOverlay`], additionally keeping the changes to the key index made since
+/// the last commit, so that finding keys by prefix sees them before they're committed.
+pub struct Overlay {
+    inner: WriteOverlay<Storage>,
+    storage: Storage,
+    /// The version of the tree this overlay is on top of.
+    version: jmt::Version,
+    /// The keys added to (`true`) or removed from (`false`) the key index since the last commit.
+    index_changes: BTreeMap<String, bool>,
+}
+
+impl Overlay {
+    pub(crate) fn new(storage: Storage, version: jmt::Version) -> Self {
+        Self {
+            inner: WriteOverlay::new(storage.clone(), version),
+            storage,
+            version,
+            index_changes: BTreeMap::new(),
+        }
+    }
+
+    /// Gets the value of a key, or `None` if it's not present or was deleted.
+    pub async fn get(&self, key: KeyHash) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .inner
+            .get(key)
+            .await?
+            .filter(|value| value != index::TOMBSTONE))
+    }
+
+    /// Gets the value of a key, with a proof of its inclusion in the tree at this version, or
+    /// `None` if it was deleted.
+    pub async fn get_with_proof(
+        &self,
+        key: Vec<u8>,
+    ) -> Result<Option<(Vec<u8>, ics23::ExistenceProof)>> {
+        let (value, proof) = self.inner.get_with_proof(key).await?;
+        if value == index::TOMBSTONE {
+            return Ok(None);
+        }
+        Ok(Some((value, proof)))
+    }
+
+    /// Puts a value under a key.
+    pub fn put(&mut self, key: KeyHash, value: Vec<u8>) {
+        self.inner.put(key, value);
+    }
+
+    /// Puts a value under a key, recording the key in the key index.
+    pub fn put_indexed(&mut self, key: String, value: Vec<u8>) {
+        self.inner.put(key.as_str().into(), value);
+        self.inner
+            .put(index::marker_key(&key), index::marker(&key, true));
+        self.index_changes.insert(key, true);
+    }
+
+    /// Deletes a key, by writing a tombstone over its value.
+    pub fn delete(&mut self, key: KeyHash) {
+        self.inner.put(key, index::TOMBSTONE.to_vec());
+    }
+
+    /// Deletes a key, removing it from the key index.
+    pub fn delete_indexed(&mut self, key: String) {
+        self.inner
+            .put(key.as_str().into(), index::TOMBSTONE.to_vec());
+        self.inner
+            .put(index::marker_key(&key), index::marker(&key, false));
+        self.index_changes.insert(key, false);
+    }
+
+    /// Returns the indexed keys starting with `prefix`, in order, as of this overlay's version,
+    /// including uncommitted changes.
+    pub async fn keys_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = self
+            .storage
+            .keys_with_prefix(prefix.to_string(), self.version)
+            .await?
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+
+        for (key, present) in self
+            .index_changes
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+        {
+            if *present {
+                keys.insert(key.clone());
+            } else {
+                keys.remove(key);
+            }
+        }
+
+        Ok(keys.into_iter().collect())
+    }
+
+    /// Commits the changes to the storage, returning the new root hash and version.
+    pub async fn commit(&mut self, storage: Storage) -> Result<(RootHash, jmt::Version)> {
+        let committed = self.inner.commit(storage).await?;
+        self.index_changes.clear();
+        self.version = committed.1;
+        Ok(committed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{StateExt, Storage};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn deleted_and_indexed_keys() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("source.db")).await.unwrap();
+        let state = storage.state().await.unwrap();
+        for (key, value) in [("a/1", 1u64), ("a/2", 2), ("a/3", 3), ("b/1", 4)] {
+            state.put_proto_indexed(key.to_string(), value).await;
+        }
+        state.delete_indexed("a/2".to_string()).await;
+
+        // Uncommitted changes are visible, in order.
+        let expected = vec![("a/1".to_string(), 1u64), ("a/3".to_string(), 3)];
+        assert_eq!(state.prefix_proto::<u64>("a/").await.unwrap(), expected);
+        assert_eq!(state.get_proto::<u64>("a/2".into()).await.unwrap(), None);
+
+        let (_, version) = state.write().await.commit(storage.clone()).await.unwrap();
+        let state = storage.state().await.unwrap();
+        assert_eq!(state.prefix_proto::<u64>("a/").await.unwrap(), expected);
+
+        // Deleting an unindexed key removes its value.
+        state.put_proto::<u64>("c".into(), 5).await;
+        state.delete("c".into()).await;
+        assert_eq!(state.get_proto::<u64>("c".into()).await.unwrap(), None);
+
+        // Deleted keys have no proof of their value.
+        state.write().await.commit(storage.clone()).await.unwrap();
+        let state = storage.state().await.unwrap();
+        assert!(state
+            .read()
+            .await
+            .get_with_proof(b"c".to_vec())
+            .await
+            .unwrap()
+            .is_none());
+
+        // The index is kept for past versions, too.
+        state.delete_indexed("a/1".to_string()).await;
+        state.put_proto_indexed("a/4".to_string(), 4u64).await;
+        state.write().await.commit(storage.clone()).await.unwrap();
+        let past = storage.state_at(version).await.unwrap();
+        assert_eq!(past.prefix_proto::<u64>("a/").await.unwrap(), expected);
+        let state = storage.state().await.unwrap();
+        assert_eq!(
+            state.prefix_proto::<u64>("a/").await.unwrap(),
+            vec![("a/3".to_string(), 3u64), ("a/4".to_string(), 4)]
+        );
+
+        // The index is restored along with the tree.
+        let restored = Storage::load(dir.path().join("restored.db")).await.unwrap();
+        restored
+            .restore(storage.snapshot(version).await.unwrap())
+            .await
+            .unwrap();
+        let state = restored.state().await.unwrap();
+        assert_eq!(state.prefix_proto::<u64>("a/").await.unwrap(), expected);
+    }
+}
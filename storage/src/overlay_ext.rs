@@ -43,6 +43,40 @@ pub trait StateExt: Send + Sync + Sized + Clone + 'static {
     async fn put_proto<P>(&self, key: KeyHash, value: P)
     where
         P: Message + Debug;
+
+    /// Puts a domain type into the state, using the proto encoding, and records its key in the
+    /// key index, so that it can be found with [`StateExt::prefix_domain`].
+    async fn put_domain_indexed<D, P>(&self, key: String, value: D)
+    where
+        D: Protobuf<P> + Send + TryFrom<P> + Clone + Debug,
+        P: Message + Default + From<D>,
+        <D as TryFrom<P>>::Error: Into<anyhow::Error>;
+
+    /// Puts a proto type into the state, and records its key in the key index, so that it can be
+    /// found with [`StateExt::prefix_proto`].
+    async fn put_proto_indexed<P>(&self, key: String, value: P)
+    where
+        P: Message + Debug;
+
+    /// Deletes a key from the state.
+    async fn delete(&self, key: KeyHash);
+
+    /// Deletes a key from the state, and removes it from the key index.
+    async fn delete_indexed(&self, key: String);
+
+    /// Reads the domain types stored under the indexed keys starting with `prefix`, in order of
+    /// their keys.
+    async fn prefix_domain<D, P>(&self, prefix: &str) -> Result<Vec<(String, D)>>
+    where
+        D: Protobuf<P> + TryFrom<P> + Clone + Debug,
+        P: Message + Default + From<D>,
+        <D as TryFrom<P>>::Error: Into<anyhow::Error>;
+
+    /// Reads the proto types stored under the indexed keys starting with `prefix`, in order of
+    /// their keys.
+    async fn prefix_proto<P>(&self, prefix: &str) -> Result<Vec<(String, P)>>
+    where
+        P: Message + Default + Debug;
 }
 
 #[async_trait]
@@ -109,4 +143,65 @@ impl StateExt for State {
     {
         self.write().await.put(key, value.encode_to_vec());
     }
+
+    #[instrument(skip(self, value))]
+    async fn put_domain_indexed<D, P>(&self, key: String, value: D)
+    where
+        D: Protobuf<P> + Send + TryFrom<P> + Clone + Debug,
+        P: Message + Default + From<D>,
+        <D as TryFrom<P>>::Error: Into<anyhow::Error>,
+    {
+        tracing::trace!(?value);
+        self.put_proto_indexed(key, P::from(value)).await;
+    }
+
+    #[instrument(skip(self, value))]
+    async fn put_proto_indexed<P>(&self, key: String, value: P)
+    where
+        P: Message + Debug,
+    {
+        self.write().await.put_indexed(key, value.encode_to_vec());
+    }
+
+    #[instrument(skip(self, key))]
+    async fn delete(&self, key: KeyHash) {
+        tracing::trace!(?key);
+        self.write().await.delete(key);
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_indexed(&self, key: String) {
+        self.write().await.delete_indexed(key);
+    }
+
+    #[instrument(skip(self))]
+    async fn prefix_domain<D, P>(&self, prefix: &str) -> Result<Vec<(String, D)>>
+    where
+        D: Protobuf<P> + TryFrom<P> + Clone + Debug,
+        P: Message + Default + From<D>,
+        <D as TryFrom<P>>::Error: Into<anyhow::Error>,
+    {
+        self.prefix_proto::<P>(prefix)
+            .await?
+            .into_iter()
+            .map(|(key, p)| Ok((key, D::try_from(p).map_err(Into::into)?)))
+            .collect()
+    }
+
+    #[instrument(skip(self))]
+    async fn prefix_proto<P>(&self, prefix: &str) -> Result<Vec<(String, P)>>
+    where
+        P: Message + Default + Debug,
+    {
+        let keys = self.read().await.keys_with_prefix(prefix).await?;
+
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            // Keys may be missing from overlays on top of older versions.
+            if let Some(value) = self.get_proto(key.as_str().into()).await? {
+                values.push((key, value));
+            }
+        }
+        Ok(values)
+    }
 }
@@ -0,0 +1,254 @@
+//! Pruning of stale JMT nodes.
+//!
+//! Every commit writes new nodes along the paths to the changed leaves, and the nodes they replace
+//! become *stale*: they're no longer part of the tree at the new version, but they're still needed
+//! to read older versions.  We find the nodes which became stale at each version by walking the
+//! tree at that version alongside the tree at the previous one, and record them in the "jmt_stale"
+//! column family, keyed by the version at which they became stale.  A stale node can be deleted once
+//! no retained version lies between the version at which it was written and the version at which
+//! it became stale.  Pruning a stale marker also prunes its entry in the key index.
+
+use std::collections::BTreeSet;
+
+use ::metrics::{counter, gauge};
+use anyhow::Result;
+use jmt::storage::{Node, NodeKey};
+use rocksdb::{IteratorMode, WriteBatch, DB};
+
+use crate::{index, metrics};
+
+/// The key in the "local" column family recording the version up to which stale nodes have been
+/// indexed.
+const INDEXED_VERSION: &str = "jmt_stale_indexed_version";
+
+/// The maximum number of deletions to write to the database in one batch.
+const BATCH_SIZE: usize = 10_000;
+
+/// Which versions of the tree to keep when pruning.
+#[derive(Clone, Debug)]
+pub struct Retention {
+    /// Keep this many of the most recent versions (at least one).
+    pub keep_recent: u64,
+    /// Also keep these versions, no matter how old they are.
+    pub keep_versions: BTreeSet<jmt::Version>,
+}
+
+/// Records the nodes which became stale at each version committed since this was last called.
+pub(crate) fn index_stale_nodes(db: &DB, latest: jmt::Version) -> Result<()> {
+    let local_cf = db
+        .cf_handle("local")
+        .expect("local column family not found");
+    let stale_cf = db
+        .cf_handle("jmt_stale")
+        .expect("jmt_stale column family not found");
+
+    let indexed = match db.get_cf(local_cf, INDEXED_VERSION)? {
+        Some(bytes) => jmt::Version::from_le_bytes(
+            bytes
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("indexed version must be 8 bytes"))?,
+        ),
+        // Nothing has been indexed yet, so start from the oldest version present, which is the
+        // genesis version unless the state was restored from a snapshot.
+        None => match earliest_version(db)? {
+            Some(earliest) => earliest,
+            None => return Ok(()),
+        },
+    };
+
+    for version in (indexed + 1)..=latest {
+        let mut stale = Vec::new();
+        // If the previous version has no root, it was never stored here (because the state was
+        // restored from a snapshot after it), so nothing can have become stale since it.
+        if get_node(db, &NodeKey::new_empty_path(version - 1))?.is_some() {
+            diff(
+                db,
+                NodeKey::new_empty_path(version - 1),
+                Some(NodeKey::new_empty_path(version)),
+                &mut stale,
+            )?;
+        }
+
+        // Record the stale nodes for each version atomically with the indexed version, so that a
+        // version is never indexed twice or skipped.
+        let mut batch = WriteBatch::default();
+        for node_key in stale.iter() {
+            batch.put_cf(stale_cf, stale_key(version, node_key)?, []);
+        }
+        batch.put_cf(local_cf, INDEXED_VERSION, version.to_le_bytes());
+        db.write(batch)?;
+
+        tracing::trace!(?version, stale = stale.len(), "indexed stale JMT nodes");
+    }
+
+    Ok(())
+}
+
+/// Deletes the stale nodes which are no longer needed by any retained version, returning the
+/// number of nodes deleted.
+pub(crate) fn prune(db: &DB, latest: jmt::Version, retention: &Retention) -> Result<usize> {
+    let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+    let stale_cf = db
+        .cf_handle("jmt_stale")
+        .expect("jmt_stale column family not found");
+
+    let oldest_recent = latest.saturating_sub(retention.keep_recent.max(1) - 1);
+
+    let mut pruned = 0;
+    let mut batch = WriteBatch::default();
+    for (key, _) in db.iterator_cf(stale_cf, IteratorMode::Start) {
+        let (stale_since, node_key) = parse_stale_key(&key)?;
+        // Entries are ordered by the version at which the node became stale, and every node which
+        // became stale after the oldest recent version is still needed to read it.
+        if stale_since > oldest_recent {
+            break;
+        }
+        // The node was part of the tree for every version from when it was written until just
+        // before it became stale.
+        if retention
+            .keep_versions
+            .range(node_key.version()..stale_since)
+            .next()
+            .is_some()
+        {
+            continue;
+        }
+
+        if let Some(node) = get_node(db, &node_key)? {
+            index::prune(db, &mut batch, &node_key, &node);
+        }
+        batch.delete_cf(jmt_cf, node_key.encode()?);
+        batch.delete_cf(stale_cf, &key);
+        pruned += 1;
+
+        if batch.len() >= BATCH_SIZE {
+            db.write(std::mem::take(&mut batch))?;
+        }
+    }
+    db.write(batch)?;
+
+    counter!(metrics::JMT_PRUNED_NODES, pruned as u64);
+    gauge!(metrics::JMT_OLDEST_RECENT_VERSION, oldest_recent as f64);
+
+    Ok(pruned)
+}
+
+/// Finds the nodes in the subtree rooted at `old` which are not in the subtree rooted at `new`
+/// (the node at the same position in the newer tree, if any).
+///
+/// Since a node's key includes its position, a node can only be shared between the two trees at
+/// the same position, and if it is, so is its entire subtree.
+fn diff(db: &DB, old: NodeKey, new: Option<NodeKey>, stale: &mut Vec<NodeKey>) -> Result<()> {
+    if Some(&old) == new.as_ref() {
+        return Ok(());
+    }
+    let old_node = match get_node(db, &old)? {
+        Some(node) => node,
+        None => return Ok(()),
+    };
+
+    if let Node::Internal(old_internal) = &old_node {
+        let new_internal = match &new {
+            Some(new) => match get_node(db, new)? {
+                Some(Node::Internal(new_internal)) => Some((new, new_internal)),
+                _ => None,
+            },
+            None => None,
+        };
+
+        for (nibble, child) in old_internal.children_sorted() {
+            let new_child = new_internal.as_ref().and_then(|(new, new_internal)| {
+                new_internal
+                    .child(*nibble)
+                    .map(|new_child| new.gen_child_node_key(new_child.version, *nibble))
+            });
+            diff(
+                db,
+                old.gen_child_node_key(child.version, *nibble),
+                new_child,
+                stale,
+            )?;
+        }
+    }
+
+    stale.push(old);
+    Ok(())
+}
+
+fn get_node(db: &DB, node_key: &NodeKey) -> Result<Option<Node>> {
+    let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+    db.get_pinned_cf(jmt_cf, &node_key.encode()?)?
+        .map(|bytes| Node::decode(&bytes))
+        .transpose()
+}
+
+fn earliest_version(db: &DB) -> Result<Option<jmt::Version>> {
+    let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+    db.iterator_cf(jmt_cf, IteratorMode::Start)
+        .next()
+        .map(|(key, _)| Ok(NodeKey::decode(&key)?.version()))
+        .transpose()
+}
+
+/// Stale node keys start with the big-endian version at which the node became stale, so that
+/// they're ordered by it.
+fn stale_key(stale_since: jmt::Version, node_key: &NodeKey) -> Result<Vec<u8>> {
+    let mut key = stale_since.to_be_bytes().to_vec();
+    key.extend_from_slice(&node_key.encode()?);
+    Ok(key)
+}
+
+fn parse_stale_key(key: &[u8]) -> Result<(jmt::Version, NodeKey)> {
+    if key.len() < 8 {
+        return Err(anyhow::anyhow!("stale node key is too short"));
+    }
+    let (stale_since, node_key) = key.split_at(8);
+    Ok((
+        jmt::Version::from_be_bytes(stale_since.try_into()?),
+        NodeKey::decode(node_key)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Retention, StateExt, Storage};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn prune_keeps_retained_versions() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().to_owned()).await.unwrap();
+        for value in 0u64..5 {
+            let state = storage.state().await.unwrap();
+            state.put_proto("foo-key".into(), value).await;
+            state
+                .put_proto(format!("bar-key/{}", value).into(), value)
+                .await;
+            state.write().await.commit(storage.clone()).await.unwrap();
+        }
+
+        let pruned = storage
+            .prune(Retention {
+                keep_recent: 2,
+                keep_versions: [1].into_iter().collect(),
+            })
+            .await
+            .unwrap();
+        assert!(pruned > 0);
+
+        assert!(storage.state_at(0).await.is_err());
+        assert!(storage.state_at(2).await.is_err());
+        for version in [1, 3, 4] {
+            let state = storage.state_at(version).await.unwrap();
+            assert_eq!(
+                state.get_proto::<u64>("foo-key".into()).await.unwrap(),
+                Some(version)
+            );
+            assert_eq!(
+                state.get_proto::<u64>("bar-key/0".into()).await.unwrap(),
+                Some(0)
+            );
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::{collections::BTreeMap, fmt::Debug};
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use jmt::{
+    storage::{LeafNode, Node, NodeKey, TreeReader},
+    JellyfishMerkleTree, KeyHash, RootHash,
+};
+use penumbra_proto::{Message, Protobuf};
+
+use penumbra_tct as tct;
+
+/// A self-contained copy of the persistent state at a particular version, used to bootstrap new
+/// nodes without replaying the chain from genesis.
+///
+/// It consists of every node of the tree written at or before that version, and the NCT as of that
+/// version.  A snapshot received from an untrusted peer must be checked against a trusted root
+/// hash (with [`StateSnapshot::root_hash`]) before it is restored.
+#[derive(Clone, Debug)]
+pub struct StateSnapshot {
+    pub(crate) version: jmt::Version,
+    /// The encoded nodes of the tree, keyed by their encoded [`NodeKey`].
+    pub(crate) nodes: BTreeMap<Vec<u8>, Vec<u8>>,
+    pub(crate) nct: tct::Tree,
+}
+
+impl StateSnapshot {
+    /// The version of the state captured by this snapshot.
+    pub fn version(&self) -> jmt::Version {
+        self.version
+    }
+
+    /// The note commitment tree as of this snapshot's version.
+    pub fn nct(&self) -> &tct::Tree {
+        &self.nct
+    }
+
+    /// Computes the root hash of the tree at this snapshot's version.
+    pub async fn root_hash(&self) -> Result<RootHash> {
+        JellyfishMerkleTree::new(self)
+            .get_root_hash_option(self.version)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("snapshot is missing the root for its version"))
+    }
+
+    /// Reads a domain type from the tree at this snapshot's version, using the proto encoding.
+    pub async fn get_domain<D, P>(&self, key: KeyHash) -> Result<Option<D>>
+    where
+        D: Protobuf<P> + TryFrom<P> + Clone + Debug,
+        P: Message + Default + From<D>,
+        <D as TryFrom<P>>::Error: Into<anyhow::Error>,
+    {
+        JellyfishMerkleTree::new(self)
+            .get(key, self.version)
+            .await?
+            .map(|bytes| D::decode(bytes.as_slice()))
+            .transpose()
+    }
+
+    /// Encodes this snapshot to bytes.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(&(self.version, &self.nodes, &self.nct))?)
+    }
+
+    /// Decodes a snapshot from bytes produced by [`StateSnapshot::encode`].
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let (version, nodes, nct) = bincode::deserialize(bytes)?;
+        Ok(Self {
+            version,
+            nodes,
+            nct,
+        })
+    }
+}
+
+/// A reader interface over the nodes in the snapshot, so that it can be verified before it's
+/// written to storage.
+impl TreeReader for StateSnapshot {
+    fn get_node_option<'future, 'a: 'future, 'n: 'future>(
+        &'a self,
+        node_key: &'n NodeKey,
+    ) -> BoxFuture<'future, Result<Option<Node>>> {
+        Box::pin(async move {
+            self.nodes
+                .get(&node_key.encode()?)
+                .map(|bytes| Node::decode(bytes))
+                .transpose()
+        })
+    }
+
+    fn get_rightmost_leaf<'future, 'a: 'future>(
+        &'a self,
+    ) -> BoxFuture<'future, Result<Option<(NodeKey, LeafNode)>>> {
+        Box::pin(async move {
+            match self.nodes.iter().next_back() {
+                Some((key, value)) => match Node::decode(value)? {
+                    Node::Leaf(leaf_node) => Ok(Some((NodeKey::decode(key)?, leaf_node))),
+                    _ => Ok(None),
+                },
+                None => Ok(None),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StateExt, Storage};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn snapshot_restores_state() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("source.db")).await.unwrap();
+        let state = storage.state().await.unwrap();
+        state.put_proto::<u64>("foo-key".into(), 1).await;
+        state.write().await.commit(storage.clone()).await.unwrap();
+        let state = storage.state().await.unwrap();
+        state.put_proto::<u64>("foo-key".into(), 2).await;
+        let (root, version) = state.write().await.commit(storage.clone()).await.unwrap();
+
+        let snapshot =
+            StateSnapshot::decode(&storage.snapshot(version).await.unwrap().encode().unwrap())
+                .unwrap();
+        assert_eq!(snapshot.root_hash().await.unwrap(), root);
+
+        let restored = Storage::load(dir.path().join("restored.db")).await.unwrap();
+        restored.restore(snapshot).await.unwrap();
+        assert_eq!(restored.latest_version().await.unwrap(), Some(version));
+        assert_eq!(
+            restored
+                .state()
+                .await
+                .unwrap()
+                .get_proto::<u64>("foo-key".into())
+                .await
+                .unwrap(),
+            Some(2)
+        );
+    }
+}
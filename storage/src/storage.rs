@@ -1,4 +1,4 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
 
 use ::metrics::gauge;
 use anyhow::Result;
@@ -7,13 +7,13 @@ use jmt::{
     storage::{Node, NodeBatch, NodeKey, TreeReader, TreeWriter},
     WriteOverlay,
 };
-use rocksdb::{Options, DB};
+use rocksdb::{IteratorMode, Options, WriteBatch, DB};
 use tokio::sync::RwLock;
 use tracing::Span;
 
 use penumbra_tct as tct;
 
-use crate::{metrics, State};
+use crate::{index, metrics, nct, pruning, Overlay, Retention, State, StateSnapshot};
 
 #[derive(Clone, Debug)]
 pub struct Storage(Arc<DB>);
@@ -30,7 +30,11 @@ impl Storage {
                     opts.create_if_missing(true);
                     opts.create_missing_column_families(true);
 
-                    Ok(Self(Arc::new(DB::open_cf(&opts, path, ["jmt", "nct"])?)))
+                    Ok(Self(Arc::new(DB::open_cf(
+                        &opts,
+                        path,
+                        ["jmt", "jmt_stale", "nct", "local", "key_index"],
+                    )?)))
                 })
             })
             .await
@@ -56,10 +60,31 @@ impl Storage {
             .unwrap_or(WriteOverlay::<Storage>::PRE_GENESIS_VERSION);
 
         tracing::debug!("creating state for version {}", version);
-        Ok(Arc::new(RwLock::new(WriteOverlay::new(
-            self.clone(),
-            version,
-        ))))
+        Ok(Arc::new(RwLock::new(Overlay::new(self.clone(), version))))
+    }
+
+    /// Returns a new [`State`] on top of a past version of the tree.
+    ///
+    /// The state is read-only in practice: committing it would fork the tree from an old version.
+    pub async fn state_at(&self, version: jmt::Version) -> Result<State> {
+        let latest = self.latest_version().await?;
+        if latest.map(|latest| version > latest).unwrap_or(true) {
+            return Err(anyhow::anyhow!(
+                "no state at version {}: the latest version is {:?}",
+                version,
+                latest
+            ));
+        }
+        if jmt::JellyfishMerkleTree::new(self)
+            .get_root_hash_option(version)
+            .await?
+            .is_none()
+        {
+            return Err(anyhow::anyhow!("no state at version {}", version));
+        }
+
+        tracing::debug!("creating state for past version {}", version);
+        Ok(Arc::new(RwLock::new(Overlay::new(self.clone(), version))))
     }
 
     /// Like [`Self::state`], but bundles in a [`tonic`] error conversion.
@@ -74,21 +99,46 @@ impl Storage {
             .map_err(|e| tonic::Status::internal(e.to_string()))
     }
 
+    /// Returns the keys in the key index starting with `prefix` which are present at `version`, in
+    /// order.
+    pub(crate) async fn keys_with_prefix(
+        &self,
+        prefix: String,
+        version: jmt::Version,
+    ) -> Result<Vec<String>> {
+        let db = self.0.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("keys_with_prefix")
+            .spawn_blocking(move || {
+                span.in_scope(|| index::keys_with_prefix(&db, &prefix, version))
+            })
+            .await?
+    }
+
+    /// Stores the NCT, writing only the changes made since it was last stored.
     pub async fn put_nct(&self, tct: &tct::Tree) -> Result<()> {
         let db = self.0.clone();
+        let span = Span::current();
+        let mut changes = tokio::task::Builder::new()
+            .name("put_nct::changes")
+            .spawn_blocking(move || span.in_scope(|| nct::Changes::new(&db)))
+            .await??;
 
         tracing::debug!("serializing TCT");
-        let tct_data = bincode::serialize(tct)?;
-        tracing::debug!(tct_bytes = tct_data.len(), "serialized TCT");
-        gauge!(metrics::TCT_SIZE_BYTES, tct_data.len() as f64);
+        tct.serialize(&mut changes).await?;
 
+        let db = self.0.clone();
         let span = Span::current();
         tokio::task::Builder::new()
             .name("put_nct")
             .spawn_blocking(move || {
                 span.in_scope(|| {
-                    let nct_cf = db.cf_handle("nct").expect("nct column family not found");
-                    db.put_cf(nct_cf, "tct", &tct_data)?;
+                    let mut batch = WriteBatch::default();
+                    let tct_bytes = changes.write_to(&db, &mut batch);
+                    db.write(batch)?;
+                    tracing::debug!(tct_bytes, "wrote TCT changes");
+                    gauge!(metrics::TCT_WRITE_BYTES, tct_bytes as f64);
                     Ok::<_, anyhow::Error>(())
                 })
             })
@@ -100,14 +150,166 @@ impl Storage {
         let span = Span::current();
         tokio::task::Builder::new()
             .name("get_nct")
+            .spawn_blocking(move || span.in_scope(|| nct::Loaded::load(&db, &db.snapshot())))
+            .await??
+            .into_tree()
+            .await
+    }
+
+    /// Takes a snapshot of the state at the given version, which should be the latest version.
+    ///
+    /// Since only the latest NCT is stored, the snapshot's NCT may be from a later version, if
+    /// another version is committed while the snapshot is taken; callers should check it against
+    /// the NCT anchor recorded in the snapshot's state.
+    pub async fn snapshot(&self, version: jmt::Version) -> Result<StateSnapshot> {
+        let db = self.0.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("snapshot")
             .spawn_blocking(move || {
                 span.in_scope(|| {
-                    let nct_cf = db.cf_handle("nct").expect("nct column family not found");
-                    if let Some(tct_bytes) = db.get_cf(nct_cf, "tct")? {
-                        Ok(bincode::deserialize(&tct_bytes)?)
-                    } else {
-                        Ok(tct::Tree::new())
+                    // Read from a consistent view of the database, so that nodes aren't written
+                    // while we iterate over them.
+                    let db_snapshot = db.snapshot();
+
+                    // Node keys are ordered by version first, so we can stop at the first node
+                    // written after the snapshot version.
+                    let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+                    let mut nodes = BTreeMap::new();
+                    for (key, value) in db_snapshot.iterator_cf(jmt_cf, IteratorMode::Start) {
+                        if NodeKey::decode(&key)?.version() > version {
+                            break;
+                        }
+                        nodes.insert(key.to_vec(), value.to_vec());
                     }
+                    tracing::debug!(?version, nodes = nodes.len(), "read JMT nodes for snapshot");
+
+                    let nct = nct::Loaded::load(&db, &db_snapshot)?;
+
+                    Ok::<_, anyhow::Error>((nodes, nct))
+                })
+            })
+            .await??;
+
+        Ok(StateSnapshot {
+            version,
+            nodes,
+            nct: nct.into_tree().await?,
+        })
+    }
+
+    /// Restores the state from a snapshot, which must already have been verified against a trusted
+    /// root hash.
+    ///
+    /// This can only be done on an empty `Storage`.
+    pub async fn restore(&self, snapshot: StateSnapshot) -> Result<()> {
+        if let Some(version) = self.latest_version().await? {
+            return Err(anyhow::anyhow!(
+                "cannot restore a snapshot over existing state at version {}",
+                version
+            ));
+        }
+
+        let mut changes = nct::Changes::empty();
+        snapshot.nct.serialize(&mut changes).await?;
+
+        let db = self.0.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("restore")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    // Write the tree, its key index, and the NCT together, so that we never store
+                    // one without the others.
+                    let mut batch = WriteBatch::default();
+                    let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+                    let mut nodes = Vec::with_capacity(snapshot.nodes.len());
+                    for (key, value) in snapshot.nodes.iter() {
+                        batch.put_cf(jmt_cf, key, value);
+                        nodes.push((NodeKey::decode(key)?, Node::decode(value)?));
+                    }
+                    index::update(&db, &mut batch, nodes.iter().map(|(k, n)| (k, n)));
+                    changes.write_to(&db, &mut batch);
+                    db.write(batch)?;
+
+                    tracing::info!(
+                        version = snapshot.version,
+                        nodes = snapshot.nodes.len(),
+                        "restored state from snapshot"
+                    );
+                    Ok::<_, anyhow::Error>(())
+                })
+            })
+            .await?
+    }
+
+    /// Deletes the nodes of the tree which are no longer needed to read any of the versions
+    /// retained by `retention`, returning the number of nodes deleted.
+    ///
+    /// Versions which are not retained can no longer be read after this.
+    pub async fn prune(&self, retention: Retention) -> Result<usize> {
+        let latest = match self.latest_version().await? {
+            Some(latest) => latest,
+            None => return Ok(0),
+        };
+
+        let db = self.0.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("prune")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    pruning::index_stale_nodes(&db, latest)?;
+                    let pruned = pruning::prune(&db, latest, &retention)?;
+                    tracing::debug!(?latest, pruned, "pruned stale JMT nodes");
+                    Ok(pruned)
+                })
+            })
+            .await?
+    }
+
+    /// Records that the operator has chosen to resume the chain from a halt at the given height.
+    ///
+    /// Like the NCT, this is stored outside of the main state: it's a decision made locally by each
+    /// node operator, which only affects consensus once the chain resumes.
+    pub async fn put_resume_height(&self, height: jmt::Version) -> Result<()> {
+        let db = self.0.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("put_resume_height")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    let local_cf = db
+                        .cf_handle("local")
+                        .expect("local column family not found");
+                    db.put_cf(local_cf, "resume_height", height.to_le_bytes())?;
+                    Ok::<_, anyhow::Error>(())
+                })
+            })
+            .await?
+    }
+
+    /// Returns the height from which the operator has chosen to resume the chain from a halt, if
+    /// any.
+    pub async fn get_resume_height(&self) -> Result<Option<jmt::Version>> {
+        let db = self.0.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("get_resume_height")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    let local_cf = db
+                        .cf_handle("local")
+                        .expect("local column family not found");
+                    db.get_cf(local_cf, "resume_height")?
+                        .map(|bytes| {
+                            Ok(jmt::Version::from_le_bytes(
+                                bytes.as_slice().try_into().map_err(|_| {
+                                    anyhow::anyhow!("resume height must be 8 bytes")
+                                })?,
+                            ))
+                        })
+                        .transpose()
                 })
             })
             .await?
@@ -134,14 +336,19 @@ impl TreeWriter for Storage {
                 .name("Storage::write_node_batch")
                 .spawn_blocking(move || {
                     span.in_scope(|| {
-                        for (node_key, node) in node_batch.clone() {
+                        // Write the nodes and the changes to the key index together, so that the
+                        // index never disagrees with the tree.
+                        let mut batch = WriteBatch::default();
+                        let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+                        for (node_key, node) in node_batch.iter() {
                             let key_bytes = &node_key.encode()?;
                             let value_bytes = &node.encode()?;
                             tracing::trace!(?key_bytes, value_bytes = ?hex::encode(&value_bytes));
 
-                            let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
-                            db.put_cf(jmt_cf, key_bytes, &value_bytes)?;
+                            batch.put_cf(jmt_cf, key_bytes, &value_bytes);
                         }
+                        index::update(&db, &mut batch, node_batch.iter());
+                        db.write(batch)?;
 
                         Ok(())
                     })
@@ -219,3 +426,33 @@ impl TreeReader for Storage {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StateExt;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn state_at_reads_past_versions() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().to_owned()).await.unwrap();
+        for value in [1u64, 2] {
+            let state = storage.state().await.unwrap();
+            state.put_proto("foo-key".into(), value).await;
+            state.write().await.commit(storage.clone()).await.unwrap();
+        }
+
+        let past = storage.state_at(0).await.unwrap();
+        assert_eq!(
+            past.get_proto::<u64>("foo-key".into()).await.unwrap(),
+            Some(1)
+        );
+        let latest = storage.state_at(1).await.unwrap();
+        assert_eq!(
+            latest.get_proto::<u64>("foo-key".into()).await.unwrap(),
+            Some(2)
+        );
+        assert!(storage.state_at(2).await.is_err());
+    }
+}
@@ -4,6 +4,7 @@ use penumbra_crypto::value;
 use penumbra_proto::{ibc as pb_ibc, stake as pbs, transaction as pb, Protobuf};
 
 mod delegate;
+mod flow_decryption;
 mod ibc;
 pub mod output;
 mod position;
@@ -16,16 +17,18 @@ mod vote;
 
 pub use self::ibc::ICS20Withdrawal;
 pub use delegate::Delegate;
+pub use flow_decryption::FlowDecryption;
 pub use output::Output;
 pub use position::{PositionClose, PositionOpen, PositionRewardClaim, PositionWithdraw};
 pub use propose::{
-    Proposal, ProposalKind, ProposalPayload, ProposalSubmit, ProposalWithdraw, ProposalWithdrawBody,
+    Proposal, ProposalKind, ProposalPayload, ProposalSubmit, ProposalWithdraw,
+    ProposalWithdrawBody, UpgradePlan,
 };
 pub use spend::Spend;
 pub use swap::Swap;
 pub use swap_claim::SwapClaim;
 pub use undelegate::Undelegate;
-pub use vote::{DelegatorVote, ValidatorVote, ValidatorVoteBody, Vote};
+pub use vote::{DelegatorVote, DelegatorVoteBody, ValidatorVote, ValidatorVoteBody, Vote};
 
 /// An action performed by a Penumbra transaction.
 #[derive(Clone, Debug)]
@@ -41,13 +44,14 @@ pub enum Action {
     SwapClaim(SwapClaim),
     ProposalSubmit(ProposalSubmit),
     ProposalWithdraw(ProposalWithdraw),
-    // DelegatorVote(DelegatorVote),
+    DelegatorVote(DelegatorVote),
     ValidatorVote(ValidatorVote),
 
     PositionOpen(PositionOpen),
     PositionClose(PositionClose),
     PositionWithdraw(PositionWithdraw),
     PositionRewardClaim(PositionRewardClaim),
+    FlowDecryption(FlowDecryption),
 
     ICS20Withdrawal(ICS20Withdrawal),
 }
@@ -69,8 +73,9 @@ impl Action {
             Action::IBCAction(_) => value::Commitment::default(),
             Action::ProposalSubmit(submit) => submit.value_commitment(),
             Action::ProposalWithdraw(_) => value::Commitment::default(),
-            // Action::DelegatorVote(_) => value::Commitment::default(),
+            Action::DelegatorVote(_) => value::Commitment::default(),
             Action::ValidatorVote(_) => value::Commitment::default(),
+            Action::FlowDecryption(_) => value::Commitment::default(),
 
             Action::PositionOpen(p) => p.value_commitment(),
             Action::PositionClose(p) => p.value_commitment(),
@@ -116,9 +121,9 @@ impl From<Action> for pb::Action {
             Action::ProposalWithdraw(inner) => pb::Action {
                 action: Some(pb::action::Action::ProposalWithdraw(inner.into())),
             },
-            // Action::DelegatorVote(inner) => pb::Action {
-            //     action: Some(pb::action::Action::DelegatorVote(inner.into())),
-            // },
+            Action::DelegatorVote(inner) => pb::Action {
+                action: Some(pb::action::Action::DelegatorVote(inner.into())),
+            },
             Action::ValidatorVote(inner) => pb::Action {
                 action: Some(pb::action::Action::ValidatorVote(inner.into())),
             },
@@ -134,6 +139,9 @@ impl From<Action> for pb::Action {
             Action::PositionRewardClaim(inner) => pb::Action {
                 action: Some(pb::action::Action::PositionRewardClaim(inner.into())),
             },
+            Action::FlowDecryption(inner) => pb::Action {
+                action: Some(pb::action::Action::FlowDecryption(inner.into())),
+            },
             Action::ICS20Withdrawal(withdrawal) => pb::Action {
                 action: Some(pb::action::Action::Ics20Withdrawal(withdrawal.into())),
             },
@@ -164,9 +172,9 @@ impl TryFrom<pb::Action> for Action {
             pb::action::Action::ProposalWithdraw(inner) => {
                 Ok(Action::ProposalWithdraw(inner.try_into()?))
             }
-            // pb::action::Action::DelegatorVote(inner) => {
-            //     Ok(Action::DelegatorVote(inner.try_into()?))
-            // }
+            pb::action::Action::DelegatorVote(inner) => {
+                Ok(Action::DelegatorVote(inner.try_into()?))
+            }
             pb::action::Action::ValidatorVote(inner) => {
                 Ok(Action::ValidatorVote(inner.try_into()?))
             }
@@ -181,6 +189,9 @@ impl TryFrom<pb::Action> for Action {
             pb::action::Action::PositionRewardClaim(inner) => {
                 Ok(Action::PositionRewardClaim(inner.try_into()?))
             }
+            pb::action::Action::FlowDecryption(inner) => {
+                Ok(Action::FlowDecryption(inner.try_into()?))
+            }
             pb::action::Action::Ics20Withdrawal(inner) => {
                 Ok(Action::ICS20Withdrawal(inner.try_into()?))
             }
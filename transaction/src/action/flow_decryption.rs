@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use penumbra_crypto::{dex::TradingPair, FlowDecryptionShare};
+use penumbra_proto::{dex as pb, Protobuf};
+
+/// A transaction action that contributes a validator's decryption shares of the
+/// total inputs of a pending batch swap.
+///
+/// This action doesn't change the transaction's value balance, and is
+/// authorized by the proofs in the decryption shares, which can only be
+/// computed with the key share of a member of the flow committee.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "pb::FlowDecryption", into = "pb::FlowDecryption")]
+pub struct FlowDecryption {
+    /// The height of the block in which the batch's swaps were submitted.
+    pub height: u64,
+    /// The trading pair of the batch.
+    pub trading_pair: TradingPair,
+    /// The share of the decryption of the batch's total input of asset 1.
+    pub delta_1_share: FlowDecryptionShare,
+    /// The share of the decryption of the batch's total input of asset 2.
+    pub delta_2_share: FlowDecryptionShare,
+}
+
+impl Protobuf<pb::FlowDecryption> for FlowDecryption {}
+
+impl From<FlowDecryption> for pb::FlowDecryption {
+    fn from(value: FlowDecryption) -> Self {
+        Self {
+            height: value.height,
+            trading_pair: Some(value.trading_pair.into()),
+            delta_1_share: Some(value.delta_1_share.into()),
+            delta_2_share: Some(value.delta_2_share.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::FlowDecryption> for FlowDecryption {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::FlowDecryption) -> Result<Self, Self::Error> {
+        Ok(Self {
+            height: value.height,
+            trading_pair: value
+                .trading_pair
+                .ok_or_else(|| anyhow::anyhow!("missing trading pair"))?
+                .try_into()?,
+            delta_1_share: value
+                .delta_1_share
+                .ok_or_else(|| anyhow::anyhow!("missing decryption share of asset 1"))?
+                .try_into()?,
+            delta_2_share: value
+                .delta_2_share
+                .ok_or_else(|| anyhow::anyhow!("missing decryption share of asset 2"))?
+                .try_into()?,
+        })
+    }
+}
@@ -113,9 +113,15 @@ impl PositionWithdraw {
             asset_id: LpNft::new(self.position_id, position::State::Closed).asset_id(),
         }
         .commit(Fr::zero());
+        let withdrawn_position_nft = Value {
+            amount: 1,
+            asset_id: LpNft::new(self.position_id, position::State::Withdrawn).asset_id(),
+        }
+        .commit(Fr::zero());
 
-        // The action consumes a closed position and produces the position's reserves.
-        self.reserves_commitment - closed_position_nft
+        // The action consumes a closed position and produces a withdrawn position and the
+        // position's reserves.
+        withdrawn_position_nft + self.reserves_commitment - closed_position_nft
     }
 }
 
@@ -123,7 +129,8 @@ impl PositionWithdraw {
 /// position.
 ///
 /// This action's contribution to the transaction's value balance is to consume a
-/// withdrawn position NFT and contribute its reward balance.
+/// withdrawn position NFT and contribute a claimed position NFT, as well as its
+/// reward balance.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(try_from = "pb::PositionRewardClaim", into = "pb::PositionRewardClaim")]
 pub struct PositionRewardClaim {
@@ -142,9 +149,15 @@ impl PositionRewardClaim {
             asset_id: LpNft::new(self.position_id, position::State::Withdrawn).asset_id(),
         }
         .commit(Fr::zero());
+        let claimed_position_nft = Value {
+            amount: 1,
+            asset_id: LpNft::new(self.position_id, position::State::Claimed).asset_id(),
+        }
+        .commit(Fr::zero());
 
-        // The action consumes a closed position and produces the position's reserves.
-        self.rewards_commitment - withdrawn_position_nft
+        // The action consumes a withdrawn position and produces a claimed position and the
+        // position's rewards.
+        claimed_position_nft + self.rewards_commitment - withdrawn_position_nft
     }
 }
 
@@ -254,3 +267,105 @@ impl TryFrom<pb::PositionRewardClaim> for PositionRewardClaim {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::{
+        asset,
+        dex::{lp::TradingFunction, TradingPair},
+        fixpoint::U64x64,
+        keys::{SeedPhrase, SpendKey},
+        memo::MemoPlaintext,
+        transaction::Fee,
+        Note, STAKING_TOKEN_ASSET_ID,
+    };
+    use penumbra_tct as tct;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        plan::{OutputPlan, SpendPlan, TransactionPlan},
+        WitnessData,
+    };
+
+    /// A reward claim spends the withdrawn position NFT and outputs the claimed
+    /// position NFT, so the transaction balances only if the claim accounts for both.
+    #[test]
+    fn reward_claim_transaction_balances() {
+        let seed_phrase = SeedPhrase::generate(&mut OsRng);
+        let sk = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let fvk = sk.full_viewing_key();
+        let (addr, _dtk) = fvk.incoming().payment_address(0u64.into());
+
+        let position = Position {
+            pair: TradingPair {
+                asset_1: *STAKING_TOKEN_ASSET_ID,
+                asset_2: asset::REGISTRY.parse_denom("cube").unwrap().id(),
+            },
+            phi: TradingFunction {
+                fee: U64x64::ratio(1, 10_000).unwrap(),
+                k: U64x64::ONE,
+                p: U64x64::ONE,
+                q: U64x64::ONE,
+            },
+            nonce: [1u8; 32],
+        };
+        let position_id = position.id();
+
+        let withdrawn_note = Note::generate(
+            &mut OsRng,
+            &addr,
+            Value {
+                amount: 1,
+                asset_id: LpNft::new(position_id, position::State::Withdrawn).asset_id(),
+            },
+        );
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, withdrawn_note.commit())
+            .unwrap();
+
+        let plan = TransactionPlan {
+            expiry_height: 0,
+            fee: Fee::default(),
+            chain_id: "penumbra-test".to_string(),
+            actions: vec![
+                SpendPlan::new(&mut OsRng, withdrawn_note.clone(), 0u64.into()).into(),
+                PositionRewardClaim {
+                    position_id,
+                    rewards_commitment: value::Commitment::default(),
+                }
+                .into(),
+                OutputPlan::new(
+                    &mut OsRng,
+                    Value {
+                        amount: 1,
+                        asset_id: LpNft::new(position_id, position::State::Claimed).asset_id(),
+                    },
+                    addr.clone(),
+                    MemoPlaintext::default(),
+                )
+                .into(),
+            ],
+            clue_plans: vec![],
+        };
+
+        let auth_data = plan.authorize(OsRng, &sk);
+        let witness_data = WitnessData {
+            anchor: nct.root(),
+            note_commitment_proofs: vec![nct.witness(withdrawn_note.commit()).unwrap()],
+        };
+        let transaction = plan
+            .build(&mut OsRng, fvk, auth_data, witness_data)
+            .unwrap();
+
+        assert!(transaction
+            .transaction_body()
+            .actions
+            .iter()
+            .any(|action| matches!(action, crate::Action::PositionRewardClaim(_))));
+        transaction
+            .binding_verification_key()
+            .verify(transaction.auth_hash().as_ref(), transaction.binding_sig())
+            .expect("reward claim transaction balances");
+    }
+}
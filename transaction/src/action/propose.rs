@@ -101,7 +101,10 @@ impl ProposalKind {
         let title = "A short title describing the intent of the proposal.".to_string();
         let description = "A human readable description of the proposal.".to_string();
         let payload = match self {
-            ProposalKind::Signaling => ProposalPayload::Signaling { commit: None },
+            ProposalKind::Signaling => ProposalPayload::Signaling {
+                commit: None,
+                upgrade: None,
+            },
             ProposalKind::Emergency => ProposalPayload::Emergency { halt_chain: false },
             ProposalKind::ParameterChange => {
                 let mut new_parameters = BTreeMap::new();
@@ -142,12 +145,14 @@ pub enum ProposalPayload {
     Signaling {
         /// An optional commit hash for code that this proposal refers to.
         commit: Option<String>,
+        /// An optional upgrade to perform if the proposal passes.
+        upgrade: Option<UpgradePlan>,
     },
     /// An emergency proposal is immediately passed when 2/3 of all validators approve it, without
     /// waiting for the voting period to conclude.
     Emergency {
-        /// If `halt_chain == true`, then the chain will immediately halt when the proposal is
-        /// passed.
+        /// If `halt_chain == true`, then the chain will halt after the block in which the proposal
+        /// is passed, until node operators resume it.
         halt_chain: bool,
     },
     /// A parameter change proposal describes changes to one or more chain parameters.
@@ -191,8 +196,11 @@ impl From<ProposalPayload> for pb::proposal::Payload {
     fn from(value: ProposalPayload) -> pb::proposal::Payload {
         pb::proposal::Payload {
             payload: Some(match value {
-                ProposalPayload::Signaling { commit } => {
-                    pb::proposal::payload::Payload::Signaling(pb::proposal::Signaling { commit })
+                ProposalPayload::Signaling { commit, upgrade } => {
+                    pb::proposal::payload::Payload::Signaling(pb::proposal::Signaling {
+                        commit,
+                        upgrade: upgrade.map(Into::into),
+                    })
                 }
                 ProposalPayload::Emergency { halt_chain } => {
                     pb::proposal::payload::Payload::Emergency(pb::proposal::Emergency {
@@ -252,6 +260,7 @@ impl TryFrom<pb::proposal::Payload> for ProposalPayload {
         match payload {
             pb::proposal::payload::Payload::Signaling(inner) => Ok(ProposalPayload::Signaling {
                 commit: inner.commit,
+                upgrade: inner.upgrade.map(TryInto::try_into).transpose()?,
             }),
             pb::proposal::payload::Payload::Emergency(inner) => Ok(ProposalPayload::Emergency {
                 halt_chain: inner.halt_chain,
@@ -302,6 +311,44 @@ impl TryFrom<pb::proposal::Payload> for ProposalPayload {
     }
 }
 
+/// A plan to upgrade the chain at a particular height.
+///
+/// Every node halts after committing the block at `height`, and resumes once its operator has run
+/// the state migration named by `migration_id` (using `pd migrate`) on the upgraded binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    try_from = "pb::proposal::UpgradePlan",
+    into = "pb::proposal::UpgradePlan"
+)]
+pub struct UpgradePlan {
+    /// The height of the last block to be executed before the upgrade.
+    pub height: u64,
+    /// The identifier of the state migration to run during the upgrade.
+    pub migration_id: String,
+}
+
+impl From<UpgradePlan> for pb::proposal::UpgradePlan {
+    fn from(value: UpgradePlan) -> pb::proposal::UpgradePlan {
+        pb::proposal::UpgradePlan {
+            height: value.height,
+            migration_id: value.migration_id,
+        }
+    }
+}
+
+impl TryFrom<pb::proposal::UpgradePlan> for UpgradePlan {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::proposal::UpgradePlan) -> Result<Self, Self::Error> {
+        Ok(UpgradePlan {
+            height: msg.height,
+            migration_id: msg.migration_id,
+        })
+    }
+}
+
+impl Protobuf<pb::proposal::UpgradePlan> for UpgradePlan {}
+
 /// A proposal submission describes the proposal to propose, and the (transparent, ephemeral) refund
 /// address for the proposal deposit, along with a key to be used to verify the signature for a
 /// withdrawal of that proposal.
@@ -1,20 +1,21 @@
-use ark_ff::Zero;
-use decaf377::Fr;
 use penumbra_crypto::dex::TradingPair;
 use penumbra_crypto::proofs::transparent::SwapProof;
 use penumbra_crypto::{dex::swap::SwapCiphertext, value};
-use penumbra_crypto::{NotePayload, Value};
+use penumbra_crypto::{FlowCiphertext, FlowEncryptionProof, NotePayload};
 use penumbra_proto::{dex as pb, Protobuf};
 
 #[derive(Clone, Debug)]
 pub struct Swap {
     // A proof that this is a valid state change.
     pub proof: SwapProof,
-    // Amounts will be plaintext until flow encryption is available.
-    // // The encrypted amount of asset 1 to be swapped.
-    // pub enc_amount_1: MockFlowCiphertext,
-    // // The encrypted amount of asset 2 to be swapped.
-    // pub enc_amount_2: MockFlowCiphertext,
+    // The encrypted amount of asset 1 to be swapped.
+    pub enc_amount_1: FlowCiphertext,
+    // The encrypted amount of asset 2 to be swapped.
+    pub enc_amount_2: FlowCiphertext,
+    // A proof that the encrypted amount of asset 1 is a well-formed encryption.
+    pub enc_amount_1_proof: FlowEncryptionProof,
+    // A proof that the encrypted amount of asset 2 is a well-formed encryption.
+    pub enc_amount_2_proof: FlowEncryptionProof,
     pub body: Body,
 }
 
@@ -22,18 +23,7 @@ impl Swap {
     /// Compute a commitment to the value contributed to a transaction by this swap.
     /// Will subtract (v1,t1), (v2,t2), and (f,fee_token)
     pub fn value_commitment(&self) -> value::Commitment {
-        let input_1 = Value {
-            amount: self.body.delta_1,
-            asset_id: self.body.trading_pair.asset_1(),
-        }
-        .commit(Fr::zero());
-        let input_2 = Value {
-            amount: self.body.delta_2,
-            asset_id: self.body.trading_pair.asset_2(),
-        }
-        .commit(Fr::zero());
-
-        -(input_1 + input_2 + self.body.fee_commitment)
+        -(self.body.delta_1_commitment + self.body.delta_2_commitment + self.body.fee_commitment)
     }
 }
 
@@ -43,7 +33,11 @@ impl From<Swap> for pb::Swap {
     fn from(s: Swap) -> Self {
         pb::Swap {
             zkproof: s.proof.into(),
+            enc_amount_1: Some(s.enc_amount_1.into()),
+            enc_amount_2: Some(s.enc_amount_2.into()),
             body: Some(s.body.into()),
+            enc_amount_1_proof: Some(s.enc_amount_1_proof.into()),
+            enc_amount_2_proof: Some(s.enc_amount_2_proof.into()),
         }
     }
 }
@@ -55,10 +49,26 @@ impl TryFrom<pb::Swap> for Swap {
             proof: s.zkproof[..]
                 .try_into()
                 .map_err(|_| anyhow::anyhow!("Swap proof malformed"))?,
+            enc_amount_1: s
+                .enc_amount_1
+                .ok_or_else(|| anyhow::anyhow!("missing enc_amount_1"))?
+                .try_into()?,
+            enc_amount_2: s
+                .enc_amount_2
+                .ok_or_else(|| anyhow::anyhow!("missing enc_amount_2"))?
+                .try_into()?,
             body: s
                 .body
                 .ok_or_else(|| anyhow::anyhow!("missing body"))?
                 .try_into()?,
+            enc_amount_1_proof: s
+                .enc_amount_1_proof
+                .ok_or_else(|| anyhow::anyhow!("missing enc_amount_1_proof"))?
+                .try_into()?,
+            enc_amount_2_proof: s
+                .enc_amount_2_proof
+                .ok_or_else(|| anyhow::anyhow!("missing enc_amount_2_proof"))?
+                .try_into()?,
         })
     }
 }
@@ -66,12 +76,8 @@ impl TryFrom<pb::Swap> for Swap {
 #[derive(Debug, Clone)]
 pub struct Body {
     pub trading_pair: TradingPair,
-    // No commitments for the values, as they're plaintext
-    // until flow encryption is available
-    // pub asset_1_commitment: value::Commitment,
-    // pub asset_2_commitment: value::Commitment,
-    pub delta_1: u64,
-    pub delta_2: u64,
+    pub delta_1_commitment: value::Commitment,
+    pub delta_2_commitment: value::Commitment,
     pub fee_commitment: value::Commitment,
     // TODO: rename to note_payload
     pub swap_nft: NotePayload,
@@ -84,8 +90,8 @@ impl From<Body> for pb::SwapBody {
     fn from(s: Body) -> Self {
         pb::SwapBody {
             trading_pair: Some(s.trading_pair.into()),
-            delta_1: s.delta_1,
-            delta_2: s.delta_2,
+            delta_1_commitment: s.delta_1_commitment.to_bytes().to_vec(),
+            delta_2_commitment: s.delta_2_commitment.to_bytes().to_vec(),
             fee_commitment: s.fee_commitment.to_bytes().to_vec(),
             swap_nft: Some(s.swap_nft.into()),
             swap_ciphertext: s.swap_ciphertext.0.to_vec(),
@@ -101,8 +107,8 @@ impl TryFrom<pb::SwapBody> for Body {
                 .trading_pair
                 .ok_or_else(|| anyhow::anyhow!("missing trading_pair"))?
                 .try_into()?,
-            delta_1: s.delta_1,
-            delta_2: s.delta_2,
+            delta_1_commitment: (&s.delta_1_commitment[..]).try_into()?,
+            delta_2_commitment: (&s.delta_2_commitment[..]).try_into()?,
             fee_commitment: (&s.fee_commitment[..]).try_into()?,
             swap_nft: s
                 .swap_nft
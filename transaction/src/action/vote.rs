@@ -3,9 +3,12 @@ use std::{
     str::FromStr,
 };
 
-use decaf377_rdsa::{Signature, SpendAuth};
-use penumbra_crypto::{GovernanceKey, IdentityKey};
+use decaf377_rdsa::{Signature, SpendAuth, VerificationKey};
+use penumbra_crypto::{
+    proofs::transparent::DelegatorVoteProof, GovernanceKey, IdentityKey, Nullifier, Value,
+};
 use penumbra_proto::{governance as pb_g, transaction as pb_t, Protobuf};
+use penumbra_tct as tct;
 use serde::{Deserialize, Serialize};
 
 /// A vote on a proposal.
@@ -188,13 +191,112 @@ impl TryFrom<pb_t::ValidatorVoteBody> for ValidatorVoteBody {
 
 impl Protobuf<pb_t::ValidatorVoteBody> for ValidatorVoteBody {}
 
+/// A vote by a delegator.
 #[derive(Debug, Clone)]
 pub struct DelegatorVote {
-    // TODO: fill this in
+    /// The body of the delegator vote.
     pub body: DelegatorVoteBody,
+    /// The signature authorizing the vote (signed with the randomized spend authorization key).
+    pub auth_sig: Signature<SpendAuth>,
+    /// The proof that the voter held the voted delegation tokens when voting started.
+    pub proof: DelegatorVoteProof,
+}
+
+impl From<DelegatorVote> for pb_t::DelegatorVote {
+    fn from(msg: DelegatorVote) -> Self {
+        let proof: Vec<u8> = msg.proof.into();
+        Self {
+            body: Some(msg.body.into()),
+            auth_sig: Some(msg.auth_sig.into()),
+            zkproof: proof.into(),
+        }
+    }
 }
 
+impl TryFrom<pb_t::DelegatorVote> for DelegatorVote {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb_t::DelegatorVote) -> Result<Self, Self::Error> {
+        Ok(Self {
+            body: msg
+                .body
+                .ok_or_else(|| anyhow::anyhow!("missing delegator vote body"))?
+                .try_into()?,
+            auth_sig: msg
+                .auth_sig
+                .ok_or_else(|| anyhow::anyhow!("missing delegator auth sig"))?
+                .try_into()?,
+            proof: (msg.zkproof[..])
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("delegator vote proof malformed"))?,
+        })
+    }
+}
+
+impl Protobuf<pb_t::DelegatorVote> for DelegatorVote {}
+
+/// A public vote as a delegator.
+///
+/// The weight of the vote is the amount of delegation tokens in the note voted with, which is
+/// revealed, along with its nullifier, so that each note can be used to vote only once.
 #[derive(Debug, Clone)]
 pub struct DelegatorVoteBody {
-    // TODO: fill this in
+    /// The proposal ID to vote on.
+    pub proposal: u64,
+    /// The position of the note commitment tree when voting on the proposal started.
+    pub start_position: tct::Position,
+    /// The vote to cast.
+    pub vote: Vote,
+    /// The delegation tokens voted with.
+    pub value: Value,
+    /// The nullifier of the note voted with.
+    pub nullifier: Nullifier,
+    /// The randomized verification key for the spend authorization signature.
+    pub rk: VerificationKey<SpendAuth>,
+}
+
+impl From<DelegatorVoteBody> for pb_t::DelegatorVoteBody {
+    fn from(value: DelegatorVoteBody) -> Self {
+        let nullifier_bytes: [u8; 32] = value.nullifier.into();
+        let rk_bytes: [u8; 32] = value.rk.into();
+        pb_t::DelegatorVoteBody {
+            proposal: value.proposal,
+            start_position: value.start_position.into(),
+            vote: Some(value.vote.into()),
+            value: Some(value.value.into()),
+            nullifier: nullifier_bytes.to_vec().into(),
+            rk: rk_bytes.to_vec().into(),
+        }
+    }
 }
+
+impl TryFrom<pb_t::DelegatorVoteBody> for DelegatorVoteBody {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb_t::DelegatorVoteBody) -> Result<Self, Self::Error> {
+        let rk_bytes: [u8; 32] = (msg.rk[..])
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("malformed rk in `DelegatorVote`"))?;
+
+        Ok(DelegatorVoteBody {
+            proposal: msg.proposal,
+            start_position: msg.start_position.into(),
+            vote: msg
+                .vote
+                .ok_or_else(|| anyhow::anyhow!("missing vote in `DelegatorVote`"))?
+                .try_into()?,
+            value: msg
+                .value
+                .ok_or_else(|| anyhow::anyhow!("missing value in `DelegatorVote`"))?
+                .try_into()?,
+            nullifier: (msg.nullifier[..])
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("malformed nullifier in `DelegatorVote`"))?,
+            rk: rk_bytes
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("malformed rk in `DelegatorVote`"))?,
+        })
+    }
+}
+
+impl Protobuf<pb_t::DelegatorVoteBody> for DelegatorVoteBody {}
@@ -9,9 +9,10 @@ use penumbra_proto::{
 
 use crate::{
     action::{
-        output, spend, swap, swap_claim, Delegate, ICS20Withdrawal, PositionClose, PositionOpen,
-        PositionRewardClaim, PositionWithdraw, Proposal, ProposalSubmit, ProposalWithdraw,
-        ProposalWithdrawBody, Undelegate, ValidatorVote, ValidatorVoteBody, Vote,
+        output, spend, swap, swap_claim, Delegate, DelegatorVote, DelegatorVoteBody,
+        FlowDecryption, ICS20Withdrawal, PositionClose, PositionOpen, PositionRewardClaim,
+        PositionWithdraw, Proposal, ProposalSubmit, ProposalWithdraw, ProposalWithdrawBody,
+        Undelegate, ValidatorVote, ValidatorVoteBody, Vote,
     },
     plan::{ProposalWithdrawPlan, TransactionPlan},
     Action, Transaction, TransactionBody,
@@ -150,8 +151,13 @@ impl TransactionPlan {
         for validator_vote in self.validator_votes() {
             state.update(validator_vote.auth_hash().as_bytes());
         }
-        for _delegator_vote in self.delegator_vote_plans() {
-            // TODO: get the authorization hash of the delegator vote body for each plan
+        for delegator_vote in self.delegator_vote_plans() {
+            state.update(
+                delegator_vote
+                    .delegator_vote_body(fvk)
+                    .auth_hash()
+                    .as_bytes(),
+            );
         }
         // These are data payloads, so just hash them directly,
         // since we consider them authorizing data.
@@ -167,6 +173,18 @@ impl TransactionPlan {
                 .hash(&payload.encode_to_vec());
             state.update(auth_hash.as_bytes());
         }
+        for position_open in self.position_openings() {
+            state.update(position_open.auth_hash().as_bytes());
+        }
+        for position_close in self.position_closings() {
+            state.update(position_close.auth_hash().as_bytes());
+        }
+        for position_withdraw in self.position_withdrawals() {
+            state.update(position_withdraw.auth_hash().as_bytes());
+        }
+        for position_reward_claim in self.position_reward_claims() {
+            state.update(position_reward_claim.auth_hash().as_bytes());
+        }
         let num_clues = self.clue_plans.len() as u32;
         state.update(&num_clues.to_le_bytes());
         for clue_plan in self.clue_plans() {
@@ -177,6 +195,23 @@ impl TransactionPlan {
     }
 }
 
+impl TransactionPlan {
+    /// Computes the [`AuthHash`] identifying this [`TransactionPlan`] when it is scheduled to be
+    /// executed by the DAO.
+    ///
+    /// DAO transactions are never built, since the DAO has no keys to build them with: instead,
+    /// their outputs are minted directly from the DAO treasury, so the plan itself is hashed.
+    pub fn dao_auth_hash(&self) -> AuthHash {
+        let plan: pb::TransactionPlan = self.clone().into();
+        AuthHash(
+            *Params::default()
+                .personal(b"PAH:dao_spend")
+                .hash(&plan.encode_to_vec())
+                .as_array(),
+        )
+    }
+}
+
 fn chain_id_auth_hash(chain_id: &str) -> Hash {
     blake2b_simd::Params::default()
         .personal(b"PAH:chain_id")
@@ -193,6 +228,7 @@ impl AuthorizingData for Action {
             Action::ProposalSubmit(submit) => submit.auth_hash(),
             Action::ProposalWithdraw(withdraw) => withdraw.auth_hash(),
             Action::ValidatorVote(vote) => vote.auth_hash(),
+            Action::DelegatorVote(vote) => vote.auth_hash(),
             Action::SwapClaim(swap_claim) => swap_claim.body.auth_hash(),
             Action::Swap(swap) => swap.body.auth_hash(),
             // These are data payloads, so just hash them directly,
@@ -208,6 +244,7 @@ impl AuthorizingData for Action {
             Action::PositionClose(p) => p.auth_hash(),
             Action::PositionWithdraw(p) => p.auth_hash(),
             Action::PositionRewardClaim(p) => p.auth_hash(),
+            Action::FlowDecryption(d) => d.auth_hash(),
             Action::ICS20Withdrawal(w) => w.auth_hash(),
         }
     }
@@ -259,8 +296,8 @@ impl AuthorizingData for swap::Body {
         // TODO: actually the trading pair isn't necessarily fixed-length
         // right now, does this have implications?
         state.update(self.trading_pair.auth_hash().as_bytes());
-        state.update(&self.delta_1.to_le_bytes());
-        state.update(&self.delta_2.to_le_bytes());
+        state.update(&self.delta_1_commitment.to_bytes());
+        state.update(&self.delta_2_commitment.to_bytes());
         state.update(&self.fee_commitment.to_bytes());
         // TODO: use common NotePayload auth_hash here
         state.update(&self.swap_nft.encode_to_vec());
@@ -421,6 +458,32 @@ impl AuthorizingData for ValidatorVoteBody {
     }
 }
 
+impl AuthorizingData for DelegatorVote {
+    fn auth_hash(&self) -> Hash {
+        self.body.auth_hash()
+    }
+}
+
+impl AuthorizingData for DelegatorVoteBody {
+    fn auth_hash(&self) -> Hash {
+        let mut state = blake2b_simd::Params::default()
+            .personal(b"PAH:del_vote")
+            .to_state();
+
+        // All of these fields are fixed-length, so we can just throw them in the hash one after the
+        // other.
+        state.update(&self.proposal.to_le_bytes());
+        state.update(&u64::from(self.start_position).to_le_bytes());
+        state.update(self.vote.auth_hash().as_bytes());
+        state.update(&self.value.amount.to_le_bytes());
+        state.update(&self.value.asset_id.to_bytes());
+        state.update(&self.nullifier.0.to_bytes());
+        state.update(&self.rk.to_bytes());
+
+        state.finalize()
+    }
+}
+
 impl AuthorizingData for PositionOpen {
     fn auth_hash(&self) -> Hash {
         let mut state = blake2b_simd::Params::default()
@@ -475,6 +538,16 @@ impl AuthorizingData for PositionRewardClaim {
     }
 }
 
+impl AuthorizingData for FlowDecryption {
+    fn auth_hash(&self) -> Hash {
+        // The decryption shares are variable-length proto messages, so hash the whole action.
+        let action: penumbra_proto::dex::FlowDecryption = self.clone().into();
+        Params::default()
+            .personal(b"PAH:flow_decrypt")
+            .hash(&action.encode_to_vec())
+    }
+}
+
 impl AuthorizingData for ICS20Withdrawal {
     fn auth_hash(&self) -> Hash {
         let mut state = blake2b_simd::Params::default()
@@ -521,7 +594,8 @@ mod tests {
     use rand_core::OsRng;
 
     use crate::{
-        plan::{OutputPlan, SpendPlan, TransactionPlan},
+        action::Vote,
+        plan::{DelegatorVotePlan, OutputPlan, SpendPlan, TransactionPlan},
         WitnessData,
     };
 
@@ -557,8 +631,18 @@ mod tests {
             },
         );
 
+        let note2 = Note::generate(
+            &mut OsRng,
+            &addr,
+            penumbra_crypto::Value {
+                amount: 5000,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            },
+        );
+
         nct.insert(tct::Witness::Keep, note0.commit()).unwrap();
         nct.insert(tct::Witness::Keep, note1.commit()).unwrap();
+        nct.insert(tct::Witness::Keep, note2.commit()).unwrap();
 
         let plan = TransactionPlan {
             expiry_height: 0,
@@ -567,6 +651,15 @@ mod tests {
             // Put outputs first to check that the auth hash
             // computation is not affected by plan ordering.
             actions: vec![
+                DelegatorVotePlan::new(
+                    &mut OsRng,
+                    0,
+                    3u64.into(),
+                    Vote::Yes,
+                    note2.clone(),
+                    2u64.into(),
+                )
+                .into(),
                 OutputPlan::new(
                     &mut OsRng,
                     Value {
@@ -593,6 +686,10 @@ mod tests {
             note_commitment_proofs: plan
                 .spend_plans()
                 .map(|spend| nct.witness(spend.note.commit()).unwrap())
+                .chain(
+                    plan.delegator_vote_plans()
+                        .map(|vote| nct.witness(vote.staked_note.commit()).unwrap()),
+                )
                 .collect(),
         };
         let transaction = plan
@@ -7,7 +7,10 @@ use penumbra_proto::{ibc as pb_ibc, stake as pb_stake, transaction as pb, Protob
 use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
 
-use crate::action::{Delegate, ProposalSubmit, Undelegate, ValidatorVote};
+use crate::action::{
+    Delegate, PositionClose, PositionOpen, PositionRewardClaim, PositionWithdraw, ProposalSubmit,
+    Undelegate, ValidatorVote,
+};
 
 mod action;
 mod auth;
@@ -170,17 +173,69 @@ impl TransactionPlan {
         })
     }
 
-    /// Convenience method to get all the destination addresses for each `OutputPlan`s.
+    pub fn position_openings(&self) -> impl Iterator<Item = &PositionOpen> {
+        self.actions.iter().filter_map(|action| {
+            if let ActionPlan::PositionOpen(v) = action {
+                Some(v)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn position_closings(&self) -> impl Iterator<Item = &PositionClose> {
+        self.actions.iter().filter_map(|action| {
+            if let ActionPlan::PositionClose(v) = action {
+                Some(v)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn position_withdrawals(&self) -> impl Iterator<Item = &PositionWithdraw> {
+        self.actions.iter().filter_map(|action| {
+            if let ActionPlan::PositionWithdraw(v) = action {
+                Some(v)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn position_reward_claims(&self) -> impl Iterator<Item = &PositionRewardClaim> {
+        self.actions.iter().filter_map(|action| {
+            if let ActionPlan::PositionRewardClaim(v) = action {
+                Some(v)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Convenience method to get all the destination addresses for each `OutputPlan`s,
+    /// followed by the claim addresses of each `SwapPlan`s and `SwapClaimPlan`s.
     pub fn dest_addresses(&self) -> Vec<Address> {
         self.output_plans()
-            .into_iter()
             .map(|plan| plan.dest_address)
+            .chain(
+                self.swap_plans()
+                    .map(|plan| plan.swap_plaintext.claim_address),
+            )
+            .chain(
+                self.swap_claim_plans()
+                    .map(|plan| plan.swap_nft_note.address()),
+            )
             .collect()
     }
 
-    /// Convenience method to get the number of `OutputPlan`s in this transaction.
+    /// Convenience method to get the number of notes created by this transaction,
+    /// i.e., the number of `OutputPlan`s, plus one swap NFT note for each `SwapPlan`,
+    /// plus two output notes for each `SwapClaimPlan`.
     pub fn num_outputs(&self) -> usize {
-        self.output_plans().into_iter().count()
+        self.output_plans().count()
+            + self.swap_plans().count()
+            + 2 * self.swap_claim_plans().count()
     }
 
     /// Method to add `CluePlan`s to a `TransactionPlan`.
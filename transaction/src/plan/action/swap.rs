@@ -3,10 +3,10 @@ use ark_ff::UniformRand;
 use decaf377::Fq;
 use penumbra_crypto::dex::swap::SwapPlaintext;
 use penumbra_crypto::{
-    proofs::transparent::SwapProof, FieldExt, Fr, FullViewingKey, Note, NotePayload, Value,
+    proofs::transparent::SwapProof, FieldExt, FlowBlindings, FlowEncryptionKey, Fr, FullViewingKey,
+    Note, NotePayload, Value,
 };
 use penumbra_proto::{transaction as pb, Protobuf};
-use penumbra_tct as tct;
 use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
@@ -16,35 +16,60 @@ use crate::action::{swap, Swap};
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(try_from = "pb::SwapPlan", into = "pb::SwapPlan")]
 pub struct SwapPlan {
-    // No commitments for the values, as they're plaintext
-    // until flow encryption is available
-    // pub asset_1_commitment: value::Commitment,
-    // pub asset_2_commitment: value::Commitment,
     pub swap_plaintext: SwapPlaintext,
     pub fee_blinding: Fr,
     pub note_blinding: Fq,
     pub esk: decaf377_ka::Secret,
+    /// The key the swap amounts are encrypted to, so that they're only revealed
+    /// in aggregate with the rest of the batch.
+    pub flow_encryption_key: FlowEncryptionKey,
+    pub enc_amount_1_blindings: FlowBlindings,
+    pub enc_amount_2_blindings: FlowBlindings,
 }
 
 impl SwapPlan {
-    /// Create a new [`SwapPlan`] that requests a swap between the given assets and input amounts.
-    pub fn new<R: CryptoRng + RngCore>(rng: &mut R, swap_plaintext: SwapPlaintext) -> SwapPlan {
+    /// Create a new [`SwapPlan`] that requests a swap between the given assets and input amounts,
+    /// encrypting the input amounts to the given flow encryption key.
+    pub fn new<R: CryptoRng + RngCore>(
+        rng: &mut R,
+        swap_plaintext: SwapPlaintext,
+        flow_encryption_key: FlowEncryptionKey,
+    ) -> SwapPlan {
         let note_blinding = Fq::rand(rng);
         let fee_blinding = Fr::rand(rng);
         let esk = decaf377_ka::Secret::new(rng);
+        let enc_amount_1_blindings = FlowBlindings::random(rng);
+        let enc_amount_2_blindings = FlowBlindings::random(rng);
         SwapPlan {
             fee_blinding,
             note_blinding,
             esk,
             swap_plaintext,
+            flow_encryption_key,
+            enc_amount_1_blindings,
+            enc_amount_2_blindings,
         }
     }
 
     /// Convenience method to construct the [`Swap`] described by this [`SwapPlan`].
-    pub fn swap(&self, fvk: &FullViewingKey, auth_path: tct::Proof) -> Swap {
+    pub fn swap<R: CryptoRng + RngCore>(&self, rng: &mut R, fvk: &FullViewingKey) -> Swap {
+        let (enc_amount_1, enc_amount_1_proof) = self.flow_encryption_key.encrypt_with_proof(
+            self.swap_plaintext.delta_1,
+            &self.enc_amount_1_blindings,
+            rng,
+        );
+        let (enc_amount_2, enc_amount_2_proof) = self.flow_encryption_key.encrypt_with_proof(
+            self.swap_plaintext.delta_2,
+            &self.enc_amount_2_blindings,
+            rng,
+        );
         Swap {
             body: self.swap_body(fvk),
-            proof: self.swap_proof(fvk, auth_path),
+            proof: self.swap_proof(rng, fvk),
+            enc_amount_1,
+            enc_amount_2,
+            enc_amount_1_proof,
+            enc_amount_2_proof,
         }
     }
 
@@ -78,8 +103,8 @@ impl SwapPlan {
 
         swap::Body {
             trading_pair: self.swap_plaintext.trading_pair,
-            delta_1: self.swap_plaintext.delta_1,
-            delta_2: self.swap_plaintext.delta_2,
+            delta_1_commitment: self.swap_plaintext.delta_1_commitment(),
+            delta_2_commitment: self.swap_plaintext.delta_2_commitment(),
             fee_commitment,
             swap_nft,
             swap_ciphertext,
@@ -87,31 +112,41 @@ impl SwapPlan {
     }
 
     /// Construct the [`SwapProof`] required by the [`swap::Body`] described by this [`SwapPlan`].
-    pub fn swap_proof(
+    pub fn swap_proof<R: CryptoRng + RngCore>(
         &self,
+        rng: &mut R,
         _fvk: &FullViewingKey,
-        _note_commitment_proof: tct::Proof,
     ) -> SwapProof {
         let swap_nft_asset_id = self.swap_plaintext.asset_id();
 
-        SwapProof {
-            claim_address: self.swap_plaintext.claim_address,
-            note_blinding: self.note_blinding,
-            fee_delta: self.swap_plaintext.fee.clone(),
-            value_t1: Value {
+        let delta_1_proof = self.flow_encryption_key.prove_commitment(
+            Value {
                 amount: self.swap_plaintext.delta_1,
                 asset_id: self.swap_plaintext.trading_pair.asset_1(),
             },
-            value_t2: Value {
+            &self.enc_amount_1_blindings,
+            self.swap_plaintext.delta_1_blinding,
+            rng,
+        );
+        let delta_2_proof = self.flow_encryption_key.prove_commitment(
+            Value {
                 amount: self.swap_plaintext.delta_2,
                 asset_id: self.swap_plaintext.trading_pair.asset_2(),
             },
+            &self.enc_amount_2_blindings,
+            self.swap_plaintext.delta_2_blinding,
+            rng,
+        );
+
+        SwapProof {
+            claim_address: self.swap_plaintext.claim_address,
+            note_blinding: self.note_blinding,
+            fee_delta: self.swap_plaintext.fee.clone(),
+            fee_blinding: self.fee_blinding,
             swap_nft_asset_id,
             esk: self.esk.clone(),
-            // TODO: no blinding factors for deltas yet, they're plaintext
-            // until flow encryption is available
-            // delta_1_blinding: self.delta_1_blinding(),
-            // delta_2_blinding: self.delta_2_blinding(),
+            delta_1_proof,
+            delta_2_proof,
         }
     }
 }
@@ -125,6 +160,9 @@ impl From<SwapPlan> for pb::SwapPlan {
             fee_blinding: msg.fee_blinding.to_bytes().to_vec().into(),
             note_blinding: msg.note_blinding.to_bytes().to_vec().into(),
             esk: msg.esk.to_bytes().to_vec().into(),
+            flow_encryption_key: Some(msg.flow_encryption_key.into()),
+            enc_amount_1_blindings: msg.enc_amount_1_blindings.to_bytes().to_vec().into(),
+            enc_amount_2_blindings: msg.enc_amount_2_blindings.to_bytes().to_vec().into(),
         }
     }
 }
@@ -144,6 +182,12 @@ impl TryFrom<pb::SwapPlan> for SwapPlan {
                 .try_into()?,
             note_blinding: Fq::from_bytes(msg.note_blinding[..].try_into()?)?,
             esk: msg.esk.as_ref().try_into()?,
+            flow_encryption_key: msg
+                .flow_encryption_key
+                .ok_or_else(|| anyhow!("missing flow_encryption_key"))?
+                .try_into()?,
+            enc_amount_1_blindings: msg.enc_amount_1_blindings[..].try_into()?,
+            enc_amount_2_blindings: msg.enc_amount_2_blindings[..].try_into()?,
         })
     }
 }
@@ -1,12 +1,11 @@
 use ark_ff::UniformRand;
 use decaf377::FieldExt;
 use penumbra_crypto::{
-    dex::{swap::SwapPlaintext, BatchSwapOutputData, TradingPair},
+    dex::{swap::SwapPlaintext, BatchSwapOutputData},
     ka,
     keys::{IncomingViewingKey, NullifierKey},
     proofs::transparent::SwapClaimProof,
-    transaction::Fee,
-    Address, Fq, FullViewingKey, Note, NotePayload, Value,
+    Fq, FullViewingKey, Note, NotePayload, Value,
 };
 use penumbra_proto::{transaction as pb, Protobuf};
 use penumbra_tct as tct;
@@ -31,30 +30,19 @@ pub struct SwapClaimPlan {
 }
 
 impl SwapClaimPlan {
-    /// Create a new [`SwapClaimPlan`] that redeems output notes to `claim_address` using
-    /// the associated swap NFT.
-    #[allow(clippy::too_many_arguments)]
+    /// Create a new [`SwapClaimPlan`] that redeems output notes to the claim address of the
+    /// swap described by `swap_plaintext`, using the associated swap NFT.
     pub fn new<R: RngCore + CryptoRng>(
         rng: &mut R,
         swap_nft_note: Note,
         swap_nft_position: Position,
-        claim_address: Address,
-        fee: Fee,
+        swap_plaintext: SwapPlaintext,
         output_data: BatchSwapOutputData,
-        trading_pair: TradingPair,
     ) -> SwapClaimPlan {
         let output_1_blinding = Fq::rand(rng);
         let output_2_blinding = Fq::rand(rng);
         let esk_1 = ka::Secret::new(rng);
         let esk_2 = ka::Secret::new(rng);
-        let swap_plaintext = SwapPlaintext::from_parts(
-            trading_pair,
-            output_data.delta_1,
-            output_data.delta_2,
-            fee,
-            claim_address,
-        )
-        .expect("unable to instantiate swap plaintext");
 
         Self {
             swap_nft_note,
@@ -68,18 +56,18 @@ impl SwapClaimPlan {
         }
     }
 
+    /// The amounts of asset 1 and asset 2 this swap claim will mint.
+    pub fn output_amounts(&self) -> (u64, u64) {
+        self.output_data
+            .pro_rata_outputs((self.swap_plaintext.delta_1, self.swap_plaintext.delta_2))
+    }
+
     /// Convenience method to construct the [`SwapClaim`] described by this
     /// [`SwapClaimPlan`].
-    pub fn swap_claim(
-        &self,
-        fvk: &FullViewingKey,
-        note_commitment_proof: tct::Proof,
-        nk: NullifierKey,
-        note_blinding: Fq,
-    ) -> SwapClaim {
+    pub fn swap_claim(&self, fvk: &FullViewingKey, note_commitment_proof: tct::Proof) -> SwapClaim {
         SwapClaim {
             body: self.swap_claim_body(fvk),
-            zkproof: self.swap_claim_proof(note_commitment_proof, nk, note_blinding),
+            zkproof: self.swap_claim_proof(note_commitment_proof, *fvk.nullifier_key()),
         }
     }
 
@@ -89,32 +77,37 @@ impl SwapClaimPlan {
         &self,
         note_commitment_proof: tct::Proof,
         nk: NullifierKey,
-        note_blinding: Fq,
     ) -> SwapClaimProof {
+        let (lambda_1, lambda_2) = self.output_amounts();
+
         SwapClaimProof {
             swap_nft_asset_id: self.swap_plaintext.asset_id(),
             claim_address: self.swap_nft_note.address(),
             note_commitment_proof,
             trading_pair: self.swap_plaintext.trading_pair,
-            note_blinding,
-            delta_1: self.output_data.delta_1,
-            delta_2: self.output_data.delta_2,
-            lambda_1: self.output_data.lambda_1,
-            lambda_2: self.output_data.lambda_2,
+            note_blinding: self.swap_nft_note.note_blinding(),
+            delta_1: self.swap_plaintext.delta_1,
+            delta_2: self.swap_plaintext.delta_2,
+            delta_1_blinding: self.swap_plaintext.delta_1_blinding,
+            delta_2_blinding: self.swap_plaintext.delta_2_blinding,
+            lambda_1,
+            lambda_2,
             note_blinding_1: self.output_1_blinding,
             note_blinding_2: self.output_2_blinding,
             esk_1: self.esk_1.clone(),
             esk_2: self.esk_2.clone(),
-            nk: nk.clone(),
+            nk,
         }
     }
 
     /// Construct the [`swap_claim::Body`] described by this plan.
     pub fn swap_claim_body(&self, fvk: &FullViewingKey) -> swap_claim::Body {
+        let (lambda_1, lambda_2) = self.output_amounts();
+
         let output_1_note = Note::from_parts(
             self.swap_nft_note.address(),
             Value {
-                amount: self.output_data.lambda_1,
+                amount: lambda_1,
                 asset_id: self.swap_plaintext.trading_pair.asset_1(),
             },
             self.output_1_blinding,
@@ -123,7 +116,7 @@ impl SwapClaimPlan {
         let output_2_note = Note::from_parts(
             self.swap_nft_note.address(),
             Value {
-                amount: self.output_data.lambda_2,
+                amount: lambda_2,
                 asset_id: self.swap_plaintext.trading_pair.asset_2(),
             },
             self.output_2_blinding,
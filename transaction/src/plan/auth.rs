@@ -15,6 +15,7 @@ impl TransactionPlan {
         let auth_hash = self.auth_hash(sk.full_viewing_key());
         let mut spend_auths = Vec::new();
         let mut withdraw_proposal_auths = Vec::new();
+        let mut delegator_vote_auths = Vec::new();
         for spend_plan in self.spend_plans() {
             let rsk = sk.spend_auth_key().randomize(&spend_plan.randomizer);
             let auth_sig = rsk.sign(&mut rng, auth_hash.as_ref());
@@ -27,10 +28,18 @@ impl TransactionPlan {
             let auth_sig = rsk.sign(&mut rng, auth_hash.as_ref());
             withdraw_proposal_auths.push(auth_sig);
         }
+        for delegator_vote_plan in self.delegator_vote_plans() {
+            let rsk = sk
+                .spend_auth_key()
+                .randomize(&delegator_vote_plan.randomizer);
+            let auth_sig = rsk.sign(&mut rng, auth_hash.as_ref());
+            delegator_vote_auths.push(auth_sig);
+        }
         AuthorizationData {
             auth_hash,
             spend_auths,
             withdraw_proposal_auths,
+            delegator_vote_auths,
         }
     }
 }
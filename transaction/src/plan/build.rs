@@ -30,13 +30,28 @@ impl TransactionPlan {
                 auth_data.spend_auths.len()
             ));
         }
-        if witness_data.note_commitment_proofs.len() != spend_count {
+        let delegator_vote_count = self.delegator_vote_plans().count();
+        if auth_data.delegator_vote_auths.len() != delegator_vote_count {
+            return Err(anyhow::anyhow!(
+                "expected {} delegator vote auths but got {}",
+                delegator_vote_count,
+                auth_data.delegator_vote_auths.len()
+            ));
+        }
+        // Auth paths are provided for each spent note, followed by each swap NFT being claimed,
+        // followed by each note used to vote as a delegator.
+        let swap_claim_count = self.swap_claim_plans().count();
+        let auth_path_count = spend_count + swap_claim_count + delegator_vote_count;
+        if witness_data.note_commitment_proofs.len() != auth_path_count {
             return Err(anyhow::anyhow!(
                 "expected {} auth paths but got {}",
-                spend_count,
+                auth_path_count,
                 witness_data.note_commitment_proofs.len()
             ));
         }
+        let mut spend_auth_paths = witness_data.note_commitment_proofs;
+        let mut swap_claim_auth_paths = spend_auth_paths.split_off(spend_count);
+        let delegator_vote_auth_paths = swap_claim_auth_paths.split_off(swap_claim_count);
 
         let mut actions = Vec::new();
         let mut fmd_clues = Vec::new();
@@ -51,7 +66,7 @@ impl TransactionPlan {
         for ((spend_plan, auth_sig), auth_path) in self
             .spend_plans()
             .zip(auth_data.spend_auths.into_iter())
-            .zip(witness_data.note_commitment_proofs.into_iter())
+            .zip(spend_auth_paths.into_iter())
         {
             // Spends add to the transaction's value balance.
             synthetic_blinding_factor += spend_plan.value_blinding;
@@ -66,29 +81,26 @@ impl TransactionPlan {
         }
 
         // Build the transaction's swaps.
-        // TODO: figure this out
-        //         the swap plans shouldn't require pulling in spend auth signatures, because the Swap just draws from the transaction's value balance. but we'll need to figure out how we handle the authentication paths (note commitment proofs) -- in the existing WitnessData struct, there's just one for each spend, in order, because (now-broken assumption) the only place they were needed was for spends.
-
-        // wondering if we should change the WitnessData domain type to store a BTreeMap<note::Commitment, tct::Proof>, and when deserializing from the proto, instead of just deserializing a list of proofs, call .commitment() on each one and use it as the key for the BTreeMap.
-
-        // then instead of having to do zips or whatever, or figure out how to maintain a brittle order dependency, we can just query for exactly the proof we want.
-        // for ((swap_plan, auth_sig), auth_path) in self
-        //     .swap_plans()
-        //     .zip(auth_data.spend_auths.into_iter())
-        //     .zip(witness_data.note_commitment_proofs.into_iter())
-        // {
-        //     actions.push(Action::Swap(swap_plan.swap(fvk, witness_data.anchor)));
-        // }
+        for swap_plan in self.swap_plans() {
+            // Swaps subtract the input amounts and the prepaid claim fee from
+            // the transaction's value balance.
+            synthetic_blinding_factor -= swap_plan.swap_plaintext.delta_1_blinding;
+            synthetic_blinding_factor -= swap_plan.swap_plaintext.delta_2_blinding;
+            synthetic_blinding_factor -= swap_plan.fee_blinding;
+            actions.push(Action::Swap(swap_plan.swap(rng, fvk)));
+        }
 
         // Build the transaction's swap claims.
-        // for swap_claim_plan in self.swap_claim_plans().cloned() {
-        //     actions.push(Action::SwapClaim(swap_claim_plan.swap_claim(
-        //         fvk,
-        //         note_commitment_proof,
-        //         nk,
-        //         note_blinding,
-        //     )));
-        // }
+        for (swap_claim_plan, auth_path) in self
+            .swap_claim_plans()
+            .zip(swap_claim_auth_paths.into_iter())
+        {
+            // Swap claims add the prepaid fee to the transaction's value balance,
+            // with a zero blinding factor.
+            actions.push(Action::SwapClaim(
+                swap_claim_plan.swap_claim(fvk, auth_path),
+            ));
+        }
 
         // Build the clue plans.
         for clue_plan in self.clue_plans() {
@@ -124,13 +136,33 @@ impl TransactionPlan {
         for validator_vote in self.validator_votes().cloned() {
             actions.push(Action::ValidatorVote(validator_vote))
         }
-        // TODO: delegator vote
+        for ((delegator_vote_plan, auth_sig), auth_path) in self
+            .delegator_vote_plans()
+            .zip(auth_data.delegator_vote_auths.into_iter())
+            .zip(delegator_vote_auth_paths.into_iter())
+        {
+            actions.push(Action::DelegatorVote(
+                delegator_vote_plan.delegator_vote(fvk, auth_sig, auth_path),
+            ));
+        }
         for vd in self.validator_definitions().cloned() {
             actions.push(Action::ValidatorDefinition(vd))
         }
         for ibc_action in self.ibc_actions().cloned() {
             actions.push(Action::IBCAction(ibc_action))
         }
+        for position_open in self.position_openings().cloned() {
+            actions.push(Action::PositionOpen(position_open))
+        }
+        for position_close in self.position_closings().cloned() {
+            actions.push(Action::PositionClose(position_close))
+        }
+        for position_withdraw in self.position_withdrawals().cloned() {
+            actions.push(Action::PositionWithdraw(position_withdraw))
+        }
+        for position_reward_claim in self.position_reward_claims().cloned() {
+            actions.push(Action::PositionRewardClaim(position_reward_claim))
+        }
 
         // Finally, compute the binding signature and assemble the transaction.
         let binding_signing_key = rdsa::SigningKey::from(synthetic_blinding_factor);
@@ -14,7 +14,10 @@ use penumbra_tct as tct;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    action::{Delegate, ProposalSubmit, ProposalWithdraw, Undelegate, ValidatorVote},
+    action::{
+        Delegate, DelegatorVote, FlowDecryption, ProposalSubmit, ProposalWithdraw, Swap, SwapClaim,
+        Undelegate, ValidatorVote,
+    },
     Action,
 };
 
@@ -90,15 +93,45 @@ impl Transaction {
         })
     }
 
-    // pub fn delegator_votes(&self) -> impl Iterator<Item = &DelegatorVote> {
-    //     self.actions().filter_map(|action| {
-    //         if let Action::DelegatorVote(v) = action {
-    //             Some(v)
-    //         } else {
-    //             None
-    //         }
-    //     })
-    // }
+    pub fn delegator_votes(&self) -> impl Iterator<Item = &DelegatorVote> {
+        self.actions().filter_map(|action| {
+            if let Action::DelegatorVote(v) = action {
+                Some(v)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn swaps(&self) -> impl Iterator<Item = &Swap> {
+        self.actions().filter_map(|action| {
+            if let Action::Swap(s) = action {
+                Some(s)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn swap_claims(&self) -> impl Iterator<Item = &SwapClaim> {
+        self.actions().filter_map(|action| {
+            if let Action::SwapClaim(c) = action {
+                Some(c)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn flow_decryptions(&self) -> impl Iterator<Item = &FlowDecryption> {
+        self.actions().filter_map(|action| {
+            if let Action::FlowDecryption(d) = action {
+                Some(d)
+            } else {
+                None
+            }
+        })
+    }
 
     pub fn ibc_actions(&self) -> impl Iterator<Item = &pb_ibc::IbcAction> {
         self.actions().filter_map(|action| {
@@ -120,26 +153,27 @@ impl Transaction {
         })
     }
 
-    // TODO: make sure payloads from Swap actions included
     pub fn note_payloads(&self) -> impl Iterator<Item = &NotePayload> {
-        self.actions().filter_map(|action| {
-            if let Action::Output(output) = action {
-                Some(&output.body.note_payload)
-            } else {
-                None
+        self.actions().flat_map(|action| match action {
+            Action::Output(output) => vec![&output.body.note_payload],
+            // Swaps create a swap NFT note, which is later spent by a SwapClaim.
+            Action::Swap(swap) => vec![&swap.body.swap_nft],
+            // SwapClaims create one output note for each asset in the trading pair.
+            Action::SwapClaim(swap_claim) => {
+                vec![&swap_claim.body.output_1, &swap_claim.body.output_2]
             }
+            _ => vec![],
         })
     }
 
-    // TODO: make sure nullifiers from SwapClaim actions included
     pub fn spent_nullifiers(&self) -> impl Iterator<Item = Nullifier> + '_ {
         self.actions().filter_map(|action| {
             // Note: adding future actions that include nullifiers
             // will need to be matched here as well as Spends
-            if let Action::Spend(spend) = action {
-                Some(spend.body.nullifier)
-            } else {
-                None
+            match action {
+                Action::Spend(spend) => Some(spend.body.nullifier),
+                Action::SwapClaim(swap_claim) => Some(swap_claim.body.nullifier),
+                _ => None,
             }
         })
     }
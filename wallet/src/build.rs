@@ -26,13 +26,22 @@ where
         })
         .await?;
 
-    // Get the witness data from the view service...
+    // Get the witness data from the view service, for each spent note followed by
+    // each claimed swap NFT, followed by each note used to vote as a delegator...
     let witness_data = view
         .witness(WitnessRequest {
             account_id: Some(fvk.hash().into()),
             note_commitments: plan
                 .spend_plans()
                 .map(|spend| spend.note.commit().into())
+                .chain(
+                    plan.swap_claim_plans()
+                        .map(|swap_claim| swap_claim.swap_nft_note.commit().into()),
+                )
+                .chain(
+                    plan.delegator_vote_plans()
+                        .map(|delegator_vote| delegator_vote.staked_note.commit().into()),
+                )
                 .collect(),
         })
         .await?;
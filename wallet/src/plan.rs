@@ -5,13 +5,18 @@ use anyhow::{anyhow, Context, Result};
 use penumbra_component::stake::rate::RateData;
 use penumbra_component::stake::validator;
 use penumbra_crypto::{
-    asset::Denom, dex::swap::SwapPlaintext, dex::TradingPair, keys::AddressIndex,
-    memo::MemoPlaintext, transaction::Fee, Address, FullViewingKey, Note, Value,
+    asset::Denom,
+    dex::{swap::SwapPlaintext, BatchSwapOutputData, TradingPair},
+    keys::AddressIndex,
+    memo::MemoPlaintext,
+    transaction::Fee,
+    Address, DelegationToken, FlowEncryptionKey, FullViewingKey, Value,
 };
 use penumbra_proto::view::NotesRequest;
+use penumbra_tct as tct;
 use penumbra_transaction::{
-    action::{Proposal, ValidatorVote},
-    plan::{OutputPlan, SpendPlan, SwapPlan, TransactionPlan},
+    action::{Proposal, ValidatorVote, Vote},
+    plan::{OutputPlan, SpendPlan, SwapClaimPlan, SwapPlan, TransactionPlan},
 };
 use penumbra_view::{SpendableNoteRecord, ViewClient};
 use rand_core::{CryptoRng, RngCore};
@@ -115,121 +120,43 @@ where
         .context("can't build undelegate plan")
 }
 
-#[allow(clippy::too_many_arguments)]
-#[allow(dead_code)]
-#[instrument(skip(fvk, view, rng, swap_nft_note, fee, source_address))]
+#[instrument(skip(view, rng, swap_plaintext, swap_nft_record, output_data))]
 pub async fn swap_claim<V, R>(
-    fvk: &FullViewingKey,
     view: &mut V,
-    rng: R,
-    swap_nft_note: Note,
-    fee: Fee,
-    source_address: Option<u64>,
+    mut rng: R,
+    swap_plaintext: SwapPlaintext,
+    swap_nft_record: SpendableNoteRecord,
+    output_data: BatchSwapOutputData,
 ) -> Result<TransactionPlan, anyhow::Error>
 where
     V: ViewClient,
     R: RngCore + CryptoRng,
 {
-    tracing::debug!(?swap_nft_note, ?fee, ?source_address);
-
-    return Err(anyhow::anyhow!("not implemented"));
+    tracing::debug!(?swap_plaintext, ?output_data);
 
     let chain_params = view.chain_params().await?;
 
+    // The swap claim is paid for by the fee prepaid in the swap, so it doesn't
+    // need to spend any notes.
     let mut plan = TransactionPlan {
         chain_id: chain_params.chain_id,
-        fee,
+        fee: swap_plaintext.fee.clone(),
         ..Default::default()
     };
 
     // Add a `SwapClaimPlan` action:
-    // plan.actions.push(
-    //     SwapClaimPlan::new(
-    //         &mut rng,
-    //         swap_nft_note,
-    //         swap_nft_position,
-    //         // The `fvk` is always the claim address.
-    //         fvk.address(),
-    //         fee,
-    //         output_data,
-    //         trading_pair,
-    //     )
-    //     .into(),
-    // );
-
-    // The value we need to spend is 1 unit of the swap NFT, plus fees.
-    let mut value_to_spend: HashMap<Denom, u64> = HashMap::new();
-    // *value_to_spend.entry(swap_nft_note.denom()).or_default() += input_value;
-    // if fee > 0 {
-    //     *value_to_spend
-    //         .entry(STAKING_TOKEN_DENOM.clone())
-    //         .or_default() += fee;
-    // }
-
-    // Add the required spends:
-    for (denom, spend_amount) in value_to_spend {
-        if spend_amount == 0 {
-            continue;
-        }
-
-        let source_index: Option<AddressIndex> = source_address.map(Into::into);
-        // Select a list of notes that provides at least the required amount.
-        let notes_to_spend = view
-            .notes(NotesRequest {
-                account_id: Some(fvk.hash().into()),
-                asset_id: Some(denom.id().into()),
-                address_index: source_index.map(Into::into),
-                amount_to_spend: spend_amount,
-                include_spent: false,
-            })
-            .await?;
-        if notes_to_spend.is_empty() {
-            // Shouldn't happen because the other side checks this, but just in case...
-            return Err(anyhow::anyhow!("not enough notes to spend",));
-        }
-
-        let change_address_index: u64 = fvk
-            .incoming()
-            .index_for_diversifier(
-                &notes_to_spend
-                    .last()
-                    .expect("notes_to_spend should never be empty")
-                    .note
-                    .diversifier(),
-            )
-            .try_into()?;
-
-        let (change_address, _dtk) = fvk.incoming().payment_address(change_address_index.into());
-        let spent: u64 = notes_to_spend
-            .iter()
-            .map(|note_record| note_record.note.amount())
-            .sum();
-
-        // Spend each of the notes we selected.
-        for note_record in notes_to_spend {
-            plan.actions
-                .push(SpendPlan::new(&mut rng, note_record.note, note_record.position).into());
-        }
-
-        // Find out how much change we have and whether to add a change output.
-        let change = spent - spend_amount;
-        if change > 0 {
-            plan.actions.push(
-                OutputPlan::new(
-                    &mut rng,
-                    Value {
-                        amount: change,
-                        asset_id: denom.id(),
-                    },
-                    change_address,
-                    MemoPlaintext::default(),
-                )
-                .into(),
-            );
-        }
-    }
-
-    // Add clue plans for `Output`s.
+    plan.actions.push(
+        SwapClaimPlan::new(
+            &mut rng,
+            swap_nft_record.note,
+            swap_nft_record.position,
+            swap_plaintext,
+            output_data,
+        )
+        .into(),
+    );
+
+    // Add clue plans for the output notes.
     let fmd_params = view.fmd_parameters().await?;
     let precision_bits = fmd_params.precision_bits;
     plan.add_all_clue_plans(&mut rng, precision_bits.into());
@@ -237,7 +164,7 @@ where
 }
 
 #[allow(clippy::too_many_arguments)]
-#[instrument(skip(fvk, view, rng, input_value, fee, source_address))]
+#[instrument(skip(fvk, view, rng, input_value, fee, flow_encryption_key, source_address))]
 pub async fn swap<V, R>(
     fvk: &FullViewingKey,
     view: &mut V,
@@ -245,6 +172,7 @@ pub async fn swap<V, R>(
     input_value: Value,
     into_denom: Denom,
     fee: Fee,
+    flow_encryption_key: FlowEncryptionKey,
     source_address: Option<u64>,
 ) -> Result<TransactionPlan, anyhow::Error>
 where
@@ -297,19 +225,25 @@ where
     let (claim_address, _dtk) = fvk.incoming().ephemeral_address(OsRng);
 
     // Create the `SwapPlaintext` representing the swap to be performed:
-    let swap_plaintext =
-        SwapPlaintext::from_parts(trading_pair, delta_1, delta_2, fee.clone(), claim_address)
-            .map_err(|_| anyhow!("error generating swap plaintext"))?;
+    let swap_plaintext = SwapPlaintext::new(
+        &mut rng,
+        trading_pair,
+        delta_1,
+        delta_2,
+        fee.clone(),
+        claim_address,
+    );
 
     // Add a `SwapPlan` action:
     plan.actions
-        .push(SwapPlan::new(&mut rng, swap_plaintext).into());
+        .push(SwapPlan::new(&mut rng, swap_plaintext, flow_encryption_key).into());
 
-    // The value we need to spend is the input value, plus fees.
+    // The value we need to spend is the input value, plus fees: once for the
+    // transaction fee, and once for the claim fee prepaid by the swap itself.
     let mut value_to_spend: HashMap<Denom, u64> = HashMap::new();
     *value_to_spend.entry(input_denom.clone()).or_default() += input_value.amount;
     if fee.amount() > 0 {
-        *value_to_spend.entry(fee_denom.clone()).or_default() += fee.amount();
+        *value_to_spend.entry(fee_denom.clone()).or_default() += 2 * fee.amount();
     }
 
     // Add the required spends:
@@ -524,3 +458,66 @@ where
         .await
         .context("can't build proposal withdraw transaction")
 }
+
+/// Generate a new transaction plan voting on a proposal as a delegator, with every note of
+/// delegation tokens the account held when voting on the proposal started.
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(fvk, view, rng))]
+pub async fn delegator_vote<V, R>(
+    fvk: &FullViewingKey,
+    view: &mut V,
+    rng: R,
+    proposal_id: u64,
+    start_position: tct::Position,
+    vote: Vote,
+    fee: Fee,
+    source_address: Option<u64>,
+) -> Result<TransactionPlan>
+where
+    V: ViewClient,
+    R: RngCore + CryptoRng,
+{
+    let assets = view.assets().await?;
+    let notes = view
+        .notes(NotesRequest {
+            account_id: Some(fvk.hash().into()),
+            include_spent: false,
+            ..Default::default()
+        })
+        .await?;
+
+    let mut planner = Planner::new(rng);
+    planner.fee(fee);
+
+    let mut voted = false;
+    for record in notes {
+        let is_delegation_token = assets
+            .get(&record.note.asset_id())
+            .map(|denom| DelegationToken::try_from(denom.clone()).is_ok())
+            .unwrap_or(false);
+
+        // Only notes which existed when voting started can be used to vote.
+        if is_delegation_token && record.position < start_position {
+            planner.delegator_vote(
+                proposal_id,
+                start_position,
+                vote,
+                record.note,
+                record.position,
+            );
+            voted = true;
+        }
+    }
+
+    if !voted {
+        return Err(anyhow!(
+            "no delegation tokens were held when voting started on proposal {}",
+            proposal_id
+        ));
+    }
+
+    planner
+        .plan(view, fvk, source_address.map(Into::into))
+        .await
+        .context("can't build delegator vote plan")
+}
@@ -14,8 +14,10 @@ use penumbra_crypto::{
 use penumbra_proto::view::NotesRequest;
 use penumbra_tct as tct;
 use penumbra_transaction::{
-    action::{Proposal, ProposalSubmit, ProposalWithdrawBody, ValidatorVote},
-    plan::{ActionPlan, OutputPlan, ProposalWithdrawPlan, SpendPlan, TransactionPlan},
+    action::{Proposal, ProposalSubmit, ProposalWithdrawBody, ValidatorVote, Vote},
+    plan::{
+        ActionPlan, DelegatorVotePlan, OutputPlan, ProposalWithdrawPlan, SpendPlan, TransactionPlan,
+    },
 };
 use penumbra_view::ViewClient;
 use rand::{CryptoRng, RngCore};
@@ -170,6 +172,29 @@ impl<R: RngCore + CryptoRng> Planner<R> {
         self
     }
 
+    /// Cast a delegator vote in this transaction, using the given `position`ed note of delegation
+    /// tokens, which must have been created before voting on the proposal started.
+    #[instrument(skip(self))]
+    pub fn delegator_vote(
+        &mut self,
+        proposal: u64,
+        start_position: tct::Position,
+        vote: Vote,
+        note: Note,
+        position: tct::Position,
+    ) -> &mut Self {
+        let delegator_vote = DelegatorVotePlan::new(
+            &mut self.rng,
+            proposal,
+            start_position,
+            vote,
+            note,
+            position,
+        );
+        self.action(delegator_vote.into());
+        self
+    }
+
     fn action(&mut self, action: ActionPlan) -> &mut Self {
         use ActionPlan::*;
 
@@ -211,8 +236,21 @@ impl<R: RngCore + CryptoRng> Planner<R> {
             PositionClose(_) => todo!(),
             PositionWithdraw(_) => todo!(),
             PositionRewardClaim(_) => todo!(),
-            Swap(_) => todo!(),
-            SwapClaim(_) => todo!(),
+            Swap(swap) => {
+                let swap_plaintext = &swap.swap_plaintext;
+                self.balance -= Value {
+                    amount: swap_plaintext.delta_1,
+                    asset_id: swap_plaintext.trading_pair.asset_1(),
+                };
+                self.balance -= Value {
+                    amount: swap_plaintext.delta_2,
+                    asset_id: swap_plaintext.trading_pair.asset_2(),
+                };
+                self.balance -= swap_plaintext.fee.value();
+            }
+            SwapClaim(swap_claim) => {
+                self.balance += swap_claim.swap_plaintext.fee.value();
+            }
             IBCAction(_) => todo!(),
             ValidatorDefinition(_) | ProposalWithdraw(_) | DelegatorVote(_) | ValidatorVote(_) => {
                 // No contribution to the value balance of the transaction